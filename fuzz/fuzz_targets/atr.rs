@@ -0,0 +1,14 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! ATR of the applet, returned by SELECT, and ATR of the T=1 interface, returned by a soft reset
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use se05x::se05x::Atr;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Atr::try_from(data);
+    let _ = se05x::t1::Atr::parse(data);
+});
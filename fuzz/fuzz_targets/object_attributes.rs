@@ -0,0 +1,22 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Object attributes, returned by ReadAttributes and in attestations, and the policies they hold
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use se05x::se05x::policies::Operation;
+use se05x::se05x::{ObjectAttributes, ObjectId};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(attributes) = ObjectAttributes::try_from(data) else {
+        return;
+    };
+    attributes.policies().for_each(drop);
+    let _ = attributes.allows(ObjectId::INVALID, Operation::Read);
+    let _ = attributes.allows(
+        attributes.authentication_object_identifier(),
+        Operation::Sign,
+    );
+});
@@ -0,0 +1,83 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Responses of the commands, parsed with `Se05XResponse::from_response`
+//!
+//! The first byte of the input selects the response type, the rest is the response data.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use se05x::se05x::commands::*;
+use se05x::se05x::gp::GpResponse;
+use se05x::se05x::scp03::PutKeyResponse;
+use se05x::se05x::Se05XResponse;
+
+macro_rules! parsers {
+    ($($response:ty),* $(,)?) => {
+        &[$(|data| {
+            let _ = <$response as Se05XResponse>::from_response(data);
+        }),*]
+    };
+}
+
+const PARSERS: &[fn(&[u8])] = parsers!(
+    CreateSessionResponse,
+    ExchangeSessionDataResponse,
+    RefreshSessionResponse,
+    CloseSessionResponse,
+    VerifySessionUserIdResponse,
+    ScpInitializeUpdateResponse,
+    ScpExternalAuthenticateResponse,
+    GetUnlockChallengeResponse,
+    ReadObjectResponse,
+    ReadAttestObjectResponse,
+    ReadAttributesResponse,
+    ReadAttributesAttestResponse,
+    ExportObjectResponse,
+    ReadTypeResponse,
+    ReadSizeResponse,
+    ReadIdListResponse,
+    CheckObjectExistsResponse,
+    GetEcCurveIdResponse,
+    ReadEcCurveListResponse,
+    ReadCryptoObjListResponse,
+    EcdsaSignResponse,
+    EddsaSignResponse,
+    EcdaaSignResponse,
+    EcdsaVerifyResponse,
+    EddsaVerifyResponse,
+    EcdhGenerateSharedSecretResponse,
+    RsaSignResponse,
+    RsaVerifyResponse,
+    RsaEncryptResponse,
+    RsaDecryptResponse,
+    CipherUpdateResponse,
+    CipherFinalResponse,
+    CipherOneShotEncryptResponse,
+    CipherOneShotDecryptResponse,
+    MacGenerateFinalResponse,
+    MacValidateFinalResponse,
+    MacOneShotGenerateResponse,
+    MacOneShotValidateResponse,
+    HkdfResponse,
+    Pbkdf2Response,
+    I2CMasterExecuteCommandSetResponse,
+    I2CMasterExecuteCommandSetAttestedResponse,
+    TlsPerformPrfResponse,
+    DigestFinalResponse,
+    DigestOneShotResponse,
+    GetVersionResponse,
+    GetTimestampResponse,
+    GetFreeMemoryResponse,
+    GetRandomResponse,
+    GpResponse,
+    PutKeyResponse,
+);
+
+fuzz_target!(|data: &[u8]| {
+    let Some((selector, data)) = data.split_first() else {
+        return;
+    };
+    PARSERS[usize::from(*selector) % PARSERS.len()](data);
+});
@@ -1,6 +1,8 @@
 # Copyright (C) 2023 Nitrokey GmbH
 # SPDX-License-Identifier: LGPL-3.0-only
 
+import os
+import re
 import toml
 import sys
 
@@ -47,12 +49,95 @@ def ty_for_resp(arg):
     else:
         return f'{arg.get("type", DEFAULT_TYPE)}'
 
+def serde_for_arg(arg, name):
+    ty = arg.get("type", DEFAULT_TYPE)
+    optional = arg.get("optional", False) and name != "then"
+    if ty == DEFAULT_TYPE:
+        return 'serde(with = "serde_bytes", borrow)'
+    if ty.startswith("&'data [u8;"):
+        if optional:
+            return 'serde(with = "serde_byte_array_ref::option", borrow)'
+        return 'serde(with = "serde_byte_array_ref", borrow)'
+    if ty.startswith("[u8;"):
+        return 'serde(with = "serde_bytes")'
+    return None
+
+def write_serde_for_arg(arg, name, outfile):
+    attr = serde_for_arg(arg, name)
+    if attr is not None:
+        outfile.write(f'    #[cfg_attr(feature = "serde", {attr})]\n')
+
+def serde_derive(args):
+    # Borrowed slices of structured data can only be serialized
+    if any("PolicySet" in a.get("type", "") for a in args):
+        return "serde::Serialize"
+    return "serde::Serialize, serde::Deserialize"
+
+def owned_ty_for_resp(arg):
+    ty = arg.get("type", DEFAULT_TYPE)
+    if ty == DEFAULT_TYPE:
+        ty = "heapless::Vec<u8, N>"
+    elif ty.startswith("&'data "):
+        ty = ty[len("&'data "):]
+    if arg.get("optional", False):
+        return f'Option<{ty}>'
+    return ty
+
+def owned_conversion_for_resp(arg):
+    ty = arg.get("type", DEFAULT_TYPE)
+    optional = arg.get("optional", False)
+    field = f'self.{arg["name"]}'
+    if ty == DEFAULT_TYPE:
+        if optional:
+            return f'{field}.map(owned_bytes).transpose()?'
+        return f'owned_bytes({field})?'
+    if ty.startswith("&'data "):
+        if optional:
+            return f'{field}.copied()'
+        return f'*{field}'
+    if ty in NOT_COPY_TYPES:
+        return f'{field}.clone()'
+    return field
+
+def write_owned_response(name, response, cfg, outfile):
+    needs_capacity = any(a.get("type", DEFAULT_TYPE) == DEFAULT_TYPE for a in response.values())
+    generics = "<const N: usize>" if needs_capacity else ""
+    outfile.write("\n")
+    outfile.write(f'/// Owned version of [`{name}Response`]\n')
+    outfile.write(cfg)
+    outfile.write('#[cfg(feature = "owned")]\n')
+    outfile.write("#[derive(Clone, Debug, PartialEq, Eq)]\n")
+    outfile.write('#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]\n')
+    outfile.write(f'pub struct {name}ResponseOwned{generics} {{\n')
+    for arg_name, arg in response.items():
+        if arg_name != "then":
+            outfile.write(f'    /// Parsed from TLV tag [`{arg_name}`]()\n')
+        else:
+            outfile.write(f'    /// Parsed from remaining data\n')
+        if arg.get("type", DEFAULT_TYPE).startswith("&'data [u8;"):
+            outfile.write('    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]\n')
+        outfile.write(f'    pub {arg["name"]}: {owned_ty_for_resp(arg)},\n')
+    outfile.write("}\n\n")
+    outfile.write(cfg)
+    outfile.write('#[cfg(feature = "owned")]\n')
+    outfile.write(f'impl {name}Response<\'_> {{\n')
+    outfile.write(f'    pub fn to_owned{generics}(&self) -> Result<{name}ResponseOwned{generics.replace("const N: usize", "N")}, Error> {{\n')
+    fields = ", ".join([f'{arg["name"]}: {owned_conversion_for_resp(arg)}' for arg in response.values()])
+    outfile.write(f'        Ok({name}ResponseOwned {{ {fields} }})\n')
+    outfile.write("    }\n")
+    outfile.write("}\n")
+
 PARSE_PATTERN = """        let (%s, rem) = take_do_until(%s, rem)?;"""
 
 PARSE_PATTERN_OPTIONAL = """        let (%s, rem) = take_opt_do_until(%s, %s, rem)?;"""
 
+PARSE_PATTERN_OPTIONAL_END = """        let (%s, rem) = take_opt_do_until_end(%s, %s, rem)?;"""
+
 DEFAULT_TYPE = "&'data [u8]"
 
+# Response field types that need to be cloned when converting to an owned response
+NOT_COPY_TYPES = ["ObjectAttributes"]
+
 def parse_for_resp(arg, name, outfile, full_response):
     tab = " "*8
     ty = arg.get("type", None)
@@ -67,10 +152,52 @@ def parse_for_resp(arg, name, outfile, full_response):
     if arg.get("optional", False):
         next = [k for k in full_response.keys() if k != "then"]
         next = f"&[{','.join(next)}]"
-        outfile.write(PARSE_PATTERN_OPTIONAL % (arg["name"], name, next))
+        pattern = PARSE_PATTERN_OPTIONAL_END if arg.get("may_end", False) else PARSE_PATTERN_OPTIONAL
+        outfile.write(pattern % (arg["name"], name, next))
     else:
         outfile.write(PARSE_PATTERN % (arg["name"], name))
 
+def layout_row(arg_name, arg):
+    if arg_name == "then":
+        tag = "remaining data"
+    else:
+        tag = f'`{arg_name}`'
+    if "value" in arg:
+        field = f'fixed to `{arg["value"]}`'
+    else:
+        field = f'`{arg["name"]}`'
+    ty = arg.get("type", DEFAULT_TYPE)
+    optional = "yes" if arg.get("optional", False) and arg_name != "then" else "no"
+    return f'/// | {tag} | {field} | `{ty}` | {optional} |\n'
+
+def write_command_doc(name, v, outfile):
+    """Doc comment of a command: its description, the layout of its TLV data and the applet features it requires"""
+    missing = [key for key in ("doc", "requires", "reference") if key not in v]
+    if missing:
+        raise Exception(f'Command {name} is missing the keys {", ".join(missing)}')
+
+    paragraphs = ["".join([f'/// {line}'.rstrip() + "\n" for line in v["doc"].strip().splitlines()])]
+
+    rows = []
+    for param in ("p1", "p2"):
+        if not isinstance(v[param], str):
+            rows.append(f'/// | {param.upper()} | `{v[param]["name"]}` | `{v[param]["type"]}` | no |\n')
+    rows += [layout_row(arg_name, arg) for arg_name, arg in flatten(v["payload"].items())]
+    if rows:
+        paragraphs.append("/// | Tag | Field | Type | Optional |\n/// |-----|-------|------|----------|\n" + "".join(rows))
+
+    response_rows = [layout_row(arg_name, arg) for arg_name, arg in v.get("response", {}).items()]
+    if response_rows:
+        paragraphs.append(f'/// The response is parsed into [`{name}Response`]:\n///\n')
+        paragraphs[-1] += "/// | Tag | Field | Type | Optional |\n/// |-----|-------|------|----------|\n" + "".join(response_rows)
+
+    if v["requires"]:
+        features = ", ".join([f'[`{f}`](AppletConfig::{f})' for f in v["requires"]])
+        paragraphs.append(f'/// Requires the applet features {features}, see [`Atr::applet_config`].\n')
+    paragraphs.append(f'/// Reference: {v["reference"]}\n')
+
+    outfile.write("///\n".join(paragraphs))
+
 def flatten(items):
     for arg_name, arg in items:
         if type(arg) is list:
@@ -79,6 +206,168 @@ def flatten(items):
         else:
             yield arg_name, arg
 
+# Sample values of the command fields used by the test vectors, as a Rust expression and its encoding.
+# The constants and enums are read from `se05x.rs`, so that the expected APDUs are computed
+# independently of the Rust code serializing the commands.
+
+CLASSES = {"NO_SM_CLA": 0x80, "SM_CLA": 0x84}
+
+def parse_constants(source):
+    constants = {}
+    pattern = re.compile(r'^pub const (\w+): (?:u8|u16|Tag|Instruction) = (?:Tag::from_u8|Instruction::Unknown)?\(?([^;]*?)\)?;', re.M)
+    for name, value in pattern.findall(source):
+        constants[name] = eval(value, {}, dict(constants))
+    return constants
+
+def parse_enums(source, constants):
+    """Variants of the enums declared with `enum_data!`, with their size and the value of their first variant always compiled"""
+    enums = {}
+    pattern = re.compile(r'#\[repr\(u(\d+)\)\]\s*pub enum (\w+) \{(.*?)\n\s*\}', re.S)
+    for bits, name, body in pattern.findall(source):
+        variants = {}
+        first = None
+        cfg = False
+        for line in body.splitlines():
+            line = line.strip()
+            if line.startswith("#[cfg"):
+                cfg = True
+                continue
+            m = re.match(r'(\w+) = (\w+),', line)
+            if m is None:
+                continue
+            value = eval(m.group(2), {}, dict(constants))
+            variants[m.group(1)] = value
+            if first is None and not cfg:
+                first = m.group(1)
+            cfg = False
+        enums[name] = (int(bits) // 8, variants, first)
+    return enums
+
+def sample_for_type(ty, k):
+    """Rust expression and encoding of a sample value of `ty`, `k` making the samples of the fields of a command distinct"""
+    if ty == DEFAULT_TYPE:
+        return f'&[{k}, {k}, {k}]', bytes([k] * 3)
+    m = re.fullmatch(r"(&'data )?\[u8; (\d+)\]", ty)
+    if m:
+        return f'{"&" if m.group(1) else ""}[{k}; {m.group(2)}]', bytes([k] * int(m.group(2)))
+    m = re.fullmatch(r"Be<u(\d+)>", ty)
+    if m:
+        return f'Be({k})', k.to_bytes(int(m.group(1)) // 8, "big")
+    if ty == "ObjectId":
+        return f'ObjectId([{k}; 4])', bytes([k] * 4)
+    if ty == "CryptoObjectId":
+        return f'CryptoObjectId([{k}; 2])', bytes([k] * 2)
+    if ty == "PolicySet<'data>":
+        rule = 0x00200000
+        return (f'PolicySet(&[Policy {{ object_id: ObjectId([{k}; 4]), access_rule: ObjectAccessRule::from_flags(ObjectPolicyFlags::ALLOW_READ) }}])',
+                bytes([8] + [k] * 4) + rule.to_bytes(4, "big"))
+    if ty == "SessionPolicy":
+        return f'SessionPolicy::new().max_apdu(Some({k}))', bytes([0x80, 0x00]) + k.to_bytes(2, "big")
+    if ty == "AppletConfig":
+        return f'AppletConfig::from_bits_retain({k})', k.to_bytes(2, "big")
+    if ty == "LockState":
+        return 'LockState::Locked', bytes([CONSTANTS["LOCKED"]])
+    if ty in ENUMS:
+        size, variants, first = ENUMS[ty]
+        return f'{ty}::{first}', variants[first].to_bytes(size, "big")
+    raise Exception(f'No sample value for type {ty}, add one to `sample_for_type`')
+
+def value_bytes(value):
+    """Encoding of the fixed value of a field, a variant of an enum"""
+    ty, variant = value.split("::")
+    size, variants, _ = ENUMS[ty]
+    return variants[variant].to_bytes(size, "big")
+
+def tlv(tag, value):
+    if len(value) < 0x80:
+        length = bytes([len(value)])
+    elif len(value) < 0x100:
+        length = bytes([0x81, len(value)])
+    else:
+        length = bytes([0x82]) + len(value).to_bytes(2, "big")
+    return bytes([tag]) + length + value
+
+def apdu(cla, ins, p1, p2, data, le, force_extended):
+    """Command APDU following ISO 7816-4, `le` being `None` for the maximum length"""
+    extended = force_extended or len(data) > 255 or le is None or le > 256
+    header = bytes([cla, ins, p1, p2])
+    if not extended:
+        lc = bytes([len(data)]) if data else b""
+        return header + lc + data + (bytes([le % 256]) if le else b"")
+    lc = bytes([0]) + len(data).to_bytes(2, "big") if data else b""
+    if le == 0:
+        return header + lc + data
+    le_field = (0 if le is None else le % 0x10000).to_bytes(2, "big")
+    return header + lc + data + (b"" if data else bytes([0])) + le_field
+
+def test_vector(name, v):
+    """Construction of a sample of the command and its expected APDU"""
+    fields = []
+    ins = CONSTANTS[v["ins"].split(" | ")[0]]
+    for part in v["ins"].split(" | ")[1:]:
+        ins |= CONSTANTS[part]
+    p = {}
+    for param in ("p1", "p2"):
+        if isinstance(v[param], str):
+            p[param] = eval(v[param], {}, dict(CONSTANTS))
+        else:
+            expr, value = sample_for_type(v[param]["type"], 0)
+            fields.append(f'{v[param]["name"]}: {expr}')
+            p[param] = value[0]
+    if v.get("maybe_transient", False):
+        fields.append("transient: true")
+        ins |= CONSTANTS["INS_TRANSIENT"]
+    if v.get("maybe_auth", False):
+        fields.append("is_auth: true")
+        ins |= CONSTANTS["INS_AUTH_OBJECT"]
+    for key, param in (("maybe_p1_mask", "p1"), ("maybe_p2_mask", "p2")):
+        if key in v:
+            ty = v[key]["type"]
+            _, variants, _ = ENUMS[ty]
+            variant = list(variants)[-1]
+            fields.append(f'{v[key]["name"]}: Some({ty}::{variant})')
+            p[param] |= variants[variant]
+
+    data = b""
+    k = 0
+    for arg_name, arg in flatten(v["payload"].items()):
+        if "value" in arg:
+            data += tlv(CONSTANTS[arg_name], value_bytes(arg["value"]))
+            continue
+        k += 1
+        expr, value = sample_for_type(arg.get("type", DEFAULT_TYPE), k)
+        if arg.get("optional", False) and arg_name != "then":
+            expr = f'Some({expr})'
+        fields.append(f'{arg["name"]}: {expr}')
+        data += value if arg_name == "then" else tlv(CONSTANTS[arg_name], value)
+
+    le = v.get("le", 0)
+    if le == "ExpectedLen::Max":
+        le = None
+    elif isinstance(le, str):
+        le = int(le)
+    expected = apdu(CLASSES[v["cla"]], ins, p["p1"], p["p2"], data, le, v.get("force_extended", False))
+
+    # Split to keep the lines short enough for rustfmt
+    hex_chunks = [f'"{expected[i:i + 32].hex().upper()}"' for i in range(0, len(expected), 32)]
+    hex_literal = hex_chunks[0] if len(hex_chunks) == 1 else "\n" + "\n".join([f'            {c}' for c in hex_chunks]) + "\n        "
+    cfg = f'    #[cfg(feature = "{v["feature"]}")]\n' if "feature" in v else ""
+    return (f'{cfg}    test_vectors::TestVector {{\n'
+            f'        name: "{name}",\n'
+            f'        encode: || test_vectors::encode(&{name} {{ {", ".join(fields)} }}),\n'
+            f'        expected: &hex!({hex_literal}),\n'
+            f'    }},\n')
+
+def write_test_vectors(vectors, outfile):
+    outfile.write("\n")
+    outfile.write("// ************* Test vectors ************* //\n")
+    outfile.write("\n")
+    outfile.write("/// Sample of each command with its expected APDU, computed from the constants of `se05x.rs`\n")
+    outfile.write('#[cfg(feature = "test-vectors")]\n')
+    outfile.write("pub const TEST_VECTORS: &[test_vectors::TestVector] = &[\n")
+    outfile.write("".join(vectors))
+    outfile.write("];\n")
+
 if len(sys.argv) != 3:
     print("Usage: ./generate_commands.py <toml data> <target file>")
     exit(1)
@@ -86,6 +375,11 @@ if len(sys.argv) != 3:
 outfile = open(sys.argv[2], "w")
 data = toml.load(sys.argv[1])
 
+se05x_source = open(os.path.join(os.path.dirname(sys.argv[1]), os.pardir, "se05x.rs")).read()
+CONSTANTS = parse_constants(se05x_source)
+ENUMS = parse_enums(se05x_source, CONSTANTS)
+vectors = []
+
 # REUSE-IgnoreStart
 outfile.write("// Copyright (C) 2023 Nitrokey GmbH\n")
 outfile.write("// SPDX-License-Identifier: LGPL-3.0-only\n\n")
@@ -143,8 +437,16 @@ for command, v in data.items():
         response_lifetime = "<'data>"
         response_lifetime_inferred = "<'_>"
 
+    # Commands only compiled with a cargo feature
+    cfg = f'#[cfg(feature = "{v["feature"]}")]\n' if "feature" in v else ""
+
+    vectors.append(test_vector(name, v))
+
+    write_command_doc(name, v, outfile)
+    outfile.write(cfg)
     outfile.write("#[derive(Clone, Debug, PartialEq, Eq)]\n")
     outfile.write("#[cfg_attr(feature = \"builder\", derive(typed_builder::TypedBuilder))]\n")
+    outfile.write(f'#[cfg_attr(feature = "serde", derive({serde_derive([a for _, a in flatten(v["payload"].items())])}))]\n')
     outfile.write(f'pub struct {name}{payload_lifetime} {{\n')
 
     pre_ins = ""
@@ -194,6 +496,7 @@ for command, v in data.items():
         else:
             outfile.write(f'    /// Serialized to remaining data\n')
 
+        write_serde_for_arg(arg, arg_name, outfile)
         if "optional" in arg and arg["optional"] == True:
             outfile.write(f'    #[cfg_attr(feature = \"builder\", builder(default, setter(strip_option(fallback = {arg["name"] + "_opt"}))))]\n')
         elif "default" in arg:
@@ -210,6 +513,7 @@ for command, v in data.items():
     if force_extended:
         command_builder = f'{command_builder}.force_extended()'
 
+    outfile.write(cfg)
     outfile.write(f'impl DataSource for {name}{payload_lifetime_inferred} {{\n')
     outfile.write('    fn len(&self) -> usize {\n')
     outfile.write(f'        {slice_val_pre}')
@@ -225,6 +529,7 @@ for command, v in data.items():
     outfile.write('    }\n')
     outfile.write("}\n")
 
+    outfile.write(cfg)
     outfile.write(f'impl<W: Writer> DataStream<W> for {name}{payload_lifetime_inferred} {{\n')
     outfile.write('    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {\n')
     outfile.write(f'        {slice_val_pre}')
@@ -237,7 +542,9 @@ for command, v in data.items():
     outfile.write("}\n")
    
     if "response" in v:
+        outfile.write(cfg)
         outfile.write("#[derive(Clone, Debug, PartialEq, Eq)]\n")
+        outfile.write(f'#[cfg_attr(feature = "serde", derive({serde_derive(list(v["response"].values()))}))]\n')
         outfile.write(f'pub struct {name}Response{response_lifetime} {{\n')
 
         for arg_name, arg in v["response"].items():
@@ -248,20 +555,29 @@ for command, v in data.items():
                 outfile.write(f'    /// Parsed from TLV tag [`{arg_name}`]()\n')
             else:
                 outfile.write(f'    /// Parsed from remaining data\n')
+            write_serde_for_arg(arg, arg_name, outfile)
             outfile.write(f'    pub {arg["name"]}: {ty_for_resp(arg)},\n')
         outfile.write("}\n")
 
-        outfile.write(f'\nimpl<\'data> Se05XResponse<\'data> for {name}Response{response_lifetime} {{\n')
+        outfile.write("\n")
+        outfile.write(cfg)
+        outfile.write(f'impl<\'data> Se05XResponse<\'data> for {name}Response{response_lifetime} {{\n')
         outfile.write("    #[inline(never)]\n")
         outfile.write("    fn from_response(rem: &'data [u8]) -> Result<Self, Error> {\n")
         for arg_name, arg in v["response"].items():
             parse_for_resp(arg, arg_name, outfile, v["response"])
-        outfile.write("        let _ = rem;\n")
+        if "then" in v["response"]:
+            outfile.write("        let _ = rem;\n")
+        else:
+            outfile.write("        check_trailing_data(rem)?;\n")
         outfile.write(f'        Ok(Self {{ {", ".join([arg["name"] for arg in v["response"].values()])} }})\n')
         outfile.write("    }\n")
         outfile.write("}\n")
+        if response_has_lifetime:
+            write_owned_response(name, v["response"], cfg, outfile)
 
     outfile.write("\n")
+    outfile.write(cfg)
     outfile.write(f'impl<W: Writer> Se05XCommand<W> for {name}{payload_lifetime_inferred} {{\n')
     if "response" not in v: 
         outfile.write(f'    type Response<\'rdata> = ();\n')
@@ -269,7 +585,12 @@ for command, v in data.items():
         outfile.write(f'    type Response<\'rdata> = {name}Response<\'rdata>;\n')
     else:
         outfile.write(f'    type Response<\'rdata> = {name}Response;\n')
+    if v.get("validate", False):
+        outfile.write(f'    fn validate(&self) -> Result<(), Error> {{\n')
+        outfile.write(f'        {name}::validate(self)\n')
+        outfile.write(f'    }}\n')
     outfile.write("}\n")
 
+write_test_vectors(vectors, outfile)
 
 outfile.flush()
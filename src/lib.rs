@@ -99,6 +99,22 @@
 //!
 //! This version exposes the required I2C NACKs. There is no need to use the `nrf` and `lpc55` features.
 //!
+//! ### Transports
+//!
+//! The T=1 over I2C link is one implementation of the `Se05xTransport` trait, which sends command APDUs and receives the responses.
+//! `Se05X` runs commands over any transport, for example a smartcard reader exchanging whole APDUs through `ApduLink`.
+//!
+//! The T=1 over I2C link is gated by the `transport` feature, enabled by default.
+//! Without it, the command definitions, the response parsing and the transports exchanging whole APDUs compile without the T=1 and I2C dependencies,
+//! for server-side verifiers and test tooling. The `embedded-hal-*`, `nrf`, `lpc55-*` and `mock` features enable `transport`.
+//!
+//! RSA keys and operations are gated by the `rsa` feature, enabled by default.
+//! DES keys, cipher modes and MACs are gated by the `legacy-des` feature, disabled by default since DES is deprecated: there is no default `des` feature.
+//!
+//! ### Errors
+//!
+//! `Error` implements `Display`. With the `std` feature, it also implements `std::error::Error`, for use with the error handling crates of desktop tools.
+//!
 //! ### Iso7816
 //!
 //! This driver uses the [`iso7816`](https://docs.rs/iso7816/latest/iso7816/) crate to implement serialization of APDUs.
@@ -108,6 +124,11 @@
 //! To simplify implementation, all supported se05x APDUs are described in `src/se05x/commands.toml`.
 //! The python script `generate_commands.py` parses the `command.toml` file and generates `src/se05x/commands.rs`, which implements all the APDUs.
 //!
+//! ### Fuzzing
+//!
+//! The parsers of the responses, of the ATRs and of the object attributes handle data from the bus. The `fuzz` directory holds [cargo-fuzz](https://github.com/rust-fuzz/cargo-fuzz) targets for them.
+//! `make fuzz` seeds the corpus and runs each target for `FUZZ_TIME` seconds. The `strict-tlv` feature of the fuzz crate fuzzes the strict parsing mode.
+//!
 //! Funding
 //! -------
 //!
@@ -117,10 +138,15 @@
 //! This project was funded through the [NGI Assure](https://nlnet.nl/assure/) Fund, a fund established by [NLnet](https://nlnet.nl/) with financial support from the European Commission's [Next Generation Internet programme](https://ngi.eu/), under the aegis of DG Communications Networks, Content and Technology under grant agreement No 957073.
 
 extern crate delog;
+#[cfg(feature = "std")]
+extern crate std;
 delog::generate_macros!();
 
+#[cfg(feature = "transport")]
 pub mod embedded_hal;
 mod macros;
+#[cfg(feature = "mock")]
+pub mod mock;
 
 pub mod se05x;
 pub mod t1;
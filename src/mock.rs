@@ -0,0 +1,672 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Host-side simulation of the se05x, to unit test code using this crate without hardware
+//!
+//! [`MockTransport`] implements the I2C traits required by [`T1oI2C`](crate::t1::T1oI2C) and speaks the T=1 protocol.
+//! `SELECT` is always answered with an ATR advertising all applet features, so [`Se05X::enable`](crate::se05x::Se05X::enable) works without setup.
+//! Other APDUs are answered from a script of expectations registered with [`expect`](MockTransport::expect).
+//! When no expectation is left, a minimal in-memory applet answers [`GetRandom`](crate::se05x::commands::GetRandom),
+//! [`CheckObjectExists`](crate::se05x::commands::CheckObjectExists), [`WriteBinary`](crate::se05x::commands::WriteBinary)
+//! and [`ReadObject`](crate::se05x::commands::ReadObject).
+//!
+//! Unexpected APDUs make the mock panic, failing the test.
+//!
+//! ```rust
+//! use se05x::mock::{MockDelay, MockTransport};
+//! use se05x::se05x::{commands::ReadObject, ObjectId, Se05X};
+//!
+//! let mut transport = MockTransport::new();
+//! transport.insert_object([0x01, 0x02, 0x03, 0x04], b"Some data");
+//! let mut se05x = Se05X::new(transport, 0x48, MockDelay);
+//! se05x.enable().unwrap();
+//! let command = ReadObject {
+//!     object_id: ObjectId([0x01, 0x02, 0x03, 0x04]),
+//!     offset: None,
+//!     length: None,
+//!     rsa_key_component: None,
+//! };
+//! let buf = &mut [0; 32];
+//! let response = se05x.run_command(&command, buf).unwrap();
+//! assert_eq!(response.data, b"Some data");
+//! ```
+
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec::Vec;
+
+use crate::embedded_hal::i2c::{Read, Write, WriteRead};
+use crate::embedded_hal::Delay;
+use crate::se05x::{
+    INS_MGMT, INS_READ, INS_WRITE, P1_BINARY, P1_DEFAULT, P2_DEFAULT, P2_EXIST, P2_RANDOM,
+    RESULT_FAILURE, RESULT_SUCCESS,
+};
+use crate::t1::{Crc, I2CErrorNack, Pcb, RBlockError, SBlock, Seq};
+
+const NAD_HD_TO_SE: u8 = 0x5A;
+const NAD_SE_TO_HD: u8 = 0xA5;
+const MAX_FRAME_DATA_LEN: usize = 0xFE;
+
+/// ATR of the applet returned to `SELECT`: version 3.1.0, all features, secure box 1.0
+const SELECT_RESPONSE: [u8; 9] = [0x03, 0x01, 0x00, 0x3F, 0xFF, 0x01, 0x00, 0x90, 0x00];
+
+/// Error of the [`MockTransport`]
+///
+/// Reading while no frame is pending is reported as an address NACK, as the se05x does while it is busy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockError;
+
+impl I2CErrorNack for MockError {
+    fn is_address_nack(&self) -> bool {
+        true
+    }
+    fn is_data_nack(&self) -> bool {
+        false
+    }
+}
+
+/// [`Delay`] that returns immediately
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MockDelay;
+
+impl Delay for MockDelay {
+    fn delay_us(&mut self, _us: u32) {}
+}
+
+/// Simulated se05x, see the [module documentation](self)
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    expectations: VecDeque<(Vec<u8>, Vec<u8>)>,
+    /// Binary files of the in-memory applet
+    objects: BTreeMap<[u8; 4], Vec<u8>>,
+    /// Bytes waiting to be read by the host
+    output: VecDeque<u8>,
+    /// Command APDU being received
+    command: Vec<u8>,
+    /// Response frames not sent yet, waiting for an acknowledgement of the host
+    pending: VecDeque<Vec<u8>>,
+    seq_snd: bool,
+    random_counter: u8,
+    /// Waiting time extensions requested before each response
+    wtx_requests: u32,
+    wtx_multiplier: u8,
+    /// IFSC announced in the ATR, `None` to return no ATR
+    ifsc: Option<u8>,
+    /// IFSD negotiated by the host
+    ifsd: Option<u8>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer the next APDU, which must be equal to `command`, with `response`
+    ///
+    /// `response` includes the status word. Expectations are consumed in the order they are registered.
+    pub fn expect(&mut self, command: &[u8], response: &[u8]) -> &mut Self {
+        self.expectations
+            .push_back((command.to_vec(), response.to_vec()));
+        self
+    }
+
+    /// Returns `true` if all expectations were consumed
+    pub fn is_done(&self) -> bool {
+        self.expectations.is_empty()
+    }
+
+    /// Content of a binary file of the in-memory applet
+    pub fn object(&self, object_id: [u8; 4]) -> Option<&[u8]> {
+        self.objects.get(&object_id).map(Vec::as_slice)
+    }
+
+    /// Add a binary file to the in-memory applet
+    pub fn insert_object(&mut self, object_id: [u8; 4], data: &[u8]) -> &mut Self {
+        self.objects.insert(object_id, data.to_vec());
+        self
+    }
+
+    /// Precede each response with `count` waiting time extension requests for `multiplier` BWT
+    pub fn request_wtx(&mut self, count: u32, multiplier: u8) -> &mut Self {
+        self.wtx_requests = count;
+        self.wtx_multiplier = multiplier;
+        self
+    }
+
+    /// Announce `ifsc` as the maximum information field size of received frames in the ATR
+    ///
+    /// Longer frames sent by the host make the mock panic.
+    pub fn set_ifsc(&mut self, ifsc: u8) -> &mut Self {
+        self.ifsc = Some(ifsc);
+        self
+    }
+
+    /// ATR returned on interface soft reset, announcing a BWT of 100ms and a MPOT of 1ms
+    #[rustfmt::skip]
+    fn atr(ifsc: u8) -> [u8; 25] {
+        [
+            // PVER, VID
+            0x01, 0xA0, 0x00, 0x00, 0x03, 0x96,
+            // DLLP: BWT, IFSC
+            0x04, 0x00, 0x64, 0x00, ifsc,
+            // PLID, PLP: MCF, config, MPOT, RFU, SEGT, WUT
+            0x02, 0x0B, 0x01, 0x90, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x0A, 0x00, 0x00,
+            // No historical bytes
+            0x00,
+        ]
+    }
+
+    fn queue_frame(&mut self, pcb: u8, data: &[u8]) {
+        let mut frame = Vec::with_capacity(data.len() + 5);
+        frame.extend_from_slice(&[NAD_SE_TO_HD, pcb, data.len() as u8]);
+        frame.extend_from_slice(data);
+        let crc = Crc::calculate(&frame).to_le_bytes();
+        frame.extend_from_slice(&crc);
+        self.output.extend(frame);
+    }
+
+    fn queue_i_block(&mut self, data: &[u8], more: bool) {
+        let seq = if self.seq_snd { Seq::ONE } else { Seq::ZERO };
+        self.seq_snd = !self.seq_snd;
+        self.queue_frame(Pcb::I(seq, more).to_byte(), data);
+    }
+
+    fn queue_response(&mut self, response: &[u8]) {
+        let frame_len = self.ifsd.map_or(MAX_FRAME_DATA_LEN, usize::from);
+        let mut chunks = response.chunks(frame_len).peekable();
+        let first = chunks.next().unwrap_or_default();
+        let more = chunks.peek().is_some();
+        self.pending = chunks.map(<[u8]>::to_vec).collect();
+        for _ in 0..self.wtx_requests {
+            self.queue_frame(Pcb::S(SBlock::WtxRequest).to_byte(), &[self.wtx_multiplier]);
+        }
+        self.queue_i_block(first, more);
+    }
+
+    fn receive_frame(&mut self, frame: &[u8]) {
+        let [nad, pcb, len, ref rest @ ..] = *frame else {
+            panic!("Frame too short: {frame:02x?}");
+        };
+        assert_eq!(nad, NAD_HD_TO_SE, "Bad NAD");
+        let len = len as usize;
+        assert_eq!(rest.len(), len + 2, "Bad frame length");
+        if let Some(ifsc) = self.ifsc {
+            assert!(len <= ifsc.into(), "Frame longer than IFSC: {len}");
+        }
+        let (data, crc) = rest.split_at(len);
+        assert_eq!(
+            Crc::calculate(&frame[..3 + len]).to_le_bytes(),
+            crc,
+            "Bad CRC"
+        );
+        match Pcb::parse(pcb).expect("Bad PCB") {
+            Pcb::S(SBlock::ResyncRequest) => {
+                self.seq_snd = false;
+                self.queue_frame(Pcb::S(SBlock::ResyncResponse).to_byte(), &[]);
+            }
+            Pcb::S(SBlock::InterfaceSoftResetRequest) => {
+                self.seq_snd = false;
+                // An invalid ATR makes the driver use the default values
+                let atr = self.ifsc.map(Self::atr);
+                self.queue_frame(
+                    Pcb::S(SBlock::InterfaceSoftResetResponse).to_byte(),
+                    atr.as_ref().map_or(&[], |atr| &atr[..]),
+                );
+            }
+            Pcb::S(SBlock::IfsRequest) => {
+                let [ifsd] = *data else {
+                    panic!("Bad IFS request: {data:02x?}");
+                };
+                self.ifsd = Some(ifsd);
+                self.queue_frame(Pcb::S(SBlock::IfsResponse).to_byte(), &[ifsd]);
+            }
+            Pcb::S(SBlock::EndOfApduSessionRequest) => {
+                self.queue_frame(Pcb::S(SBlock::EndOfApduSessionResponse).to_byte(), &[]);
+            }
+            Pcb::S(SBlock::SeChipResetRequest) => {
+                self.seq_snd = false;
+                self.queue_frame(Pcb::S(SBlock::SeChipResetResponse).to_byte(), &[]);
+            }
+            Pcb::S(SBlock::WtxResponse) => {
+                assert_eq!(data, [self.wtx_multiplier], "Bad WTX response");
+            }
+            Pcb::S(block) => panic!("Unexpected S-block: {block:?}"),
+            Pcb::R(_, RBlockError::NoError) => {
+                let next = self.pending.pop_front().expect("Unexpected R-block");
+                let more = !self.pending.is_empty();
+                self.queue_i_block(&next, more);
+            }
+            Pcb::R(_, error) => panic!("Host reported an error: {error:?}"),
+            Pcb::I(seq, more) => {
+                self.command.extend_from_slice(data);
+                if more {
+                    self.queue_frame(Pcb::R(!seq, RBlockError::NoError).to_byte(), &[]);
+                } else {
+                    let command = core::mem::take(&mut self.command);
+                    let response = self.process_apdu(&command);
+                    self.queue_response(&response);
+                }
+            }
+        }
+    }
+
+    fn process_apdu(&mut self, apdu: &[u8]) -> Vec<u8> {
+        let [cla, ins, p1, p2, ..] = *apdu else {
+            panic!("APDU too short: {apdu:02x?}");
+        };
+        if (cla, ins, p1, p2) == (0x00, 0xA4, 0x04, 0x00) {
+            return SELECT_RESPONSE.to_vec();
+        }
+        if let Some((command, response)) = self.expectations.pop_front() {
+            assert_eq!(apdu, command, "Unexpected APDU");
+            return response;
+        }
+        let data = apdu_data(apdu);
+        let mut response = Vec::new();
+        let ins_kind = ins & 0x1F;
+        let sw: u16 = match (cla, ins, p1, p2) {
+            _ if ins_kind == u8::from(INS_MGMT) && p2 == P2_RANDOM => {
+                let len = tlv(data, 0x41).expect("Missing length");
+                let len = u16::from_be_bytes(len.try_into().expect("Bad length")) as usize;
+                let random: Vec<u8> = (0..len)
+                    .map(|_| {
+                        self.random_counter = self.random_counter.wrapping_add(1);
+                        self.random_counter
+                    })
+                    .collect();
+                push_tlv(&mut response, 0x41, &random);
+                0x9000
+            }
+            _ if ins_kind == u8::from(INS_MGMT) && p2 == P2_EXIST => {
+                let id = object_id(data);
+                let result = if self.objects.contains_key(&id) {
+                    RESULT_SUCCESS
+                } else {
+                    RESULT_FAILURE
+                };
+                push_tlv(&mut response, 0x41, &[result]);
+                0x9000
+            }
+            _ if ins_kind == u8::from(INS_WRITE) && p1 & 0x1F == P1_BINARY => {
+                let id = object_id(data);
+                let offset = tlv(data, 0x42).map_or(0, |o| {
+                    u16::from_be_bytes(o.try_into().expect("Bad offset")) as usize
+                });
+                let file_length = tlv(data, 0x43)
+                    .map(|l| u16::from_be_bytes(l.try_into().expect("Bad file length")) as usize);
+                let value = tlv(data, 0x44).unwrap_or_default();
+                match (self.objects.get_mut(&id), file_length) {
+                    (None, Some(file_length)) if offset + value.len() <= file_length => {
+                        let mut file = alloc::vec![0; file_length];
+                        file[offset..][..value.len()].copy_from_slice(value);
+                        self.objects.insert(id, file);
+                        0x9000
+                    }
+                    (Some(file), None) if offset + value.len() <= file.len() => {
+                        file[offset..][..value.len()].copy_from_slice(value);
+                        0x9000
+                    }
+                    _ => 0x6A80,
+                }
+            }
+            _ if ins_kind == u8::from(INS_READ) && p1 == P1_DEFAULT && p2 == P2_DEFAULT => {
+                let id = object_id(data);
+                match self.objects.get(&id) {
+                    Some(file) => {
+                        let offset = tlv(data, 0x42).map_or(0, |o| {
+                            u16::from_be_bytes(o.try_into().expect("Bad offset")) as usize
+                        });
+                        let length = tlv(data, 0x43)
+                            .map_or(file.len().saturating_sub(offset), |l| {
+                                u16::from_be_bytes(l.try_into().expect("Bad length")) as usize
+                            });
+                        match file.get(offset..offset + length) {
+                            Some(value) => {
+                                push_tlv(&mut response, 0x41, value);
+                                0x9000
+                            }
+                            None => 0x6A80,
+                        }
+                    }
+                    None => 0x6A82,
+                }
+            }
+            _ => panic!("Unexpected APDU: {apdu:02x?}"),
+        };
+        response.extend_from_slice(&sw.to_be_bytes());
+        response
+    }
+}
+
+/// Data field of a command APDU, supporting short and extended lengths
+fn apdu_data(apdu: &[u8]) -> &[u8] {
+    match apdu {
+        [_, _, _, _] | [_, _, _, _, _] | [_, _, _, _, 0, _, _] => &[],
+        [_, _, _, _, 0, lc1, lc2, rest @ ..] => &rest[..u16::from_be_bytes([*lc1, *lc2]) as usize],
+        [_, _, _, _, lc, rest @ ..] => &rest[..*lc as usize],
+        _ => &[],
+    }
+}
+
+/// Find the value of the TLV with `tag`
+fn tlv(mut data: &[u8], tag: u8) -> Option<&[u8]> {
+    while let [t, rest @ ..] = data {
+        let (len, rest) = match rest {
+            [0x81, len, rest @ ..] => (*len as usize, rest),
+            [0x82, len1, len2, rest @ ..] => (u16::from_be_bytes([*len1, *len2]) as usize, rest),
+            [len, rest @ ..] => (*len as usize, rest),
+            [] => return None,
+        };
+        let (value, rest) = rest.split_at(len.min(rest.len()));
+        if *t == tag {
+            return Some(value);
+        }
+        data = rest;
+    }
+    None
+}
+
+fn push_tlv(buf: &mut Vec<u8>, tag: u8, value: &[u8]) {
+    buf.push(tag);
+    match value.len() {
+        len @ 0..=0x7F => buf.push(len as u8),
+        len @ 0x80..=0xFF => buf.extend_from_slice(&[0x81, len as u8]),
+        len => {
+            buf.push(0x82);
+            buf.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+    }
+    buf.extend_from_slice(value);
+}
+
+fn object_id(data: &[u8]) -> [u8; 4] {
+    tlv(data, 0x41)
+        .and_then(|id| id.try_into().ok())
+        .expect("Missing object ID")
+}
+
+impl Write<u8> for MockTransport {
+    type Error = MockError;
+
+    fn write(&mut self, _address: u8, bytes: &[u8]) -> Result<(), MockError> {
+        self.receive_frame(bytes);
+        Ok(())
+    }
+}
+
+impl Read<u8> for MockTransport {
+    type Error = MockError;
+
+    fn read(&mut self, _address: u8, buffer: &mut [u8]) -> Result<(), MockError> {
+        if self.output.len() < buffer.len() {
+            return Err(MockError);
+        }
+        let len = buffer.len();
+        for (dst, src) in buffer.iter_mut().zip(self.output.drain(..len)) {
+            *dst = src;
+        }
+        Ok(())
+    }
+}
+
+impl WriteRead<u8> for MockTransport {
+    type Error = MockError;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), MockError> {
+        self.write(address, bytes)?;
+        self.read(address, buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::commands::{CheckObjectExists, ReadObject, WriteBinary};
+    use crate::se05x::{ObjectId, Se05X, MIN_SCRATCH_LEN};
+    use crate::t1::{FrameSender, T1Config, T1oI2C, WaitReason};
+    use alloc::sync::Arc;
+    use core::sync::atomic::{AtomicU32, Ordering};
+    use iso7816::Status;
+
+    #[test]
+    fn in_memory_applet() {
+        let mut se05x = Se05X::new(MockTransport::new(), 0x48, MockDelay);
+        let atr = se05x.enable().unwrap();
+        assert_eq!((atr.major, atr.minor), (3, 1));
+
+        let object_id = ObjectId([0x01, 0x02, 0x03, 0x04]);
+        let exists = |se05x: &mut Se05X<T1oI2C<MockTransport, MockDelay>>| {
+            se05x
+                .run_command(&CheckObjectExists { object_id }, &mut [0; 16])
+                .unwrap()
+                .result
+                .is_success()
+        };
+        assert!(!exists(&mut se05x));
+
+        // Long enough to be chained over several frames in both directions
+        let data: Vec<u8> = (0..600).map(|i| i as u8).collect();
+        se05x
+            .run_command(
+                &WriteBinary {
+                    transient: false,
+                    policy: None,
+                    object_id,
+                    offset: None,
+                    file_length: Some(600.into()),
+                    data: Some(&data),
+                },
+                &mut [0; 2],
+            )
+            .unwrap();
+        assert!(exists(&mut se05x));
+
+        let buf = &mut [0; 700];
+        let response = se05x
+            .run_command(
+                &ReadObject {
+                    object_id,
+                    offset: None,
+                    length: None,
+                    rsa_key_component: None,
+                },
+                buf,
+            )
+            .unwrap();
+        assert_eq!(response.data, &*data);
+    }
+
+    #[test]
+    fn scratch_buffer() {
+        let mut se05x = Se05X::new(MockTransport::new(), 0x48, MockDelay);
+        se05x.enable().unwrap();
+
+        let object_id = ObjectId([0x01, 0x02, 0x03, 0x04]);
+        let data: Vec<u8> = (0..600).map(|i| i as u8).collect();
+        let scratch = &mut [0; MIN_SCRATCH_LEN + 700];
+        se05x
+            .run_command_with_scratch(
+                &WriteBinary {
+                    transient: false,
+                    policy: None,
+                    object_id,
+                    offset: None,
+                    file_length: Some(600.into()),
+                    data: Some(&data),
+                },
+                scratch,
+            )
+            .unwrap();
+        let response = se05x
+            .run_command_with_scratch(
+                &ReadObject {
+                    object_id,
+                    offset: None,
+                    length: None,
+                    rsa_key_component: None,
+                },
+                scratch,
+            )
+            .unwrap();
+        assert_eq!(response.data, &*data);
+    }
+
+    #[test]
+    fn waiting_time_extension() {
+        let mut transport = MockTransport::new();
+        transport.request_wtx(3, 10);
+        let mut se05x = Se05X::new(transport, 0x48, MockDelay);
+        se05x.enable().unwrap();
+
+        // 3 extensions of 10 times the default BWT of 100ms exceed a 2s limit
+        let mut transport = MockTransport::new();
+        transport.request_wtx(3, 10);
+        let config = T1Config {
+            max_wtx_us: 2_000_000,
+            ..Default::default()
+        };
+        let mut se05x = Se05X::with_config(transport, 0x48, MockDelay, config);
+        assert_eq!(
+            se05x.enable(),
+            Err(crate::se05x::Error::T1(crate::t1::Error::Timeout))
+        );
+    }
+
+    #[test]
+    fn wait_hook() {
+        let extensions = Arc::new(AtomicU32::new(0));
+        let counted = extensions.clone();
+        let kick_watchdog = move |reason| {
+            if reason == WaitReason::TimeExtension {
+                counted.fetch_add(1, Ordering::Relaxed);
+            }
+        };
+
+        let mut transport = MockTransport::new();
+        transport.request_wtx(3, 10);
+        let mut se05x = Se05X::new(transport, 0x48, MockDelay);
+        se05x.set_wait_hook(Some(Box::leak(Box::new(kick_watchdog))));
+        se05x.enable().unwrap();
+        assert_eq!(extensions.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn poll_response() {
+        const COMMAND: [u8; 5] = [0x80, 0x04, 0x00, 0x20, 0x00];
+        let mut transport = MockTransport::new();
+        transport
+            .expect(&COMMAND, &[0x01, 0x02, 0x90, 0x00])
+            .request_wtx(2, 1);
+        let mut t1 = T1oI2C::new(transport, 0x48, MockDelay);
+        t1.interface_soft_reset(&mut [0; 64]).unwrap();
+        let mut sender = FrameSender::new(&mut t1, COMMAND.len());
+        assert_eq!(sender.write_data(&COMMAND).unwrap(), COMMAND.len());
+
+        let response_buf = &mut [0; 16];
+        let mut reception = t1.start_reception();
+        let mut extensions = 0;
+        let (len, status) = loop {
+            match t1.poll_receive_apdu(response_buf, &mut reception) {
+                Ok(response) => break response,
+                Err(nb::Error::WouldBlock) => {
+                    assert_eq!(reception.wait_us(), 100_000);
+                    extensions += 1;
+                }
+                Err(nb::Error::Other(err)) => panic!("{err:?}"),
+            }
+        };
+        assert_eq!(extensions, 2);
+        assert_eq!(
+            (&response_buf[..len], status),
+            (&[0x01, 0x02][..], Status::Success)
+        );
+    }
+
+    #[test]
+    fn information_field_size() {
+        let mut transport = MockTransport::new();
+        transport.set_ifsc(32);
+        let mut se05x = Se05X::new(transport, 0x48, MockDelay);
+        se05x.enable().unwrap();
+        assert_eq!(se05x.transport().ifsc(), 32);
+        se05x.transport().negotiate_ifsd(64).unwrap();
+
+        let object_id = ObjectId([0x01, 0x02, 0x03, 0x04]);
+        let data: Vec<u8> = (0..300).map(|i| i as u8).collect();
+        se05x
+            .run_command(
+                &WriteBinary {
+                    transient: false,
+                    policy: None,
+                    object_id,
+                    offset: None,
+                    file_length: Some(300.into()),
+                    data: Some(&data),
+                },
+                &mut [0; 2],
+            )
+            .unwrap();
+        let buf = &mut [0; 400];
+        let response = se05x
+            .run_command(
+                &ReadObject {
+                    object_id,
+                    offset: None,
+                    length: None,
+                    rsa_key_component: None,
+                },
+                buf,
+            )
+            .unwrap();
+        assert_eq!(response.data, &*data);
+    }
+
+    #[test]
+    fn link_control() {
+        let mut transport = MockTransport::new();
+        transport.insert_object([1, 2, 3, 4], &[]);
+        let mut se05x = Se05X::new(transport, 0x48, MockDelay);
+        se05x.enable().unwrap();
+        se05x.end_of_apdu_session().unwrap();
+        se05x.resync().unwrap();
+        se05x.soft_reset().unwrap();
+        assert_eq!(se05x.chip_reset().unwrap().major, 3);
+        let response = se05x
+            .run_command(
+                &CheckObjectExists {
+                    object_id: ObjectId([1, 2, 3, 4]),
+                },
+                &mut [0; 16],
+            )
+            .unwrap();
+        assert!(response.result.is_success());
+    }
+
+    #[test]
+    fn expectations() {
+        let mut transport = MockTransport::new();
+        transport.expect(
+            &[
+                0x80, 0x04, 0x00, 0x27, 0x00, 0x00, 0x06, 0x41, 0x04, 1, 2, 3, 4, 0x00, 0x00,
+            ],
+            &[0x6A, 0x82],
+        );
+        let mut se05x = Se05X::new(transport, 0x48, MockDelay);
+        se05x.enable().unwrap();
+        let err = se05x
+            .run_command(
+                &CheckObjectExists {
+                    object_id: ObjectId([1, 2, 3, 4]),
+                },
+                &mut [0; 16],
+            )
+            .unwrap_err();
+        assert_eq!(err, crate::se05x::Error::Status(0x6A82.into()));
+    }
+}
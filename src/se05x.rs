@@ -1,37 +1,191 @@
 // Copyright (C) 2023 Nitrokey GmbH
 // SPDX-License-Identifier: LGPL-3.0-only
 
-use core::{array::TryFromSliceError, convert::Infallible, fmt::Debug};
+use core::{
+    array::TryFromSliceError,
+    convert::Infallible,
+    fmt::{self, Debug},
+    ops::{Range, RangeBounds, RangeInclusive},
+};
 
+#[cfg(feature = "transport")]
 use crate::embedded_hal::Delay;
 use bitflags::bitflags;
 use delog::hexstr;
 use hex_literal::hex;
 use iso7816::{
     command::{
-        class::{NO_SM_CLA, SM_CLA, ZERO_CLA},
-        writer::IntoWriter,
+        class::{Class, NO_SM_CLA, SM_CLA, ZERO_CLA},
         CommandBuilder, DataSource, DataStream, ExpectedLen, Writer,
     },
     tlv::{take_data_object, Tag, Tlv},
     Instruction, Status,
 };
 
-use crate::t1::{self, DataReceived, FrameSender, I2CForT1, T1oI2C};
+use crate::t1::{self, FRAME_BUFFER_LEN};
+#[cfg(feature = "transport")]
+use crate::t1::{I2CForT1, T1Config, T1oI2C, WaitHook};
 
 use self::commands::{CreateEcCurve, SetEcCurveParam};
 
 pub mod commands;
 
+pub mod apdu;
+pub mod attestation;
+pub mod backup;
+pub mod batch;
+pub mod block_cipher;
+#[cfg(feature = "aead")]
+pub mod ccm;
+pub mod certificate;
+pub mod cipher_stream;
 pub mod constants;
+pub mod digest;
+pub mod ec_key;
+pub mod ecdaa;
+pub mod ecdh;
+pub mod expected_len;
+pub mod gp;
+pub mod i2cm;
+pub mod kdf;
+#[cfg(feature = "std")]
+pub mod key_uri;
+pub mod keystore;
+pub mod mac;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod pcr;
+pub mod poison;
 pub mod policies;
+pub mod provision;
+pub mod reserved;
+pub mod rng;
+#[cfg(feature = "rsa")]
+pub mod rsa;
+#[cfg(feature = "rustls")]
+pub mod rustls_signer;
+#[cfg(feature = "aes-session")]
+pub mod scp03;
+#[cfg(feature = "aes-session")]
+pub mod secure_channel;
+pub mod secure_messaging;
+pub mod session;
+#[cfg(any(feature = "p256", feature = "ed25519"))]
+pub mod signer;
+pub mod sigutil;
+#[cfg(feature = "tcp")]
+pub mod tcp;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+pub mod tls;
+pub mod transport;
+
+use self::transport::{ApduSink, ApduWriter, RawCommand, RawCommandSent, Se05xTransport};
+
+/// Driver for the se05x, running commands over the transport `T`
+///
+/// [`Se05X::new`] creates a driver using the T=1 over I2C link. Other links are used with [`Se05X::from_transport`].
+pub struct Se05X<T> {
+    transport: T,
+    /// Pattern filling response buffers before each command, in debug builds
+    response_poison: Option<u8>,
+    apdu_hook: Option<fn(&ApduTraceEvent)>,
+    /// ATR returned by the last [`enable`](Se05X::enable)
+    atr: Option<Atr>,
+    explain_policy_violations: bool,
+    /// Retries of commands failing with a transient error, applied by [`run_command`](Se05X::run_command)
+    retry_policy: Option<RetryPolicy>,
+    /// Number of commands retried because of a transient error
+    retries: u32,
+    /// AID of the applet selected by [`enable`](Se05X::enable)
+    aid: &'static [u8],
+    /// Re-select the applet and retry commands failing after a power loss
+    auto_recovery: bool,
+    /// A command was retried after re-selecting the applet since the last [`take_recovered`](Se05X::take_recovered)
+    recovered: bool,
+    /// Clock timing the exchanges recorded in `metrics`
+    #[cfg(feature = "metrics")]
+    metrics_clock: Option<&'static mut (dyn metrics::MetricsClock + Send)>,
+    #[cfg(feature = "metrics")]
+    metrics: metrics::CommandMetrics,
+}
 
-pub struct Se05X<Twi, D> {
-    t1: T1oI2C<Twi, D>,
+/// Command exchange reported to the hook set with [`Se05X::set_apdu_hook`]
+///
+/// Payloads are not included, so that key material is never exposed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApduTraceEvent {
+    pub cla: u8,
+    pub ins: u8,
+    pub p1: u8,
+    pub p2: u8,
+    /// Length of the command APDU, including the header
+    pub len: usize,
+    /// Status word of the response, `None` if the exchange failed
+    pub status: Option<Status>,
 }
 
 pub const MAX_APDU_PAYLOAD_LENGTH: usize = 889;
+/// Maximum size of the APDU header, extended `Lc` and `Le` fields
+const MAX_APDU_OVERHEAD: usize = 9;
+/// Size of the data chunks sent by [`write_binary_chunked`](Se05X::write_binary_chunked) and [`cipher_update_chunked`](Se05X::cipher_update_chunked)
+pub const CHUNK_LEN: usize = 512;
+/// Offsets of binary files are 16-bit, data cannot be written past this length
+const MAX_BINARY_FILE_LEN: usize = 0x10000;
+/// Status word of operations forbidden by the policy of the object, without an authenticated session
+const SW_SECURITY_STATUS_NOT_SATISFIED: u16 = 0x6982;
+/// Status word of operations forbidden by the policy of the object
+const SW_CONDITIONS_NOT_SATISFIED: u16 = 0x6985;
+/// Minimum length of the scratch buffer of [`Se05X::run_command_with_scratch`]: a frame and a status word
+pub const MIN_SCRATCH_LEN: usize = FRAME_BUFFER_LEN + 2;
+
+/// Compile-time check of the length of scratch buffers
+struct ScratchLen<const N: usize>;
+
+impl<const N: usize> ScratchLen<N> {
+    const CHECK: () = assert!(N >= MIN_SCRATCH_LEN, "scratch buffer too small");
+}
 
+/// Status word of failed authentications, the low nibble holding the remaining attempts
+const SW_AUTH_FAILED: u16 = 0x63C0;
+/// Status word of failed writes to the persistent memory
+const SW_MEMORY_FAILURE: u16 = 0x6581;
+/// Status word of operations running out of persistent memory
+const SW_NOT_ENOUGH_MEMORY: u16 = 0x6A84;
+/// Status word of commands with an instruction unknown to the selected application
+const SW_INS_NOT_SUPPORTED: u16 = 0x6D00;
+/// Status word of commands with a class unknown to the selected application
+const SW_CLA_NOT_SUPPORTED: u16 = 0x6E00;
+
+/// Errors returned by the driver
+///
+/// Errors can be converted to a [`Status`] to be forwarded by firmware dispatching APDUs, and back with `Error::from`.
+/// Status words returned by the se05x are passed unchanged. Errors raised by the driver itself use the following status words,
+/// which are kept stable across versions:
+///
+/// | Status word       | Error                              | Category                       |
+/// |-------------------|------------------------------------|--------------------------------|
+/// | `0x0000`          | [`Error::Unknown`]                 | [`ErrorCategory::Internal`]    |
+/// | `0x0001`          | [`Error::Tlv`]                     | [`ErrorCategory::Parsing`]     |
+/// | `0x0002`          | [`t1::Error::Unknown`]             | [`ErrorCategory::Transport`]   |
+/// | `0x0003`          | [`t1::Error::AddressNack`]         | [`ErrorCategory::Transport`]   |
+/// | `0x0004`          | [`t1::Error::DataNack`]            | [`ErrorCategory::Transport`]   |
+/// | `0x0005`          | [`t1::Error::BadCrc`]              | [`ErrorCategory::Transport`]   |
+/// | `0x0006`          | [`t1::Error::BadPcb`]              | [`ErrorCategory::Transport`]   |
+/// | `0x0007`          | [`t1::Error::BadAddress`]          | [`ErrorCategory::Transport`]   |
+/// | `0x0008`          | [`t1::Error::ReceptionBuffer`]     | [`ErrorCategory::Transport`]   |
+/// | `0x0009`          | [`t1::Error::Timeout`]             | [`ErrorCategory::Transport`]   |
+/// | `0x000A`          | [`Error::CommandTooLong`]          | [`ErrorCategory::Internal`]    |
+/// | `0x000B`          | [`Error::FreshnessMismatch`]       | [`ErrorCategory::Parsing`]     |
+/// | `0x1000..=0x1FFF` | [`t1::Error::Line`], line number   | [`ErrorCategory::Transport`]   |
+/// | `0x2000..=0x2FFF` | [`Error::Line`], line number       | [`ErrorCategory::Internal`]    |
+/// | `0x3000..=0x300F` | [`Error::Unsupported`], feature bit | [`ErrorCategory::Internal`]    |
+///
+/// Line numbers above `0x0FFF` are saturated, so they don't survive the round-trip.
+/// [`Error::PolicyViolation`] converts to the status word returned by the se05x, and comes back as [`Error::Status`].
+/// So does [`Error::AuthFailed`], with the remaining attempts saturated at 15.
+/// [`Error::NotDeleted`] converts to `0x000C`, which comes back as [`Error::Status`] since the object ID is lost.
+/// Likewise, [`Error::InvalidKeyLength`] converts to `0x000D`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
     Unknown,
@@ -39,6 +193,45 @@ pub enum Error {
     T1(t1::Error),
     Status(Status),
     Tlv,
+    /// The command exceeds [`MAX_APDU_PAYLOAD_LENGTH`] and was not sent
+    CommandTooLong,
+    /// The applet variant does not offer the feature, according to the [`Atr`]
+    ///
+    /// The command was not sent. See [`Se05X::supports`].
+    Unsupported(AppletConfig),
+    /// An attestation does not echo the freshness random of the request
+    FreshnessMismatch,
+    /// The policy of `object_id` forbids the operation
+    ///
+    /// Only returned by helpers when [explaining policy violations](Se05X::set_explain_policy_violations).
+    PolicyViolation {
+        object_id: ObjectId,
+        /// Authentication object that must be used in a session for the operation
+        required_auth: ObjectId,
+        /// Status word returned by the se05x
+        status: Status,
+    },
+    /// Authentication with an authentication object failed (`0x63Cx`)
+    ///
+    /// Only returned by the authentication helpers, such as [`Session::verify_user_id`](session::Session::verify_user_id).
+    /// See [`Se05X::read_max_attempts`] to monitor the attempts before the object is locked.
+    AuthFailed {
+        /// Attempts left before the authentication object is locked, as reported by the se05x
+        remaining_attempts: u8,
+    },
+    /// `object_id` still exists after it was deleted
+    ///
+    /// Only returned by [`Se05X::delete_and_verify`], for example when the policy of the object forbids its deletion.
+    NotDeleted {
+        object_id: ObjectId,
+    },
+    /// A key of `len` bytes cannot be stored as a `key_type` key
+    ///
+    /// The command was not sent. See [`SymmKeyType::check_key_len`].
+    InvalidKeyLength {
+        key_type: SymmKeyType,
+        len: usize,
+    },
 }
 
 impl From<Infallible> for Error {
@@ -55,7 +248,10 @@ impl From<TryFromSliceError> for Error {
 impl From<Error> for Status {
     fn from(value: Error) -> Self {
         match value {
-            Error::Status(status) => status,
+            Error::Status(status) | Error::PolicyViolation { status, .. } => status,
+            Error::AuthFailed { remaining_attempts } => {
+                Status::from(SW_AUTH_FAILED | u16::from(remaining_attempts.min(0x0F)))
+            }
             Error::Unknown => Status::from(0x0000),
             Error::Tlv => Status::from(0x0001),
             Error::T1(t1::Error::Unknown) => Status::from(0x0002),
@@ -66,18 +262,241 @@ impl From<Error> for Status {
             Error::T1(t1::Error::BadAddress) => Status::from(0x0007),
             Error::T1(t1::Error::ReceptionBuffer) => Status::from(0x0008),
             Error::T1(t1::Error::Timeout) => Status::from(0x0009),
+            Error::CommandTooLong => Status::from(0x000A),
+            Error::FreshnessMismatch => Status::from(0x000B),
+            Error::NotDeleted { .. } => Status::from(0x000C),
+            Error::InvalidKeyLength { .. } => Status::from(0x000D),
             Error::T1(t1::Error::Line(l)) => Status::from(0x1000 + l.min(0x0FFF) as u16),
             Error::Line(l) => Status::from(0x2000 + l.min(0x0FFF) as u16),
+            Error::Unsupported(feature) => {
+                Status::from(0x3000 + (feature.bits().trailing_zeros() as u16).min(0x000F))
+            }
+        }
+    }
+}
+
+impl From<Status> for Error {
+    fn from(value: Status) -> Self {
+        let sw: u16 = value.into();
+        match sw {
+            0x0000 => Error::Unknown,
+            0x0001 => Error::Tlv,
+            0x0002 => Error::T1(t1::Error::Unknown),
+            0x0003 => Error::T1(t1::Error::AddressNack),
+            0x0004 => Error::T1(t1::Error::DataNack),
+            0x0005 => Error::T1(t1::Error::BadCrc),
+            0x0006 => Error::T1(t1::Error::BadPcb),
+            0x0007 => Error::T1(t1::Error::BadAddress),
+            0x0008 => Error::T1(t1::Error::ReceptionBuffer),
+            0x0009 => Error::T1(t1::Error::Timeout),
+            0x000A => Error::CommandTooLong,
+            0x000B => Error::FreshnessMismatch,
+            0x1000..=0x1FFF => Error::T1(t1::Error::Line((sw - 0x1000).into())),
+            0x2000..=0x2FFF => Error::Line((sw - 0x2000).into()),
+            0x3000..=0x300F => {
+                Error::Unsupported(AppletConfig::from_bits_retain(1 << (sw - 0x3000)))
+            }
+            _ => Error::Status(value),
         }
     }
 }
 
+/// Coarse classification of an [`Error`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// Communication with the se05x failed
+    Transport,
+    /// The se05x returned an error status word
+    Status,
+    /// The response from the se05x could not be parsed
+    Parsing,
+    /// Error in the driver, or invalid arguments
+    Internal,
+}
+
 impl From<t1::Error> for Error {
     fn from(value: t1::Error) -> Self {
         Self::T1(value)
     }
 }
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown => f.write_str("Unknown error"),
+            Self::Line(l) => write!(f, "Error coming from line: {l}"),
+            Self::T1(err) => write!(f, "T=1 error: {err}"),
+            Self::Status(status) => {
+                let sw: u16 = (*status).into();
+                write!(f, "The se05x returned the status word {sw:04X}")
+            }
+            Self::Tlv => f.write_str("Failed to parse the TLV data of the response"),
+            Self::CommandTooLong => f.write_str("Command too long"),
+            Self::Unsupported(feature) => {
+                write!(f, "The applet does not support {feature:?}")
+            }
+            Self::FreshnessMismatch => {
+                f.write_str("The attestation does not echo the freshness random of the request")
+            }
+            Self::PolicyViolation {
+                object_id,
+                required_auth,
+                status,
+            } => {
+                let sw: u16 = (*status).into();
+                write!(
+                    f,
+                    "The policy of {object_id} requires a session authenticated with {required_auth} (status word {sw:04X})"
+                )
+            }
+            Self::AuthFailed { remaining_attempts } => {
+                write!(
+                    f,
+                    "Authentication failed, {remaining_attempts} attempts remaining"
+                )
+            }
+            Self::InvalidKeyLength { key_type, len } => {
+                write!(f, "Invalid {key_type:?} key length: {len} bytes")
+            }
+            Self::NotDeleted { object_id } => {
+                write!(f, "{object_id} still exists after its deletion")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl Error {
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Error::T1(_) => ErrorCategory::Transport,
+            Error::Status(_)
+            | Error::PolicyViolation { .. }
+            | Error::AuthFailed { .. }
+            | Error::NotDeleted { .. } => ErrorCategory::Status,
+            Error::Tlv | Error::FreshnessMismatch => ErrorCategory::Parsing,
+            Error::Unknown
+            | Error::Line(_)
+            | Error::CommandTooLong
+            | Error::Unsupported(_)
+            | Error::InvalidKeyLength { .. } => ErrorCategory::Internal,
+        }
+    }
+
+    /// Returns `true` if the error is a status word reporting that the se05x is temporarily unable to process the command
+    ///
+    /// The se05x answers with `0x64xx` (execution error, non-volatile memory unchanged) while it is busy with
+    /// internal maintenance of its persistent memory, for example wear-leveling after many writes.
+    /// Retrying the command after a short delay is expected to succeed.
+    pub fn is_transient(&self) -> bool {
+        self.device_condition() == Some(DeviceCondition::Busy)
+    }
+
+    /// Returns `true` if the error is characteristic of a se05x that was power-cycled since the applet was selected
+    ///
+    /// After a power loss, the applet is no longer selected, so that its commands are rejected with `0x6D00` or `0x6E00`.
+    /// This is a heuristic: these errors can have other causes.
+    /// T=1 errors, such as CRC errors or timeouts, are not considered: they are more often caused by a glitch of the link,
+    /// after which the command may have been executed. See [`Se05X::set_auto_recovery`].
+    pub fn is_power_loss(&self) -> bool {
+        match self {
+            Error::Status(status) => matches!(
+                u16::from(*status),
+                SW_INS_NOT_SUPPORTED | SW_CLA_NOT_SUPPORTED
+            ),
+            _ => false,
+        }
+    }
+
+    /// Convert a `0x63Cx` status word returned by an authentication into [`Error::AuthFailed`]
+    fn auth_failure(self) -> Self {
+        let Error::Status(status) = self else {
+            return self;
+        };
+        let sw: u16 = status.into();
+        if sw & 0xFFF0 != SW_AUTH_FAILED {
+            return self;
+        }
+        Error::AuthFailed {
+            remaining_attempts: (sw & 0x000F) as u8,
+        }
+    }
+
+    /// Condition of the se05x reported by the status word of the error, if any
+    pub fn device_condition(&self) -> Option<DeviceCondition> {
+        let Error::Status(status) = self else {
+            return None;
+        };
+        let sw: u16 = (*status).into();
+        match sw {
+            0x6400..=0x64FF => Some(DeviceCondition::Busy),
+            SW_MEMORY_FAILURE => Some(DeviceCondition::MemoryFailure),
+            SW_NOT_ENOUGH_MEMORY => Some(DeviceCondition::MemoryFull),
+            _ => None,
+        }
+    }
+}
+
+/// Resource condition of the se05x, reported by some status words
+///
+/// See [`Error::device_condition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceCondition {
+    /// The se05x is busy with the maintenance of its persistent memory (`0x64xx`)
+    ///
+    /// The command can be retried, see [`Se05X::set_retry_policy`].
+    Busy,
+    /// Writing the persistent memory failed (`0x6581`)
+    MemoryFailure,
+    /// The persistent memory is full (`0x6A84`)
+    ///
+    /// Objects must be deleted before the command can succeed.
+    MemoryFull,
+}
+
+/// Result of [`Se05X::is_alive`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Liveness {
+    /// The applet answered
+    Alive,
+    /// The se05x answered, but the applet is not selected: call [`Se05X::enable`]
+    NeedsReenable,
+    /// The se05x did not answer: it may need to be power-cycled
+    Unresponsive,
+}
+
+/// Bounded exponential backoff for retrying commands that failed with a [transient](Error::is_transient) error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt
+    pub max_retries: u8,
+    /// Delay before the first retry (microseconds)
+    pub initial_backoff_us: u32,
+    /// Upper bound of the delay between two attempts (microseconds)
+    pub max_backoff_us: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff_us: 1_000,
+            max_backoff_us: 50_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the retry number `attempt`, starting at 0
+    pub fn backoff_us(&self, attempt: u8) -> u32 {
+        self.initial_backoff_us
+            .saturating_mul(1 << attempt.min(31))
+            .min(self.max_backoff_us)
+    }
+}
+
 pub trait Se05XResponse<'a>: Sized {
     fn from_response(data: &'a [u8]) -> Result<Self, Error>;
 }
@@ -90,16 +509,48 @@ impl<'a> Se05XResponse<'a> for () {
 
 pub trait Se05XCommand<W: Writer>: DataStream<W> {
     type Response<'a>: Se05XResponse<'a>;
+
+    /// Reject invalid combinations of fields before the command is sent
+    fn validate(&self) -> Result<(), Error> {
+        Ok(())
+    }
 }
 
 impl<W: Writer, C: Se05XCommand<W>> Se05XCommand<W> for &C {
     type Response<'a> = C::Response<'a>;
+
+    fn validate(&self) -> Result<(), Error> {
+        C::validate(self)
+    }
 }
 
+/// Combinator to run any command within a session
+///
+/// `se05x.run_command(&command.in_session(session_id), buf)` is equivalent to `se05x.run_session_command(session_id, &command, buf)`.
+pub trait InSession: Sized {
+    /// Wrap the command in a [`ProcessSessionCmd`] for `session_id`
+    fn in_session(self, session_id: SessionId) -> ProcessSessionCmd<Self> {
+        ProcessSessionCmd {
+            session_id,
+            apdu: self,
+        }
+    }
+}
+
+impl<C: DataSource> InSession for C {}
+
+/// AID of the IoT applet, selected by [`Se05X::enable`] unless [configured otherwise](Se05X::set_aid)
+///
+/// The IoT applet is installed under this AID on the SE050 and SE051 variants, for all the applet versions.
+/// The version of the applet is reported in the [`Atr`].
 pub const APP_ID: [u8; 0x10] = hex!("A0000003965453000000010300000000");
+/// AID of the Supplementary Security Domain of the IoT applet, holding the applet SCP03 keys
+pub const SSD_AID: [u8; 11] = hex!("D276000085304A434F9003");
+/// AID of the GlobalPlatform Issuer Security Domain
+pub const ISD_AID: [u8; 8] = hex!("A000000151000000");
 
 #[cfg(feature = "embedded-hal-v0.2.7")]
-impl<M, N, E> Se05X<crate::embedded_hal::Hal027<M>, crate::embedded_hal::Hal027<N>>
+impl<M, N, E> Se05X<T1oI2C<crate::embedded_hal::Hal027<M>, crate::embedded_hal::Hal027<N>>>
 where
     N: embedded_hal_v0_2_7::blocking::delay::DelayUs<u32>,
     M: embedded_hal_v0_2_7::blocking::i2c::Write<Error = E>
@@ -117,7 +568,7 @@ where
 }
 
 #[cfg(feature = "embedded-hal-v1.0")]
-impl<M, N, E> Se05X<crate::embedded_hal::Hal10<M>, crate::embedded_hal::Hal10<N>>
+impl<M, N, E> Se05X<T1oI2C<crate::embedded_hal::Hal10<M>, crate::embedded_hal::Hal10<N>>>
 where
     N: embedded_hal_v1_0::delay::DelayNs,
     M: embedded_hal_v1_0::i2c::I2c<Error = E>,
@@ -132,65 +583,418 @@ where
     }
 }
 
-impl<Twi: I2CForT1, D: Delay> Se05X<Twi, D> {
+#[cfg(feature = "transport")]
+impl<Twi: I2CForT1, D: Delay> Se05X<T1oI2C<Twi, D>> {
     pub fn new(twi: Twi, se_address: u8, delay: D) -> Self {
+        Self::with_config(twi, se_address, delay, T1Config::default())
+    }
+
+    /// Create a driver with custom timing and retry configuration of the T=1 layer
+    ///
+    /// Use this if the defaults are not reliable with the I2C bus of the board.
+    pub fn with_config(twi: Twi, se_address: u8, delay: D, config: T1Config) -> Self {
+        Self::from_transport(T1oI2C::with_config(twi, se_address, delay, config))
+    }
+
+    pub fn set_t1_retry_count(&mut self, value: u32) {
+        self.transport.retry_count = value;
+    }
+
+    /// Call `hook` while waiting for the se05x to respond, see [`T1oI2C::set_wait_hook`]
+    pub fn set_wait_hook(&mut self, hook: Option<&'static mut (dyn WaitHook + Send)>) {
+        self.transport.set_wait_hook(hook);
+    }
+
+    /// Resynchronize the T=1 sequence counters, to recover from bus errors
+    ///
+    /// The applet stays selected.
+    pub fn resync(&mut self) -> Result<(), Error> {
+        Ok(self.transport.resync()?)
+    }
+
+    /// Reset the T=1 interface and read the link parameters from its ATR
+    pub fn soft_reset(&mut self) -> Result<(), Error> {
+        self.transport.interface_soft_reset(&mut [0; 64])?;
+        Ok(())
+    }
+
+    /// Signal the end of the APDU session, letting the se05x enter its low power state
+    ///
+    /// The se05x wakes up on the next command.
+    pub fn end_of_apdu_session(&mut self) -> Result<(), Error> {
+        Ok(self.transport.end_of_apdu_session()?)
+    }
+
+    /// Perform a cold reset of the se05x, then [`enable`](Self::enable) it again
+    ///
+    /// Sessions and transient objects are lost.
+    pub fn chip_reset(&mut self) -> Result<Atr, Error> {
+        self.atr = None;
+        self.transport.chip_reset()?;
+        self.enable()
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Create a driver running commands over `transport`
+    pub fn from_transport(transport: T) -> Self {
         Self {
-            t1: T1oI2C::new(twi, se_address, delay),
+            transport,
+            response_poison: None,
+            apdu_hook: None,
+            atr: None,
+            explain_policy_violations: false,
+            retry_policy: None,
+            retries: 0,
+            aid: &APP_ID,
+            auto_recovery: false,
+            recovered: false,
+            #[cfg(feature = "metrics")]
+            metrics_clock: None,
+            #[cfg(feature = "metrics")]
+            metrics: metrics::CommandMetrics::default(),
         }
     }
 
-    pub fn set_t1_retry_count(&mut self, value: u32) {
-        self.t1.retry_count = value;
+    pub fn transport(&mut self) -> &mut T {
+        &mut self.transport
     }
 
-    fn receive_apdu<'buf>(
-        &mut self,
-        buffer: &'buf mut [u8],
-    ) -> Result<(&'buf [u8], Status), Error> {
-        match self.t1.receive_data(buffer)? {
-            DataReceived::IBlocks(len) if len >= 2 => Ok((
-                &buffer[..len - 2],
-                Status::from([buffer[len - 2], buffer[len - 1]]),
-            )),
-            DataReceived::SBlock {
-                block: _,
-                i_data: _,
-                s_data: _,
-            } => Err(Error::Line(line!())),
-            _ => {
-                error!("Got too short apdu");
-                Err(Error::Line(line!()))
+    pub fn into_transport(self) -> T {
+        self.transport
+    }
+
+    /// Fill the response buffer with `pattern` before each command
+    ///
+    /// This only has an effect in debug builds, and helps finding reads of stale response data.
+    /// See the [`poison`]() module.
+    pub fn set_response_poison(&mut self, pattern: Option<u8>) {
+        self.response_poison = pattern;
+    }
+
+    /// Report the header and status of each command exchanged with the se05x to `hook`
+    ///
+    /// `GET RESPONSE` commands used to reassemble long responses are reported too.
+    pub fn set_apdu_hook(&mut self, hook: Option<fn(&ApduTraceEvent)>) {
+        self.apdu_hook = hook;
+    }
+
+    /// Explain the policy errors of the high-level read and write helpers
+    ///
+    /// When a helper fails because of the policy of the object, its attributes are read to return an [`Error::PolicyViolation`]
+    /// naming the authentication object required, instead of the bare status word.
+    /// This costs an additional command on such failures.
+    pub fn set_explain_policy_violations(&mut self, explain: bool) {
+        self.explain_policy_violations = explain;
+    }
+
+    /// Turn `err`, returned by an operation on `object_id`, into an [`Error::PolicyViolation`] if it is caused by a policy
+    ///
+    /// Only has an effect if [explaining policy violations](Self::set_explain_policy_violations) is enabled.
+    /// If the attributes of the object can't be read, `err` is returned unchanged.
+    pub fn explain_policy_error(&mut self, object_id: ObjectId, err: Error) -> Error {
+        let Error::Status(status) = err else {
+            return err;
+        };
+        let sw: u16 = status.into();
+        if !self.explain_policy_violations
+            || !matches!(
+                sw,
+                SW_SECURITY_STATUS_NOT_SATISFIED | SW_CONDITIONS_NOT_SATISFIED
+            )
+        {
+            return err;
+        }
+        let buf = &mut [0; OBJECT_ATTRIBUTES_MAX_LEN + 8];
+        match self.run_command(
+            &commands::ReadAttributes {
+                object_id,
+                offset: None,
+                length: None,
+                rsa_key_component: None,
+            },
+            buf,
+        ) {
+            Ok(response) => Error::PolicyViolation {
+                object_id,
+                required_auth: response.attributes.authentication_object_identifier(),
+                status,
+            },
+            Err(_err) => {
+                error!("Failed to read the attributes of {object_id:?}: {_err:?}");
+                err
             }
         }
     }
 
+    /// Returns `true` if the policy of `object_id` allows `operation` outside of a session
+    ///
+    /// This lets user interfaces disable operations that would fail with a security status.
+    /// See [`ObjectAttributes::allows`] for the limits of the check.
+    pub fn can_perform(
+        &mut self,
+        object_id: ObjectId,
+        operation: policies::Operation,
+    ) -> Result<bool, Error> {
+        let buf = &mut [0; OBJECT_ATTRIBUTES_MAX_LEN + 8];
+        self.run_command(
+            &commands::ReadAttributes {
+                object_id,
+                offset: None,
+                length: None,
+                rsa_key_component: None,
+            },
+            buf,
+        )?
+        .attributes
+        .allows(ObjectId::INVALID, operation)
+    }
+
+    /// Send `command` and receive the response into `response_buf`, returning its length and status
+    fn exchange(
+        &mut self,
+        command: &dyn for<'a> DataStream<ApduWriter<'a>>,
+        frame_buffer: Option<&mut [u8; FRAME_BUFFER_LEN]>,
+        response_buf: &mut [u8],
+    ) -> Result<(usize, Status), Error> {
+        #[cfg(feature = "metrics")]
+        let start = self.metrics_clock.as_mut().map(|clock| clock.now_us());
+        let len = command.len();
+        let mut header = [0; 4];
+        let mut write = |sink: &mut dyn ApduSink| {
+            let mut writer = ApduWriter::new(sink);
+            let written = command.to_writer(&mut writer);
+            header = writer.header();
+            written
+        };
+        let written = match frame_buffer {
+            Some(frame_buffer) => self.transport.send_apdu_in(len, frame_buffer, &mut write),
+            None => write(&mut self.transport.send_apdu(len)?),
+        };
+        let [cla, ins, p1, p2] = header;
+        let result = written.and_then(|()| {
+            self.transport
+                .receive_apdu(response_buf)
+                .map(|(response, status)| (response.len(), status))
+        });
+        if let Some(hook) = self.apdu_hook {
+            hook(&ApduTraceEvent {
+                cla,
+                ins,
+                p1,
+                p2,
+                len,
+                status: result.as_ref().ok().map(|&(_, status)| status),
+            });
+        }
+        #[cfg(feature = "metrics")]
+        if let (Some(clock), Some(start)) = (self.metrics_clock.as_mut(), start) {
+            self.metrics
+                .record(ins, clock.now_us().saturating_sub(start));
+        }
+        result
+    }
+
+    /// Select the IoT applet under `aid` instead of [`APP_ID`] in the next calls to [`enable`](Self::enable)
+    ///
+    /// Use this if the applet instance is installed under a non-default AID.
+    pub fn set_aid(&mut self, aid: &'static [u8]) {
+        self.aid = aid;
+    }
+
     pub fn enable(&mut self) -> Result<Atr, Error> {
-        self.t1.resync()?;
-        self.t1.interface_soft_reset(&mut [0; 64])?;
+        self.transport.reset()?;
         let mut resp_buffer = [0; 9];
-        let atr = self.run_command(&Select, &mut resp_buffer)?;
+        let atr = self.run_command(&SelectAid { aid: self.aid }, &mut resp_buffer)?;
         debug!("Got ATR: {atr:02x?}");
+        self.atr = Some(atr);
         Ok(atr)
     }
 
+    /// Check that the se05x answers and that the applet is selected, with a [`GetVersion`](commands::GetVersion)
+    ///
+    /// This lets watchdogs decide whether to call [`enable`](Self::enable) again or to power-cycle the se05x.
+    /// No command is sent before the first call to [`enable`](Self::enable). The timeouts are those of the transport,
+    /// and the [retry policy](Self::set_retry_policy) applies.
+    pub fn is_alive(&mut self) -> Liveness {
+        if self.atr.is_none() {
+            return Liveness::NeedsReenable;
+        }
+        match self.run_command(&commands::GetVersion {}, &mut [0; 16]) {
+            Ok(_) => Liveness::Alive,
+            Err(err) if err.is_transient() => Liveness::Alive,
+            Err(err) if err.category() == ErrorCategory::Transport => {
+                warn!("se05x unresponsive: {err:?}");
+                Liveness::Unresponsive
+            }
+            Err(_err) => {
+                warn!("Applet not selected: {_err:?}");
+                Liveness::NeedsReenable
+            }
+        }
+    }
+
+    /// Read the version of the applet with [`GetVersion`](commands::GetVersion)
+    pub fn applet_version(&mut self) -> Result<AppletVersion, Error> {
+        Ok(self
+            .run_command(&commands::GetVersion {}, &mut [0; 16])?
+            .version_info
+            .version)
+    }
+
+    /// ATR returned by the last call to [`enable`](Self::enable)
+    pub fn atr(&self) -> Option<Atr> {
+        self.atr
+    }
+
+    /// Returns `true` if the applet variant offers all the features in `feature`
+    ///
+    /// The features are read from the [`Atr`] returned by [`enable`](Self::enable). Before that, all features are assumed to be supported.
+    pub fn supports(&self, feature: AppletConfig) -> bool {
+        match self.atr {
+            Some(atr) => atr.applet_config.contains(feature),
+            None => true,
+        }
+    }
+
+    /// Return [`Error::Unsupported`] if the applet variant does not offer `feature`
+    ///
+    /// This is checked by the high-level helpers before sending commands.
+    pub fn require_feature(&self, feature: AppletConfig) -> Result<(), Error> {
+        if self.supports(feature) {
+            Ok(())
+        } else {
+            warn!("Unsupported applet feature: {feature:?}");
+            Err(Error::Unsupported(feature))
+        }
+    }
+
+    /// Retry commands that fail with a [transient](Error::is_transient) error following `policy`
+    ///
+    /// The retries apply to all commands and are counted in [`retry_count`](Self::retry_count).
+    /// Other errors are returned immediately. Disabled by default.
+    ///
+    /// Only enable retries for commands that are idempotent in the application: the se05x does not change its persistent memory when it reports a transient error,
+    /// but the retry re-sends the whole command, including to update a counter or a crypto object.
+    pub fn set_retry_policy(&mut self, policy: Option<RetryPolicy>) {
+        self.retry_policy = policy;
+    }
+
+    /// Number of commands that were retried because of the [retry policy](Self::set_retry_policy)
+    pub fn retry_count(&self) -> u32 {
+        self.retries
+    }
+
+    /// Recover from a power loss of the se05x by re-selecting the applet
+    ///
+    /// When a command fails with an error that [suggests a power loss](Error::is_power_loss), the transport is reset, the applet is selected again
+    /// as by [`enable`](Self::enable) and the command is retried once. Disabled by default, and only active after the first call to [`enable`](Self::enable).
+    ///
+    /// Sessions and transient objects do not survive a power loss: check [`take_recovered`](Self::take_recovered) to find out whether they must be set up again.
+    /// As with the [retry policy](Self::set_retry_policy), only enable this if the commands are idempotent in the application.
+    pub fn set_auto_recovery(&mut self, enabled: bool) {
+        self.auto_recovery = enabled;
+    }
+
+    /// Returns `true` if the applet was re-selected by the [auto-recovery](Self::set_auto_recovery) since the last call, and clears the flag
+    pub fn take_recovered(&mut self) -> bool {
+        core::mem::take(&mut self.recovered)
+    }
+
     fn run_command_buf_response<'buf>(
         &mut self,
-        command: &dyn for<'a> DataStream<FrameSender<'a, Twi, D>>,
+        command: &dyn for<'a> DataStream<ApduWriter<'a>>,
+        response_buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        self.run_command_in(command, None, response_buf)
+    }
+
+    /// Run `command`, buffering the frames sent to the transport in `frame_buffer` if provided
+    fn run_command_in<'buf>(
+        &mut self,
+        command: &dyn for<'a> DataStream<ApduWriter<'a>>,
+        mut frame_buffer: Option<&mut [u8; FRAME_BUFFER_LEN]>,
         response_buf: &'buf mut [u8],
     ) -> Result<&'buf [u8], Error> {
-        let mut sender = self.t1.into_writer(command.len())?;
-        command.to_writer(&mut sender)?;
-        self.t1.wait_segt();
-        let (response, status) = self.receive_apdu(response_buf)?;
+        if command.len() > MAX_APDU_PAYLOAD_LENGTH + MAX_APDU_OVERHEAD {
+            error!("Command too long: {}", command.len());
+            return Err(Error::CommandTooLong);
+        }
+        let mut attempt = 0;
+        let mut recovered = false;
+        let written = loop {
+            match (
+                self.run_command_once(command, frame_buffer.as_deref_mut(), response_buf),
+                self.retry_policy,
+            ) {
+                (Err(err), Some(policy)) if err.is_transient() && attempt < policy.max_retries => {
+                    warn!("Retrying command after transient error: {err:?}");
+                    self.retries = self.retries.saturating_add(1);
+                    self.transport.delay_us(policy.backoff_us(attempt));
+                    attempt += 1;
+                }
+                (Err(err), _)
+                    if self.auto_recovery
+                        && !recovered
+                        && self.atr.is_some()
+                        && err.is_power_loss() =>
+                {
+                    warn!("Re-selecting the applet after error: {err:?}");
+                    recovered = true;
+                    // Disables the recovery of the select itself
+                    self.atr = None;
+                    if let Err(_recovery_err) = self.enable() {
+                        error!("Failed to re-select the applet: {_recovery_err:?}");
+                        return Err(err);
+                    }
+                    self.recovered = true;
+                }
+                (result, _) => break result?,
+            }
+        };
+        Ok(&response_buf[..written])
+    }
+
+    /// Send `command` once and receive the whole response, returning its length
+    fn run_command_once(
+        &mut self,
+        command: &dyn for<'a> DataStream<ApduWriter<'a>>,
+        mut frame_buffer: Option<&mut [u8; FRAME_BUFFER_LEN]>,
+        response_buf: &mut [u8],
+    ) -> Result<usize, Error> {
+        if let (true, Some(pattern)) = (cfg!(debug_assertions), self.response_poison) {
+            response_buf.fill(pattern);
+        }
+        let (mut written, mut status) =
+            self.exchange(command, frame_buffer.as_deref_mut(), response_buf)?;
+        // Reassemble responses split with 61xx status words
+        while let Some(le) = more_available(status) {
+            debug!("{le} more bytes available, written {written}");
+            if written >= response_buf.len() {
+                error!("Response buffer full with {le} more bytes available");
+                return Err(Error::Line(line!()));
+            }
+            let (len, next_status) = self.exchange(
+                &GetResponse { le },
+                frame_buffer.as_deref_mut(),
+                &mut response_buf[written..],
+            )?;
+            if len == 0 {
+                error!("GET RESPONSE returned no data");
+                return Err(Error::Line(line!()));
+            }
+            written += len;
+            status = next_status;
+        }
         if status != Status::Success {
             return Err(Error::Status(status));
         }
-        Ok(response)
+        Ok(written)
     }
 
     fn run_command_internal<'buf, R: Se05XResponse<'buf>>(
         &mut self,
-        command: &dyn for<'a> DataStream<FrameSender<'a, Twi, D>>,
+        command: &dyn for<'a> DataStream<ApduWriter<'a>>,
         response_buf: &'buf mut [u8],
     ) -> Result<R, Error> {
         let response = self.run_command_buf_response(command, response_buf)?;
@@ -198,23 +1002,153 @@ impl<Twi: I2CForT1, D: Delay> Se05X<Twi, D> {
         R::from_response(response)
     }
 
-    pub fn run_command<'buf, C: for<'a> Se05XCommand<FrameSender<'a, Twi, D>>>(
+    pub fn run_command<'buf, C: for<'a> Se05XCommand<ApduWriter<'a>>>(
         &mut self,
         command: &C,
         response_buf: &'buf mut [u8],
-    ) -> Result<<C as Se05XCommand<FrameSender<'_, Twi, D>>>::Response<'buf>, Error> {
+    ) -> Result<<C as Se05XCommand<ApduWriter<'_>>>::Response<'buf>, Error> {
+        command.validate()?;
         self.run_command_internal(command, response_buf)
     }
 
+    /// Run a command, returning error status words instead of failing
+    ///
+    /// The response is only parsed if the status is [`Status::Success`], otherwise `None` is returned with the status.
+    /// This lets flows branch on expected statuses, such as the `63Cx` attempt counters of [`VerifySessionUserId`](commands::VerifySessionUserId).
+    /// Transport errors and malformed responses are still returned as errors.
+    pub fn run_command_with_status<'buf, C: for<'a> Se05XCommand<ApduWriter<'a>>>(
+        &mut self,
+        command: &C,
+        response_buf: &'buf mut [u8],
+    ) -> Result<
+        (
+            Option<<C as Se05XCommand<ApduWriter<'_>>>::Response<'buf>>,
+            Status,
+        ),
+        Error,
+    > {
+        command.validate()?;
+        match self.run_command_internal(command, response_buf) {
+            Ok(response) => Ok((Some(response), Status::Success)),
+            Err(Error::Status(status)) => Ok((None, status)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Send a command APDU built from raw fields, bypassing the commands defined by this crate
+    ///
+    /// This is an escape hatch for vendor specific or undocumented commands, for example during applet updates.
+    /// The response data and status word are returned as received: error status words are not converted to errors,
+    /// responses split with `61xx` status words are not reassembled, and neither the [retry policy](Self::set_retry_policy)
+    /// nor the [auto-recovery](Self::set_auto_recovery) apply. The [APDU hook](Self::set_apdu_hook) still traces the command.
+    ///
+    /// Raw commands can change state the driver relies on, such as the selected applet or an open session, without it noticing.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run_raw_apdu<'buf>(
+        &mut self,
+        cla: u8,
+        ins: u8,
+        p1: u8,
+        p2: u8,
+        data: &[u8],
+        le: ExpectedLen,
+        response_buf: &'buf mut [u8],
+    ) -> Result<(&'buf [u8], Status), Error> {
+        let Ok(class) = Class::from_byte(cla) else {
+            error!("Invalid class byte: {cla:02x}");
+            return Err(Error::Line(line!()));
+        };
+        let command = CommandBuilder::new(class, ins.into(), p1, p2, data, le);
+        if command.len() > MAX_APDU_PAYLOAD_LENGTH + MAX_APDU_OVERHEAD {
+            error!("Command too long: {}", command.len());
+            return Err(Error::CommandTooLong);
+        }
+        let (len, status) = self.exchange(&command, None, response_buf)?;
+        Ok((&response_buf[..len], status))
+    }
+
+    /// Start streaming a command APDU of `len` bytes, including the header, for commands not defined by this crate
+    ///
+    /// Custom [`DataStream`] implementations are written to the returned [`RawCommand`], which is then [finished](RawCommand::finish)
+    /// before the response is received with [`receive_raw_response`](Self::receive_raw_response).
+    /// As with [`run_raw_apdu`](Self::run_raw_apdu), nothing is retried or recovered, and the state of the driver is not updated.
+    ///
+    /// ```rust,no_run
+    /// # use iso7816::command::Writer;
+    /// # use se05x::se05x::{Se05X, transport::Se05xTransport};
+    /// # fn raw<T: Se05xTransport>(se05x: &mut Se05X<T>) -> Result<(), se05x::se05x::Error> {
+    /// // GetVersion, with the applet version in the response
+    /// let apdu = [0x80, 0x04, 0x00, 0x20, 0x00];
+    /// let mut command = se05x.begin_raw_command(apdu.len())?;
+    /// command.write_all(&apdu)?;
+    /// let sent = command.finish()?;
+    /// let (_version, _status) = se05x.receive_raw_response(sent, &mut [0; 32])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn begin_raw_command(&mut self, len: usize) -> Result<RawCommand<'_, T>, Error> {
+        if len > MAX_APDU_PAYLOAD_LENGTH + MAX_APDU_OVERHEAD {
+            error!("Command too long: {len}");
+            return Err(Error::CommandTooLong);
+        }
+        Ok(RawCommand::new(self.transport.send_apdu(len)?, len))
+    }
+
+    /// Receive the response to a command sent with [`begin_raw_command`](Self::begin_raw_command)
+    ///
+    /// The response data and status word are returned as received. The [APDU hook](Self::set_apdu_hook) traces the command.
+    pub fn receive_raw_response<'buf>(
+        &mut self,
+        sent: RawCommandSent,
+        response_buf: &'buf mut [u8],
+    ) -> Result<(&'buf [u8], Status), Error> {
+        let result = self.transport.receive_apdu(response_buf);
+        if let Some(hook) = self.apdu_hook {
+            let [cla, ins, p1, p2] = sent.header;
+            hook(&ApduTraceEvent {
+                cla,
+                ins,
+                p1,
+                p2,
+                len: sent.len,
+                status: result.as_ref().ok().map(|&(_, status)| status),
+            });
+        }
+        result
+    }
+
+    /// Run a command with a single `scratch` buffer, holding both the frames sent to the transport and the response
+    ///
+    /// The first [`FRAME_BUFFER_LEN`] bytes of `scratch` buffer the frames, the rest receives the response.
+    /// This avoids the frame buffer of [`T1oI2C`] on the stack, lowering the RAM high-water mark of firmware.
+    /// `scratch` must be at least [`MIN_SCRATCH_LEN`] bytes long, which is checked at compile time.
+    pub fn run_command_with_scratch<
+        'buf,
+        C: for<'a> Se05XCommand<ApduWriter<'a>>,
+        const N: usize,
+    >(
+        &mut self,
+        command: &C,
+        scratch: &'buf mut [u8; N],
+    ) -> Result<<C as Se05XCommand<ApduWriter<'_>>>::Response<'buf>, Error> {
+        let () = ScratchLen::<N>::CHECK;
+        command.validate()?;
+        let (frame_buffer, response_buf) = scratch.split_at_mut(FRAME_BUFFER_LEN);
+        let response =
+            self.run_command_in(command, Some(frame_buffer.try_into()?), response_buf)?;
+        Se05XResponse::from_response(response)
+    }
+
     /// Run a command within a session
-    pub fn run_session_command<'buf, C: for<'a> Se05XCommand<FrameSender<'a, Twi, D>>>(
+    pub fn run_session_command<'buf, C: for<'a> Se05XCommand<ApduWriter<'a>>>(
         &mut self,
         session_id: SessionId,
         command: &C,
         response_buf: &'buf mut [u8],
-    ) -> Result<<C as Se05XCommand<FrameSender<'_, Twi, D>>>::Response<'buf>, Error> {
+    ) -> Result<<C as Se05XCommand<ApduWriter<'_>>>::Response<'buf>, Error> {
+        command.validate()?;
         self.run_command_internal(
-            &ProcessSessionCmd::<&dyn for<'a> DataStream<FrameSender<'a, Twi, D>>> {
+            &ProcessSessionCmd::<&dyn for<'a> DataStream<ApduWriter<'a>>> {
                 session_id,
                 apdu: command,
             },
@@ -222,25 +1156,198 @@ impl<Twi: I2CForT1, D: Delay> Se05X<Twi, D> {
         )
     }
 
-    /// Prior to being used with the se05x, the curve constants need to be configured for the secure element
-    ///
-    /// This method configures the secure element to be able to use the given curve.
+    /// Sign a pre-hashed message with [`EcdsaSign`](commands::EcdsaSign), retrying while the se05x is busy
     ///
-    /// The values for the `data` parameter can be found in the [`constants`]() module
-    pub fn create_and_set_curve_params(
+    /// After heavy writes the se05x can reject commands with a [transient](Error::is_transient) status word.
+    /// Such failures are retried following `policy`, which replaces the [retry policy](Self::set_retry_policy) for this command only.
+    /// The retries are counted in [`retry_count`](Self::retry_count). Any other error is returned immediately.
+    pub fn sign_digest_with_retry<'buf>(
         &mut self,
-        data: &constants::CurveInitializer,
-    ) -> Result<(), Error> {
-        let response_buf = &mut [0; 2];
-        self.run_command(&CreateEcCurve { curve: data.curve }, response_buf)?;
-        self.run_command(
-            &SetEcCurveParam {
-                curve: data.curve,
-                param: EcCurveParam::ParamA,
-                value: data.constants.a,
+        key_id: ObjectId,
+        algo: EcDsaSignatureAlgo,
+        digest: &[u8],
+        policy: RetryPolicy,
+        response_buf: &'buf mut [u8],
+    ) -> Result<commands::EcdsaSignResponse<'buf>, Error> {
+        self.require_feature(AppletConfig::ECDSA_ECDH_ECDHE)?;
+        let previous = self.retry_policy.replace(policy);
+        let response = self.run_command(
+            &commands::EcdsaSign {
+                key_id,
+                algo,
+                data: digest,
             },
             response_buf,
-        )?;
+        );
+        self.retry_policy = previous;
+        response
+    }
+
+    /// Sign a message with [`EcdsaSign`](commands::EcdsaSign), hashing it on the host first
+    ///
+    /// The message is hashed with the digest matching `algo`. [`EcDsaSignatureAlgo::Plain`] is rejected, as it has no digest.
+    #[cfg(feature = "host-digest")]
+    pub fn sign_message<'buf>(
+        &mut self,
+        key_id: ObjectId,
+        algo: EcDsaSignatureAlgo,
+        message: &[u8],
+        response_buf: &'buf mut [u8],
+    ) -> Result<commands::EcdsaSignResponse<'buf>, Error> {
+        self.require_feature(AppletConfig::ECDSA_ECDH_ECDHE)?;
+        let mut digest_buf = [0; 64];
+        let digest = algo.host_digest(message, &mut digest_buf)?;
+        self.run_command(
+            &commands::EcdsaSign {
+                key_id,
+                algo,
+                data: digest,
+            },
+            response_buf,
+        )
+    }
+
+    /// Verify the ECDSA `signature` of `digest` with a public key that is not stored in the se05x
+    ///
+    /// `public_key` is written to a transient object at [`EPHEMERAL_PUBLIC_KEY_ID`], which is deleted after the verification, even if it failed.
+    /// The parameters of `curve` must already be set in the se05x.
+    /// `digest` is the hash of the message matching `algo`, as for [`EcdsaVerify`](commands::EcdsaVerify).
+    ///
+    /// Returns `Ok(false)` if the signature is invalid.
+    pub fn verify_with_ephemeral_pubkey(
+        &mut self,
+        curve: EcCurve,
+        public_key: &[u8],
+        digest: &[u8],
+        signature: &[u8],
+        algo: EcDsaSignatureAlgo,
+    ) -> Result<bool, Error> {
+        self.require_feature(AppletConfig::ECDSA_ECDH_ECDHE)?;
+        if !curve.is_weierstrass() {
+            error!("ECDSA is not available with {curve:?}");
+            return Err(Error::Line(line!()));
+        }
+        ec_key::check_public_key(curve, public_key)?;
+        let object_id = EPHEMERAL_PUBLIC_KEY_ID;
+        // Left over by an interrupted verification
+        if self.exists(object_id)? {
+            self.run_command(&commands::DeleteSecureObject { object_id }, &mut [0; 2])?;
+        }
+        self.run_command(
+            &commands::WriteEcKey {
+                transient: true,
+                is_auth: false,
+                key_type: Some(P1KeyType::Public),
+                policy: None,
+                max_attempts: None,
+                object_id,
+                curve: Some(curve),
+                private_key: None,
+                public_key: Some(public_key),
+            },
+            &mut [0; 2],
+        )?;
+        let verified = self.run_command(
+            &commands::EcdsaVerify {
+                key_id: object_id,
+                algo,
+                data: digest,
+                signature,
+            },
+            &mut [0; 16],
+        );
+        let deleted = self.run_command(&commands::DeleteSecureObject { object_id }, &mut [0; 2]);
+        let verified = verified?.result.is_success();
+        deleted?;
+        Ok(verified)
+    }
+
+    /// Write `data` to an existing binary file, starting at `offset`, in chunks of [`CHUNK_LEN`]
+    ///
+    /// The file must already exist, for example created with [`WriteBinary`](commands::WriteBinary) and a `file_length`.
+    /// Nothing is written if `data` does not fit below the maximum offset of a file.
+    pub fn write_binary_chunked(
+        &mut self,
+        object_id: ObjectId,
+        offset: u16,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        if usize::from(offset) + data.len() > MAX_BINARY_FILE_LEN {
+            error!(
+                "Writing {} bytes at offset {offset} exceeds the maximum file length",
+                data.len()
+            );
+            return Err(Error::Line(line!()));
+        }
+        let response_buf = &mut [0; 2];
+        let offsets = (usize::from(offset)..).step_by(CHUNK_LEN);
+        for (chunk_offset, chunk) in offsets.zip(data.chunks(CHUNK_LEN)) {
+            self.run_command(
+                &commands::WriteBinary {
+                    transient: false,
+                    policy: None,
+                    object_id,
+                    // Below MAX_BINARY_FILE_LEN, checked above
+                    offset: Some((chunk_offset as u16).into()),
+                    file_length: None,
+                    data: Some(chunk),
+                },
+                response_buf,
+            )
+            .map_err(|err| self.explain_policy_error(object_id, err))?;
+        }
+        Ok(())
+    }
+
+    /// Feed `input` to a cipher operation initialized with [`CipherEncryptInit`](commands::CipherEncryptInit) or [`CipherDecryptInit`](commands::CipherDecryptInit),
+    /// in chunks of [`CHUNK_LEN`]
+    ///
+    /// The output of each [`CipherUpdate`](commands::CipherUpdate) is appended to `output`. Returns the number of bytes written to `output`.
+    pub fn cipher_update_chunked(
+        &mut self,
+        cipher_id: CryptoObjectId,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<usize, Error> {
+        let response_buf = &mut [0; CHUNK_LEN + 32];
+        let mut written = 0;
+        for chunk in input.chunks(CHUNK_LEN) {
+            let response = self.run_command(
+                &commands::CipherUpdate {
+                    cipher_id,
+                    data: chunk,
+                },
+                response_buf,
+            )?;
+            let Some(out) = output.get_mut(written..written + response.data.len()) else {
+                error!("Output buffer too small");
+                return Err(Error::Line(line!()));
+            };
+            out.copy_from_slice(response.data);
+            written += response.data.len();
+        }
+        Ok(written)
+    }
+
+    /// Prior to being used with the se05x, the curve constants need to be configured for the secure element
+    ///
+    /// This method configures the secure element to be able to use the given curve.
+    ///
+    /// The values for the `data` parameter can be found in the [`constants`]() module
+    pub fn create_and_set_curve_params(
+        &mut self,
+        data: &constants::CurveInitializer,
+    ) -> Result<(), Error> {
+        let response_buf = &mut [0; 2];
+        self.run_command(&CreateEcCurve { curve: data.curve }, response_buf)?;
+        self.run_command(
+            &SetEcCurveParam {
+                curve: data.curve,
+                param: EcCurveParam::ParamA,
+                value: data.constants.a,
+            },
+            response_buf,
+        )?;
         self.run_command(
             &SetEcCurveParam {
                 curve: data.curve,
@@ -287,28 +1394,50 @@ impl<Twi: I2CForT1, D: Delay> Se05X<Twi, D> {
     /// Since the commands are hard-coded, the constants can be quite large. If only a subset of the curves are used,
     /// it is recommended to instead use [`create_and_set_curve_params`]()
     pub fn create_and_set_curve(&mut self, curve: EcCurve) -> Result<(), Error> {
-        let Some(constants) = curve.params() else {
+        let Some(constants) = curve.required_params()? else {
             // Curve doesn't need configuring params
             return Ok(());
         };
         self.create_and_set_curve_params(&constants::CurveInitializer { constants, curve })
     }
 
+    /// Authenticate the AES session `session_id` with `key`, without secure messaging
+    ///
+    /// See [`authenticate_aes128_session_with_level`](Self::authenticate_aes128_session_with_level).
     #[cfg(feature = "aes-session")]
-    pub fn authenticate_aes128_session<R: rand::CryptoRng + rand::RngCore>(
+    pub fn authenticate_aes128_session<R: rng::HostRng>(
         &mut self,
         session_id: SessionId,
         key: &[u8; 16],
         rng: &mut R,
     ) -> Result<bool, Error> {
+        let channel =
+            self.authenticate_aes128_session_with_level(session_id, key, SecurityLevel::None, rng)?;
+        Ok(channel.is_some())
+    }
+
+    /// Authenticate the AES session `session_id` with `key`, setting the secure messaging of its commands to `security_level`
+    ///
+    /// Returns `None` if the card cryptogram does not match `key`.
+    /// The se05x then rejects the commands of the session that are not protected as required by `security_level`:
+    /// run them with [`run_secure_command`](Self::run_secure_command) and the returned [`SecureChannel`](secure_channel::SecureChannel).
+    /// With [`SecurityLevel::Mac`], commands carry a C-MAC but are not encrypted, which some deployments require for performance.
+    #[cfg(feature = "aes-session")]
+    pub fn authenticate_aes128_session_with_level<R: rng::HostRng>(
+        &mut self,
+        session_id: SessionId,
+        key: &[u8; 16],
+        security_level: SecurityLevel,
+        rng: &mut R,
+    ) -> Result<Option<secure_channel::SecureChannel>, Error> {
         debug_now!("authenticating AES session");
         let mut buf = [0; 1024];
         use aes::Aes128;
         use cmac::{Cmac, Mac};
-        use rand::Rng;
 
         use crate::se05x::commands::{ScpExternalAuthenticate, ScpInitializeUpdate};
-        let host_challenge: [u8; 8] = rng.gen();
+        let mut host_challenge = [0; 8];
+        rng.fill_random(self, &mut host_challenge)?;
         let chal = self.run_session_command(
             session_id,
             &ScpInitializeUpdate { host_challenge },
@@ -340,7 +1469,7 @@ impl<Twi: I2CForT1, D: Delay> Se05X<Twi, D> {
         dda[11] = DATA_DERIVATION_SENC;
         let mut mac = Cmac::<Aes128>::new(key.into());
         mac.update(&dda);
-        let _tag_senc: &[u8; 16] = &mac.finalize().into_bytes().into();
+        let tag_senc: [u8; 16] = mac.finalize().into_bytes().into();
 
         dda[11] = DATA_DERIVATION_SMAC;
         let mut mac = Cmac::<Aes128>::new(key.into());
@@ -350,7 +1479,7 @@ impl<Twi: I2CForT1, D: Delay> Se05X<Twi, D> {
         dda[11] = DATA_DERIVATION_SRMAC;
         let mut mac = Cmac::<Aes128>::new(key.into());
         mac.update(&dda);
-        let _tag_srmac: &[u8; 16] = &mac.finalize().into_bytes().into();
+        let tag_srmac: [u8; 16] = mac.finalize().into_bytes().into();
 
         // *** Verifying card cryptogram *** //
         const DATA_CARD_CRYPTOGRAM: u8 = 0;
@@ -371,7 +1500,7 @@ impl<Twi: I2CForT1, D: Delay> Se05X<Twi, D> {
                 chal.se05x_challenge.card_challenge,
                 chal.se05x_challenge.card_cryptogram
             );
-            return Ok(false);
+            return Ok(None);
         }
 
         debug_now!("Verified card cryptogram");
@@ -387,25 +1516,293 @@ impl<Twi: I2CForT1, D: Delay> Se05X<Twi, D> {
         // APDU header
         // FIXME: Secure messaging should be handled by `run_command`
         // BLOCKING: Expected len is not authenticated, so need adapted API from CommandBuilder
-        mac.update(&hex!("84 82 0000 10"));
+        mac.update(&[
+            SM_CLA.into_inner(),
+            INS_EXTERNAL_AUTHENTICATE.into(),
+            security_level.into(),
+            P2_DEFAULT,
+            0x10,
+        ]);
         mac.update(&host_cryptogram);
+        // The full MAC is the chaining value of the first command of the session
+        let mac_chaining: [u8; 16] = mac.finalize().into_bytes().into();
 
         debug_now!("Running external authenticate");
         self.run_session_command(
             session_id,
             &ScpExternalAuthenticate {
+                security_level,
                 host_cryptogram,
-                mac: mac.finalize().into_bytes()[..8].try_into().unwrap(),
+                mac: mac_chaining[..8].try_into().unwrap(),
             },
             &mut buf,
-        )?;
+        )
+        .map_err(Error::auth_failure)?;
         debug_now!("Authenticate success");
-        Ok(true)
+        Ok(Some(secure_channel::SecureChannel::new(
+            session_id,
+            security_level,
+            secure_channel::SessionKeys {
+                enc: tag_senc,
+                mac: *tag_smac,
+                rmac: tag_srmac,
+            },
+            mac_chaining,
+        )))
+    }
+
+    /// Unlock a transport-locked applet
+    ///
+    /// Retrieves a challenge with [`GetUnlockChallenge`](commands::GetUnlockChallenge) and answers it with [`SetLockState`](commands::SetLockState),
+    /// using the response computed by [`transport_unlock_response`] from the host copy of the [`TRANSPORT`](ObjectId::TRANSPORT) key.
+    #[cfg(feature = "aes-session")]
+    pub fn transport_unlock(
+        &mut self,
+        key: &[u8; 16],
+        lock_indicator: TransientIndicator,
+    ) -> Result<(), Error> {
+        let mut buf = [0; 128];
+        let challenge = *self
+            .run_command(&commands::GetUnlockChallenge {}, &mut buf)?
+            .challenge;
+        let unlock_response = transport_unlock_response(key, &challenge);
+        self.run_command(
+            &commands::SetLockState {
+                lock_indicator,
+                lock_state: LockState::Unlocked,
+                unlock_response: Some(&unlock_response),
+            },
+            &mut buf,
+        )?;
+        Ok(())
+    }
+
+    /// Read the current [`Timestamp`] with [`GetTimestamp`](commands::GetTimestamp)
+    pub fn timestamp(&mut self) -> Result<Timestamp, Error> {
+        Ok(self
+            .run_command(&commands::GetTimestamp {}, &mut [0; 20])?
+            .parsed())
+    }
+
+    /// Query the available memory of every [`Memory`] type with [`GetFreeMemory`](commands::GetFreeMemory)
+    pub fn memory_report(&mut self) -> Result<MemoryReport, Error> {
+        let mut available = |memory| -> Result<u16, Error> {
+            Ok(self
+                .run_command(&commands::GetFreeMemory { memory }, &mut [0; 16])?
+                .available
+                .0)
+        };
+        Ok(MemoryReport {
+            persistent: available(Memory::Persistent)?,
+            transient_reset: available(Memory::TransientReset)?,
+            transient_deselect: available(Memory::TransientDeselect)?,
+        })
+    }
+
+    /// Call `on_object` with the ID of each object matching `filter` and whose ID is within `range`
+    ///
+    /// Objects are listed page by page with [`ReadIdList`](commands::ReadIdList), and the range is checked while iterating over each page.
+    /// The IDs are not sorted by the applet, so all the objects matching `filter` are listed. Returns the number of objects within `range`.
+    pub fn list_objects_in_range(
+        &mut self,
+        filter: SecureObjectFilter,
+        range: impl RangeBounds<u32>,
+        mut on_object: impl FnMut(ObjectId),
+    ) -> Result<usize, Error> {
+        let buf = &mut [0; 1024];
+        let mut listed: u16 = 0;
+        let mut matched = 0;
+        loop {
+            let list = self.run_command(
+                &commands::ReadIdList {
+                    offset: listed.into(),
+                    filter,
+                },
+                buf,
+            )?;
+            if list.ids.len() % 4 != 0 {
+                error!("Invalid ID list length: {}", list.ids.len());
+                return Err(Error::Tlv);
+            }
+            for id in list.ids.chunks_exact(4) {
+                listed += 1;
+                let object_id = ObjectId(id.try_into()?);
+                if range.contains(&u32::from_be_bytes(object_id.0)) {
+                    matched += 1;
+                    on_object(object_id);
+                }
+            }
+            if list.more == MoreIndicator::NoMore || list.ids.is_empty() {
+                return Ok(matched);
+            }
+        }
+    }
+
+    /// Delete the objects matching `filter`, and whose ID is within `range` if given
+    ///
+    /// Objects are listed with [`ReadIdList`](commands::ReadIdList) and deleted one by one with [`DeleteSecureObject`](commands::DeleteSecureObject).
+    /// [Reserved](ObjectId::is_reserved) objects are never deleted, which makes this a safer alternative to [`DeleteAll`](commands::DeleteAll).
+    /// The result of each deletion is passed to `on_result`. Returns the number of deleted objects.
+    ///
+    /// Fails if the se05x announces more objects but lists none.
+    pub fn delete_objects_matching(
+        &mut self,
+        filter: SecureObjectFilter,
+        range: Option<Range<u32>>,
+        mut on_result: impl FnMut(ObjectId, Result<(), Error>),
+    ) -> Result<usize, Error> {
+        let buf = &mut [0; 1024];
+        let mut deleted = 0;
+        // Number of listed objects that are kept, and are therefore skipped by the next listing
+        let mut kept: u16 = 0;
+        loop {
+            let previous = (deleted, kept);
+            let list = self.run_command(
+                &commands::ReadIdList {
+                    offset: kept.into(),
+                    filter,
+                },
+                buf,
+            )?;
+            if list.ids.len() % 4 != 0 {
+                error!("Invalid ID list length: {}", list.ids.len());
+                return Err(Error::Tlv);
+            }
+            for id in list.ids.chunks_exact(4) {
+                let object_id = ObjectId(id.try_into()?);
+                let id = u32::from_be_bytes(object_id.0);
+                let out_of_range = matches!(&range, Some(range) if !range.contains(&id));
+                if out_of_range || object_id.is_reserved() {
+                    kept = kept.checked_add(1).ok_or(Error::Line(line!()))?;
+                    continue;
+                }
+                let result = self
+                    .run_command(&commands::DeleteSecureObject { object_id }, &mut [0; 2])
+                    .map(|_| ());
+                if result.is_ok() {
+                    deleted += 1;
+                } else {
+                    kept = kept.checked_add(1).ok_or(Error::Line(line!()))?;
+                }
+                on_result(object_id, result);
+            }
+            if list.more == MoreIndicator::NoMore {
+                return Ok(deleted);
+            }
+            if (deleted, kept) == previous {
+                error!("More objects announced, but none listed");
+                return Err(Error::Line(line!()));
+            }
+        }
+    }
+
+    /// Delete `object_id` with [`DeleteSecureObject`](commands::DeleteSecureObject), then check that it no longer exists
+    ///
+    /// If the object is still present, [`Error::NotDeleted`] is returned, whether the deletion failed or not.
+    /// If the deletion failed and the object is gone, the error of the deletion is returned.
+    pub fn delete_and_verify(&mut self, object_id: ObjectId) -> Result<(), Error> {
+        let deleted = self.run_command(&commands::DeleteSecureObject { object_id }, &mut [0; 2]);
+        if self.exists(object_id)? {
+            if let Err(_err) = deleted {
+                error!("Failed to delete {object_id:?}: {_err:?}");
+            }
+            return Err(Error::NotDeleted { object_id });
+        }
+        deleted?;
+        Ok(())
+    }
+
+    /// [`delete_and_verify`](Self::delete_and_verify) `object_id`, returning the memory freed by the deletion
+    ///
+    /// The freed memory is the difference between two [`memory_report`](Self::memory_report)s, so it also includes the changes caused by the se05x itself,
+    /// for example the release of transient memory used by sessions.
+    pub fn delete_and_measure(&mut self, object_id: ObjectId) -> Result<MemoryReport, Error> {
+        let before = self.memory_report()?;
+        self.delete_and_verify(object_id)?;
+        Ok(self.memory_report()?.freed_since(&before))
+    }
+
+    /// Import a symmetric key wrapped with the key encryption key `kek_id`
+    ///
+    /// `wrapped` is the key wrapped with RFC 3394 AES key wrap, as produced by [`wrap_key_for_import`], so that the key never crosses the bus in plaintext.
+    pub fn import_wrapped_symm_key(
+        &mut self,
+        key_type: SymmKeyType,
+        object_id: ObjectId,
+        kek_id: ObjectId,
+        wrapped: &[u8],
+    ) -> Result<(), Error> {
+        self.require_feature(match key_type {
+            SymmKeyType::Aes => AppletConfig::AES,
+            #[cfg(feature = "legacy-des")]
+            SymmKeyType::Des => AppletConfig::DES,
+            SymmKeyType::Hmac => AppletConfig::HMAC,
+        })?;
+        self.run_command(
+            &commands::WriteSymmKey {
+                transient: false,
+                is_auth: false,
+                key_type,
+                policy: None,
+                max_attempts: None,
+                object_id,
+                kek_id: Some(kek_id),
+                value: wrapped,
+            },
+            &mut [0; 2],
+        )?;
+        Ok(())
+    }
+}
+
+/// Wrap `key` with the AES key encryption key `kek` (RFC 3394), for import with [`Se05X::import_wrapped_symm_key`]
+///
+/// `kek` must be 16, 24 or 32 bytes long, and must also be stored in the se05x. The wrapped key is 8 bytes longer than `key`.
+#[cfg(feature = "key-wrap")]
+pub fn wrap_key_for_import<'buf>(
+    kek: &[u8],
+    key: &[u8],
+    buf: &'buf mut [u8],
+) -> Result<&'buf [u8], Error> {
+    use aes_kw::{KekAes128, KekAes192, KekAes256};
+
+    let Some(out) = buf.get_mut(..key.len() + 8) else {
+        error!("Buffer too small for wrapped key");
+        return Err(Error::Line(line!()));
+    };
+    let wrapped = match kek.len() {
+        16 => KekAes128::try_from(kek).and_then(|kek| kek.wrap(key, out)),
+        24 => KekAes192::try_from(kek).and_then(|kek| kek.wrap(key, out)),
+        32 => KekAes256::try_from(kek).and_then(|kek| kek.wrap(key, out)),
+        _ => {
+            error!("Invalid KEK length: {}", kek.len());
+            return Err(Error::Line(line!()));
+        }
+    };
+    if let Err(_err) = wrapped {
+        error!("Failed to wrap key: {_err:?}");
+        return Err(Error::Line(line!()));
     }
+    Ok(out)
+}
+
+/// Compute the response to a challenge obtained with [`GetUnlockChallenge`](commands::GetUnlockChallenge)
+///
+/// The response is the challenge encrypted with the AES-128 [`TRANSPORT`](ObjectId::TRANSPORT) key.
+#[cfg(feature = "aes-session")]
+pub fn transport_unlock_response(key: &[u8; 16], challenge: &[u8; 16]) -> [u8; 16] {
+    use aes::cipher::{BlockEncrypt, KeyInit};
+    use aes::Aes128;
+
+    let cipher = Aes128::new(key.into());
+    let mut block = (*challenge).into();
+    cipher.encrypt_block(&mut block);
+    block.into()
 }
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct AppletConfig: u16 {
         const ECDAA = 0x0001;
         const ECDSA_ECDH_ECDHE = 0x0002;
@@ -421,14 +1818,22 @@ bitflags! {
         const MIFARE = 0x0800;
         const FIPS_MODE_DISABLED = 0x1000;
         const I2CM = 0x2000;
+        /// SPAKE2+, only available on SE051 applets
+        #[cfg(feature = "se051")]
+        const PAKE = 0x4000;
         const ECC_ALL = 0x000F;
         const RSA_ALL = 0x0060;
+        /// All features of SE050 applets
         const ALL = 0x3FFF;
+        /// All features of SE051 applets, [`ALL`](Self::ALL) and [`PAKE`](Self::PAKE)
+        #[cfg(feature = "se051")]
+        const ALL_SE051 = 0x7FFF;
     }
 }
 
 pub struct Select;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Atr {
     pub major: u8,
     pub minor: u8,
@@ -459,6 +1864,21 @@ impl Atr {
     }
 }
 
+impl Atr {
+    /// Version of the selected applet
+    pub fn version(&self) -> AppletVersion {
+        AppletVersion::new(self.major, self.minor, self.patch)
+    }
+
+    /// Returns `true` if the applet implements the SE051 command set (applet version 7 and later)
+    ///
+    /// Commands and algorithms only available on SE051 applets, such as [`EdDsaSignatureAlgo::PreHashSha512`], should only be used if this returns `true`.
+    #[cfg(feature = "se051")]
+    pub fn supports_se051_commands(&self) -> bool {
+        self.version().supports(VersionFeature::Se051Commands)
+    }
+}
+
 impl<'a> Se05XResponse<'a> for Atr {
     fn from_response(data: &'a [u8]) -> Result<Self, Error> {
         Self::parse(data)
@@ -472,27 +1892,127 @@ impl<'a> TryFrom<&'a [u8]> for Atr {
     }
 }
 
-pub type VersionInfo = Atr;
+/// Version of the IoT applet, ordered by major, minor and patch version
+///
+/// ```
+/// # use se05x::se05x::AppletVersion;
+/// assert!(AppletVersion::new(7, 2, 0) >= AppletVersion::new(7, 0, 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AppletVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+}
+
+impl AppletVersion {
+    pub const fn new(major: u8, minor: u8, patch: u8) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Returns `true` if this version of the applet offers `feature`
+    pub fn supports(&self, feature: VersionFeature) -> bool {
+        *self >= feature.min_version()
+    }
+}
+
+impl fmt::Display for AppletVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Capability of the applet that depends on its version rather than on its [`AppletConfig`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VersionFeature {
+    /// Commands and algorithms of the SE051 command set, available from applet version 7
+    Se051Commands,
+}
+
+impl VersionFeature {
+    /// First applet version offering the feature
+    pub const fn min_version(self) -> AppletVersion {
+        match self {
+            Self::Se051Commands => AppletVersion::new(7, 0, 0),
+        }
+    }
+}
+
+/// Version information returned by [`GetVersion`](commands::GetVersion)
+///
+/// Unlike the [`Atr`], it can be read at any time while the applet is selected, see [`Se05X::applet_version`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VersionInfo {
+    pub version: AppletVersion,
+    pub applet_config: AppletConfig,
+    pub secure_box_major: u8,
+    pub secure_box_minor: u8,
+}
+
+impl<'a> TryFrom<&'a [u8]> for VersionInfo {
+    type Error = Error;
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        let [major, minor, patch, config1, config2, secure_box_major, secure_box_minor] = *value
+        else {
+            error!("Invalid version info length: {}", value.len());
+            return Err(Error::Tlv);
+        };
+        Ok(Self {
+            version: AppletVersion::new(major, minor, patch),
+            applet_config: AppletConfig::from_bits_retain(u16::from_be_bytes([config1, config2])),
+            secure_box_major,
+            secure_box_minor,
+        })
+    }
+}
+
+/// Size of the object attributes preceding the policy set
+const OBJECT_ATTRIBUTES_HEADER_LEN: usize = 14;
+/// Maximum size of the object attributes, including a policy set of up to 256 bytes and the origin
+const OBJECT_ATTRIBUTES_MAX_LEN: usize = OBJECT_ATTRIBUTES_HEADER_LEN + 256 + 1;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ObjectAttributes {
+    raw: heapless::Vec<u8, OBJECT_ATTRIBUTES_MAX_LEN>,
     identifier: ObjectId,
     class: SecureObjectType,
     authentication_indicator: SetIndicator,
     authentication_attempts_counter: u16,
     authentication_object_identifier: ObjectId,
     max_authentication_attempts: u16,
+    /// Length of the policy set following the header
+    policy_len: usize,
+    origin: Option<Origin>,
+}
+
+/// Split the policy set from the origin following it, `None` if there is no origin
+fn split_origin(data: &[u8]) -> (&[u8], Option<u8>) {
+    let mut policies = policies::PolicyIter::new(data);
+    for _ in policies.by_ref() {}
+    match *policies.remainder() {
+        [origin] => (&data[..data.len() - 1], Some(origin)),
+        _ => (data, None),
+    }
 }
 
 impl ObjectAttributes {
     fn parse(data: &[u8]) -> Result<Self, Error> {
-        let [obj_id0, obj_id1, obj_id2, obj_id3, class, auth_indicator, attempts_counter0, attempts_counter1, auth_obj_id0, auth_obj_id1, auth_obj_id2, auth_obj_id3, max_auth_attempts0, max_auth_attempts1, _policy @ ..] =
+        let [obj_id0, obj_id1, obj_id2, obj_id3, class, auth_indicator, attempts_counter0, attempts_counter1, auth_obj_id0, auth_obj_id1, auth_obj_id2, auth_obj_id3, max_auth_attempts0, max_auth_attempts1, policy @ ..] =
             data
         else {
             return Err(Error::Line(line!()));
         };
+        let (policy, origin) = split_origin(policy);
 
         Ok(Self {
+            raw: heapless::Vec::from_slice(data).map_err(|_| Error::Line(line!()))?,
             identifier: ObjectId([*obj_id0, *obj_id1, *obj_id2, *obj_id3]),
             class: (*class).try_into().map_err(|_| Error::Line(line!()))?,
             authentication_indicator: (*auth_indicator)
@@ -512,6 +2032,8 @@ impl ObjectAttributes {
                 *max_auth_attempts0,
                 *max_auth_attempts1,
             ]),
+            policy_len: policy.len(),
+            origin: origin.and_then(|origin| origin.try_into().ok()),
         })
     }
 
@@ -533,6 +2055,94 @@ impl ObjectAttributes {
     pub fn max_authentication_attempts(&self) -> u16 {
         self.max_authentication_attempts
     }
+    /// The attributes as they were received from the secure element
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// Whether the object was imported, generated in the se05x or provisioned by NXP
+    ///
+    /// `None` if the attributes do not end with a known origin.
+    pub fn origin(&self) -> Option<Origin> {
+        self.origin
+    }
+
+    /// The policy set of the object as received, without the origin
+    ///
+    /// Empty if the object has the default policy of the applet.
+    pub fn raw_policy(&self) -> &[u8] {
+        &self.raw[OBJECT_ATTRIBUTES_HEADER_LEN..][..self.policy_len]
+    }
+
+    /// Policies of the object
+    pub fn policies(&self) -> policies::PolicyIter<'_> {
+        policies::PolicyIter::new(self.raw_policy())
+    }
+
+    /// Returns `true` if the policies of the object allow `operation` in a session authenticated with `auth_object`
+    ///
+    /// Without a session, `auth_object` is [`ObjectId::INVALID`], whose policy applies to all users without a policy of their own.
+    /// Objects without policies are reported as allowing every operation: the default policies of the applet are not modelled.
+    /// The PCR and secure messaging requirements of the access rules are not checked.
+    pub fn allows(
+        &self,
+        auth_object: ObjectId,
+        operation: policies::Operation,
+    ) -> Result<bool, Error> {
+        let mut has_policies = false;
+        let mut fallback = None;
+        for policy in self.policies() {
+            let policy = policy?;
+            has_policies = true;
+            if policy.object_id == auth_object {
+                return Ok(policy.access_rule.allows(operation));
+            }
+            if policy.object_id == ObjectId::INVALID {
+                fallback = Some(policy.access_rule);
+            }
+        }
+        Ok(match fallback {
+            Some(rule) => rule.allows(operation),
+            None => !has_policies,
+        })
+    }
+}
+
+/// Serialized as the attributes received from the secure element
+#[cfg(feature = "serde")]
+impl serde::Serialize for ObjectAttributes {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.raw)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ObjectAttributes {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = ObjectAttributes;
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("object attributes")
+            }
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                ObjectAttributes::parse(v)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Bytes(v), &self))
+            }
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut raw: heapless::Vec<u8, OBJECT_ATTRIBUTES_MAX_LEN> = heapless::Vec::new();
+                while let Some(b) = seq.next_element()? {
+                    raw.push(b)
+                        .map_err(|_| serde::de::Error::invalid_length(raw.len() + 1, &self))?;
+                }
+                self.visit_bytes(&raw)
+            }
+        }
+        deserializer.deserialize_bytes(Visitor)
+    }
 }
 
 impl<'a> Se05XResponse<'a> for ObjectAttributes {
@@ -550,10 +2160,45 @@ impl<'a> TryFrom<&'a [u8]> for ObjectAttributes {
 
 impl Select {
     fn command(&self) -> CommandBuilder<&'static [u8]> {
-        CommandBuilder::new(ZERO_CLA, 0xA4.into(), 0x04, 0x00, &APP_ID, 7)
+        SelectAid { aid: &APP_ID }.command()
     }
 }
 
+/// Select the IoT applet installed under `aid`
+///
+/// [`Select`] selects the default [`APP_ID`].
+/// The command can also select a security domain, such as [`ISD_AID`] with [`Se05X::select_isd`]:
+/// `Le` is `00` to accept the whole response, the File Control Information of a security domain being longer than the ATR of the applet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelectAid<'a> {
+    pub aid: &'a [u8],
+}
+
+impl<'a> SelectAid<'a> {
+    fn command(&self) -> CommandBuilder<&'a [u8]> {
+        CommandBuilder::new(ZERO_CLA, 0xA4.into(), 0x04, 0x00, self.aid, 256)
+    }
+}
+
+impl DataSource for SelectAid<'_> {
+    fn len(&self) -> usize {
+        self.command().len()
+    }
+    fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+impl<W: Writer> DataStream<W> for SelectAid<'_> {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as Writer>::Error> {
+        self.command().to_writer(writer)
+    }
+}
+
+impl<W: Writer> Se05XCommand<W> for SelectAid<'_> {
+    type Response<'a> = Atr;
+}
+
 impl DataSource for Select {
     fn len(&self) -> usize {
         self.command().len()
@@ -573,17 +2218,59 @@ impl<W: Writer> Se05XCommand<W> for Select {
     type Response<'a> = Atr;
 }
 
-pub struct ProcessSessionCmd<C> {
-    pub session_id: SessionId,
-    pub apdu: C,
+/// ISO 7816-4 GET RESPONSE, retrieving the remaining data announced by a `61xx` status word
+struct GetResponse {
+    le: u16,
 }
 
-impl<C: DataSource> ProcessSessionCmd<C> {
-    fn data(&self) -> (Tlv<SessionId>, Tlv<&C>) {
-        (
-            Tlv::new(TAG_SESSION_ID, self.session_id),
-            Tlv::new(TAG_1, &self.apdu),
-        )
+impl GetResponse {
+    fn command(&self) -> CommandBuilder<&'static [u8]> {
+        CommandBuilder::new(ZERO_CLA, 0xC0.into(), 0x00, 0x00, &[], self.le)
+    }
+}
+
+impl DataSource for GetResponse {
+    fn len(&self) -> usize {
+        self.command().len()
+    }
+    fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+impl<W: Writer> DataStream<W> for GetResponse {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as Writer>::Error> {
+        self.command().to_writer(writer)
+    }
+}
+
+/// Number of bytes announced by a `61xx` status word
+fn more_available(status: Status) -> Option<u16> {
+    let sw: u16 = status.into();
+    match sw.to_be_bytes() {
+        [0x61, 0] => Some(256),
+        [0x61, len] => Some(len.into()),
+        _ => None,
+    }
+}
+
+/// Command run within the session `session_id`, usually created with [`InSession::in_session`]
+///
+/// The response is the response of `apdu`, and `apdu` is validated before the command is sent.
+/// Commands can be nested, for example to run an attestation within a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessSessionCmd<C> {
+    pub session_id: SessionId,
+    /// Wrapped command
+    pub apdu: C,
+}
+
+impl<C: DataSource> ProcessSessionCmd<C> {
+    fn data(&self) -> (Tlv<SessionId>, Tlv<&C>) {
+        (
+            Tlv::new(TAG_SESSION_ID, self.session_id),
+            Tlv::new(TAG_1, &self.apdu),
+        )
     }
     fn command(&self) -> CommandBuilder<(Tlv<SessionId>, Tlv<&C>)> {
         CommandBuilder::new(
@@ -615,9 +2302,14 @@ impl<W: Writer, C: DataStream<W>> DataStream<W> for ProcessSessionCmd<C> {
 
 impl<W: Writer, C: Se05XCommand<W>> Se05XCommand<W> for ProcessSessionCmd<C> {
     type Response<'a> = C::Response<'a>;
+
+    fn validate(&self) -> Result<(), Error> {
+        self.apdu.validate()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Se05xChallenge {
     pub key_diversification_data: [u8; 10],
     pub key_information: [u8; 3],
@@ -672,6 +2364,12 @@ impl Debug for ObjectId {
     }
 }
 
+impl fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:08X}", u32::from_be_bytes(self.0))
+    }
+}
+
 impl ObjectId {
     /// Invalid object ID.
     /// Can be used in policy to configure no-session access
@@ -714,6 +2412,244 @@ impl ObjectId {
     pub const RESTRICT: ObjectId = ObjectId(hex!("7FFF020A"));
 }
 
+/// Object IDs reserved for the applet, including [`ObjectId::TRANSPORT`] to [`ObjectId::RESTRICT`]
+pub const APPLET_RESERVED_RANGE: RangeInclusive<u32> = 0x7FFF_0000..=0x7FFF_FFFF;
+/// Object IDs of the objects trust provisioned by NXP, such as the attestation keys
+pub const NXP_PROVISIONED_RANGE: RangeInclusive<u32> = 0xF000_0000..=0xFFFF_FFFF;
+/// Object ID of the transient public key written by [`Se05X::verify_with_ephemeral_pubkey`]
+///
+/// This is the last ID of the [`APPLICATION_RANGE`]: applications must not store their own objects there.
+pub const EPHEMERAL_PUBLIC_KEY_ID: ObjectId = ObjectId(hex!("EFFFFFFF"));
+/// Object IDs available to applications
+///
+/// The range is interrupted by [`APPLET_RESERVED_RANGE`].
+pub const APPLICATION_RANGE: RangeInclusive<u32> = 0x0000_0001..=0xEFFF_FFFF;
+
+impl ObjectId {
+    /// Create an object ID, rejecting [`INVALID`](Self::INVALID), the reserved ranges and the [`EPHEMERAL_PUBLIC_KEY_ID`]
+    pub fn new_checked(id: u32) -> Option<Self> {
+        let this = Self(id.to_be_bytes());
+        (!this.is_reserved()).then_some(this)
+    }
+
+    /// Returns `true` if the object ID is not available to applications
+    ///
+    /// See [`APPLET_RESERVED_RANGE`], [`NXP_PROVISIONED_RANGE`] and [`EPHEMERAL_PUBLIC_KEY_ID`].
+    pub fn is_reserved(self) -> bool {
+        let id = u32::from_be_bytes(self.0);
+        !APPLICATION_RANGE.contains(&id)
+            || APPLET_RESERVED_RANGE.contains(&id)
+            || self == EPHEMERAL_PUBLIC_KEY_ID
+    }
+
+    /// Returns `true` if the object ID is in the range trust provisioned by NXP
+    ///
+    /// See [`NXP_PROVISIONED_RANGE`] and [`reserved::NXP_OBJECTS`].
+    pub fn is_provisioned(self) -> bool {
+        NXP_PROVISIONED_RANGE.contains(&u32::from_be_bytes(self.0))
+    }
+}
+
+/// Allocator yielding object IDs from a range, skipping reserved IDs
+///
+/// The allocator does not check whether the objects exist on the se05x.
+#[derive(Clone, Debug)]
+pub struct ObjectIdAllocator {
+    next: u32,
+    end: u32,
+    exhausted: bool,
+}
+
+impl ObjectIdAllocator {
+    /// Allocate IDs from `range`
+    ///
+    /// The bounds of the range must not be reserved. IDs reserved for the applet within the range are skipped.
+    pub fn new(range: RangeInclusive<u32>) -> Result<Self, Error> {
+        let (start, end) = range.into_inner();
+        let start_reserved = ObjectId(start.to_be_bytes()).is_reserved();
+        let end_reserved = ObjectId(end.to_be_bytes()).is_reserved();
+        if start > end || start_reserved || end_reserved {
+            error!("Invalid object ID range: {start:08x}..={end:08x}");
+            return Err(Error::Line(line!()));
+        }
+        Ok(Self {
+            next: start,
+            end,
+            exhausted: false,
+        })
+    }
+}
+
+impl Default for ObjectIdAllocator {
+    /// Allocate from the whole [`APPLICATION_RANGE`], up to the [`EPHEMERAL_PUBLIC_KEY_ID`] excluded
+    fn default() -> Self {
+        Self {
+            next: *APPLICATION_RANGE.start(),
+            end: u32::from_be_bytes(EPHEMERAL_PUBLIC_KEY_ID.0) - 1,
+            exhausted: false,
+        }
+    }
+}
+
+impl Iterator for ObjectIdAllocator {
+    type Item = ObjectId;
+
+    fn next(&mut self) -> Option<ObjectId> {
+        loop {
+            if self.exhausted {
+                return None;
+            }
+            let id = self.next;
+            if id == self.end {
+                self.exhausted = true;
+            } else {
+                self.next += 1;
+            }
+            if APPLET_RESERVED_RANGE.contains(&id) {
+                // Skip the whole reserved region at once
+                if *APPLET_RESERVED_RANGE.end() >= self.end {
+                    self.exhausted = true;
+                } else {
+                    self.next = APPLET_RESERVED_RANGE.end() + 1;
+                }
+                continue;
+            }
+            return Some(ObjectId(id.to_be_bytes()));
+        }
+    }
+}
+
+/// Range of object IDs owned by a component of the firmware
+///
+/// Components sharing a se05x, such as TLS, disk encryption and attestation, each get a namespace and address their objects by slot,
+/// so that their IDs never collide. The ranges are checked when the namespace is created, at compile time for constants:
+///
+/// ```
+/// # use se05x::se05x::{ObjectId, ObjectNamespace};
+/// const TLS: ObjectNamespace = ObjectNamespace::tenant(0x2000_0000, 0, 0x100);
+/// const FDE: ObjectNamespace = ObjectNamespace::tenant(0x2000_0000, 1, 0x100);
+/// const _: () = assert!(ObjectNamespace::disjoint(&[TLS, FDE]));
+///
+/// const TLS_KEY: ObjectId = TLS.id(1);
+/// assert_eq!(TLS_KEY, ObjectId(hex_literal::hex!("20000001")));
+/// assert_eq!(FDE.slot_of(ObjectId(hex_literal::hex!("20000105"))), Some(5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectNamespace {
+    start: u32,
+    len: u32,
+}
+
+impl ObjectNamespace {
+    /// Namespace of `len` IDs starting at `start`
+    ///
+    /// Panics if the range is empty, not within the [`APPLICATION_RANGE`], includes the [`EPHEMERAL_PUBLIC_KEY_ID`] or overlaps the [`APPLET_RESERVED_RANGE`].
+    /// Use [`try_new`](Self::try_new) for ranges that are not known at compile time.
+    pub const fn new(start: u32, len: u32) -> Self {
+        match Self::try_new(start, len) {
+            Some(namespace) => namespace,
+            None => panic!("invalid object namespace"),
+        }
+    }
+
+    /// Namespace of `len` IDs starting at `start`, or `None` if the range is not valid for [`new`](Self::new)
+    pub const fn try_new(start: u32, len: u32) -> Option<Self> {
+        if len == 0 {
+            return None;
+        }
+        let Some(last) = start.checked_add(len - 1) else {
+            return None;
+        };
+        let in_application_range = start >= *APPLICATION_RANGE.start()
+            && last < u32::from_be_bytes(EPHEMERAL_PUBLIC_KEY_ID.0);
+        let out_of_reserved_range =
+            last < *APPLET_RESERVED_RANGE.start() || start > *APPLET_RESERVED_RANGE.end();
+        if in_application_range && out_of_reserved_range {
+            Some(Self { start, len })
+        } else {
+            None
+        }
+    }
+
+    /// Namespace number `tenant` of a sequence of namespaces of `len` IDs each, starting at `start`
+    ///
+    /// Panics under the same conditions as [`new`](Self::new), or if the start of the namespace overflows.
+    /// Use [`try_tenant`](Self::try_tenant) for values that are not known at compile time.
+    pub const fn tenant(start: u32, tenant: u32, len: u32) -> Self {
+        match Self::try_tenant(start, tenant, len) {
+            Some(namespace) => namespace,
+            None => panic!("invalid object namespace"),
+        }
+    }
+
+    /// Namespace number `tenant` of a sequence of namespaces of `len` IDs each, or `None` if it is not valid for [`tenant`](Self::tenant)
+    pub const fn try_tenant(start: u32, tenant: u32, len: u32) -> Option<Self> {
+        let Some(offset) = tenant.checked_mul(len) else {
+            return None;
+        };
+        let Some(start) = start.checked_add(offset) else {
+            return None;
+        };
+        Self::try_new(start, len)
+    }
+
+    /// ID of `slot`
+    ///
+    /// Panics if `slot` is out of the namespace. Use [`get`](Self::get) for slots that are not known at compile time.
+    pub const fn id(&self, slot: u32) -> ObjectId {
+        match self.get(slot) {
+            Some(id) => id,
+            None => panic!("slot out of the object namespace"),
+        }
+    }
+
+    /// ID of `slot`, or `None` if it is out of the namespace
+    pub const fn get(&self, slot: u32) -> Option<ObjectId> {
+        if slot < self.len {
+            Some(ObjectId((self.start + slot).to_be_bytes()))
+        } else {
+            None
+        }
+    }
+
+    /// Slot of `id`, or `None` if it is not in the namespace
+    pub fn slot_of(&self, id: ObjectId) -> Option<u32> {
+        let slot = u32::from_be_bytes(id.0).checked_sub(self.start)?;
+        (slot < self.len).then_some(slot)
+    }
+
+    /// Returns `true` if `id` is in the namespace
+    pub fn contains(&self, id: ObjectId) -> bool {
+        self.slot_of(id).is_some()
+    }
+
+    /// Range of IDs of the namespace, for [`ObjectIdAllocator::new`] and [`Se05X::list_objects_in_range`]
+    pub const fn range(&self) -> RangeInclusive<u32> {
+        self.start..=self.start + (self.len - 1)
+    }
+
+    /// Returns `true` if the two namespaces share IDs
+    pub const fn overlaps(&self, other: &Self) -> bool {
+        self.start <= other.start + (other.len - 1) && other.start <= self.start + (self.len - 1)
+    }
+
+    /// Returns `true` if no two of `namespaces` overlap
+    pub const fn disjoint(namespaces: &[Self]) -> bool {
+        let mut i = 0;
+        while i < namespaces.len() {
+            let mut j = i + 1;
+            while j < namespaces.len() {
+                if namespaces[i].overlaps(&namespaces[j]) {
+                    return false;
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+        true
+    }
+}
+
 impl TryFrom<&[u8]> for ObjectId {
     type Error = TryFromSliceError;
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
@@ -784,6 +2720,22 @@ impl<W: Writer> DataStream<W> for ObjectId {
     }
 }
 
+impl DataSource for AppletConfig {
+    fn len(&self) -> usize {
+        2
+    }
+
+    fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+impl<W: Writer> DataStream<W> for AppletConfig {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as Writer>::Error> {
+        writer.write_all(&self.bits().to_be_bytes())
+    }
+}
+
 pub const TAG_SESSION_ID: Tag = Tag::from_u8(0x10);
 pub const TAG_POLICY: Tag = Tag::from_u8(0x11);
 pub const TAG_MAX_ATTEMPTS: Tag = Tag::from_u8(0x12);
@@ -845,6 +2797,9 @@ pub const P1_MAC: u8 = 0x0D;
 pub const P1_CIPHER: u8 = 0x0E;
 pub const P1_TLS: u8 = 0x0F;
 pub const P1_CRYPTO_OBJ: u8 = 0x10;
+/// SPAKE2+ operations, only available on SE051 applets
+#[cfg(feature = "se051")]
+pub const P1_PAKE: u8 = 0x13;
 
 pub const P2_DEFAULT: u8 = 0x00;
 pub const P2_GENERATE: u8 = 0x03;
@@ -998,6 +2953,13 @@ pub const CC_SIGNATURE: u8 = 0x03;
 pub const RESULT_SUCCESS: u8 = 0x01;
 pub const RESULT_FAILURE: u8 = 0x02;
 
+/// SPAKE2+ party A, the prover
+#[cfg(feature = "se051")]
+pub const SPAKE2PLUS_DEVICE_TYPE_A: u8 = 0x01;
+/// SPAKE2+ party B, the verifier
+#[cfg(feature = "se051")]
+pub const SPAKE2PLUS_DEVICE_TYPE_B: u8 = 0x02;
+
 pub const PERSISTENT: u8 = 0x01;
 pub const TRANSIENT: u8 = 0x02;
 
@@ -1033,6 +2995,8 @@ pub const SIG_ECDSA_SHA_512: u8 = 0x26;
 
 /// EDDSA Pure (using SHA512 as digest)
 pub const SIG_ED25519PURE: u8 = 0xA3;
+/// Ed25519ph: message input must be pre-hashed (using SHA512). Only supported by SE051 applets
+pub const SIG_ED25519PH_SHA_512: u8 = 0xA2;
 
 /// Message input must be pre-hashed (using SHA256)
 pub const SIG_ECDAA: u8 = 0xF4;
@@ -1123,8 +3087,64 @@ pub const SCP_REQUIRED: u8 = 0x01;
 /// No platform SCP required.
 pub const SCP_NOT_REQUIRED: u8 = 0x02;
 
+/// Copy response data into an owned buffer, see the `to_owned` methods of the responses
+#[cfg(feature = "owned")]
+fn owned_bytes<const N: usize>(data: &[u8]) -> Result<heapless::Vec<u8, N>, Error> {
+    heapless::Vec::from_slice(data).map_err(|_| {
+        error!("Response data too large: {} > {N}", data.len());
+        Error::Line(line!())
+    })
+}
+
+/// Serde adapter for references to byte arrays, borrowed from the input
+#[cfg(feature = "serde")]
+mod serde_byte_array_ref {
+    use serde::{de::Error, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        value: &&[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(*value)
+    }
+
+    pub fn deserialize<'de: 'a, 'a, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<&'a [u8; N], D::Error> {
+        let bytes: &'a [u8] = serde_bytes::deserialize(deserializer)?;
+        bytes
+            .try_into()
+            .map_err(|_| D::Error::invalid_length(bytes.len(), &"a byte array"))
+    }
+
+    pub mod option {
+        use serde::{Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer, const N: usize>(
+            value: &Option<&[u8; N]>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serde_bytes::serialize(&value.map(|v| &v[..]), serializer)
+        }
+
+        pub fn deserialize<'de: 'a, 'a, D: Deserializer<'de>, const N: usize>(
+            deserializer: D,
+        ) -> Result<Option<&'a [u8; N]>, D::Error> {
+            let bytes: Option<&'a [u8]> = serde_bytes::deserialize(deserializer)?;
+            bytes
+                .map(|bytes| {
+                    bytes
+                        .try_into()
+                        .map_err(|_| serde::de::Error::invalid_length(bytes.len(), &"a byte array"))
+                })
+                .transpose()
+        }
+    }
+}
+
 /// Big-endian encoded integer
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Be<I>(pub I);
 
 impl<I> From<I> for Be<I> {
@@ -1213,26 +3233,30 @@ macro_rules! enum_data {
         #[repr($repr:tt)]
         $vis:vis enum $name:ident {
             $(
-                $(#[$var_tag:meta])?
+                $(#[$var_tag:meta])*
                 $var:ident = $num:tt
             ),+
             $(,)*
         }
     ) => {
         #[$outer]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         #[repr($repr)]
         $vis enum $name {
             $(
-                $(#[$var_tag])?
+                $(#[$var_tag])*
                 $var = $num,
             )*
         }
 
+        // The attributes of the variants are repeated on the match arms, so that variants can be gated with `cfg`
         impl From<$name> for $repr {
+            #[allow(unused_doc_comments)]
             fn from(val: $name) -> $repr {
                 match val {
                     $(
-                         $name::$var => $num,
+                        $(#[$var_tag])*
+                        $name::$var => $num,
                     )*
                 }
             }
@@ -1240,9 +3264,11 @@ macro_rules! enum_data {
 
         impl TryFrom<$repr> for $name {
             type Error = ();
+            #[allow(unused_doc_comments)]
             fn try_from(val: $repr) -> ::core::result::Result<Self, ()> {
                 match val {
                     $(
+                        $(#[$var_tag])*
                         $num => Ok($name::$var),
                     )*
                     _ => Err(())
@@ -1305,6 +3331,7 @@ enum_data!(
 );
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LockState {
     Locked,
     Unlocked,
@@ -1364,6 +3391,7 @@ enum_data!(
     }
 );
 
+#[cfg(feature = "rsa")]
 enum_data!(
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     #[repr(u8)]
@@ -1413,30 +3441,133 @@ enum_data!(
 
 impl EcCurve {
     /// None means that the constant doesn't need configuring its parameters (curve 25519)
+    ///
+    /// The parameters are only available if the `curve-*` feature of the curve is enabled, which `all-curves` does by default.
+    /// Otherwise, `None` is also returned, see [`needs_params`](Self::needs_params).
     pub fn params(&self) -> Option<constants::CurveConstants> {
         match self {
+            #[cfg(feature = "curve-nist-p192")]
             Self::NistP192 => Some(constants::PRIME192V1),
+            #[cfg(feature = "curve-nist-p224")]
             Self::NistP224 => Some(constants::SECP224R1),
+            #[cfg(feature = "curve-nist-p256")]
             Self::NistP256 => Some(constants::PRIME256V1),
+            #[cfg(feature = "curve-nist-p384")]
             Self::NistP384 => Some(constants::SECP384R1),
+            #[cfg(feature = "curve-nist-p521")]
             Self::NistP521 => Some(constants::SECP521R1),
 
+            #[cfg(feature = "curve-brainpool160")]
             Self::Brainpool160 => Some(constants::BRAINPOOL_P160R1),
+            #[cfg(feature = "curve-brainpool192")]
             Self::Brainpool192 => Some(constants::BRAINPOOL_P192R1),
+            #[cfg(feature = "curve-brainpool224")]
             Self::Brainpool224 => Some(constants::BRAINPOOL_P224R1),
+            #[cfg(feature = "curve-brainpool256")]
             Self::Brainpool256 => Some(constants::BRAINPOOL_P256R1),
+            #[cfg(feature = "curve-brainpool320")]
             Self::Brainpool320 => Some(constants::BRAINPOOL_P320R1),
+            #[cfg(feature = "curve-brainpool384")]
             Self::Brainpool384 => Some(constants::BRAINPOOL_P384R1),
+            #[cfg(feature = "curve-brainpool512")]
             Self::Brainpool512 => Some(constants::BRAINPOOL_P512R1),
 
+            #[cfg(feature = "curve-secp160k1")]
             Self::Secp160k1 => Some(constants::SECP160K1),
+            #[cfg(feature = "curve-secp192k1")]
             Self::Secp192k1 => Some(constants::SECP192K1),
+            #[cfg(feature = "curve-secp224k1")]
             Self::Secp224k1 => Some(constants::SECP224K1),
+            #[cfg(feature = "curve-secp256k1")]
             Self::Secp256k1 => Some(constants::SECP256K1),
 
+            #[cfg(feature = "curve-tpm-bn-p256")]
             Self::TpmEccBnP256 => Some(constants::TPM_BN_P256),
             Self::IdEccEd25519 => None,
             Self::IdEccMontDh25519 => None,
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// Whether the parameters of the curve need to be configured before it can be used
+    pub fn needs_params(&self) -> bool {
+        !matches!(self, Self::IdEccEd25519 | Self::IdEccMontDh25519)
+    }
+
+    /// Parameters to configure, failing if they are needed but their `curve-*` feature is disabled
+    pub(crate) fn required_params(&self) -> Result<Option<constants::CurveConstants>, Error> {
+        match self.params() {
+            None if self.needs_params() => {
+                error!("Parameters of {self:?} are not enabled");
+                Err(Error::Line(line!()))
+            }
+            params => Ok(params),
+        }
+    }
+}
+
+/// No secure messaging
+pub const SECURITY_LEVEL_NONE: u8 = 0x00;
+/// Command MAC
+pub const SECURITY_LEVEL_C_MAC: u8 = 0x01;
+/// Command encryption, always with command MAC
+pub const SECURITY_LEVEL_C_DECRYPTION: u8 = 0x02;
+/// Response MAC
+pub const SECURITY_LEVEL_R_MAC: u8 = 0x10;
+/// Response encryption, always with response MAC
+pub const SECURITY_LEVEL_R_ENCRYPTION: u8 = 0x20;
+
+// Secure messaging of the commands of an SCP03 session, set by `ScpExternalAuthenticate`.
+// Each level is a combination of the `SECURITY_LEVEL_*` bits allowed by GlobalPlatform Amendment D.
+enum_data!(
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum SecurityLevel {
+        /// Authentication only, the commands of the session are sent in plain
+        None = 0x00,
+        /// C-MAC: integrity of the commands, without encryption
+        Mac = 0x01,
+        /// C-MAC and C-DECRYPTION
+        MacEnc = 0x03,
+        /// C-MAC, C-DECRYPTION and R-MAC
+        MacEncRMac = 0x13,
+        /// C-MAC, C-DECRYPTION, R-MAC and R-ENCRYPTION
+        MacEncRMacREnc = 0x33,
+    }
+);
+
+impl SecurityLevel {
+    fn has(self, bits: u8) -> bool {
+        u8::from(self) & bits == bits
+    }
+
+    /// Returns `true` if the commands carry a MAC
+    pub fn command_mac(self) -> bool {
+        self.has(SECURITY_LEVEL_C_MAC)
+    }
+
+    /// Returns `true` if the data of the commands is encrypted
+    pub fn command_encryption(self) -> bool {
+        self.has(SECURITY_LEVEL_C_DECRYPTION)
+    }
+
+    /// Returns `true` if the responses carry a MAC
+    pub fn response_mac(self) -> bool {
+        self.has(SECURITY_LEVEL_R_MAC)
+    }
+
+    /// Returns `true` if the data of the responses is encrypted
+    pub fn response_encryption(self) -> bool {
+        self.has(SECURITY_LEVEL_R_ENCRYPTION)
+    }
+
+    /// Class byte of the commands sent at this level
+    pub fn cla(self) -> Class {
+        if self.command_mac() {
+            SM_CLA
+        } else {
+            NO_SM_CLA
         }
     }
 }
@@ -1446,11 +3577,35 @@ enum_data!(
     #[repr(u8)]
     pub enum SymmKeyType {
         Aes = P1_AES,
+        #[cfg(feature = "legacy-des")]
         Des = P1_DES,
         Hmac = P1_HMAC,
     }
 );
 
+impl SymmKeyType {
+    /// Check that a key of `len` bytes can be stored as a key of this type
+    ///
+    /// AES keys are 16, 24 or 32 bytes long, DES keys 8, 16 or 24 bytes long, and HMAC keys 1 to [`MAX_HMAC_KEY_LEN`](mac::MAX_HMAC_KEY_LEN) bytes long.
+    /// The se05x would otherwise reject the key with an unspecific status word.
+    pub fn check_key_len(self, len: usize) -> Result<(), Error> {
+        let valid = match self {
+            Self::Aes => matches!(len, 16 | 24 | 32),
+            #[cfg(feature = "legacy-des")]
+            Self::Des => matches!(len, 8 | 16 | 24),
+            Self::Hmac => (1..=mac::MAX_HMAC_KEY_LEN).contains(&len),
+        };
+        if !valid {
+            error!("Invalid {self:?} key length: {len}");
+            return Err(Error::InvalidKeyLength {
+                key_type: self,
+                len,
+            });
+        }
+        Ok(())
+    }
+}
+
 enum_data!(
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     #[repr(u16)]
@@ -1582,6 +3737,16 @@ impl Se05XResult {
     }
 }
 
+#[cfg(feature = "se051")]
+enum_data!(
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum Spake2PlusDeviceType {
+        A = SPAKE2PLUS_DEVICE_TYPE_A,
+        B = SPAKE2PLUS_DEVICE_TYPE_B,
+    }
+);
+
 enum_data!(
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     #[repr(u8)]
@@ -1613,15 +3778,23 @@ enum_data!(
         HmacSha256 = HMAC_SHA256,
         HmacSha384 = HMAC_SHA384,
         HmacSha512 = HMAC_SHA512,
+        #[cfg(feature = "legacy-des")]
         DesMac4Iso9797M2 = DES_MAC4_ISO9797_M2,
+        #[cfg(feature = "legacy-des")]
         DesMac4Iso97971M2Alg3 = DES_MAC4_ISO9797_1_M2_ALG3,
+        #[cfg(feature = "legacy-des")]
         DesMac4Iso9797M1 = DES_MAC4_ISO9797_M1,
+        #[cfg(feature = "legacy-des")]
         DesMac4Iso97971M1Alg3 = DES_MAC4_ISO9797_1_M1_ALG3,
+        #[cfg(feature = "legacy-des")]
         DesMac8Iso9797M2 = DES_MAC8_ISO9797_M2,
+        #[cfg(feature = "legacy-des")]
         DesMac8Iso97971M2Alg3 = DES_MAC8_ISO9797_1_M2_ALG3,
+        #[cfg(feature = "legacy-des")]
         DesMac8Iso97971M1Alg3 = DES_MAC8_ISO9797_1_M1_ALG3,
         // DesMac8Iso97971M1Alg3 = DES_MAC8_ISO9797_1_M1_ALG3,
         Cmac128 = CMAC128,
+        #[cfg(feature = "legacy-des")]
         DesCmac8 = DES_CMAC8,
         AesCmac16 = AES_CMAC16,
     }
@@ -1631,13 +3804,21 @@ enum_data!(
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     #[repr(u8)]
     pub enum CipherMode {
+        #[cfg(feature = "legacy-des")]
         DesCbcNopad = DES_CBC_NOPAD,
+        #[cfg(feature = "legacy-des")]
         DesCbcIso9797M1 = DES_CBC_ISO9797_M1,
+        #[cfg(feature = "legacy-des")]
         DesCbcIso9797M2 = DES_CBC_ISO9797_M2,
+        #[cfg(feature = "legacy-des")]
         DesCbcPkcs5 = DES_CBC_PKCS5,
+        #[cfg(feature = "legacy-des")]
         DesEcbNopad = DES_ECB_NOPAD,
+        #[cfg(feature = "legacy-des")]
         DesEcbIso9797M1 = DES_ECB_ISO9797_M1,
+        #[cfg(feature = "legacy-des")]
         DesEcbIso9797M2 = DES_ECB_ISO9797_M2,
+        #[cfg(feature = "legacy-des")]
         DesEcbPkcs5 = DES_ECB_PKCS5,
         AesEcbNopad = AES_ECB_NOPAD,
         AesCbcNopad = AES_CBC_NOPAD,
@@ -1667,11 +3848,39 @@ enum_data!(
     }
 );
 
+#[cfg(feature = "host-digest")]
+impl EcDsaSignatureAlgo {
+    /// Hash `message` with the digest expected as input for this algorithm
+    ///
+    /// Fails for [`Plain`](Self::Plain), which has no digest.
+    pub fn host_digest<'a>(self, message: &[u8], buf: &'a mut [u8; 64]) -> Result<&'a [u8], Error> {
+        fn hash<'a, H: sha2::Digest>(message: &[u8], buf: &'a mut [u8; 64]) -> &'a [u8] {
+            let digest = H::digest(message);
+            buf[..digest.len()].copy_from_slice(&digest);
+            &buf[..digest.len()]
+        }
+
+        Ok(match self {
+            Self::Plain => {
+                error!("No host digest for plain ECDSA");
+                return Err(Error::Line(line!()));
+            }
+            Self::Sha => hash::<sha1::Sha1>(message, buf),
+            Self::Sha224 => hash::<sha2::Sha224>(message, buf),
+            Self::Sha256 => hash::<sha2::Sha256>(message, buf),
+            Self::Sha384 => hash::<sha2::Sha384>(message, buf),
+            Self::Sha512 => hash::<sha2::Sha512>(message, buf),
+        })
+    }
+}
+
 enum_data!(
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     #[repr(u8)]
     pub enum EdDsaSignatureAlgo {
         Pure = SIG_ED25519PURE,
+        /// The data is the SHA-512 digest of the message (RFC 8032 Ed25519ph)
+        PreHashSha512 = SIG_ED25519PH_SHA_512,
     }
 );
 
@@ -1683,6 +3892,17 @@ enum_data!(
     }
 );
 
+enum_data!(
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum TlsPrfType {
+        ClientHello = P2_TLS_PRF_CLI_HELLO,
+        ServerHello = P2_TLS_PRF_SRV_HELLO,
+        ClientRandom = P2_TLS_PRF_CLI_RND,
+        ServerRandom = P2_TLS_PRF_SRV_RND,
+    }
+);
+
 enum_data!(
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     #[repr(u8)]
@@ -1693,6 +3913,119 @@ enum_data!(
     }
 );
 
+impl Memory {
+    /// Value of the `transient` field of the write commands creating an object in this memory
+    ///
+    /// The write commands only tell transient objects from persistent ones with [`INS_TRANSIENT`], and the applet clears the content of transient objects on reset.
+    /// Objects can therefore not be created in [`TransientDeselect`](Self::TransientDeselect) memory, which is rejected.
+    pub fn transient(self) -> Result<bool, Error> {
+        match self {
+            Self::Persistent => Ok(false),
+            Self::TransientReset => Ok(true),
+            Self::TransientDeselect => {
+                error!("Objects cannot be created in clear-on-deselect memory");
+                Err(Error::Line(line!()))
+            }
+        }
+    }
+}
+
+/// Timestamp of the se05x, as returned by [`GetTimestamp`](commands::GetTimestamp) and in attestations
+///
+/// The 12 bytes are made of a 4-byte power-up counter followed by an 8-byte counter running within the power cycle, both big-endian.
+/// Timestamps are therefore ordered even across power cycles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timestamp {
+    pub power_up_counter: u32,
+    pub counter: u64,
+}
+
+impl Timestamp {
+    pub fn from_bytes(bytes: &[u8; 12]) -> Self {
+        let (power_up_counter, counter) = bytes.split_at(4);
+        Self {
+            power_up_counter: u32::from_be_bytes(power_up_counter.try_into().unwrap()),
+            counter: u64::from_be_bytes(counter.try_into().unwrap()),
+        }
+    }
+
+    pub fn to_bytes(self) -> [u8; 12] {
+        let mut bytes = [0; 12];
+        bytes[..4].copy_from_slice(&self.power_up_counter.to_be_bytes());
+        bytes[4..].copy_from_slice(&self.counter.to_be_bytes());
+        bytes
+    }
+
+    /// The timestamp as a single monotonic value
+    pub fn as_u128(self) -> u128 {
+        (u128::from(self.power_up_counter) << 64) | u128::from(self.counter)
+    }
+}
+
+impl From<&[u8; 12]> for Timestamp {
+    fn from(bytes: &[u8; 12]) -> Self {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl commands::GetTimestampResponse<'_> {
+    pub fn parsed(&self) -> Timestamp {
+        self.timestamp.into()
+    }
+}
+
+impl commands::ReadAttestObjectResponse<'_> {
+    /// The [`timestamp`](Self::timestamp) of the attestation
+    pub fn parsed_timestamp(&self) -> Timestamp {
+        self.timestamp.into()
+    }
+}
+
+/// Available memory of each [`Memory`] type, obtained with [`Se05X::memory_report`]
+///
+/// The se05x saturates the reported values at `0x7FFF` bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryReport {
+    pub persistent: u16,
+    pub transient_reset: u16,
+    pub transient_deselect: u16,
+}
+
+impl MemoryReport {
+    pub fn available(&self, memory: Memory) -> u16 {
+        match memory {
+            Memory::Persistent => self.persistent,
+            Memory::TransientReset => self.transient_reset,
+            Memory::TransientDeselect => self.transient_deselect,
+        }
+    }
+
+    /// Returns `true` if at least `required` bytes are available for every memory type
+    ///
+    /// This can be checked before provisioning objects, to detect early that the provisioning would fail.
+    pub fn has_room_for(&self, required: &MemoryReport) -> bool {
+        self.persistent >= required.persistent
+            && self.transient_reset >= required.transient_reset
+            && self.transient_deselect >= required.transient_deselect
+    }
+
+    /// Memory that became available since the `before` report, for every memory type
+    ///
+    /// Memory types with less available memory than in `before` are reported as 0.
+    pub fn freed_since(&self, before: &MemoryReport) -> MemoryReport {
+        MemoryReport {
+            persistent: self.persistent.saturating_sub(before.persistent),
+            transient_reset: self.transient_reset.saturating_sub(before.transient_reset),
+            transient_deselect: self
+                .transient_deselect
+                .saturating_sub(before.transient_deselect),
+        }
+    }
+}
+
+#[cfg(feature = "rsa")]
 enum_data!(
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     #[repr(u8)]
@@ -1710,6 +4043,7 @@ enum_data!(
     }
 );
 
+#[cfg(feature = "rsa")]
 enum_data!(
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     #[repr(u8)]
@@ -1720,15 +4054,47 @@ enum_data!(
     }
 );
 
+// Origin of a secure object, the last byte of its `ObjectAttributes`
 enum_data!(
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     #[repr(u8)]
-    pub enum SetIndicator {
+    pub enum Origin {
+        /// Generated outside the se05x and imported
+        External = ORIGIN_EXTERNAL,
+        /// Generated inside the se05x
+        Internal = ORIGIN_INTERNAL,
+        /// Trust provisioned by NXP
+        Provisioned = ORIGIN_PROVISIONED,
+    }
+);
+
+enum_data!(
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum SetIndicator {
         Set = SET,
         NotSet = NOT_SET,
     }
 );
 
+/// Reject data objects with unexpected tags in responses, instead of skipping them, with the `strict-tlv` feature
+fn check_skipped_tag(_tag: Tag) -> Result<(), Error> {
+    if cfg!(feature = "strict-tlv") {
+        error!("Unexpected tag in response: {_tag:?}");
+        return Err(Error::Tlv);
+    }
+    Ok(())
+}
+
+/// Reject the bytes left after the last field of a response, instead of ignoring them, with the `strict-tlv` feature
+fn check_trailing_data(rem: &[u8]) -> Result<(), Error> {
+    if cfg!(feature = "strict-tlv") && !rem.is_empty() {
+        error!("Trailing data in response: {} bytes", rem.len());
+        return Err(Error::Tlv);
+    }
+    Ok(())
+}
+
 fn take_do_until<'data, E, T: TryFrom<&'data [u8], Error = E>>(
     tag: Tag,
     data: &'data [u8],
@@ -1743,6 +4109,7 @@ where
             if read_tag == tag {
                 return Ok((value, r));
             }
+            check_skipped_tag(read_tag)?;
             rem_inner = r;
         }
     }
@@ -1775,6 +4142,7 @@ where
             } else if next.contains(&read_tag) {
                 return Ok((None, rem_inner));
             }
+            check_skipped_tag(read_tag)?;
             rem_inner = r;
         }
     }
@@ -1783,6 +4151,21 @@ where
     Ok((value.map(TryInto::try_into).transpose()?, rem))
 }
 
+/// Same as [`take_opt_do_until`], but also returns `None` if the response ends before the tag
+fn take_opt_do_until_end<'data, E, T: TryFrom<&'data [u8], Error = E>>(
+    tag: Tag,
+    next: &[Tag],
+    data: &'data [u8],
+) -> Result<(Option<T>, &'data [u8]), Error>
+where
+    Error: From<E>,
+{
+    if data.is_empty() {
+        return Ok((None, data));
+    }
+    take_opt_do_until(tag, next, data)
+}
+
 impl commands::ReadEcCurveListResponse<'_> {
     pub fn is_set(&self, curve: EcCurve) -> bool {
         let id: u8 = curve.into();
@@ -1794,10 +4177,277 @@ impl commands::ReadEcCurveListResponse<'_> {
     }
 }
 
+impl<'data> commands::ReadCryptoObjListResponse<'data> {
+    /// Iterate over the crypto objects of the list, as `(id, context, subtype)`
+    ///
+    /// The subtype is the [`Digest`], [`CipherMode`], [`MacAlgo`] or signature algorithm the object was created with, depending on the context.
+    /// The applet returns the whole list in a single response, there is no [`MoreIndicator`] for this command.
+    pub fn entries(&self) -> CryptoObjListIter<'data> {
+        CryptoObjListIter { rem: self.list }
+    }
+
+    /// Find a crypto object created for `context` and `subtype`
+    pub fn find(
+        &self,
+        context: CryptoContext,
+        subtype: u8,
+    ) -> Result<Option<CryptoObjectId>, Error> {
+        for entry in self.entries() {
+            let (id, entry_context, entry_subtype) = entry?;
+            if entry_context == context && entry_subtype == subtype {
+                return Ok(Some(id));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Iterator over the entries of a [`ReadCryptoObjListResponse`](commands::ReadCryptoObjListResponse)
+#[derive(Debug, Clone)]
+pub struct CryptoObjListIter<'data> {
+    rem: &'data [u8],
+}
+
+impl Iterator for CryptoObjListIter<'_> {
+    type Item = Result<(CryptoObjectId, CryptoContext, u8), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rem.is_empty() {
+            return None;
+        }
+        if self.rem.len() < 4 {
+            error!("Invalid crypto object list length: {}", self.rem.len());
+            self.rem = &[];
+            return Some(Err(Error::Tlv));
+        }
+        let (entry, rem) = self.rem.split_at(4);
+        self.rem = rem;
+        let (id, context, subtype) = ([entry[0], entry[1]], entry[2], entry[3]);
+        let Ok(context) = context.try_into() else {
+            error!("Invalid crypto context: {context:02x}");
+            return Some(Err(Error::Tlv));
+        };
+        Some(Ok((CryptoObjectId(id), context, subtype)))
+    }
+}
+
+/// Write a BER-TLV encoded data object to the start of `buf`, returning the number of bytes written
+fn write_tlv(buf: &mut [u8], tag: u8, value: &[u8]) -> Result<usize, Error> {
+    let len = value.len();
+    let mut header = [tag, 0, 0, 0];
+    let header_len = match len {
+        0..=0x7F => {
+            header[1] = len as u8;
+            2
+        }
+        0x80..=0xFF => {
+            header[1] = 0x81;
+            header[2] = len as u8;
+            3
+        }
+        0x100..=0xFFFF => {
+            header[1] = 0x82;
+            header[2..].copy_from_slice(&(len as u16).to_be_bytes());
+            4
+        }
+        _ => return Err(Error::Line(line!())),
+    };
+    let total = header_len + len;
+    if buf.len() < total {
+        return Err(Error::Line(line!()));
+    }
+    buf[..header_len].copy_from_slice(&header[..header_len]);
+    buf[header_len..total].copy_from_slice(value);
+    Ok(total)
+}
+
+impl commands::ReadAttestObjectResponse<'_> {
+    /// Build the data covered by the attestation [`signature`](Self::signature)
+    ///
+    /// The signature is computed over the TLV-encoded response fields, `TAG_1` (if present) up to `TAG_5`, in the order they are returned by the secure element.
+    /// This does not communicate with the secure element, so offline verifiers can rebuild the signed data from the response fields alone.
+    pub fn signed_data<'buf>(&self, buf: &'buf mut [u8]) -> Result<&'buf [u8], Error> {
+        let mut offset = 0;
+        if let Some(data) = self.data {
+            offset += write_tlv(&mut buf[offset..], 0x41, data)?;
+        }
+        offset += write_tlv(&mut buf[offset..], 0x42, self.attributes.as_bytes())?;
+        offset += write_tlv(&mut buf[offset..], 0x43, self.timestamp)?;
+        offset += write_tlv(&mut buf[offset..], 0x44, self.freshness_random)?;
+        offset += write_tlv(&mut buf[offset..], 0x45, self.chip_unique_id)?;
+        Ok(&buf[..offset])
+    }
+}
+
+fn invalid_command(_reason: &str) -> Result<(), Error> {
+    error!("Invalid command: {_reason}");
+    Err(Error::Line(line!()))
+}
+
+fn check_auth_fields(is_auth: bool, max_attempts: Option<Be<u16>>) -> Result<(), Error> {
+    if max_attempts.is_some() && !is_auth {
+        return invalid_command("max_attempts is only allowed for authentication objects");
+    }
+    Ok(())
+}
+
+impl commands::WriteEcKey<'_> {
+    /// Check the combination of fields, called by [`Se05X::run_command`] before the command is sent
+    ///
+    /// A new key requires a `curve`. As a `policy` can only be set on creation, it requires a `curve` too.
+    /// With a `curve`, the `public_key` must be in the format described in [`ec_key`].
+    pub fn validate(&self) -> Result<(), Error> {
+        check_auth_fields(self.is_auth, self.max_attempts)?;
+        if self.policy.is_some() && self.curve.is_none() {
+            return invalid_command("a new EC key requires a curve");
+        }
+        if let (Some(curve), Some(public_key)) = (self.curve, self.public_key) {
+            ec_key::check_public_key(curve, public_key)?;
+        }
+        let generate = self.private_key.is_none() && self.public_key.is_none();
+        match self.key_type {
+            Some(P1KeyType::Public) if self.private_key.is_some() => {
+                invalid_command("public key with a private key value")
+            }
+            Some(P1KeyType::Private) if self.public_key.is_some() => {
+                invalid_command("private key with a public key value")
+            }
+            Some(P1KeyType::Public | P1KeyType::Private) if generate => {
+                invalid_command("only key pairs can be generated")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl commands::GenEcKey<'_> {
+    /// Check the combination of fields, called by [`Se05X::run_command`] before the command is sent
+    ///
+    /// As a `policy` can only be set on creation, it requires a `curve`.
+    pub fn validate(&self) -> Result<(), Error> {
+        check_auth_fields(self.is_auth, self.max_attempts)?;
+        if self.policy.is_some() && self.curve.is_none() {
+            return invalid_command("a new EC key requires a curve");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rsa")]
+impl commands::WriteRsaKey<'_> {
+    /// Check the combination of fields, called by [`Se05X::run_command`] before the command is sent
+    ///
+    /// A new key requires a `key_size`. As a `policy` can only be set on creation, it requires a `key_size` too.
+    pub fn validate(&self) -> Result<(), Error> {
+        check_auth_fields(self.is_auth, self.max_attempts)?;
+        if self.policy.is_some() && self.key_size.is_none() {
+            return invalid_command("a new RSA key requires a key size");
+        }
+        Ok(())
+    }
+}
+
+impl commands::WriteSymmKey<'_> {
+    /// Check the combination of fields, called by [`Se05X::run_command`] before the command is sent
+    pub fn validate(&self) -> Result<(), Error> {
+        check_auth_fields(self.is_auth, self.max_attempts)?;
+        // Wrapped keys are longer than the key itself
+        if self.kek_id.is_none() {
+            self.key_type.check_key_len(self.value.len())
+        } else if self.value.is_empty() {
+            invalid_command("empty key value")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl commands::WriteBinary<'_> {
+    /// Check the combination of fields, called by [`Se05X::run_command`] before the command is sent
+    ///
+    /// `file_length` and `policy` can only be given when creating the file, and `offset` only when updating it.
+    /// On creation, `data` must fit in the file.
+    pub fn validate(&self) -> Result<(), Error> {
+        match (self.file_length, self.offset) {
+            (Some(_), Some(_)) => invalid_command("file_length and offset are exclusive"),
+            (None, _) if self.policy.is_some() => {
+                invalid_command("a policy requires a file_length")
+            }
+            (None, _) if self.data.is_none() => invalid_command("no data to write"),
+            (Some(Be(file_length)), None)
+                if self.data.map_or(0, <[u8]>::len) > usize::from(file_length) =>
+            {
+                invalid_command("data longer than file_length")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl commands::ReadObject {
+    /// Check the combination of fields, called by [`Se05X::run_command`] before the command is sent
+    ///
+    /// Only the public components of RSA keys can be read, and never partially.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self.rsa_key_component {
+            None | Some(RsaKeyComponent::Na) => Ok(()),
+            Some(RsaKeyComponent::Mod | RsaKeyComponent::PubExp)
+                if self.offset.is_some() || self.length.is_some() =>
+            {
+                invalid_command("RSA key components cannot be read partially")
+            }
+            Some(RsaKeyComponent::Mod | RsaKeyComponent::PubExp) => Ok(()),
+            Some(_) => invalid_command("only public RSA key components can be read"),
+        }
+    }
+}
+
+impl commands::TlsPerformPrf<'_> {
+    /// Check the combination of fields, called by [`Se05X::run_command`] before the command is sent
+    pub fn validate(&self) -> Result<(), Error> {
+        if !(1..=64).contains(&self.label.len()) {
+            return invalid_command("TLS PRF label must be 1 to 64 bytes long");
+        }
+        if !matches!(self.random.len(), 32 | 64) {
+            return invalid_command("TLS PRF random must be 32 or 64 bytes long");
+        }
+        Ok(())
+    }
+}
+
+impl commands::Hkdf<'_> {
+    /// Check the combination of fields, called by [`Se05X::run_command`] before the command is sent
+    ///
+    /// The input key material cannot be replaced by the derived key.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.requested_len.0 == 0 {
+            return invalid_command("empty derived key");
+        }
+        if self.target == Some(self.ikm) {
+            return invalid_command("the derived key cannot replace the input key material");
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{commands::CipherOneShotEncrypt, *};
 
+    #[test]
+    fn curve_params() {
+        assert!(!EcCurve::IdEccEd25519.needs_params());
+        assert_eq!(EcCurve::IdEccEd25519.required_params(), Ok(None));
+        assert_eq!(
+            EcCurve::NistP256.required_params().is_ok(),
+            cfg!(feature = "curve-nist-p256")
+        );
+        assert_eq!(
+            EcCurve::Secp256k1.params().is_some(),
+            cfg!(feature = "curve-secp256k1")
+        );
+    }
+
     #[test]
     fn encrypt_length() {
         let key_id = ObjectId(hex!("03445566"));
@@ -1811,4 +4461,1203 @@ mod tests {
         };
         assert!(command.len() < MAX_APDU_PAYLOAD_LENGTH);
     }
+
+    #[test]
+    fn transient_status() {
+        assert!(Error::Status(Status::from(0x6400)).is_transient());
+        assert!(Error::Status(Status::from(0x6402)).is_transient());
+        assert!(!Error::Status(Status::from(0x6985)).is_transient());
+        assert!(!Error::Status(Status::Success).is_transient());
+        assert!(!Error::T1(t1::Error::Timeout).is_transient());
+    }
+
+    const GET_RANDOM: [u8; 13] = hex!("80 04 00 49 00 00 04 41 02 0002 00 00");
+    const RANDOM: [u8; 6] = hex!("41 02 ABCD 90 00");
+    const BUSY: [u8; 2] = hex!("64 00");
+    const GET_VERSION: [u8; 5] = hex!("80 04 00 20 0B");
+
+    #[test]
+    fn retry_transient_errors() {
+        let policy = RetryPolicy {
+            max_retries: 2,
+            initial_backoff_us: 1_000,
+            max_backoff_us: 50_000,
+        };
+        let command = commands::GetRandom { length: 2.into() };
+        let buf = &mut [0; 16];
+        let mut script = apdu::ScriptedTransport::new();
+        script
+            .expect(&GET_RANDOM, &BUSY)
+            // Retried twice before succeeding
+            .expect(&GET_RANDOM, &BUSY)
+            .expect(&GET_RANDOM, &BUSY)
+            .expect(&GET_RANDOM, &RANDOM)
+            // Retried twice before giving up
+            .expect(&GET_RANDOM, &BUSY)
+            .expect(&GET_RANDOM, &BUSY)
+            .expect(&GET_RANDOM, &BUSY);
+        let mut se05x = script.enable();
+
+        let err = se05x.run_command(&command, buf).err().unwrap();
+        assert_eq!(err.device_condition(), Some(DeviceCondition::Busy));
+
+        se05x.set_retry_policy(Some(policy));
+        assert_eq!(se05x.run_command(&command, buf).unwrap().data, [0xAB, 0xCD]);
+        assert_eq!(se05x.retry_count(), 2);
+
+        assert!(se05x.run_command(&command, buf).unwrap_err().is_transient());
+        assert_eq!(se05x.retry_count(), 4);
+        let script = se05x.transport().inner_mut();
+        assert!(script.is_done());
+        assert_eq!(script.delays(), [1_000, 2_000, 1_000, 2_000]);
+    }
+
+    #[test]
+    fn auto_recovery() {
+        let command = commands::GetRandom { length: 2.into() };
+        let buf = &mut [0; 16];
+        // The se05x is power-cycled, deselecting the applet, while running the second and the fourth command
+        let power_loss = Error::T1(t1::Error::Timeout);
+        let mut script = apdu::ScriptedTransport::new();
+        script
+            .expect(&GET_RANDOM, &RANDOM)
+            .expect_error(&GET_RANDOM, power_loss)
+            .expect(&GET_RANDOM, &hex!("6D 00"))
+            .expect_error(&GET_RANDOM, power_loss)
+            .expect(&GET_RANDOM, &hex!("6D 00"))
+            .expect(&SELECT, &apdu::SELECT_RESPONSE)
+            .expect(&GET_RANDOM, &RANDOM);
+        let mut se05x = script.enable();
+        se05x.run_command(&command, buf).unwrap();
+        assert_eq!(
+            se05x.run_command(&command, buf).unwrap_err(),
+            Error::T1(t1::Error::Timeout)
+        );
+        assert!(!Error::T1(t1::Error::Timeout).is_power_loss());
+        assert!(!Error::T1(t1::Error::BadCrc).is_power_loss());
+        assert!(se05x
+            .run_command(&command, buf)
+            .unwrap_err()
+            .is_power_loss());
+        assert!(!se05x.take_recovered());
+
+        se05x.set_auto_recovery(true);
+        // The link error itself is not recovered, only the rejection of the next command
+        assert_eq!(
+            se05x.run_command(&command, buf).unwrap_err(),
+            Error::T1(t1::Error::Timeout)
+        );
+        assert!(!se05x.take_recovered());
+        assert_eq!(se05x.run_command(&command, buf).unwrap().data, [0xAB, 0xCD]);
+        assert!(se05x.take_recovered());
+        assert!(!se05x.take_recovered());
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn status_pass_through() {
+        let command = commands::GetRandom { length: 2.into() };
+        let buf = &mut [0; 16];
+        let mut script = apdu::ScriptedTransport::new();
+        script
+            .expect(&GET_RANDOM, &BUSY)
+            .expect(&GET_RANDOM, &RANDOM);
+        let mut se05x = script.enable();
+
+        let (response, status) = se05x.run_command_with_status(&command, buf).unwrap();
+        assert_eq!(response, None);
+        assert_eq!(status, Status::from(0x6400));
+
+        let (response, status) = se05x.run_command_with_status(&command, buf).unwrap();
+        assert_eq!(response.unwrap().data, [0xAB, 0xCD]);
+        assert_eq!(status, Status::Success);
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    const SELECT: [u8; 22] = hex!("00 A4 04 00 10 A0000003965453000000010300000000 00");
+
+    const CUSTOM_AID: [u8; 16] = hex!("A0000003965453000000010300000001");
+
+    #[test]
+    fn select_custom_aid() {
+        let mut script = apdu::ScriptedTransport::new();
+        script.expect(&SELECT, &hex!("6A 82")).expect(
+            &hex!("00 A4 04 00 10 A0000003965453000000010300000001 00"),
+            &apdu::SELECT_RESPONSE,
+        );
+        let mut se05x = apdu::ApduSe05X::from_transport(apdu::ApduLink::new(script));
+        assert_eq!(se05x.enable(), Err(Error::Status(0x6A82.into())));
+        se05x.set_aid(&CUSTOM_AID);
+        assert_eq!(se05x.enable().unwrap().major, 3);
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn raw_apdu() {
+        let mut script = apdu::ScriptedTransport::new();
+        script
+            .expect(&hex!("80 F0 01 02 02 AABB 04"), &hex!("010203 90 00"))
+            .expect(&hex!("80 F1 00 00"), &hex!("6D 00"));
+        let mut se05x = script.enable();
+        let buf = &mut [0; 16];
+        assert_eq!(
+            se05x.run_raw_apdu(0x80, 0xF0, 0x01, 0x02, &[0xAA, 0xBB], 4.into(), buf),
+            Ok((&[0x01, 0x02, 0x03][..], Status::Success))
+        );
+        // Error status words are returned, not raised
+        assert_eq!(
+            se05x.run_raw_apdu(0x80, 0xF1, 0x00, 0x00, &[], 0.into(), buf),
+            Ok((&[][..], Status::from(0x6D00)))
+        );
+        assert!(se05x
+            .run_raw_apdu(0xFF, 0xF0, 0x00, 0x00, &[], 0.into(), buf)
+            .is_err());
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn raw_command() {
+        use iso7816::command::Writer;
+
+        let mut script = apdu::ScriptedTransport::new();
+        script.expect(&hex!("80 F0 01 02 02 AABB 04"), &hex!("010203 90 00"));
+        let mut se05x = script.enable();
+        let mut command = se05x.begin_raw_command(8).unwrap();
+        command.write_all(&[0x80, 0xF0, 0x01]).unwrap();
+        command.write_all(&[0x02, 0x02, 0xAA, 0xBB, 0x04]).unwrap();
+        let sent = command.finish().unwrap();
+        assert_eq!(
+            se05x.receive_raw_response(sent, &mut [0; 16]),
+            Ok((&[0x01, 0x02, 0x03][..], Status::Success))
+        );
+
+        let mut command = se05x.begin_raw_command(4).unwrap();
+        assert!(command.write_all(&[0x80, 0xF0, 0x01, 0x02, 0x00]).is_err());
+        let mut command = se05x.begin_raw_command(4).unwrap();
+        command.write_all(&[0x80, 0xF0]).unwrap();
+        assert!(command.finish().is_err());
+        assert!(se05x
+            .begin_raw_command(MAX_APDU_PAYLOAD_LENGTH + MAX_APDU_OVERHEAD + 1)
+            .is_err());
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn ephemeral_pubkey() {
+        let mut public_key = [0x11; 65];
+        public_key[0] = ec_key::SEC1_UNCOMPRESSED;
+        let exists = hex!("80 04 00 27 00 00 06 41 04 EFFFFFFF 00 00");
+        let delete = hex!("80 04 00 28 06 41 04 EFFFFFFF");
+        let write = [
+            &hex!("80 81 21 00 4C 41 04 EFFFFFFF 42 01 03 44 41")[..],
+            &public_key,
+        ]
+        .concat();
+        let verify = |signature: [u8; 2]| {
+            [
+                &hex!("80 03 0C 0A 2F 41 04 EFFFFFFF 42 01 21 43 20")[..],
+                &[0x22; 32],
+                &hex!("45 02"),
+                &signature,
+                &hex!("03"),
+            ]
+            .concat()
+        };
+        // Starts with a leftover ephemeral key
+        let mut script = apdu::ScriptedTransport::new();
+        script
+            .expect(&exists, &hex!("41 01 01 90 00"))
+            .expect(&delete, &hex!("90 00"))
+            .expect(&write, &hex!("90 00"))
+            .expect(&verify([0x30, 0x00]), &hex!("41 01 01 90 00"))
+            .expect(&delete, &hex!("90 00"))
+            .expect(&exists, &hex!("41 01 02 90 00"))
+            .expect(&write, &hex!("90 00"))
+            .expect(&verify([0xBA, 0xD0]), &hex!("41 01 02 90 00"))
+            .expect(&delete, &hex!("90 00"));
+        let mut se05x = script.enable();
+        let digest = [0x22; 32];
+        let curve = EcCurve::NistP256;
+        let algo = EcDsaSignatureAlgo::Sha256;
+        assert_eq!(
+            se05x.verify_with_ephemeral_pubkey(curve, &public_key, &digest, &[0x30, 0x00], algo),
+            Ok(true)
+        );
+        assert_eq!(
+            se05x.verify_with_ephemeral_pubkey(curve, &public_key, &digest, &[0xBA, 0xD0], algo),
+            Ok(false)
+        );
+        assert!(se05x
+            .verify_with_ephemeral_pubkey(curve, &public_key[1..], &digest, &[0x30, 0x00], algo)
+            .is_err());
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn delete_objects_matching() {
+        let mut script = apdu::ScriptedTransport::new();
+        // The reserved `7FFF0200` is skipped, and the next listing starts after it
+        script
+            .expect(
+                &hex!("80 02 00 25 00 00 07 41 02 0000 42 01 FF 00 00"),
+                &hex!("41 01 02 42 04 7FFF0200 90 00"),
+            )
+            // More objects are announced, but none is listed
+            .expect(
+                &hex!("80 02 00 25 00 00 07 41 02 0001 42 01 FF 00 00"),
+                &hex!("41 01 02 42 00 90 00"),
+            );
+        let mut se05x = script.enable();
+        assert!(se05x
+            .delete_objects_matching(SecureObjectFilter::All, None, |_, _| panic!())
+            .is_err());
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn delete_and_verify() {
+        let free_memory = |memory: u8, free: u16| {
+            (
+                [0x80, 0x04, 0x00, 0x22, 0x03, 0x41, 0x01, memory, 0x06],
+                [&hex!("41 02")[..], &free.to_be_bytes(), &hex!("90 00")].concat(),
+            )
+        };
+        // The policy of `00000002` forbids its deletion and `00000003` is kept despite a successful deletion
+        let mut script = apdu::ScriptedTransport::new();
+        script
+            .expect(&hex!("80 04 00 28 06 41 04 00000002"), &hex!("69 85"))
+            .expect(
+                &hex!("80 04 00 27 00 00 06 41 04 00000002 00 00"),
+                &hex!("41 01 01 90 00"),
+            )
+            .expect(&hex!("80 04 00 28 06 41 04 00000003"), &hex!("90 00"))
+            .expect(
+                &hex!("80 04 00 27 00 00 06 41 04 00000003 00 00"),
+                &hex!("41 01 01 90 00"),
+            );
+        // Deleting `00000001` frees 50 bytes of persistent memory
+        for (memory, free) in [(1, 100), (2, 100), (3, 100)] {
+            let (command, response) = free_memory(memory, free);
+            script.expect(&command, &response);
+        }
+        script
+            .expect(&hex!("80 04 00 28 06 41 04 00000001"), &hex!("90 00"))
+            .expect(
+                &hex!("80 04 00 27 00 00 06 41 04 00000001 00 00"),
+                &hex!("41 01 02 90 00"),
+            );
+        for (memory, free) in [(1, 150), (2, 100), (3, 100)] {
+            let (command, response) = free_memory(memory, free);
+            script.expect(&command, &response);
+        }
+        let mut se05x = script.enable();
+        for object_id in [ObjectId([0, 0, 0, 2]), ObjectId([0, 0, 0, 3])] {
+            assert_eq!(
+                se05x.delete_and_verify(object_id),
+                Err(Error::NotDeleted { object_id })
+            );
+        }
+        assert_eq!(
+            se05x.delete_and_measure(ObjectId([0, 0, 0, 1])),
+            Ok(MemoryReport {
+                persistent: 50,
+                transient_reset: 0,
+                transient_deselect: 0,
+            })
+        );
+        assert!(se05x.transport().inner_mut().is_done());
+        assert_eq!(
+            Error::from(Status::from(Error::NotDeleted {
+                object_id: ObjectId([0, 0, 0, 2])
+            })),
+            Error::Status(Status::from(0x000C))
+        );
+    }
+
+    #[test]
+    fn list_objects_in_range() {
+        // Lists `00000001`, `20000000`, `2FFFFFFF` and `30000000` in pages of two objects
+        let mut script = apdu::ScriptedTransport::new();
+        for _ in 0..2 {
+            script
+                .expect(
+                    &hex!("80 02 00 25 00 00 07 41 02 0000 42 01 FF 00 00"),
+                    &hex!("41 01 02  42 08 00000001 20000000  9000"),
+                )
+                .expect(
+                    &hex!("80 02 00 25 00 00 07 41 02 0002 42 01 FF 00 00"),
+                    &hex!("41 01 01  42 08 2FFFFFFF 30000000  9000"),
+                );
+        }
+        let mut se05x = script.enable();
+        let mut ids: heapless::Vec<ObjectId, 4> = heapless::Vec::new();
+        let count = se05x
+            .list_objects_in_range(SecureObjectFilter::All, 0x2000_0000..=0x2FFF_FFFF, |id| {
+                ids.push(id).unwrap()
+            })
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(
+            ids,
+            [ObjectId(hex!("20000000")), ObjectId(hex!("2FFFFFFF"))]
+        );
+        let count = se05x
+            .list_objects_in_range(SecureObjectFilter::All, .., |_| {})
+            .unwrap();
+        assert_eq!(count, 4);
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn applet_version() {
+        let info = VersionInfo::try_from(&hex!("07 02 00 3F FF 01 00")[..]).unwrap();
+        assert_eq!(info.version, AppletVersion::new(7, 2, 0));
+        assert_eq!(info.applet_config, AppletConfig::ALL);
+        assert!(VersionInfo::try_from(&hex!("07 02 00 3F FF 01")[..]).is_err());
+        assert!(info.version.supports(VersionFeature::Se051Commands));
+        assert!(!AppletVersion::new(3, 1, 1).supports(VersionFeature::Se051Commands));
+        assert!(AppletVersion::new(3, 12, 0) > AppletVersion::new(3, 9, 7));
+
+        let mut script = apdu::ScriptedTransport::new();
+        script.expect(&GET_VERSION, &hex!("41 07 03 01 00 3F FF 01 00  9000"));
+        let mut se05x = apdu::ApduSe05X::from_transport(apdu::ApduLink::new(script));
+        let atr = se05x.enable().unwrap();
+        assert_eq!(se05x.applet_version(), Ok(atr.version()));
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn liveness() {
+        // The applet is deselected after the first `GetVersion`, then the se05x stops answering
+        let mut script = apdu::ScriptedTransport::new();
+        script
+            .expect(&GET_VERSION, &hex!("41 07 03 01 00 3F FF 01 00  9000"))
+            .expect(&GET_VERSION, &hex!("6D 00"))
+            .expect_error(&GET_VERSION, Error::T1(t1::Error::Timeout));
+        let mut se05x = apdu::ApduSe05X::from_transport(apdu::ApduLink::new(script));
+        assert_eq!(se05x.is_alive(), Liveness::NeedsReenable);
+        se05x.enable().unwrap();
+        assert_eq!(se05x.is_alive(), Liveness::Alive);
+        assert_eq!(se05x.is_alive(), Liveness::NeedsReenable);
+        assert_eq!(se05x.is_alive(), Liveness::Unresponsive);
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn sign_retry_replaces_policy() {
+        let policy = RetryPolicy {
+            max_retries: 2,
+            initial_backoff_us: 1_000,
+            max_backoff_us: 50_000,
+        };
+        let key_id = ObjectId([1, 2, 3, 4]);
+        let algo = EcDsaSignatureAlgo::Sha256;
+        let buf = &mut [0; 16];
+        let sign = [
+            &hex!("80 03 0C 09 00 00 2B 41 04 01020304 42 01 21 43 20")[..],
+            &[0; 32],
+            &hex!("00 00"),
+        ]
+        .concat();
+        let mut script = apdu::ScriptedTransport::new();
+        script
+            .expect(&sign, &BUSY)
+            .expect(&sign, &BUSY)
+            .expect(&sign, &hex!("41 02 3000 90 00"))
+            .expect(&sign, &BUSY)
+            .expect(&sign, &BUSY)
+            .expect(&sign, &BUSY);
+        let mut se05x = script.enable();
+        se05x.set_retry_policy(Some(policy));
+
+        let signature = se05x.sign_digest_with_retry(key_id, algo, &[0; 32], policy, buf);
+        assert_eq!(signature.unwrap().signature, [0x30, 0x00]);
+        assert_eq!(se05x.retry_count(), 2);
+
+        // Three attempts, not nested in the retries of the retry policy
+        let signature = se05x.sign_digest_with_retry(key_id, algo, &[0; 32], policy, buf);
+        assert!(signature.unwrap_err().is_transient());
+        assert_eq!(se05x.retry_count(), 4);
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn attributes_allow() {
+        use policies::Operation;
+
+        let no_policy = hex!("01020304 01 01 0000 00000000 0000 01");
+        let attributes = ObjectAttributes::parse(&no_policy).unwrap();
+        assert_eq!(attributes.policies().count(), 0);
+        assert_eq!(
+            attributes.allows(ObjectId::INVALID, Operation::Sign),
+            Ok(true)
+        );
+
+        // Signing for everyone, deletion only in sessions authenticated with 00000001
+        let policies = hex!(
+            "01020304 01 01 0000 00000000 0000"
+            "08 00000000 10000000"
+            "08 00000001 00040000"
+            "02"
+        );
+        let attributes = ObjectAttributes::parse(&policies).unwrap();
+        let user = ObjectId([0, 0, 0, 1]);
+        assert_eq!(
+            attributes.allows(ObjectId::INVALID, Operation::Sign),
+            Ok(true)
+        );
+        assert_eq!(
+            attributes.allows(ObjectId::INVALID, Operation::Delete),
+            Ok(false)
+        );
+        assert_eq!(attributes.allows(user, Operation::Delete), Ok(true));
+        assert_eq!(attributes.allows(user, Operation::Sign), Ok(false));
+        assert_eq!(
+            attributes.allows(ObjectId([0, 0, 0, 2]), Operation::Sign),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn attributes_origin() {
+        let no_policy = hex!("01020304 01 01 0000 00000000 0000 03");
+        let attributes = ObjectAttributes::parse(&no_policy).unwrap();
+        assert_eq!(attributes.origin(), Some(Origin::Provisioned));
+        assert_eq!(attributes.raw_policy(), []);
+
+        let policies = hex!(
+            "01020304 01 01 0000 00000000 0000"
+            "08 00000000 10000000"
+            "01"
+        );
+        let attributes = ObjectAttributes::parse(&policies).unwrap();
+        assert_eq!(attributes.origin(), Some(Origin::External));
+        assert_eq!(attributes.raw_policy(), hex!("08 00000000 10000000"));
+        let mut iter = attributes.policies();
+        assert_eq!(iter.next().unwrap().unwrap().object_id, ObjectId([0; 4]));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.remainder(), []);
+
+        // Without origin
+        let attributes = ObjectAttributes::parse(&policies[..policies.len() - 1]).unwrap();
+        assert_eq!(attributes.origin(), None);
+        assert_eq!(attributes.raw_policy(), hex!("08 00000000 10000000"));
+    }
+
+    #[test]
+    fn device_condition() {
+        assert_eq!(
+            Error::Status(0x6A84.into()).device_condition(),
+            Some(DeviceCondition::MemoryFull)
+        );
+        assert_eq!(
+            Error::Status(0x6581.into()).device_condition(),
+            Some(DeviceCondition::MemoryFailure)
+        );
+        assert!(!Error::Status(0x6A84.into()).is_transient());
+        assert_eq!(Error::Status(0x6985.into()).device_condition(), None);
+        assert_eq!(Error::Tlv.device_condition(), None);
+    }
+
+    #[test]
+    fn retry_backoff() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            initial_backoff_us: 1_000,
+            max_backoff_us: 5_000,
+        };
+        assert_eq!(policy.backoff_us(0), 1_000);
+        assert_eq!(policy.backoff_us(1), 2_000);
+        assert_eq!(policy.backoff_us(2), 4_000);
+        assert_eq!(policy.backoff_us(3), 5_000);
+        assert_eq!(policy.backoff_us(200), 5_000);
+    }
+
+    #[test]
+    fn attestation_signed_data() {
+        let data = [0xAB; 200];
+        let attributes = hex!("01020304 01 01 0000 00000000 0000 08 00000000 00040000 01");
+        let mut response = [0; 512];
+        let mut offset = 0;
+        for (tag, value) in [
+            (0x41, &data[..]),
+            (0x42, &attributes[..]),
+            (0x43, &[0x03; 12][..]),
+            (0x44, &[0x04; 16][..]),
+            (0x45, &[0x05; 18][..]),
+        ] {
+            offset += write_tlv(&mut response[offset..], tag, value).unwrap();
+        }
+        let signed_len = offset;
+        offset += write_tlv(&mut response[offset..], 0x46, &[0x06; 8]).unwrap();
+        assert_eq!(&response[..5], &hex!("41 81 C8 AB AB"));
+
+        let parsed =
+            commands::ReadAttestObjectResponse::from_response(&response[..offset]).unwrap();
+        assert_eq!(parsed.attributes.identifier(), ObjectId(hex!("01020304")));
+        let mut buf = [0; 512];
+        assert_eq!(
+            parsed.signed_data(&mut buf).unwrap(),
+            &response[..signed_len]
+        );
+        assert!(parsed.signed_data(&mut [0; 64]).is_err());
+
+        #[cfg(feature = "owned")]
+        {
+            let owned = parsed.to_owned::<200>().unwrap();
+            assert_eq!(owned.data.as_deref(), Some(&data[..]));
+            assert_eq!(&owned.chip_unique_id, parsed.chip_unique_id);
+            assert!(parsed.to_owned::<199>().is_err());
+        }
+        #[cfg(feature = "serde")]
+        {
+            let serialized = postcard::to_slice(&parsed, &mut buf).unwrap();
+            let deserialized: commands::ReadAttestObjectResponse =
+                postcard::from_bytes(serialized).unwrap();
+            assert_eq!(deserialized, parsed);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "aes-session")]
+    fn unlock_response() {
+        // FIPS-197 Appendix C.1
+        let key = hex!("000102030405060708090a0b0c0d0e0f");
+        let challenge = hex!("00112233445566778899aabbccddeeff");
+        assert_eq!(
+            transport_unlock_response(&key, &challenge),
+            hex!("69c4e0d86a7b0430d8cdb78070b4c55a")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "host-digest")]
+    fn host_digest() {
+        let mut buf = [0; 64];
+        assert_eq!(
+            EcDsaSignatureAlgo::Sha256
+                .host_digest(b"abc", &mut buf)
+                .unwrap(),
+            hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(
+            EcDsaSignatureAlgo::Sha
+                .host_digest(b"abc", &mut buf)
+                .unwrap(),
+            hex!("a9993e364706816aba3e25717850c26c9cd0d89d")
+        );
+        assert_eq!(
+            EcDsaSignatureAlgo::Sha384
+                .host_digest(b"abc", &mut buf)
+                .unwrap()
+                .len(),
+            48
+        );
+        assert!(EcDsaSignatureAlgo::Plain
+            .host_digest(b"abc", &mut buf)
+            .is_err());
+    }
+
+    #[test]
+    fn security_level() {
+        assert!(SecurityLevel::Mac.command_mac());
+        assert!(!SecurityLevel::Mac.command_encryption());
+        assert!(SecurityLevel::MacEncRMac.response_mac());
+        assert!(!SecurityLevel::MacEncRMac.response_encryption());
+        assert!(SecurityLevel::MacEncRMacREnc.response_encryption());
+        assert_eq!(SecurityLevel::None.cla(), NO_SM_CLA);
+        assert_eq!(SecurityLevel::MacEnc.cla(), SM_CLA);
+
+        let mut buf: heapless::Vec<u8, 32> = heapless::Vec::new();
+        commands::ScpExternalAuthenticate {
+            security_level: SecurityLevel::Mac,
+            host_cryptogram: [0x11; 8],
+            mac: [0x22; 8],
+        }
+        .to_writer(&mut buf)
+        .unwrap();
+        assert_eq!(
+            buf,
+            hex!("84 82 01 00 10 1111111111111111 2222222222222222")
+        );
+    }
+
+    #[test]
+    fn error_display() {
+        assert_eq!(
+            Error::Status(Status::from(0x6A82)).to_string(),
+            "The se05x returned the status word 6A82"
+        );
+        assert_eq!(
+            Error::T1(t1::Error::Timeout).to_string(),
+            "T=1 error: Read timed out"
+        );
+        assert_eq!(
+            Error::PolicyViolation {
+                object_id: ObjectId([1, 2, 3, 4]),
+                required_auth: ObjectId::INVALID,
+                status: Status::from(0x6985),
+            }
+            .to_string(),
+            "The policy of 01020304 requires a session authenticated with 00000000 (status word 6985)"
+        );
+        assert_eq!(
+            Error::AuthFailed {
+                remaining_attempts: 2
+            }
+            .to_string(),
+            "Authentication failed, 2 attempts remaining"
+        );
+        assert_eq!(
+            Error::Status(Status::from(0x63C2)).auth_failure(),
+            Error::AuthFailed {
+                remaining_attempts: 2
+            }
+        );
+        assert_eq!(
+            Status::from(Error::AuthFailed {
+                remaining_attempts: 20
+            }),
+            Status::from(0x63CF)
+        );
+        assert_eq!(
+            Error::Status(Status::from(0x6985)).auth_failure(),
+            Error::Status(Status::from(0x6985))
+        );
+    }
+
+    #[test]
+    fn status_round_trip() {
+        let errors = [
+            Error::Unknown,
+            Error::Tlv,
+            Error::Line(42),
+            Error::Status(Status::from(0x6985)),
+            Error::T1(t1::Error::Unknown),
+            Error::T1(t1::Error::AddressNack),
+            Error::T1(t1::Error::DataNack),
+            Error::T1(t1::Error::BadCrc),
+            Error::T1(t1::Error::BadPcb),
+            Error::T1(t1::Error::BadAddress),
+            Error::T1(t1::Error::ReceptionBuffer),
+            Error::T1(t1::Error::Timeout),
+            Error::T1(t1::Error::Line(0x0FFF)),
+            Error::CommandTooLong,
+            Error::FreshnessMismatch,
+            Error::Unsupported(AppletConfig::ECDAA),
+            Error::Unsupported(AppletConfig::I2CM),
+        ];
+        for error in errors {
+            assert_eq!(Error::from(Status::from(error)), error);
+        }
+        assert_eq!(Error::Line(42).category(), ErrorCategory::Internal);
+        assert_eq!(
+            Error::from(Status::from(0x6985)).category(),
+            ErrorCategory::Status
+        );
+        assert_eq!(
+            Error::from(Status::from(0x0009)).category(),
+            ErrorCategory::Transport
+        );
+    }
+
+    #[test]
+    fn get_response_chaining() {
+        assert_eq!(more_available(Status::from(0x6110)), Some(0x10));
+        assert_eq!(more_available(Status::from(0x6100)), Some(256));
+        assert_eq!(more_available(Status::Success), None);
+        assert_eq!(more_available(Status::from(0x6A80)), None);
+
+        let command = commands::GetRandom { length: 2.into() };
+        let mut script = apdu::ScriptedTransport::new();
+        // GET RESPONSE returning no data, but still announcing more
+        script
+            .expect(&GET_RANDOM, &hex!("61 02"))
+            .expect(&hex!("00 C0 00 00 02"), &hex!("61 02"));
+        let mut se05x = script.enable();
+        assert!(se05x.run_command(&command, &mut [0; 16]).is_err());
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn optional_do_at_end() {
+        let (data, rem): (Option<&[u8]>, _) = take_opt_do_until_end(TAG_1, &[TAG_2], &[]).unwrap();
+        assert_eq!((data, rem), (None, &[][..]));
+        assert!(take_opt_do_until::<_, &[u8]>(TAG_1, &[TAG_2], &[]).is_err());
+        let (data, rem): (Option<&[u8]>, _) =
+            take_opt_do_until_end(TAG_1, &[TAG_2], &hex!("42 01 AA")).unwrap();
+        assert_eq!((data, rem), (None, &hex!("42 01 AA")[..]));
+    }
+
+    #[test]
+    fn command_too_long() {
+        let data = [0; MAX_APDU_PAYLOAD_LENGTH];
+        let command = commands::WriteBinary {
+            transient: false,
+            policy: None,
+            object_id: ObjectId(hex!("01020304")),
+            offset: None,
+            file_length: None,
+            data: Some(&data),
+        };
+        assert!(command.len() > MAX_APDU_PAYLOAD_LENGTH + MAX_APDU_OVERHEAD);
+        let command = commands::WriteBinary {
+            data: Some(&data[..CHUNK_LEN]),
+            offset: Some(0xFFFF.into()),
+            ..command
+        };
+        assert!(command.len() <= MAX_APDU_PAYLOAD_LENGTH);
+    }
+
+    #[test]
+    fn crypto_obj_list() {
+        let list = hex!("0001 01 04  0002 02 0D");
+        let response = commands::ReadCryptoObjListResponse { list: &list };
+        let entries: heapless::Vec<_, 2> = response.entries().collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            entries,
+            [
+                (CryptoObjectId(hex!("0001")), CryptoContext::Digest, 0x04),
+                (CryptoObjectId(hex!("0002")), CryptoContext::Cipher, 0x0D),
+            ]
+        );
+        assert_eq!(
+            response.find(CryptoContext::Cipher, 0x0D),
+            Ok(Some(CryptoObjectId(hex!("0002"))))
+        );
+        assert_eq!(response.find(CryptoContext::Signature, 0x0D), Ok(None));
+
+        let truncated = commands::ReadCryptoObjListResponse { list: &list[..6] };
+        assert_eq!(truncated.find(CryptoContext::Cipher, 0x0D), Err(Error::Tlv));
+        let invalid = commands::ReadCryptoObjListResponse {
+            list: &hex!("0001 07 04"),
+        };
+        assert!(invalid.entries().next().unwrap().is_err());
+    }
+
+    #[test]
+    fn object_namespace() {
+        let tls = ObjectNamespace::tenant(0x1000_0000, 2, 0x10);
+        assert_eq!(tls.range(), 0x1000_0020..=0x1000_002F);
+        assert_eq!(tls.get(0x10), None);
+        assert_eq!(tls.slot_of(ObjectId(hex!("1000002F"))), Some(0xF));
+        assert!(!tls.contains(ObjectId(hex!("1000001F"))));
+        let next = ObjectNamespace::tenant(0x1000_0000, 3, 0x10);
+        assert!(ObjectNamespace::disjoint(&[tls, next]));
+        assert!(!ObjectNamespace::disjoint(&[
+            tls,
+            next,
+            ObjectNamespace::new(0x1000_002F, 2)
+        ]));
+        let mut allocator = ObjectIdAllocator::new(tls.range()).unwrap();
+        assert_eq!(allocator.next(), Some(tls.id(0)));
+
+        for (start, len) in [
+            (0, 1),
+            (0x7FFE_FFFF, 2),
+            (0xEFFF_FFFE, 2),
+            (0x1000_0000, 0),
+            (u32::MAX, 2),
+        ] {
+            assert_eq!(ObjectNamespace::try_new(start, len), None);
+            assert!(std::panic::catch_unwind(|| ObjectNamespace::new(start, len)).is_err());
+        }
+        assert_eq!(
+            ObjectNamespace::try_tenant(0x1000_0000, 3, 0x10),
+            Some(next)
+        );
+        assert_eq!(
+            ObjectNamespace::try_tenant(0x1000_0000, u32::MAX, 0x10),
+            None
+        );
+        assert!(std::panic::catch_unwind(|| tls.id(0x10)).is_err());
+    }
+
+    #[test]
+    fn object_id_allocation() {
+        assert_eq!(ObjectId::new_checked(0), None);
+        assert_eq!(ObjectId::new_checked(0x7FFF_0200), None);
+        assert_eq!(ObjectId::new_checked(0xF000_0012), None);
+        assert_eq!(
+            ObjectId::new_checked(0x0000_1000),
+            Some(ObjectId(hex!("00001000")))
+        );
+        assert!(ObjectId::TRANSPORT.is_reserved());
+
+        assert!(ObjectIdAllocator::new(0x7FFF_0000..=0x7FFF_0001).is_err());
+        assert!(ObjectIdAllocator::new(0x7FFF_FFFF..=0x8000_0000).is_err());
+        let ids: heapless::Vec<ObjectId, 4> = ObjectIdAllocator::new(0x7FFE_FFFE..=0x8000_0001)
+            .unwrap()
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                ObjectId(hex!("7FFEFFFE")),
+                ObjectId(hex!("7FFEFFFF")),
+                ObjectId(hex!("80000000")),
+                ObjectId(hex!("80000001")),
+            ]
+        );
+        // The last application ID is used by `verify_with_ephemeral_pubkey`
+        assert_eq!(ObjectId::new_checked(0xEFFF_FFFF), None);
+        assert!(EPHEMERAL_PUBLIC_KEY_ID.is_reserved());
+        assert!(ObjectIdAllocator::new(0xEFFF_FFFE..=0xEFFF_FFFF).is_err());
+        let mut allocator = ObjectIdAllocator::new(0xEFFF_FFFE..=0xEFFF_FFFE).unwrap();
+        assert_eq!(allocator.next(), Some(ObjectId(hex!("EFFFFFFE"))));
+        assert_eq!(allocator.next(), None);
+        assert_eq!(ObjectIdAllocator::default().end, 0xEFFF_FFFE);
+    }
+
+    #[cfg(feature = "key-wrap")]
+    #[test]
+    fn key_wrap() {
+        // RFC 3394, section 4.1
+        let kek = hex!("000102030405060708090A0B0C0D0E0F");
+        let key = hex!("00112233445566778899AABBCCDDEEFF");
+        let buf = &mut [0; 32];
+        assert_eq!(
+            wrap_key_for_import(&kek, &key, buf).unwrap(),
+            hex!("1FA68B0A8112B447AEF34BD8FB5A7B829D3E862371D2CFE5")
+        );
+        assert!(wrap_key_for_import(&kek[..15], &key, buf).is_err());
+        assert!(wrap_key_for_import(&kek, &key, &mut [0; 23]).is_err());
+    }
+
+    #[test]
+    fn memory_report() {
+        let report = MemoryReport {
+            persistent: 0x7FFF,
+            transient_reset: 100,
+            transient_deselect: 200,
+        };
+        assert_eq!(report.available(Memory::TransientReset), 100);
+        assert!(report.has_room_for(&MemoryReport::default()));
+        assert!(report.has_room_for(&MemoryReport {
+            persistent: 4096,
+            transient_deselect: 200,
+            ..Default::default()
+        }));
+        assert!(!report.has_room_for(&MemoryReport {
+            transient_reset: 101,
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn session_commands() {
+        let session_id = SessionId(hex!("0102030405060708"));
+        let command = commands::DeleteSecureObject {
+            object_id: ObjectId(hex!("01020304")),
+        };
+        assert_eq!(
+            command.clone().in_session(session_id),
+            ProcessSessionCmd {
+                session_id,
+                apdu: command,
+            }
+        );
+
+        let write = commands::WriteBinary {
+            transient: false,
+            policy: None,
+            object_id: ObjectId(hex!("01020304")),
+            offset: Some(2.into()),
+            file_length: Some(4.into()),
+            data: Some(&[1]),
+        };
+        // The wrapped command is validated
+        let command = (&write).in_session(session_id);
+        assert!(Se05XCommand::<ApduWriter<'_>>::validate(&command).is_err());
+        // Commands can be nested
+        let nested = command.in_session(session_id);
+        assert!(nested.len() > write.len());
+    }
+
+    #[test]
+    fn set_applet_features() {
+        let mut buf: heapless::Vec<u8, 16> = heapless::Vec::new();
+        commands::SetAppletFeatures {
+            features: AppletConfig::ECC_ALL | AppletConfig::AES,
+        }
+        .to_writer(&mut buf)
+        .unwrap();
+        assert_eq!(buf, hex!("80 04 00 3F 04 41 02 00 8F"));
+    }
+
+    #[test]
+    fn strict_tlv() {
+        use commands::CreateSessionResponse;
+        let session_id = SessionId(hex!("0102030405060708"));
+        let response = CreateSessionResponse::from_response(&hex!("41 08 0102030405060708"));
+        assert_eq!(response, Ok(CreateSessionResponse { session_id }));
+
+        // Unexpected tag before the expected one, and trailing data
+        for data in [
+            &hex!("42 01 00 41 08 0102030405060708")[..],
+            &hex!("41 08 0102030405060708 42 01 00")[..],
+        ] {
+            let response = CreateSessionResponse::from_response(data);
+            if cfg!(feature = "strict-tlv") {
+                assert_eq!(response, Err(Error::Tlv));
+            } else {
+                assert_eq!(response, Ok(CreateSessionResponse { session_id }));
+            }
+        }
+    }
+
+    #[test]
+    fn memory_transient() {
+        assert_eq!(Memory::Persistent.transient(), Ok(false));
+        assert_eq!(Memory::TransientReset.transient(), Ok(true));
+        assert!(Memory::TransientDeselect.transient().is_err());
+    }
+
+    #[test]
+    fn symm_key_len() {
+        let write = commands::WriteSymmKey {
+            transient: false,
+            is_auth: false,
+            key_type: SymmKeyType::Aes,
+            policy: None,
+            max_attempts: None,
+            object_id: ObjectId([1, 2, 3, 4]),
+            kek_id: None,
+            value: &[0x11; 24],
+        };
+        assert_eq!(write.validate(), Ok(()));
+        assert_eq!(
+            commands::WriteSymmKey {
+                value: &[0x11; 20],
+                ..write
+            }
+            .validate(),
+            Err(Error::InvalidKeyLength {
+                key_type: SymmKeyType::Aes,
+                len: 20
+            })
+        );
+        // Wrapped keys are not checked against the key length
+        assert_eq!(
+            commands::WriteSymmKey {
+                kek_id: Some(ObjectId([5, 6, 7, 8])),
+                value: &[0x11; 24],
+                ..write
+            }
+            .validate(),
+            Ok(())
+        );
+        #[cfg(feature = "legacy-des")]
+        assert_eq!(
+            SymmKeyType::Des.check_key_len(32),
+            Err(Error::InvalidKeyLength {
+                key_type: SymmKeyType::Des,
+                len: 32
+            })
+        );
+        assert_eq!(SymmKeyType::Hmac.check_key_len(64), Ok(()));
+        assert_eq!(
+            SymmKeyType::Hmac.check_key_len(0),
+            Err(Error::InvalidKeyLength {
+                key_type: SymmKeyType::Hmac,
+                len: 0
+            })
+        );
+        assert_eq!(
+            Error::InvalidKeyLength {
+                key_type: SymmKeyType::Aes,
+                len: 20
+            }
+            .to_string(),
+            "Invalid Aes key length: 20 bytes"
+        );
+    }
+
+    #[test]
+    fn gen_ec_key() {
+        let gen_ec_key = commands::GenEcKey {
+            transient: false,
+            is_auth: false,
+            policy: None,
+            max_attempts: None,
+            object_id: ObjectId(hex!("01020304")),
+            curve: Some(EcCurve::NistP256),
+        };
+        let mut buf: heapless::Vec<u8, 16> = heapless::Vec::new();
+        gen_ec_key.to_writer(&mut buf).unwrap();
+        assert_eq!(buf, hex!("80 01 61 00 09 41 04 01020304 42 01 03"));
+        assert_eq!(gen_ec_key.validate(), Ok(()));
+
+        use policies::{ObjectAccessRule, ObjectPolicyFlags, Policy, PolicySet};
+        let policies = &[Policy {
+            object_id: ObjectId::INVALID,
+            access_rule: ObjectAccessRule::from_flags(ObjectPolicyFlags::ALLOW_SIGN),
+        }];
+        assert!(commands::GenEcKey {
+            policy: Some(PolicySet(policies)),
+            curve: None,
+            ..gen_ec_key
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn validate_commands() {
+        let read_modulus = commands::ReadObject {
+            object_id: ObjectId(hex!("01020304")),
+            offset: None,
+            length: None,
+            rsa_key_component: Some(RsaKeyComponent::Mod),
+        };
+        assert_eq!(read_modulus.validate(), Ok(()));
+        let partial = commands::ReadObject {
+            length: Some(16.into()),
+            ..read_modulus
+        };
+        assert!(partial.validate().is_err());
+        let private = commands::ReadObject {
+            rsa_key_component: Some(RsaKeyComponent::PrivExp),
+            ..read_modulus
+        };
+        assert!(private.validate().is_err());
+
+        let write_binary = commands::WriteBinary {
+            transient: false,
+            policy: None,
+            object_id: ObjectId(hex!("01020304")),
+            offset: None,
+            file_length: Some(4.into()),
+            data: Some(&[1, 2, 3, 4]),
+        };
+        assert_eq!(write_binary.validate(), Ok(()));
+        let update = commands::WriteBinary {
+            offset: Some(2.into()),
+            file_length: None,
+            ..write_binary.clone()
+        };
+        assert_eq!(update.validate(), Ok(()));
+        assert!(commands::WriteBinary {
+            offset: Some(2.into()),
+            ..write_binary.clone()
+        }
+        .validate()
+        .is_err());
+        assert!(commands::WriteBinary {
+            file_length: Some(3.into()),
+            ..write_binary.clone()
+        }
+        .validate()
+        .is_err());
+        assert!(commands::WriteBinary {
+            data: None,
+            ..update
+        }
+        .validate()
+        .is_err());
+
+        let write_ec_key = commands::WriteEcKey {
+            transient: false,
+            is_auth: false,
+            key_type: Some(P1KeyType::KeyPair),
+            policy: None,
+            max_attempts: None,
+            object_id: ObjectId(hex!("01020304")),
+            curve: Some(EcCurve::NistP256),
+            private_key: None,
+            public_key: None,
+        };
+        assert_eq!(write_ec_key.validate(), Ok(()));
+        assert!(commands::WriteEcKey {
+            key_type: Some(P1KeyType::Public),
+            ..write_ec_key.clone()
+        }
+        .validate()
+        .is_err());
+        assert!(commands::WriteEcKey {
+            max_attempts: Some(3.into()),
+            ..write_ec_key.clone()
+        }
+        .validate()
+        .is_err());
+        let mut public_key = [0; 65];
+        public_key[0] = ec_key::SEC1_UNCOMPRESSED;
+        assert_eq!(
+            commands::WriteEcKey {
+                key_type: Some(P1KeyType::Public),
+                public_key: Some(&public_key),
+                ..write_ec_key.clone()
+            }
+            .validate(),
+            Ok(())
+        );
+        assert!(commands::WriteEcKey {
+            key_type: Some(P1KeyType::Public),
+            public_key: Some(&public_key[1..]),
+            ..write_ec_key
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[cfg(feature = "se051")]
+    #[test]
+    fn pake_commands() {
+        let mut buf: heapless::Vec<u8, 16> = heapless::Vec::new();
+        commands::PakeConfigDevice {
+            id: CryptoObjectId(hex!("0001")),
+            device_type: Spake2PlusDeviceType::B,
+        }
+        .to_writer(&mut buf)
+        .unwrap();
+        assert_eq!(buf, hex!("80 03 13 04 07 41 02 0001 42 01 02"));
+        assert!(AppletConfig::ALL_SE051.contains(AppletConfig::ALL | AppletConfig::PAKE));
+        assert!(!AppletConfig::ALL.contains(AppletConfig::PAKE));
+    }
+
+    #[test]
+    fn timestamp() {
+        let bytes = hex!("00000002 0000000000000100");
+        let timestamp = Timestamp::from_bytes(&bytes);
+        assert_eq!(
+            timestamp,
+            Timestamp {
+                power_up_counter: 2,
+                counter: 0x100,
+            }
+        );
+        assert_eq!(timestamp.to_bytes(), bytes);
+        assert_eq!(timestamp.as_u128(), (2 << 64) | 0x100);
+        // A later power cycle is more recent, even with a lower counter
+        assert!(Timestamp::from_bytes(&hex!("00000003 0000000000000001")) > timestamp);
+        assert!(Timestamp::from_bytes(&hex!("00000002 0000000000000101")) > timestamp);
+    }
+
+    #[test]
+    fn write_binary_chunked() {
+        let write = |offset: u16| {
+            [
+                &hex!("80 01 06 00 00 02 0E 41 04 00000001 42 02")[..],
+                &offset.to_be_bytes(),
+                &hex!("44 82 0200"),
+                &[0; CHUNK_LEN],
+            ]
+            .concat()
+        };
+        let mut script = apdu::ScriptedTransport::new();
+        script
+            .expect(&write(0xFC00), &hex!("90 00"))
+            .expect(&write(0xFE00), &hex!("90 00"));
+        let mut se05x = script.enable();
+        let object_id = ObjectId([0, 0, 0, 1]);
+        // Ends exactly at the end of the offset range
+        let data = [0; 2 * CHUNK_LEN];
+        se05x
+            .write_binary_chunked(object_id, 0xFC00, &data)
+            .unwrap();
+        // The second chunk is out of range, nothing is written
+        assert!(se05x
+            .write_binary_chunked(object_id, 0xFE00, &data)
+            .is_err());
+        assert!(se05x.into_transport().into_inner().is_done());
+    }
 }
@@ -0,0 +1,282 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Running commands over transports that exchange whole APDUs
+//!
+//! Desktop tools usually reach the se05x of a development kit through a smartcard reader rather than over I2C.
+//! Such transports implement [`ApduTransport`]. [`ApduLink`] adapts them to [`Se05xTransport`], so that [`Se05X`] runs over them.
+//!
+//! With the `pcsc` feature, [`ApduTransport`] is implemented for `pcsc::Card`.
+
+#[cfg(feature = "pcsc")]
+extern crate std;
+
+use iso7816::Status;
+
+use super::transport::{ApduSink, Se05xTransport};
+use super::{Error, Se05X, MAX_APDU_OVERHEAD, MAX_APDU_PAYLOAD_LENGTH};
+
+/// Maximum length of a serialized command APDU
+const MAX_COMMAND_LEN: usize = MAX_APDU_PAYLOAD_LENGTH + MAX_APDU_OVERHEAD;
+
+/// Transport exchanging complete command and response APDUs
+pub trait ApduTransport {
+    /// Send `command` and write the response, including the status word, to `response_buf`
+    fn transmit<'buf>(
+        &mut self,
+        command: &[u8],
+        response_buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error>;
+
+    /// Bring the transport back to a state where the applet can be selected
+    fn reset(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Wait for `us` microseconds
+    fn delay_us(&mut self, _us: u32) {}
+}
+
+impl<T: ApduTransport + ?Sized> ApduTransport for &mut T {
+    fn transmit<'buf>(
+        &mut self,
+        command: &[u8],
+        response_buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        T::transmit(self, command, response_buf)
+    }
+
+    fn reset(&mut self) -> Result<(), Error> {
+        T::reset(self)
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        T::delay_us(self, us)
+    }
+}
+
+#[cfg(feature = "pcsc")]
+impl ApduTransport for pcsc::Card {
+    fn transmit<'buf>(
+        &mut self,
+        command: &[u8],
+        response_buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        pcsc::Card::transmit(self, command, response_buf).map_err(|_err| {
+            error!("PC/SC transmit failed: {_err:?}");
+            Error::Line(line!())
+        })
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        std::thread::sleep(std::time::Duration::from_micros(us.into()))
+    }
+}
+
+/// [`Se05xTransport`] over an [`ApduTransport`]
+///
+/// Command APDUs are buffered until the response is requested, and then transmitted at once.
+pub struct ApduLink<T> {
+    transport: T,
+    command: [u8; MAX_COMMAND_LEN],
+    len: usize,
+}
+
+/// [`Se05X`] reached through an [`ApduTransport`]
+pub type ApduSe05X<T> = Se05X<ApduLink<T>>;
+
+impl<T: ApduTransport> ApduLink<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            command: [0; MAX_COMMAND_LEN],
+            len: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+}
+
+/// Buffer receiving the command APDU of an [`ApduLink`]
+pub struct ApduBuffer<'a> {
+    buf: &'a mut [u8; MAX_COMMAND_LEN],
+    len: &'a mut usize,
+    expected: usize,
+}
+
+impl ApduSink for ApduBuffer<'_> {
+    fn write_apdu(&mut self, data: &[u8]) -> Result<usize, Error> {
+        let end = *self.len + data.len();
+        if end > self.expected {
+            error!("Writing more data than expected");
+            return Err(Error::Line(line!()));
+        }
+        self.buf[*self.len..end].copy_from_slice(data);
+        *self.len = end;
+        Ok(data.len())
+    }
+}
+
+impl<T: ApduTransport> Se05xTransport for ApduLink<T> {
+    type Sink<'a>
+        = ApduBuffer<'a>
+    where
+        Self: 'a;
+
+    fn send_apdu(&mut self, len: usize) -> Result<ApduBuffer<'_>, Error> {
+        if len > MAX_COMMAND_LEN {
+            error!("Command too long: {len}");
+            return Err(Error::CommandTooLong);
+        }
+        self.len = 0;
+        Ok(ApduBuffer {
+            buf: &mut self.command,
+            len: &mut self.len,
+            expected: len,
+        })
+    }
+
+    fn receive_apdu<'buf>(
+        &mut self,
+        response_buf: &'buf mut [u8],
+    ) -> Result<(&'buf [u8], Status), Error> {
+        let command = &self.command[..self.len];
+        let response = self.transport.transmit(command, response_buf)?;
+        let [ref data @ .., sw1, sw2] = *response else {
+            error!("Got too short apdu");
+            return Err(Error::Line(line!()));
+        };
+        Ok((data, Status::from([sw1, sw2])))
+    }
+
+    fn reset(&mut self) -> Result<(), Error> {
+        self.transport.reset()
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.transport.delay_us(us)
+    }
+}
+
+/// ATR returned to `SELECT` by [`ScriptedTransport`]: version 3.1.0, all features, secure box 1.0
+#[cfg(test)]
+pub(crate) const SELECT_RESPONSE: [u8; 9] = [0x03, 0x01, 0x00, 0x3F, 0xFF, 0x01, 0x00, 0x90, 0x00];
+
+/// Expected APDU and the response, including the status word, or the error of its transmission
+#[cfg(test)]
+type Expectation = (Vec<u8>, Result<Vec<u8>, Error>);
+
+/// [`ApduTransport`] answering from a script of expectations, for the tests of the crate
+///
+/// `SELECT` is answered with [`SELECT_RESPONSE`], unless it is the next expectation.
+/// Other APDUs must be equal to the next expectation registered with [`expect`](Self::expect), else the transport panics.
+/// The delays requested through [`delay_us`](ApduTransport::delay_us) are recorded.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct ScriptedTransport {
+    expectations: std::collections::VecDeque<Expectation>,
+    delays: Vec<u32>,
+}
+
+#[cfg(test)]
+impl ScriptedTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer the next APDU, which must be equal to `command`, with `response`
+    ///
+    /// `response` includes the status word. Expectations are consumed in the order they are registered.
+    pub fn expect(&mut self, command: &[u8], response: &[u8]) -> &mut Self {
+        self.expectations
+            .push_back((command.to_vec(), Ok(response.to_vec())));
+        self
+    }
+
+    /// Fail the transmission of the next APDU, which must be equal to `command`, with `error`
+    pub fn expect_error(&mut self, command: &[u8], error: Error) -> &mut Self {
+        self.expectations.push_back((command.to_vec(), Err(error)));
+        self
+    }
+
+    /// Delays requested so far, in microseconds
+    pub fn delays(&self) -> &[u32] {
+        &self.delays
+    }
+
+    /// Returns `true` if all expectations were consumed
+    pub fn is_done(&self) -> bool {
+        self.expectations.is_empty()
+    }
+
+    /// Enabled [`Se05X`] running over the script
+    pub fn enable(self) -> ApduSe05X<Self> {
+        let mut se05x = ApduSe05X::from_transport(ApduLink::new(self));
+        se05x.enable().unwrap();
+        se05x
+    }
+}
+
+#[cfg(test)]
+impl ApduTransport for ScriptedTransport {
+    fn transmit<'buf>(
+        &mut self,
+        command: &[u8],
+        response_buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        let is_expected =
+            matches!(self.expectations.front(), Some((expected, _)) if expected == command);
+        let response = match command {
+            [0x00, 0xA4, ..] if !is_expected => Ok(SELECT_RESPONSE.to_vec()),
+            _ => {
+                let Some((expected, response)) = self.expectations.pop_front() else {
+                    panic!("Unexpected command: {command:02x?}");
+                };
+                assert!(
+                    command == expected,
+                    "Unexpected command: {command:02x?}, expected {expected:02x?}"
+                );
+                response
+            }
+        }?;
+        response_buf[..response.len()].copy_from_slice(&response);
+        Ok(&response_buf[..response.len()])
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.delays.push(us);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::commands::GetRandom;
+    use hex_literal::hex;
+
+    #[test]
+    fn run_over_apdu_transport() {
+        let mut script = ScriptedTransport::new();
+        // GetRandom answered in two parts with a `61xx` status word
+        script
+            .expect(
+                &hex!("80 04 00 49 00 00 04 41 02 0004 00 00"),
+                &hex!("41 04 01 61 03"),
+            )
+            .expect(&hex!("00 C0 00 00 03"), &hex!("02 03 04 90 00"));
+        let mut se05x = script.enable();
+        assert_eq!(se05x.atr().unwrap().major, 3);
+        let buf = &mut [0; 16];
+        let response = se05x
+            .run_command(&GetRandom { length: 4.into() }, buf)
+            .unwrap();
+        assert_eq!(response.data, [0x01, 0x02, 0x03, 0x04]);
+        assert!(se05x.into_transport().into_inner().is_done());
+    }
+}
@@ -0,0 +1,581 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Attested reads
+//!
+//! [`ReadAttestObject`] signs the object read together with a 16-byte freshness random chosen by the host, to prove the attestation is recent.
+//! [`Se05X::read_attested`] generates the freshness random with a [`HostRng`], and checks that the attestation echoes it.
+//! With [`DeviceRng`](super::rng::DeviceRng), the freshness random is requested from the se05x first.
+//!
+//! [`Se05X::read_counter_attested`] reads a monotonic counter this way, as evidence against rollbacks.
+//! [`Se05X::read_attested_provisioned`] attests with the key pair provisioned by NXP, and returns its certificate so that verifiers can build the chain up to the NXP attestation CA.
+//! With the `verify-attestation` feature, attestations by a NIST P-256 key can be verified on the host with [`ReadAttestObjectResponse::verify_p256`],
+//! and [`Se05X::read_counter_attested_verified`] only returns counter values with a valid signature.
+
+use super::commands::{ReadAttestObject, ReadAttestObjectResponse, ReadObject};
+use super::reserved::{
+    ATTESTATION_CERT_EC, ATTESTATION_CERT_RSA, ATTESTATION_KEY_EC, ATTESTATION_KEY_RSA,
+};
+use super::rng::HostRng;
+use super::transport::Se05xTransport;
+#[cfg(feature = "verify-attestation")]
+use super::OBJECT_ATTRIBUTES_MAX_LEN;
+use super::{AttestationAlgo, Error, ObjectId, Se05X};
+
+/// Length of the freshness random of attestations
+pub const FRESHNESS_RANDOM_LEN: usize = 16;
+/// Size of the buffer holding the signed data of an attested counter
+///
+/// The signed data holds a counter value of up to 8 bytes, the object attributes with a full policy set, the timestamp, the freshness random and the chip unique ID, each as a TLV.
+#[cfg(feature = "verify-attestation")]
+const SIGNED_DATA_MAX_LEN: usize = tlv_len(8)
+    + tlv_len(OBJECT_ATTRIBUTES_MAX_LEN)
+    + tlv_len(12)
+    + tlv_len(FRESHNESS_RANDOM_LEN)
+    + tlv_len(18);
+
+/// Length of a TLV holding `len` bytes, as written by [`write_tlv`](super::write_tlv)
+#[cfg(feature = "verify-attestation")]
+const fn tlv_len(len: usize) -> usize {
+    match len {
+        0..=0x7F => 2 + len,
+        0x80..=0xFF => 3 + len,
+        _ => 4 + len,
+    }
+}
+
+/// Counter value read with [`Se05X::read_counter_attested`]
+#[derive(Debug)]
+pub struct AttestedCounter<'buf> {
+    /// Value of the counter
+    pub value: u64,
+    /// The attestation evidence, which can be verified with [`ReadAttestObjectResponse::signed_data`]
+    pub attestation: ReadAttestObjectResponse<'buf>,
+}
+
+/// Attested read made with a key pair provisioned by NXP, returned by [`Se05X::read_attested_provisioned`]
+#[derive(Debug)]
+pub struct AttestationChain<'buf> {
+    /// Key pair that signed the attestation
+    pub attestation_key: ObjectId,
+    /// DER certificate of the [`attestation_key`](Self::attestation_key), signed by the NXP attestation CA
+    pub certificate: &'buf [u8],
+    /// The attestation evidence, which can be verified with the public key of the [`certificate`](Self::certificate)
+    pub attestation: ReadAttestObjectResponse<'buf>,
+}
+
+impl AttestationAlgo {
+    /// Returns `true` for the algorithms attesting with an RSA key pair
+    pub fn is_rsa(self) -> bool {
+        !matches!(
+            self,
+            Self::ECdsaSha
+                | Self::ECdsaSha224
+                | Self::ECdsaSha256
+                | Self::ECdsaSha384
+                | Self::ECdsaSha512
+        )
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Key pair and certificate binary file provisioned by NXP to attest with `algo`
+    ///
+    /// Returns `None` if the se05x does not hold them, which depends on its configuration.
+    pub fn provisioned_attestation_key(
+        &mut self,
+        algo: AttestationAlgo,
+    ) -> Result<Option<(ObjectId, ObjectId)>, Error> {
+        let (key, certificate) = if algo.is_rsa() {
+            (ATTESTATION_KEY_RSA, ATTESTATION_CERT_RSA)
+        } else {
+            (ATTESTATION_KEY_EC, ATTESTATION_CERT_EC)
+        };
+        if self.exists(key)? && self.exists(certificate)? {
+            Ok(Some((key, certificate)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Perform the read described by `read`, attested by the key pair provisioned by NXP for `algo`
+    ///
+    /// The certificate of the attestation key is read first, at the start of `buf`, and the attestation uses the rest of `buf`.
+    /// If the se05x holds no provisioned attestation key for `algo`, the call fails.
+    pub fn read_attested_provisioned<'buf, R: HostRng>(
+        &mut self,
+        read: &ReadObject,
+        algo: AttestationAlgo,
+        rng: &mut R,
+        buf: &'buf mut [u8],
+    ) -> Result<AttestationChain<'buf>, Error> {
+        let Some((attestation_key, certificate_id)) = self.provisioned_attestation_key(algo)?
+        else {
+            error!("No provisioned attestation key for {algo:?}");
+            return Err(Error::Line(line!()));
+        };
+        let len = self.read_certificate(certificate_id, buf)?.len();
+        let (certificate, buf) = buf.split_at_mut(len);
+        let attestation = self.read_attested(read, attestation_key, algo, rng, buf)?;
+        Ok(AttestationChain {
+            attestation_key,
+            certificate,
+            attestation,
+        })
+    }
+
+    /// Perform the read described by `read`, attested by `attestation_object`
+    ///
+    /// The freshness random is generated with `rng`. If the attestation does not echo it, [`Error::FreshnessMismatch`] is returned.
+    pub fn read_attested<'buf, R: HostRng>(
+        &mut self,
+        read: &ReadObject,
+        attestation_object: ObjectId,
+        attestation_algo: AttestationAlgo,
+        rng: &mut R,
+        buf: &'buf mut [u8],
+    ) -> Result<ReadAttestObjectResponse<'buf>, Error> {
+        let mut freshness_random = [0; FRESHNESS_RANDOM_LEN];
+        rng.fill_random(self, &mut freshness_random)?;
+        let attestation = self
+            .run_command(
+                &ReadAttestObject {
+                    object_id: read.object_id,
+                    offset: read.offset,
+                    length: read.length,
+                    rsa_key_component: read.rsa_key_component,
+                    attestation_object,
+                    attestation_algo,
+                    freshness_random: &freshness_random,
+                },
+                buf,
+            )
+            .map_err(|err| self.explain_policy_error(read.object_id, err))?;
+        if attestation.freshness_random != &freshness_random {
+            error!("Attestation freshness mismatch");
+            return Err(Error::FreshnessMismatch);
+        }
+        Ok(attestation)
+    }
+
+    /// Read the value of the counter `counter_id`, attested by `attestation_object`
+    ///
+    /// The freshness random of the attestation is generated with `rng`.
+    pub fn read_counter_attested<'buf, R: HostRng>(
+        &mut self,
+        counter_id: ObjectId,
+        attestation_object: ObjectId,
+        attestation_algo: AttestationAlgo,
+        rng: &mut R,
+        buf: &'buf mut [u8],
+    ) -> Result<AttestedCounter<'buf>, Error> {
+        let attestation = self.read_attested(
+            &ReadObject {
+                object_id: counter_id,
+                offset: None,
+                length: None,
+                rsa_key_component: None,
+            },
+            attestation_object,
+            attestation_algo,
+            rng,
+            buf,
+        )?;
+        let value = match attestation.data {
+            Some(data @ [_, ..]) if data.len() <= 8 => {
+                let mut value = [0; 8];
+                value[8 - data.len()..].copy_from_slice(data);
+                u64::from_be_bytes(value)
+            }
+            _data => {
+                error!("Invalid counter value: {_data:02x?}");
+                return Err(Error::Line(line!()));
+            }
+        };
+        Ok(AttestedCounter { value, attestation })
+    }
+
+    /// Read the value of the counter `counter_id`, attested with [`AttestationAlgo::ECdsaSha256`] by the key pair of `public_key`
+    ///
+    /// Like [`read_counter_attested`](Self::read_counter_attested), but the value is only returned once the signature is verified with [`ReadAttestObjectResponse::verify_p256`].
+    #[cfg(feature = "verify-attestation")]
+    pub fn read_counter_attested_verified<'buf, R: HostRng>(
+        &mut self,
+        counter_id: ObjectId,
+        attestation_object: ObjectId,
+        public_key: &p256::ecdsa::VerifyingKey,
+        rng: &mut R,
+        buf: &'buf mut [u8],
+    ) -> Result<AttestedCounter<'buf>, Error> {
+        let counter = self.read_counter_attested(
+            counter_id,
+            attestation_object,
+            AttestationAlgo::ECdsaSha256,
+            rng,
+            buf,
+        )?;
+        counter
+            .attestation
+            .verify_p256(public_key, &mut [0; SIGNED_DATA_MAX_LEN])?;
+        Ok(counter)
+    }
+}
+
+#[cfg(feature = "verify-attestation")]
+impl ReadAttestObjectResponse<'_> {
+    /// Verify the signature of an attestation made with [`AttestationAlgo::ECdsaSha256`] by the key pair of `public_key`
+    ///
+    /// `buf` must hold the [signed data](Self::signed_data).
+    pub fn verify_p256(
+        &self,
+        public_key: &p256::ecdsa::VerifyingKey,
+        buf: &mut [u8],
+    ) -> Result<(), Error> {
+        use p256::ecdsa::signature::Verifier;
+
+        let signed_data = self.signed_data(buf)?;
+        let signature = p256::ecdsa::Signature::from_der(self.signature).map_err(|_err| {
+            error!("Invalid attestation signature: {_err:?}");
+            Error::Line(line!())
+        })?;
+        public_key.verify(signed_data, &signature).map_err(|_err| {
+            error!("Attestation verification failed: {_err:?}");
+            Error::Line(line!())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use crate::se05x::rng::DeviceRng;
+    use hex_literal::hex;
+
+    /// DER `SEQUENCE` of 128 bytes, standing for the certificate of the provisioned EC attestation key
+    const CERTIFICATE: [u8; 131] = {
+        let mut certificate = [0; 131];
+        certificate[0] = 0x30;
+        certificate[1] = 0x81;
+        certificate[2] = 0x80;
+        certificate
+    };
+
+    /// Random returned by GetRandom, used as freshness random of the attestations
+    const FRESHNESS: [u8; 16] = hex!("000102030405060708090A0B0C0D0E0F");
+
+    /// Expect the freshness random, then ReadAttestObject of `object_id` attested by `attestation_key`, answered with `response`
+    fn expect_attestation(
+        script: &mut ScriptedTransport,
+        object_id: ObjectId,
+        attestation_key: ObjectId,
+        response: &[u8],
+    ) {
+        script
+            .expect(
+                &hex!("80 04 00 49 00 00 04 41 02 0010 00 00"),
+                &[&hex!("41 10")[..], &FRESHNESS, &hex!("90 00")].concat(),
+            )
+            .expect(
+                &[
+                    &hex!("80 22 00 00 21 41 04")[..],
+                    &object_id.0,
+                    &hex!("45 04"),
+                    &attestation_key.0,
+                    &hex!("46 01 21 47 10"),
+                    &FRESHNESS,
+                ]
+                .concat(),
+                response,
+            );
+    }
+
+    /// Attestation of the value `AA BB`, with the freshness random `freshness`
+    fn attestation_response(freshness: &[u8]) -> Vec<u8> {
+        [
+            &hex!("41 02 AABB 42 0F 01020304 0101 000000000000000000 43 0C 000000000000000000000000 44 10")[..],
+            freshness,
+            &hex!("45 12 000000000000000000000000000000000000 46 02 3000 90 00"),
+        ]
+        .concat()
+    }
+
+    const READ: ReadObject = ReadObject {
+        object_id: ObjectId([1, 2, 3, 4]),
+        offset: None,
+        length: None,
+        rsa_key_component: None,
+    };
+
+    #[test]
+    fn read_attested() {
+        let attestation_key = ObjectId([5, 6, 7, 8]);
+        let mut script = ScriptedTransport::new();
+        for freshness in [FRESHNESS, FRESHNESS, FRESHNESS.map(|b| b + 1)] {
+            expect_attestation(
+                &mut script,
+                READ.object_id,
+                attestation_key,
+                &attestation_response(&freshness),
+            );
+        }
+        let mut se05x = script.enable();
+        let buf = &mut [0; 128];
+        let attestation = se05x
+            .read_attested(
+                &READ,
+                attestation_key,
+                AttestationAlgo::ECdsaSha256,
+                &mut DeviceRng,
+                buf,
+            )
+            .unwrap();
+        assert_eq!(attestation.data, Some(&[0xAA, 0xBB][..]));
+        assert_eq!(attestation.freshness_random, &FRESHNESS);
+        let counter = se05x
+            .read_counter_attested(
+                READ.object_id,
+                attestation_key,
+                AttestationAlgo::ECdsaSha256,
+                &mut DeviceRng,
+                buf,
+            )
+            .unwrap();
+        assert_eq!(counter.value, 0xAABB);
+
+        // The freshness random of the attestation is not the one of the command
+        assert_eq!(
+            se05x
+                .read_attested(
+                    &READ,
+                    attestation_key,
+                    AttestationAlgo::ECdsaSha256,
+                    &mut DeviceRng,
+                    buf,
+                )
+                .err(),
+            Some(Error::FreshnessMismatch)
+        );
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn read_attested_provisioned() {
+        // Only the EC attestation key and its certificate exist among the provisioned objects
+        let exists = |object_id: ObjectId| {
+            [
+                &hex!("80 04 00 27 00 00 06 41 04")[..],
+                &object_id.0,
+                &hex!("00 00"),
+            ]
+            .concat()
+        };
+        let read_certificate = |offset: u16, len: u16| {
+            [
+                &hex!("80 02 00 00 00 00 0E 41 04")[..],
+                &ATTESTATION_CERT_EC.0,
+                &[0x42, 0x02],
+                &offset.to_be_bytes(),
+                &[0x43, 0x02],
+                &len.to_be_bytes(),
+                &hex!("00 00"),
+            ]
+            .concat()
+        };
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(&exists(ATTESTATION_KEY_RSA), &hex!("41 01 02 90 00"))
+            .expect(&exists(ATTESTATION_KEY_RSA), &hex!("41 01 02 90 00"))
+            .expect(&exists(ATTESTATION_KEY_EC), &hex!("41 01 01 90 00"))
+            .expect(&exists(ATTESTATION_CERT_EC), &hex!("41 01 01 90 00"))
+            .expect(
+                &read_certificate(0, 4),
+                &[&hex!("41 04")[..], &CERTIFICATE[..4], &hex!("90 00")].concat(),
+            )
+            .expect(
+                &read_certificate(4, 0x7F),
+                &[&hex!("41 7F")[..], &CERTIFICATE[4..], &hex!("90 00")].concat(),
+            );
+        expect_attestation(
+            &mut script,
+            READ.object_id,
+            ATTESTATION_KEY_EC,
+            &attestation_response(&FRESHNESS),
+        );
+        let mut se05x = script.enable();
+        assert_eq!(
+            se05x
+                .provisioned_attestation_key(AttestationAlgo::RsaSha256Pkcs1)
+                .unwrap(),
+            None
+        );
+        assert!(se05x
+            .read_attested_provisioned(
+                &READ,
+                AttestationAlgo::RsaSha256Pkcs1,
+                &mut DeviceRng,
+                &mut [0; 256]
+            )
+            .is_err());
+
+        let buf = &mut [0; 256];
+        let chain = se05x
+            .read_attested_provisioned(&READ, AttestationAlgo::ECdsaSha256, &mut DeviceRng, buf)
+            .unwrap();
+        assert_eq!(chain.attestation_key, ATTESTATION_KEY_EC);
+        assert_eq!(chain.certificate, CERTIFICATE);
+        assert_eq!(chain.attestation.data, Some(&[0xAA, 0xBB][..]));
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[cfg(feature = "verify-attestation")]
+    #[test]
+    fn verify_p256() {
+        use crate::se05x::{write_tlv, Se05XResponse};
+        use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+
+        let key = SigningKey::from_slice(&[0x11; 32]).unwrap();
+        let attributes = hex!("01020304 01 01 0000 00000000 0000 08 00000000 00040000 01");
+        let mut response = [0; 256];
+        let mut offset = 0;
+        for (tag, value) in [
+            (0x41, &[0x00, 0x2A][..]),
+            (0x42, &attributes[..]),
+            (0x43, &[0x03; 12][..]),
+            (0x44, &[0x04; 16][..]),
+            (0x45, &[0x05; 18][..]),
+        ] {
+            offset += write_tlv(&mut response[offset..], tag, value).unwrap();
+        }
+        let signature: Signature = key.sign(&response[..offset]);
+        let signature = signature.to_der();
+        offset += write_tlv(&mut response[offset..], 0x46, signature.as_bytes()).unwrap();
+
+        let attestation = ReadAttestObjectResponse::from_response(&response[..offset]).unwrap();
+        let buf = &mut [0; 256];
+        assert!(attestation.verify_p256(key.verifying_key(), buf).is_ok());
+        let other = SigningKey::from_slice(&[0x22; 32]).unwrap();
+        assert!(attestation.verify_p256(other.verifying_key(), buf).is_err());
+    }
+
+    /// Attributes of the counter, with a single policy
+    #[cfg(feature = "verify-attestation")]
+    const COUNTER_ATTRIBUTES: [u8; 24] =
+        hex!("01020304 01 01 0000 00000000 0000 08 00000000 00040000 01");
+
+    /// Attestation of the counter value `0x2A` with `attributes`, signed with the key `[0x11; 32]`
+    ///
+    /// If `tampered` is set, the value is changed to `0x2B` after signing.
+    #[cfg(feature = "verify-attestation")]
+    fn signed_counter_response(attributes: &[u8], tampered: bool) -> Vec<u8> {
+        use crate::se05x::write_tlv;
+        use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+
+        let mut response = [0; 512];
+        let mut offset = 0;
+        for (tag, value) in [
+            (0x41, &[0x00, 0x2A][..]),
+            (0x42, attributes),
+            (0x43, &[0x03; 12][..]),
+            (0x44, &FRESHNESS[..]),
+            (0x45, &[0x05; 18][..]),
+        ] {
+            offset += write_tlv(&mut response[offset..], tag, value).unwrap();
+        }
+        let key = SigningKey::from_slice(&[0x11; 32]).unwrap();
+        let signature: Signature = key.sign(&response[..offset]);
+        let signature = signature.to_der();
+        offset += write_tlv(&mut response[offset..], 0x46, signature.as_bytes()).unwrap();
+        if tampered {
+            response[3] = 0x2B;
+        }
+        [&response[..offset], &hex!("90 00")].concat()
+    }
+
+    #[cfg(feature = "verify-attestation")]
+    #[test]
+    fn read_counter_attested_verified() {
+        use p256::ecdsa::SigningKey;
+
+        let key = SigningKey::from_slice(&[0x11; 32]).unwrap();
+        let buf = &mut [0; 256];
+        let attestation_key = ObjectId([5, 6, 7, 8]);
+        let mut script = ScriptedTransport::new();
+        for tampered in [false, true, true] {
+            expect_attestation(
+                &mut script,
+                READ.object_id,
+                attestation_key,
+                &signed_counter_response(&COUNTER_ATTRIBUTES, tampered),
+            );
+        }
+        let mut se05x = script.enable();
+        let counter = se05x
+            .read_counter_attested_verified(
+                READ.object_id,
+                attestation_key,
+                key.verifying_key(),
+                &mut DeviceRng,
+                buf,
+            )
+            .unwrap();
+        assert_eq!(counter.value, 0x2A);
+
+        // Tampered value
+        assert!(se05x
+            .read_counter_attested(
+                READ.object_id,
+                attestation_key,
+                AttestationAlgo::ECdsaSha256,
+                &mut DeviceRng,
+                buf,
+            )
+            .is_ok_and(|counter| counter.value == 0x2B));
+        assert!(se05x
+            .read_counter_attested_verified(
+                READ.object_id,
+                attestation_key,
+                key.verifying_key(),
+                &mut DeviceRng,
+                buf,
+            )
+            .is_err());
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[cfg(feature = "verify-attestation")]
+    #[test]
+    fn read_counter_attested_verified_full_policy() {
+        use p256::ecdsa::SigningKey;
+
+        // 28 policies and the origin, leaving the signed data longer than 256 bytes
+        let mut attributes = COUNTER_ATTRIBUTES[..14].to_vec();
+        for _ in 0..28 {
+            attributes.extend_from_slice(&hex!("08 00000000 00040000"));
+        }
+        attributes.push(0x01);
+
+        let key = SigningKey::from_slice(&[0x11; 32]).unwrap();
+        let attestation_key = ObjectId([5, 6, 7, 8]);
+        let mut script = ScriptedTransport::new();
+        expect_attestation(
+            &mut script,
+            READ.object_id,
+            attestation_key,
+            &signed_counter_response(&attributes, false),
+        );
+        let mut se05x = script.enable();
+        let buf = &mut [0; 512];
+        let counter = se05x
+            .read_counter_attested_verified(
+                READ.object_id,
+                attestation_key,
+                key.verifying_key(),
+                &mut DeviceRng,
+                buf,
+            )
+            .unwrap();
+        assert_eq!(counter.value, 0x2A);
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+}
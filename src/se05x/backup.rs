@@ -0,0 +1,283 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Backup and restore of transient key objects
+//!
+//! The se05x only exports transient objects whose policy allows [`ALLOW_IMPORT_EXPORT`](super::policies::ObjectPolicyFlags::ALLOW_IMPORT_EXPORT).
+//! The exported data is encrypted and authenticated by the applet and can only be imported back into the same se05x.
+//! RSA keys are exported one component at a time.
+//!
+//! [`Se05X::backup_object`] packs the exported data of all components in a single buffer:
+//!
+//! | Field | Length |
+//! |-------|--------|
+//! | [`SecureObjectType`] of the object | 1 |
+//! | Size of the object, as returned by [`ReadSize`] | 2 |
+//! | For each component: the [`RsaKeyComponent`], [`Na`](RsaKeyComponent::Na) for other keys | 1 |
+//! | Length of the exported component | 2 |
+//! | Exported component | |
+//!
+//! [`Se05X::restore_object`] imports it back into the existing object, and checks the object afterwards.
+
+use super::commands::{ExportObject, ImportObject, ReadSize, ReadType};
+use super::transport::Se05xTransport;
+use super::{Error, ObjectId, RsaKeyComponent, Se05X, SecureObjectType, TransientIndicator};
+
+/// Length of the header of a backup: object type and size
+const HEADER_LEN: usize = 3;
+/// Length of the header of each component: component and length
+const COMPONENT_HEADER_LEN: usize = 3;
+/// Largest number of components of an object: the 7 components of an RSA CRT key pair
+const MAX_COMPONENTS: usize = 7;
+
+/// Components exported for an object of type `ty`
+fn components(ty: SecureObjectType) -> Result<&'static [RsaKeyComponent], Error> {
+    use RsaKeyComponent::*;
+    Ok(match ty {
+        SecureObjectType::EcKeyPair
+        | SecureObjectType::EcPrivKey
+        | SecureObjectType::EcPubKey
+        | SecureObjectType::AesKey
+        | SecureObjectType::DesKey
+        | SecureObjectType::HmacKey => &[Na],
+        SecureObjectType::RsaKeyPair => &[Mod, PubExp, PrivExp],
+        SecureObjectType::RsaPrivKey => &[Mod, PrivExp],
+        SecureObjectType::RsaKeyPairCrt => &[Mod, PubExp, P, Q, Dp, Dq, InvQ],
+        SecureObjectType::RsaPrivKeyCrt => &[P, Q, Dp, Dq, InvQ],
+        SecureObjectType::RsaPubKey => &[Mod, PubExp],
+        _ty => {
+            error!("Objects of type {_ty:?} cannot be exported");
+            return Err(Error::Line(line!()));
+        }
+    })
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Export the transient key object `object_id` into `buf`, returning the backup
+    ///
+    /// See the [module documentation](self) for the format of the backup.
+    /// `buf` must hold all the exported components and 3 bytes of overhead per component.
+    pub fn backup_object<'buf>(
+        &mut self,
+        object_id: ObjectId,
+        buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        let ty = self.transient_key_type(object_id)?;
+        let size = self.object_size(object_id)?;
+        if buf.len() < HEADER_LEN {
+            error!("Buffer too small for backup");
+            return Err(Error::Line(line!()));
+        }
+        buf[0] = ty.into();
+        buf[1..HEADER_LEN].copy_from_slice(&size.to_be_bytes());
+        let mut written = HEADER_LEN;
+        for &component in components(ty)? {
+            let base = buf.as_ptr() as usize;
+            let Some(rest) = buf.get_mut(written + COMPONENT_HEADER_LEN..) else {
+                error!("Buffer too small for backup");
+                return Err(Error::Line(line!()));
+            };
+            let data = self
+                .run_command(
+                    &ExportObject {
+                        object_id,
+                        rsa_key_component: component,
+                    },
+                    rest,
+                )
+                .map_err(|err| self.explain_policy_error(object_id, err))?
+                .data;
+            let (start, len) = (data.as_ptr() as usize - base, data.len());
+            let Ok(len_bytes) = u16::try_from(len) else {
+                error!("Exported component too long: {len}");
+                return Err(Error::Line(line!()));
+            };
+            buf[written] = component.into();
+            buf[written + 1..written + COMPONENT_HEADER_LEN]
+                .copy_from_slice(&len_bytes.to_be_bytes());
+            written += COMPONENT_HEADER_LEN;
+            buf.copy_within(start..start + len, written);
+            written += len;
+        }
+        Ok(&buf[..written])
+    }
+
+    /// Import a backup made with [`backup_object`](Self::backup_object) into `object_id`
+    ///
+    /// The object must exist as a transient object of the same type, which is the case after a reset of the se05x cleared its content.
+    /// The whole backup is checked before the first component is imported.
+    /// After the import, the object is checked to exist with the size it had when it was backed up.
+    pub fn restore_object(&mut self, object_id: ObjectId, data: &[u8]) -> Result<(), Error> {
+        let backup = parse_backup(data)?;
+        let ty = backup.ty;
+        if !self.exists(object_id)? {
+            error!("Object to restore does not exist: {object_id:?}");
+            return Err(Error::Line(line!()));
+        }
+        let current_ty = self.transient_key_type(object_id)?;
+        if current_ty != ty {
+            error!("Backup of a {ty:?} cannot be restored into a {current_ty:?}");
+            return Err(Error::Line(line!()));
+        }
+
+        for (component, serialized_object) in backup.components {
+            self.run_command(
+                &ImportObject {
+                    transient: true,
+                    object_id,
+                    rsa_key_component: (component != RsaKeyComponent::Na).then_some(component),
+                    serialized_object,
+                },
+                &mut [0; 2],
+            )
+            .map_err(|err| self.explain_policy_error(object_id, err))?;
+        }
+
+        if !self.exists(object_id)? || self.object_size(object_id)? != backup.size {
+            error!("Restored object does not match the backup");
+            return Err(Error::Line(line!()));
+        }
+        Ok(())
+    }
+
+    /// Type of `object_id`, which must be a transient key
+    fn transient_key_type(&mut self, object_id: ObjectId) -> Result<SecureObjectType, Error> {
+        let response = self.run_command(&ReadType { object_id }, &mut [0; 16])?;
+        if response.transient_indicator != TransientIndicator::Transient {
+            error!("Only transient objects can be exported: {object_id:?}");
+            return Err(Error::Line(line!()));
+        }
+        components(response.ty)?;
+        Ok(response.ty)
+    }
+
+    fn object_size(&mut self, object_id: ObjectId) -> Result<u16, Error> {
+        let size = self
+            .run_command(&ReadSize { object_id }, &mut [0; 16])?
+            .size
+            .0;
+        size.try_into().map_err(|_| {
+            error!("Object too large: {size}");
+            Error::Line(line!())
+        })
+    }
+}
+
+/// Parsed backup
+struct Backup<'a> {
+    ty: SecureObjectType,
+    size: u16,
+    components: heapless::Vec<(RsaKeyComponent, &'a [u8]), MAX_COMPONENTS>,
+}
+
+/// Parse a backup, checking the whole backup
+///
+/// This is done before the first import, so that an invalid backup leaves the object untouched.
+fn parse_backup(data: &[u8]) -> Result<Backup<'_>, Error> {
+    let (&[ty, size1, size2], mut rest) = split_header::<HEADER_LEN>(data)?;
+    let Ok(ty) = SecureObjectType::try_from(ty) else {
+        error!("Invalid object type in backup: {ty:02x}");
+        return Err(Error::Line(line!()));
+    };
+    let mut components = heapless::Vec::new();
+    for &component in self::components(ty)? {
+        let (&[tag, len1, len2], tail) = split_header::<COMPONENT_HEADER_LEN>(rest)?;
+        let len = u16::from_be_bytes([len1, len2]).into();
+        if tag != u8::from(component) || tail.len() < len {
+            error!("Invalid component in backup: {tag:02x}");
+            return Err(Error::Line(line!()));
+        }
+        let (serialized_object, tail) = tail.split_at(len);
+        rest = tail;
+        // Cannot fail: no object type has more than MAX_COMPONENTS components
+        components.push((component, serialized_object)).ok();
+    }
+    if !rest.is_empty() {
+        error!("Trailing data in backup: {}", rest.len());
+        return Err(Error::Line(line!()));
+    }
+    Ok(Backup {
+        ty,
+        size: u16::from_be_bytes([size1, size2]),
+        components,
+    })
+}
+
+/// Split the header of `N` bytes from the start of `data`
+fn split_header<const N: usize>(data: &[u8]) -> Result<(&[u8; N], &[u8]), Error> {
+    if data.len() < N {
+        error!("Backup too short");
+        return Err(Error::Line(line!()));
+    }
+    let (header, rest) = data.split_at(N);
+    Ok((header.try_into()?, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use crate::se05x::TYPE_RSA_KEY_PAIR;
+    use hex_literal::hex;
+
+    #[test]
+    fn backup_restore_rsa() {
+        let read_type = hex!("80 02 00 26 00 00 06 41 04 01020304 00 00");
+        let read_size = hex!("80 02 00 07 00 00 06 41 04 01020304 00 00");
+        let exists = hex!("80 04 00 27 00 00 06 41 04 01020304 00 00");
+        let rsa_key_pair = [0x41, 0x01, TYPE_RSA_KEY_PAIR, 0x42, 0x01, 0x01, 0x90, 0x00];
+        let size = hex!("41 08 0000000000000100 90 00");
+        let import_modulus = hex!("80 81 00 18 0F 41 04 01020304 42 01 00 43 04 00000000");
+
+        // Transient RSA key pair exporting each component as 4 times its component byte
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(&read_type, &rsa_key_pair)
+            .expect(&read_size, &size)
+            .expect(
+                &hex!("80 02 00 19 00 00 09 41 04 01020304 42 01 00 01 00"),
+                &hex!("41 04 00000000 90 00"),
+            )
+            .expect(
+                &hex!("80 02 00 19 00 00 09 41 04 01020304 42 01 01 01 00"),
+                &hex!("41 04 01010101 90 00"),
+            )
+            .expect(
+                &hex!("80 02 00 19 00 00 09 41 04 01020304 42 01 02 01 00"),
+                &hex!("41 04 02020202 90 00"),
+            )
+            // Truncated restores are rejected before the first command
+            .expect(&exists, &hex!("41 01 01 90 00"))
+            .expect(&read_type, &rsa_key_pair)
+            .expect(&import_modulus, &hex!("90 00"))
+            .expect(
+                &hex!("80 81 00 18 0F 41 04 01020304 42 01 01 43 04 01010101"),
+                &hex!("90 00"),
+            )
+            .expect(
+                &hex!("80 81 00 18 0F 41 04 01020304 42 01 02 43 04 02020202"),
+                &hex!("90 00"),
+            )
+            .expect(&exists, &hex!("41 01 01 90 00"))
+            .expect(&read_size, &size);
+        let mut se05x = script.enable();
+        let object_id = ObjectId([1, 2, 3, 4]);
+        let buf = &mut [0; 64];
+        let backup = se05x.backup_object(object_id, buf).unwrap();
+        // Header, then modulus, public exponent and private exponent
+        assert_eq!(
+            backup,
+            hex!("04 0100  00 0004 00000000  01 0004 01010101  02 0004 02020202")
+        );
+        let backup: heapless::Vec<u8, 64> = backup.try_into().unwrap();
+
+        // Missing the private exponent, or with trailing data
+        assert!(se05x.restore_object(object_id, &backup[..10]).is_err());
+        assert!(se05x.restore_object(object_id, &backup[..23]).is_err());
+        let mut trailing = backup.clone();
+        trailing.push(0).unwrap();
+        assert!(se05x.restore_object(object_id, &trailing).is_err());
+        assert!(se05x.restore_object(object_id, &backup).is_ok());
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+}
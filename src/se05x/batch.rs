@@ -0,0 +1,167 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Running a sequence of commands at once
+//!
+//! Provisioning usually sends many commands whose responses are only checked at the end.
+//! A [`CommandBatch`] collects such commands and validates them when they are added, so that an invalid command is rejected before any of them is sent.
+//! [`Se05X::run_batch`] then runs them in order, writing all responses to a single buffer.
+//!
+//! The T=1 protocol carries a single command APDU per exchange, and the se05x answers each one before accepting the next.
+//! Each command of a batch therefore takes its own exchange, exactly like a call to [`Se05X::run_command`]: batching does not reduce the I2C round trips.
+
+use iso7816::command::DataStream;
+
+use super::transport::{ApduWriter, Se05xTransport};
+use super::{Error, Se05X, Se05XCommand, Se05XResponse};
+
+/// Sequence of up to `N` commands, run with [`Se05X::run_batch`]
+pub struct CommandBatch<'c, const N: usize> {
+    commands: heapless::Vec<&'c dyn for<'a> DataStream<ApduWriter<'a>>, N>,
+}
+
+impl<'c, const N: usize> Default for CommandBatch<'c, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'c, const N: usize> CommandBatch<'c, N> {
+    pub fn new() -> Self {
+        Self {
+            commands: heapless::Vec::new(),
+        }
+    }
+
+    /// Validate `command` and add it to the batch
+    pub fn push<C: for<'a> Se05XCommand<ApduWriter<'a>>>(
+        &mut self,
+        command: &'c C,
+    ) -> Result<(), Error> {
+        command.validate()?;
+        self.commands.push(command).map_err(|_| {
+            error!("Command batch is full");
+            Error::Line(line!())
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+/// Responses of a [`CommandBatch`], in the order of the commands
+pub struct BatchResponses<'buf, const N: usize> {
+    responses: heapless::Vec<&'buf [u8], N>,
+}
+
+impl<'buf, const N: usize> BatchResponses<'buf, N> {
+    /// Raw response to the command at `index`, without the status word
+    pub fn raw(&self, index: usize) -> Option<&'buf [u8]> {
+        self.responses.get(index).copied()
+    }
+
+    /// Parse the response to the command at `index`
+    ///
+    /// `R` must be the response type of the command.
+    pub fn get<R: Se05XResponse<'buf>>(&self, index: usize) -> Result<R, Error> {
+        let Some(response) = self.raw(index) else {
+            error!("No response at index {index}");
+            return Err(Error::Line(line!()));
+        };
+        R::from_response(response)
+    }
+
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Run the commands of `batch` in order, stopping at the first error
+    ///
+    /// The responses are written one after the other to `response_buf`, which must be large enough for all of them.
+    pub fn run_batch<'buf, const N: usize>(
+        &mut self,
+        batch: &CommandBatch<'_, N>,
+        response_buf: &'buf mut [u8],
+    ) -> Result<BatchResponses<'buf, N>, Error> {
+        let mut ranges: heapless::Vec<(usize, usize), N> = heapless::Vec::new();
+        let mut used = 0;
+        for command in &batch.commands {
+            let area = &mut response_buf[used..];
+            let base = area.as_ptr() as usize;
+            let response = match self.run_command_buf_response(*command, area) {
+                Ok(response) => response,
+                Err(err) => {
+                    error!("Command {} of batch failed: {err:?}", ranges.len());
+                    return Err(err);
+                }
+            };
+            let start = used + (response.as_ptr() as usize - base);
+            used = start + response.len();
+            // Cannot fail: there are at most N commands
+            ranges.push((start, used)).ok();
+        }
+        let response_buf: &'buf [u8] = response_buf;
+        Ok(BatchResponses {
+            responses: ranges
+                .iter()
+                .map(|&(start, end)| &response_buf[start..end])
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use crate::se05x::commands::{DeleteSecureObject, GetRandom, GetRandomResponse};
+    use crate::se05x::ObjectId;
+    use hex_literal::hex;
+
+    #[test]
+    fn run_batch() {
+        let random = hex!("80 04 00 49 00 00 04 41 02 0002 00 00");
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(&random, &hex!("41 02 0101 90 00"))
+            .expect(&random, &hex!("41 02 0202 90 00"))
+            .expect(&random, &hex!("41 02 0303 90 00"))
+            .expect(&random, &hex!("41 02 0404 90 00"))
+            .expect(&hex!("80 04 00 28 06 41 04 01020304"), &hex!("6A 82"));
+        let mut se05x = script.enable();
+
+        let random = GetRandom { length: 2.into() };
+        let delete = DeleteSecureObject {
+            object_id: ObjectId([1, 2, 3, 4]),
+        };
+        let mut batch = CommandBatch::<3>::new();
+        batch.push(&random).unwrap();
+        batch.push(&random).unwrap();
+        let buf = &mut [0; 32];
+        let responses = se05x.run_batch(&batch, buf).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses.get::<GetRandomResponse>(0).unwrap().data, [1, 1]);
+        assert_eq!(responses.get::<GetRandomResponse>(1).unwrap().data, [2, 2]);
+        assert!(responses.get::<GetRandomResponse>(2).is_err());
+
+        batch.push(&delete).unwrap();
+        assert!(batch.push(&delete).is_err());
+        let buf = &mut [0; 32];
+        assert_eq!(
+            se05x.run_batch(&batch, buf).err(),
+            Some(Error::Status(0x6A82.into()))
+        );
+        assert!(se05x.into_transport().into_inner().is_done());
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Block cipher interface for AES keys stored in the se05x
+//!
+//! [`Se05xAes`] has the methods of the `BlockEncrypt`/`BlockDecrypt` traits of the `cipher` crate, but does not implement them:
+//! these traits cannot report errors, and the communication with the secure element can fail.
+//! The methods of [`Se05xAes`] return a [`Result`] instead.
+//!
+//! With the `aead` feature, [`Se05xAesCcm`](super::ccm::Se05xAesCcm) builds AES-CCM on top of [`Se05xAes`],
+//! implementing the fallible `AeadInPlace` trait of the `aead` crate.
+
+use super::commands::{CipherOneShotDecrypt, CipherOneShotEncrypt, WriteSymmKey};
+use super::policies::PolicySet;
+use super::transport::Se05xTransport;
+use super::{AppletConfig, CipherMode, Error, ObjectId, Se05X, SymmKeyType};
+
+/// AES block size
+pub const AES_BLOCK_SIZE: usize = 16;
+
+/// Number of blocks sent in a single command
+pub(super) const BLOCKS_PER_COMMAND: usize = 32;
+
+/// AES block cipher backed by an AES key stored in the se05x
+///
+/// Blocks are processed with [`AesEcbNopad`](CipherMode::AesEcbNopad) through [`CipherOneShotEncrypt`] and [`CipherOneShotDecrypt`],
+/// batching up to 32 blocks per command.
+pub struct Se05xAes<'se, T> {
+    se: &'se mut Se05X<T>,
+    key_id: ObjectId,
+}
+
+impl<'se, T: Se05xTransport> Se05xAes<'se, T> {
+    pub fn new(se: &'se mut Se05X<T>, key_id: ObjectId) -> Self {
+        Self { se, key_id }
+    }
+
+    pub fn key_id(&self) -> ObjectId {
+        self.key_id
+    }
+
+    pub fn encrypt_block(&mut self, block: &mut [u8; AES_BLOCK_SIZE]) -> Result<(), Error> {
+        self.encrypt_blocks(core::slice::from_mut(block))
+    }
+
+    pub fn decrypt_block(&mut self, block: &mut [u8; AES_BLOCK_SIZE]) -> Result<(), Error> {
+        self.decrypt_blocks(core::slice::from_mut(block))
+    }
+
+    pub fn encrypt_blocks(&mut self, blocks: &mut [[u8; AES_BLOCK_SIZE]]) -> Result<(), Error> {
+        self.process_blocks(blocks, true)
+    }
+
+    pub fn decrypt_blocks(&mut self, blocks: &mut [[u8; AES_BLOCK_SIZE]]) -> Result<(), Error> {
+        self.process_blocks(blocks, false)
+    }
+
+    fn process_blocks(
+        &mut self,
+        blocks: &mut [[u8; AES_BLOCK_SIZE]],
+        encrypt: bool,
+    ) -> Result<(), Error> {
+        self.se.require_feature(AppletConfig::AES)?;
+        let mode = CipherMode::AesEcbNopad;
+        let mut input = [0; BLOCKS_PER_COMMAND * AES_BLOCK_SIZE];
+        let mut response_buf = [0; BLOCKS_PER_COMMAND * AES_BLOCK_SIZE + 16];
+        for chunk in blocks.chunks_mut(BLOCKS_PER_COMMAND) {
+            let len = chunk.len() * AES_BLOCK_SIZE;
+            for (dst, src) in input.chunks_exact_mut(AES_BLOCK_SIZE).zip(chunk.iter()) {
+                dst.copy_from_slice(src);
+            }
+            let output = if encrypt {
+                self.se
+                    .run_command(
+                        &CipherOneShotEncrypt {
+                            key_id: self.key_id,
+                            mode,
+                            plaintext: &input[..len],
+                            initialization_vector: None,
+                        },
+                        &mut response_buf,
+                    )?
+                    .ciphertext
+            } else {
+                self.se
+                    .run_command(
+                        &CipherOneShotDecrypt {
+                            key_id: self.key_id,
+                            mode,
+                            ciphertext: &input[..len],
+                            initialization_vector: None,
+                        },
+                        &mut response_buf,
+                    )?
+                    .plaintext
+            };
+            if output.len() != len {
+                error!("Unexpected output length: {} != {len}", output.len());
+                return Err(Error::Line(line!()));
+            }
+            for (dst, src) in chunk.iter_mut().zip(output.chunks_exact(AES_BLOCK_SIZE)) {
+                dst.copy_from_slice(src);
+            }
+        }
+        Ok(())
+    }
+
+    /// CBC-MAC of `data` chained from `iv`, computed with [`AesCbcNopad`](CipherMode::AesCbcNopad)
+    ///
+    /// `data` must be a whole number of blocks. Returns the last block of the ciphertext.
+    #[cfg(feature = "aead")]
+    pub(super) fn cbc_mac(
+        &mut self,
+        iv: &[u8; AES_BLOCK_SIZE],
+        data: &[u8],
+    ) -> Result<[u8; AES_BLOCK_SIZE], Error> {
+        self.se.require_feature(AppletConfig::AES)?;
+        if !data.len().is_multiple_of(AES_BLOCK_SIZE) {
+            error!("Input is not a whole number of blocks: {}", data.len());
+            return Err(Error::Line(line!()));
+        }
+        let mut mac = *iv;
+        let mut response_buf = [0; BLOCKS_PER_COMMAND * AES_BLOCK_SIZE + 16];
+        for chunk in data.chunks(BLOCKS_PER_COMMAND * AES_BLOCK_SIZE) {
+            let ciphertext = self
+                .se
+                .run_command(
+                    &CipherOneShotEncrypt {
+                        key_id: self.key_id,
+                        mode: CipherMode::AesCbcNopad,
+                        plaintext: chunk,
+                        initialization_vector: Some(&mac),
+                    },
+                    &mut response_buf,
+                )?
+                .ciphertext;
+            if ciphertext.len() != chunk.len() {
+                error!(
+                    "Unexpected output length: {} != {}",
+                    ciphertext.len(),
+                    chunk.len()
+                );
+                return Err(Error::Line(line!()));
+            }
+            mac.copy_from_slice(&ciphertext[chunk.len() - AES_BLOCK_SIZE..]);
+        }
+        Ok(mac)
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Store the AES `key` in a new AES key object, to be used with [`Se05xAes`]
+    ///
+    /// The key must be 16, 24 or 32 bytes long, this is checked by [`WriteSymmKey::validate`] before the key is sent.
+    pub fn import_aes_key(
+        &mut self,
+        object_id: ObjectId,
+        key: &[u8],
+        policy: Option<PolicySet<'_>>,
+    ) -> Result<(), Error> {
+        self.require_feature(AppletConfig::AES)?;
+        self.run_command(
+            &WriteSymmKey {
+                transient: false,
+                is_auth: false,
+                key_type: SymmKeyType::Aes,
+                policy,
+                max_attempts: None,
+                object_id,
+                kek_id: None,
+                value: key,
+            },
+            &mut [0; 2],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use hex_literal::hex;
+
+    /// Expect a one-shot ECB encryption (`p2` is `37`) or decryption (`p2` is `38`) of `data` by `01020304`, answered with the inverted bits of `data`
+    fn expect_ecb(script: &mut ScriptedTransport, p2: u8, data: &[u8]) {
+        let len = (data.len() as u16).to_be_bytes();
+        let data_header: &[u8] = match data.len() {
+            0..=0x7F => &[0x43, len[1]],
+            0x80..=0xFF => &[0x43, 0x81, len[1]],
+            _ => &[0x43, 0x82, len[0], len[1]],
+        };
+        let lc = (9 + data_header.len() + data.len()) as u16;
+        let inverted: Vec<u8> = data.iter().map(|b| !b).collect();
+        script.expect(
+            &[
+                &[0x80, 0x03, 0x0E, p2, 0x00][..],
+                &lc.to_be_bytes(),
+                &hex!("41 04 01020304 42 01 0E"),
+                data_header,
+                data,
+                &hex!("00 00"),
+            ]
+            .concat(),
+            &[&[0x41, 0x82][..], &len, &inverted, &hex!("90 00")].concat(),
+        );
+    }
+
+    #[test]
+    fn batch_blocks() {
+        let plaintext: Vec<[u8; AES_BLOCK_SIZE]> =
+            (0..70).map(|i| [i as u8; AES_BLOCK_SIZE]).collect();
+        let ciphertext: Vec<[u8; AES_BLOCK_SIZE]> =
+            plaintext.iter().map(|block| block.map(|b| !b)).collect();
+        let mut script = ScriptedTransport::new();
+        // Commands of 32, 32 and 6 blocks
+        for blocks in plaintext.chunks(BLOCKS_PER_COMMAND) {
+            expect_ecb(&mut script, 0x37, blocks.as_flattened());
+        }
+        // Commands of 32, then of 32 and 6 blocks
+        for blocks in [
+            &ciphertext[..BLOCKS_PER_COMMAND],
+            &ciphertext[BLOCKS_PER_COMMAND..],
+        ] {
+            for blocks in blocks.chunks(BLOCKS_PER_COMMAND) {
+                expect_ecb(&mut script, 0x38, blocks.as_flattened());
+            }
+        }
+        let mut se05x = script.enable();
+        let mut blocks = plaintext.clone();
+        let mut aes = Se05xAes::new(&mut se05x, ObjectId([1, 2, 3, 4]));
+        aes.encrypt_blocks(&mut blocks).unwrap();
+        assert_eq!(blocks, ciphertext);
+        aes.decrypt_blocks(&mut blocks[..BLOCKS_PER_COMMAND])
+            .unwrap();
+        aes.decrypt_blocks(&mut blocks[BLOCKS_PER_COMMAND..])
+            .unwrap();
+        assert_eq!(blocks, plaintext);
+        assert!(se05x.into_transport().into_inner().is_done());
+    }
+}
@@ -0,0 +1,384 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! AES-CCM with AES keys stored in the se05x
+//!
+//! [`Se05xAesCcm`] implements the [`AeadInPlace`] trait of the `aead` crate with 13 byte nonces and 16 byte tags (NIST SP 800-38C).
+//! The CBC-MAC and the keystream are computed by the se05x with [`Se05xAes`], the formatting of the blocks is done on the host.
+//! The message must not be longer than [`CCM_MAX_MESSAGE_LEN`].
+//!
+//! The trait takes `&self`, so the adapter holds the [`Se05xAes`] in a [`RefCell`].
+//! Errors of the secure element are reported as an [`aead::Error`].
+
+use core::cell::RefCell;
+
+use aead::consts::{U0, U13, U16};
+use aead::{AeadCore, AeadInPlace, Error as AeadError, Nonce, Tag};
+
+use super::block_cipher::{Se05xAes, AES_BLOCK_SIZE, BLOCKS_PER_COMMAND};
+use super::transport::Se05xTransport;
+use super::{Error, ObjectId};
+
+/// Length of the nonce
+const NONCE_LEN: usize = 13;
+/// Length of the tag
+const TAG_LEN: usize = 16;
+/// Length of the message length field, `15 - NONCE_LEN`
+const LEN_FIELD_LEN: usize = 2;
+/// Largest message length that fits in the length field
+pub const CCM_MAX_MESSAGE_LEN: usize = 0xFFFF;
+
+type Block = [u8; AES_BLOCK_SIZE];
+
+fn aead_error(_err: Error) -> AeadError {
+    debug!("AEAD operation failed: {_err:?}");
+    AeadError
+}
+
+/// AES-CCM backed by an AES key stored in the se05x
+pub struct Se05xAesCcm<'se, T> {
+    aes: RefCell<Se05xAes<'se, T>>,
+}
+
+impl<'se, T: Se05xTransport> Se05xAesCcm<'se, T> {
+    pub fn new(aes: Se05xAes<'se, T>) -> Self {
+        Self {
+            aes: RefCell::new(aes),
+        }
+    }
+
+    pub fn key_id(&self) -> ObjectId {
+        self.aes.borrow().key_id()
+    }
+}
+
+impl<T> AeadCore for Se05xAesCcm<'_, T> {
+    type NonceSize = U13;
+    type TagSize = U16;
+    type CiphertextOverhead = U0;
+}
+
+impl<T: Se05xTransport> AeadInPlace for Se05xAesCcm<'_, T> {
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Tag<Self>, AeadError> {
+        let mut aes = self.aes.try_borrow_mut().map_err(|_| AeadError)?;
+        let mac = cbc_mac(&mut aes, nonce.as_ref(), associated_data, buffer).map_err(aead_error)?;
+        let s0 = apply_keystream(&mut aes, nonce.as_ref(), buffer).map_err(aead_error)?;
+        let mut tag = Tag::<Self>::default();
+        for ((t, m), s) in tag.iter_mut().zip(mac).zip(s0) {
+            *t = m ^ s;
+        }
+        Ok(tag)
+    }
+
+    /// On failure, `buffer` is zeroed
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag<Self>,
+    ) -> Result<(), AeadError> {
+        let mut aes = self.aes.try_borrow_mut().map_err(|_| AeadError)?;
+        let result = apply_keystream(&mut aes, nonce.as_ref(), buffer).and_then(|s0| {
+            let mac = cbc_mac(&mut aes, nonce.as_ref(), associated_data, buffer)?;
+            Ok((s0, mac))
+        });
+        let valid = match result {
+            Ok((s0, mac)) => {
+                let diff = mac
+                    .iter()
+                    .zip(s0)
+                    .zip(tag)
+                    .fold(0, |acc, ((m, s), t)| acc | (m ^ s ^ t));
+                diff == 0
+            }
+            Err(_err) => {
+                debug!("AEAD operation failed: {_err:?}");
+                false
+            }
+        };
+        if !valid {
+            buffer.fill(0);
+            return Err(AeadError);
+        }
+        Ok(())
+    }
+}
+
+/// Input of the CBC-MAC, sent in batches of [`BLOCKS_PER_COMMAND`] blocks
+struct MacInput {
+    buf: [u8; BLOCKS_PER_COMMAND * AES_BLOCK_SIZE],
+    len: usize,
+    mac: Block,
+}
+
+impl MacInput {
+    fn update<T: Se05xTransport>(
+        &mut self,
+        aes: &mut Se05xAes<'_, T>,
+        mut data: &[u8],
+    ) -> Result<(), Error> {
+        while !data.is_empty() {
+            if self.len == self.buf.len() {
+                self.flush(aes)?;
+            }
+            let n = data.len().min(self.buf.len() - self.len);
+            self.buf[self.len..][..n].copy_from_slice(&data[..n]);
+            self.len += n;
+            data = &data[n..];
+        }
+        Ok(())
+    }
+
+    /// Pad the input with zeros to a whole number of blocks
+    fn pad(&mut self) {
+        let padded = self.len.next_multiple_of(AES_BLOCK_SIZE);
+        self.buf[self.len..padded].fill(0);
+        self.len = padded;
+    }
+
+    fn flush<T: Se05xTransport>(&mut self, aes: &mut Se05xAes<'_, T>) -> Result<(), Error> {
+        self.mac = aes.cbc_mac(&self.mac, &self.buf[..self.len])?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl Drop for MacInput {
+    fn drop(&mut self) {
+        self.buf.fill(0);
+    }
+}
+
+/// CBC-MAC of the formatted nonce, associated data and `message`
+fn cbc_mac<T: Se05xTransport>(
+    aes: &mut Se05xAes<'_, T>,
+    nonce: &[u8],
+    associated_data: &[u8],
+    message: &[u8],
+) -> Result<Block, Error> {
+    let Ok(message_len) = u16::try_from(message.len()) else {
+        error!("Message too long: {}", message.len());
+        return Err(Error::Line(line!()));
+    };
+    let mut input = MacInput {
+        buf: [0; BLOCKS_PER_COMMAND * AES_BLOCK_SIZE],
+        len: 0,
+        mac: [0; AES_BLOCK_SIZE],
+    };
+
+    let mut b0 = [0; AES_BLOCK_SIZE];
+    b0[0] = (((TAG_LEN - 2) / 2) << 3 | (LEN_FIELD_LEN - 1)) as u8;
+    if !associated_data.is_empty() {
+        b0[0] |= 0x40;
+    }
+    b0[1..][..NONCE_LEN].copy_from_slice(nonce);
+    b0[1 + NONCE_LEN..].copy_from_slice(&message_len.to_be_bytes());
+    input.update(aes, &b0)?;
+
+    if !associated_data.is_empty() {
+        match u16::try_from(associated_data.len()) {
+            Ok(len) if len < 0xFF00 => input.update(aes, &len.to_be_bytes())?,
+            _ => {
+                let Ok(len) = u32::try_from(associated_data.len()) else {
+                    error!("Associated data too long: {}", associated_data.len());
+                    return Err(Error::Line(line!()));
+                };
+                input.update(aes, &[0xFF, 0xFE])?;
+                input.update(aes, &len.to_be_bytes())?;
+            }
+        }
+        input.update(aes, associated_data)?;
+        input.pad();
+    }
+
+    input.update(aes, message)?;
+    input.pad();
+    input.flush(aes)?;
+    Ok(input.mac)
+}
+
+/// XOR `buffer` with the keystream of the counter blocks 1 and up, returning the encrypted counter block 0
+fn apply_keystream<T: Se05xTransport>(
+    aes: &mut Se05xAes<'_, T>,
+    nonce: &[u8],
+    buffer: &mut [u8],
+) -> Result<Block, Error> {
+    if buffer.len() > CCM_MAX_MESSAGE_LEN {
+        error!("Message too long: {}", buffer.len());
+        return Err(Error::Line(line!()));
+    }
+    let mut counter_block = [0; AES_BLOCK_SIZE];
+    counter_block[0] = (LEN_FIELD_LEN - 1) as u8;
+    counter_block[1..][..NONCE_LEN].copy_from_slice(nonce);
+
+    let mut s0 = [0; AES_BLOCK_SIZE];
+    let mut keystream = [[0; AES_BLOCK_SIZE]; BLOCKS_PER_COMMAND];
+    let block_count = buffer.len().div_ceil(AES_BLOCK_SIZE) + 1;
+    let mut counter = 0;
+    while counter < block_count {
+        let batch = &mut keystream[..(block_count - counter).min(BLOCKS_PER_COMMAND)];
+        for (i, block) in batch.iter_mut().enumerate() {
+            *block = counter_block;
+            block[1 + NONCE_LEN..].copy_from_slice(&((counter + i) as u16).to_be_bytes());
+        }
+        aes.encrypt_blocks(batch)?;
+        for (i, block) in batch.iter().enumerate() {
+            match counter + i {
+                0 => s0 = *block,
+                index => {
+                    let start = (index - 1) * AES_BLOCK_SIZE;
+                    let end = buffer.len().min(start + AES_BLOCK_SIZE);
+                    for (b, k) in buffer[start..end].iter_mut().zip(block) {
+                        *b ^= k;
+                    }
+                }
+            }
+        }
+        counter += batch.len();
+    }
+    keystream.fill([0; AES_BLOCK_SIZE]);
+    Ok(s0)
+}
+
+#[cfg(all(test, feature = "aes-session"))]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::{ApduLink, ApduSe05X, ApduTransport, SELECT_RESPONSE};
+    use aes::cipher::{BlockEncrypt, KeyInit};
+    use aes::Aes128;
+    use hex_literal::hex;
+
+    const KEY: [u8; 16] = hex!("000102030405060708090A0B0C0D0E0F");
+    const NONCE: [u8; 13] = hex!("101112131415161718191A1B1C");
+
+    /// Answers SELECT and the one-shot AES ECB and CBC encryptions with [`KEY`]
+    ///
+    /// Records the number of blocks of each command.
+    #[derive(Default)]
+    struct Script {
+        blocks: Vec<usize>,
+    }
+
+    impl ApduTransport for Script {
+        fn transmit<'buf>(
+            &mut self,
+            command: &[u8],
+            response_buf: &'buf mut [u8],
+        ) -> Result<&'buf [u8], Error> {
+            let payload = match command {
+                [0x00, 0xA4, ..] => {
+                    response_buf[..SELECT_RESPONSE.len()].copy_from_slice(&SELECT_RESPONSE);
+                    return Ok(&response_buf[..SELECT_RESPONSE.len()]);
+                }
+                [0x80, 0x03, 0x0E, 0x37, 0x00, lc1, lc2, payload @ ..] => {
+                    &payload[..u16::from_be_bytes([*lc1, *lc2]).into()]
+                }
+                _ => panic!("Unexpected command: {command:02x?}"),
+            };
+            // Key ID, mode, data and IV
+            let (mode, data, iv) = match payload {
+                [0x41, 0x04, _, _, _, _, 0x42, 0x01, mode, rest @ ..] => {
+                    let (data, rest) = match rest {
+                        [0x43, 0x82, l1, l2, rest @ ..] => {
+                            rest.split_at(u16::from_be_bytes([*l1, *l2]).into())
+                        }
+                        [0x43, 0x81, len, rest @ ..] | [0x43, len, rest @ ..] => {
+                            rest.split_at(*len as usize)
+                        }
+                        _ => panic!("Unexpected payload: {payload:02x?}"),
+                    };
+                    match (*mode, rest) {
+                        (0x0E, []) => (*mode, data, [0; 16]),
+                        (0x0D, [0x44, 0x10, iv @ ..]) => (*mode, data, iv.try_into().unwrap()),
+                        _ => panic!("Unexpected payload: {payload:02x?}"),
+                    }
+                }
+                _ => panic!("Unexpected payload: {payload:02x?}"),
+            };
+            self.blocks.push(data.len() / AES_BLOCK_SIZE);
+            let cipher = Aes128::new(&KEY.into());
+            let response = &mut response_buf[..data.len() + 6];
+            response[..2].copy_from_slice(&[0x41, 0x82]);
+            response[2..4].copy_from_slice(&(data.len() as u16).to_be_bytes());
+            let mut chain = iv;
+            for (out, block) in response[4..]
+                .chunks_exact_mut(AES_BLOCK_SIZE)
+                .zip(data.chunks_exact(AES_BLOCK_SIZE))
+            {
+                let mut block: Block = block.try_into().unwrap();
+                if mode == 0x0D {
+                    block.iter_mut().zip(chain).for_each(|(b, c)| *b ^= c);
+                }
+                cipher.encrypt_block((&mut block).into());
+                chain = block;
+                out.copy_from_slice(&block);
+            }
+            let len = response.len();
+            response[len - 2..].copy_from_slice(&[0x90, 0x00]);
+            Ok(response)
+        }
+    }
+
+    #[test]
+    fn ccm() {
+        let mut se05x = ApduSe05X::from_transport(ApduLink::new(Script::default()));
+        se05x.enable().unwrap();
+        let ccm = Se05xAesCcm::new(Se05xAes::new(&mut se05x, ObjectId([1, 2, 3, 4])));
+        let nonce = Nonce::<Se05xAesCcm<'_, ()>>::from(NONCE);
+
+        let tag = ccm.encrypt_in_place_detached(&nonce, &[], &mut []).unwrap();
+        assert_eq!(tag[..], hex!("d5e7b9742adec972909274ecbe50c0c9"));
+
+        let associated_data = hex!("2021222324252627");
+        let plaintext: [u8; 24] = core::array::from_fn(|i| 0x40 + i as u8);
+        let mut buffer = plaintext;
+        let tag = ccm
+            .encrypt_in_place_detached(&nonce, &associated_data, &mut buffer)
+            .unwrap();
+        assert_eq!(
+            buffer,
+            hex!("3ca03202fc19a893f31002da634a22ce7a82072a2bc2c87d")
+        );
+        assert_eq!(tag[..], hex!("c7b361033190bbc19ddfe178dab54305"));
+        ccm.decrypt_in_place_detached(&nonce, &associated_data, &mut buffer, &tag)
+            .unwrap();
+        assert_eq!(buffer, plaintext);
+
+        // Tampered associated data
+        ccm.encrypt_in_place_detached(&nonce, &associated_data, &mut buffer)
+            .unwrap();
+        assert!(ccm
+            .decrypt_in_place_detached(&nonce, &associated_data[1..], &mut buffer, &tag)
+            .is_err());
+        assert_eq!(buffer, [0; 24]);
+    }
+
+    #[test]
+    fn ccm_batches() {
+        let mut se05x = ApduSe05X::from_transport(ApduLink::new(Script::default()));
+        se05x.enable().unwrap();
+        let ccm = Se05xAesCcm::new(Se05xAes::new(&mut se05x, ObjectId([1, 2, 3, 4])));
+        let nonce = Nonce::<Se05xAesCcm<'_, ()>>::from(NONCE);
+        let associated_data = [0; 300];
+        let plaintext: Vec<u8> = (0..600).map(|i| (i * 7) as u8).collect();
+        let mut buffer = plaintext.clone();
+        let tag = ccm
+            .encrypt_in_place_detached(&nonce, &associated_data, &mut buffer)
+            .unwrap();
+        assert_eq!(tag[..], hex!("e03fdd3821610dd90572e22d1bde24cf"));
+        ccm.decrypt_in_place_detached(&nonce, &associated_data, &mut buffer, &tag)
+            .unwrap();
+        assert_eq!(buffer, plaintext);
+        // CBC-MAC of 58 blocks, then the keystream of 39 blocks
+        assert_eq!(
+            se05x.into_transport().into_inner().blocks[..4],
+            [32, 26, 32, 7]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Helpers for X.509 certificate workflows
+//!
+//! DER encoded certificates can be stored in binary files. The file created by [`Se05X::write_certificate`] is exactly as long as the certificate.
+//! [`Se05X::read_certificate`] relies on the length in the DER header to know how much to read.
+//!
+//! For certificate signing requests, [`Se05X::read_public_key_attested`] reads the public key of a key pair together with the attestation proving it was generated in the se05x.
+
+use super::commands::{ReadAttestObjectResponse, ReadObject, WriteBinary};
+use super::rng::HostRng;
+use super::transport::Se05xTransport;
+use super::{AttestationAlgo, Error, ObjectId, Se05X, CHUNK_LEN};
+
+/// Length of the DER header read before the rest of the certificate
+const DER_HEADER_LEN: usize = 4;
+
+/// Total length of the DER encoded `SEQUENCE` starting `data`, including its header
+///
+/// Only the lengths encoded on up to two bytes are supported, which covers all certificates that fit in a binary file.
+pub fn der_sequence_len(data: &[u8]) -> Option<usize> {
+    match *data {
+        [0x30, len, ..] if len < 0x80 => Some(2 + len as usize),
+        [0x30, 0x81, len, ..] if len >= 0x80 => Some(3 + len as usize),
+        [0x30, 0x82, hi, lo, ..] if hi != 0 => Some(4 + u16::from_be_bytes([hi, lo]) as usize),
+        _ => None,
+    }
+}
+
+/// Public key read with [`Se05X::read_public_key_attested`]
+#[derive(Debug)]
+pub struct AttestedPublicKey<'buf> {
+    /// The public key, as an uncompressed SEC1 point for Weierstrass curves, or 32 bytes for Edwards and Montgomery curves
+    pub public_key: &'buf [u8],
+    /// The attestation evidence, which can be verified with [`ReadAttestObjectResponse::signed_data`]
+    pub attestation: ReadAttestObjectResponse<'buf>,
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Read the public key of the EC key pair `key_id`, attested by `attestation_key`
+    ///
+    /// The freshness random of the attestation is generated with `rng`.
+    pub fn read_public_key_attested<'buf, R: HostRng>(
+        &mut self,
+        key_id: ObjectId,
+        attestation_key: ObjectId,
+        algo: AttestationAlgo,
+        rng: &mut R,
+        buf: &'buf mut [u8],
+    ) -> Result<AttestedPublicKey<'buf>, Error> {
+        let attestation = self.read_attested(
+            &ReadObject {
+                object_id: key_id,
+                offset: None,
+                length: None,
+                rsa_key_component: None,
+            },
+            attestation_key,
+            algo,
+            rng,
+            buf,
+        )?;
+        let Some(public_key) = attestation.data else {
+            error!("Attested object has no public key");
+            return Err(Error::Line(line!()));
+        };
+        let is_sec1_point = public_key.first() == Some(&0x04) && public_key.len() % 2 == 1;
+        if !is_sec1_point && public_key.len() != 32 {
+            error!("Unexpected public key format");
+            return Err(Error::Line(line!()));
+        }
+        Ok(AttestedPublicKey {
+            public_key,
+            attestation,
+        })
+    }
+
+    /// Store a DER encoded certificate in a new binary file
+    ///
+    /// The file is created with the length of the certificate, so `object_id` must not exist yet.
+    /// The structure of the certificate is checked before anything is written.
+    pub fn write_certificate(&mut self, object_id: ObjectId, der: &[u8]) -> Result<(), Error> {
+        if der_sequence_len(der) != Some(der.len()) {
+            error!("Invalid DER certificate");
+            return Err(Error::Line(line!()));
+        }
+        let file_length = u16::try_from(der.len()).map_err(|_| Error::Line(line!()))?;
+        let (first, rest) = der.split_at(der.len().min(CHUNK_LEN));
+        self.run_command(
+            &WriteBinary {
+                transient: false,
+                policy: None,
+                object_id,
+                offset: None,
+                file_length: Some(file_length.into()),
+                data: Some(first),
+            },
+            &mut [0; 2],
+        )
+        .map_err(|err| self.explain_policy_error(object_id, err))?;
+        self.write_binary_chunked(object_id, first.len() as u16, rest)
+    }
+
+    /// Read a DER encoded certificate written with [`write_certificate`](Self::write_certificate)
+    ///
+    /// Only the bytes covered by the DER length are read, and returned.
+    pub fn read_certificate<'buf>(
+        &mut self,
+        object_id: ObjectId,
+        buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        let mut header = [0; DER_HEADER_LEN];
+        self.read_binary_into(object_id, 0, &mut header)?;
+        let Some(len) = der_sequence_len(&header).filter(|&len| len >= DER_HEADER_LEN) else {
+            error!("Object does not hold a DER certificate");
+            return Err(Error::Line(line!()));
+        };
+        let Some(cert) = buf.get_mut(..len) else {
+            error!("Buffer too small for certificate: {len}");
+            return Err(Error::Line(line!()));
+        };
+        cert[..DER_HEADER_LEN].copy_from_slice(&header);
+        self.read_binary_into(
+            object_id,
+            DER_HEADER_LEN as u16,
+            &mut cert[DER_HEADER_LEN..],
+        )?;
+        Ok(cert)
+    }
+
+    /// Fill `data` with the content of a binary file starting at `offset`, in chunks of [`CHUNK_LEN`]
+    pub(crate) fn read_binary_into(
+        &mut self,
+        object_id: ObjectId,
+        offset: u16,
+        data: &mut [u8],
+    ) -> Result<(), Error> {
+        let response_buf = &mut [0; CHUNK_LEN + 8];
+        let mut offset = offset;
+        for chunk in data.chunks_mut(CHUNK_LEN) {
+            let response = self
+                .run_command(
+                    &ReadObject {
+                        object_id,
+                        offset: Some(offset.into()),
+                        length: Some((chunk.len() as u16).into()),
+                        rsa_key_component: None,
+                    },
+                    response_buf,
+                )
+                .map_err(|err| self.explain_policy_error(object_id, err))?;
+            if response.data.len() != chunk.len() {
+                error!("Unexpected read length: {}", response.data.len());
+                return Err(Error::Line(line!()));
+            }
+            chunk.copy_from_slice(response.data);
+            offset = offset
+                .checked_add(chunk.len() as u16)
+                .ok_or(Error::Line(line!()))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use hex_literal::hex;
+
+    #[test]
+    fn explain_policy_violations() {
+        let read = hex!("80 02 00 00 00 00 0E 41 04 01020304 42 02 0000 43 02 0004 00 00");
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(&read, &hex!("69 85"))
+            .expect(&read, &hex!("69 85"))
+            .expect(
+                &hex!("80 02 00 3B 06 41 04 01020304"),
+                &hex!("42 0E 01020304 07 01 0000 7FFF0200 0000 90 00"),
+            );
+        let mut se05x = script.enable();
+        let object_id = ObjectId([1, 2, 3, 4]);
+        let buf = &mut [0; 64];
+        assert_eq!(
+            se05x.read_certificate(object_id, buf).err(),
+            Some(Error::Status(0x6985.into()))
+        );
+        se05x.set_explain_policy_violations(true);
+        let err = se05x.read_certificate(object_id, buf).err().unwrap();
+        assert_eq!(
+            err,
+            Error::PolicyViolation {
+                object_id,
+                required_auth: ObjectId([0x7F, 0xFF, 0x02, 0x00]),
+                status: 0x6985.into(),
+            }
+        );
+        assert_eq!(iso7816::Status::from(err), 0x6985.into());
+        assert!(se05x.into_transport().into_inner().is_done());
+    }
+
+    #[test]
+    fn der_length() {
+        assert_eq!(der_sequence_len(&[0x30, 0x03, 1, 2, 3]), Some(5));
+        assert_eq!(der_sequence_len(&[0x30, 0x81, 0x80]), Some(0x83));
+        assert_eq!(der_sequence_len(&[0x30, 0x82, 0x01, 0xF4]), Some(0x1F8));
+        // Non-minimal encodings are not DER
+        assert_eq!(der_sequence_len(&[0x30, 0x81, 0x10]), None);
+        assert_eq!(der_sequence_len(&[0x30, 0x82, 0x00, 0x90]), None);
+        assert_eq!(der_sequence_len(&[0x04, 0x01, 0x00]), None);
+        assert_eq!(der_sequence_len(&[0x30]), None);
+    }
+}
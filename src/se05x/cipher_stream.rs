@@ -0,0 +1,400 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Streaming encryption and decryption with keys stored in the se05x
+//!
+//! [`CipherUpdate`](super::commands::CipherUpdate) only accepts whole blocks, and at most [`CHUNK_LEN`](super::CHUNK_LEN) bytes fit in a command (see [`Se05X::cipher_update_chunked`]).
+//! [`EncryptStream`] and [`DecryptStream`] accept input of any length: partial blocks are kept on the host until more input arrives,
+//! and the rest is processed with [`CipherFinal`] by [`finish`](EncryptStream::finish).
+//! The IV is chained by the se05x between the commands.
+//!
+//! The streams create the crypto object of the operation, and delete it when finished or dropped.
+
+use super::commands::{
+    CipherDecryptInit, CipherEncryptInit, CipherFinal, CreateCipherObject, DeleteCryptoObj,
+};
+use super::transport::Se05xTransport;
+use super::{AppletConfig, CipherMode, CryptoObjectId, Error, ObjectId, Se05X};
+
+/// Largest block size of the cipher modes
+const MAX_BLOCK_SIZE: usize = 16;
+
+impl CipherMode {
+    /// Block size of the cipher
+    pub fn block_size(&self) -> usize {
+        match self {
+            #[cfg(feature = "legacy-des")]
+            Self::DesCbcNopad
+            | Self::DesCbcIso9797M1
+            | Self::DesCbcIso9797M2
+            | Self::DesCbcPkcs5
+            | Self::DesEcbNopad
+            | Self::DesEcbIso9797M1
+            | Self::DesEcbIso9797M2
+            | Self::DesEcbPkcs5 => 8,
+            Self::AesEcbNopad
+            | Self::AesCbcNopad
+            | Self::AesCbcIso9797M1
+            | Self::AesCbcIso9797M2
+            | Self::AesCbcPkcs5
+            | Self::AesCtr => 16,
+        }
+    }
+
+    /// Whether the mode pads the input to a whole number of blocks
+    pub fn is_padded(&self) -> bool {
+        match self {
+            #[cfg(feature = "legacy-des")]
+            Self::DesCbcNopad | Self::DesEcbNopad => false,
+            Self::AesEcbNopad | Self::AesCbcNopad | Self::AesCtr => false,
+            _ => true,
+        }
+    }
+}
+
+/// Byte counts of a finished stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamEnd {
+    /// Bytes written to the output by [`finish`](EncryptStream::finish)
+    pub written: usize,
+    /// Total input of the stream
+    pub total_input: usize,
+    /// Total output of the stream
+    pub total_output: usize,
+}
+
+struct CipherStream<'se, T: Se05xTransport> {
+    se: &'se mut Se05X<T>,
+    cipher_id: CryptoObjectId,
+    block_size: usize,
+    /// Keep the last block for [`CipherFinal`], so that the padding can be removed
+    hold_back_block: bool,
+    pending: [u8; MAX_BLOCK_SIZE],
+    pending_len: usize,
+    total_input: usize,
+    total_output: usize,
+    finished: bool,
+}
+
+impl<'se, T: Se05xTransport> CipherStream<'se, T> {
+    fn new(
+        se: &'se mut Se05X<T>,
+        key_id: ObjectId,
+        cipher_id: CryptoObjectId,
+        mode: CipherMode,
+        initialization_vector: Option<&[u8]>,
+        encrypt: bool,
+    ) -> Result<Self, Error> {
+        se.require_feature(match mode.block_size() {
+            8 => AppletConfig::DES,
+            _ => AppletConfig::AES,
+        })?;
+        se.run_command(
+            &CreateCipherObject {
+                id: cipher_id,
+                subtype: mode,
+            },
+            &mut [0; 2],
+        )?;
+        let stream = Self {
+            se,
+            cipher_id,
+            block_size: mode.block_size(),
+            hold_back_block: !encrypt && mode.is_padded(),
+            pending: [0; MAX_BLOCK_SIZE],
+            pending_len: 0,
+            total_input: 0,
+            total_output: 0,
+            finished: false,
+        };
+        // On failure, the crypto object is deleted when `stream` is dropped
+        if encrypt {
+            stream.se.run_command(
+                &CipherEncryptInit {
+                    key_id,
+                    cipher_id,
+                    initialization_vector,
+                },
+                &mut [0; 2],
+            )?;
+        } else {
+            stream.se.run_command(
+                &CipherDecryptInit {
+                    key_id,
+                    cipher_id,
+                    initialization_vector,
+                },
+                &mut [0; 2],
+            )?;
+        }
+        Ok(stream)
+    }
+
+    /// Number of bytes to keep on the host out of `available`
+    fn keep(&self, available: usize) -> usize {
+        match available % self.block_size {
+            0 if self.hold_back_block => self.block_size.min(available),
+            rest => rest,
+        }
+    }
+
+    fn update(&mut self, mut input: &[u8], output: &mut [u8]) -> Result<usize, Error> {
+        self.total_input += input.len();
+        let available = self.pending_len + input.len();
+        let mut send = available - self.keep(available);
+        let mut written = 0;
+        if send != 0 && self.pending_len != 0 {
+            // Complete the pending block and send it on its own
+            let (completion, remaining) = input.split_at(self.block_size - self.pending_len);
+            self.pending[self.pending_len..self.block_size].copy_from_slice(completion);
+            input = remaining;
+            send -= self.block_size;
+            self.pending_len = 0;
+            let block = &self.pending[..self.block_size];
+            written += self
+                .se
+                .cipher_update_chunked(self.cipher_id, block, output)?;
+        }
+        let (sent, remaining) = input.split_at(send);
+        written += self
+            .se
+            .cipher_update_chunked(self.cipher_id, sent, &mut output[written..])?;
+        self.total_output += written;
+        self.pending[self.pending_len..][..remaining.len()].copy_from_slice(remaining);
+        self.pending_len += remaining.len();
+        Ok(written)
+    }
+
+    fn finish(&mut self, output: &mut [u8]) -> Result<StreamEnd, Error> {
+        self.finished = true;
+        let response_buf = &mut [0; MAX_BLOCK_SIZE * 2 + 8];
+        let response = self.se.run_command(
+            &CipherFinal {
+                cipher_id: self.cipher_id,
+                data: &self.pending[..self.pending_len],
+            },
+            response_buf,
+        );
+        self.pending.fill(0);
+        let result = response.and_then(|response| self.write_output(response.data, output));
+        self.se
+            .run_command(&DeleteCryptoObj { id: self.cipher_id }, &mut [0; 2])?;
+        Ok(StreamEnd {
+            written: result?,
+            total_input: self.total_input,
+            total_output: self.total_output,
+        })
+    }
+
+    fn write_output(&mut self, data: &[u8], output: &mut [u8]) -> Result<usize, Error> {
+        let Some(out) = output.get_mut(..data.len()) else {
+            error!("Output buffer too small");
+            return Err(Error::Line(line!()));
+        };
+        out.copy_from_slice(data);
+        self.total_output += data.len();
+        Ok(data.len())
+    }
+}
+
+impl<T: Se05xTransport> Drop for CipherStream<'_, T> {
+    fn drop(&mut self) {
+        self.pending.fill(0);
+        if self.finished {
+            return;
+        }
+        if let Err(_err) = self
+            .se
+            .run_command(&DeleteCryptoObj { id: self.cipher_id }, &mut [0; 2])
+        {
+            warn!("Failed to delete crypto object on drop: {_err:?}");
+        }
+    }
+}
+
+macro_rules! cipher_stream {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        pub struct $name<'se, T: Se05xTransport>(CipherStream<'se, T>);
+
+        impl<T: Se05xTransport> $name<'_, T> {
+            /// Process `input`, writing the output to `output`
+            ///
+            /// Returns the number of bytes written. `output` must hold at least the length of `input` and one block.
+            pub fn update(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, Error> {
+                self.0.update(input, output)
+            }
+
+            /// Process the remaining input with [`CipherFinal`] and delete the crypto object
+            ///
+            /// `output` must hold at least two blocks.
+            pub fn finish(mut self, output: &mut [u8]) -> Result<StreamEnd, Error> {
+                self.0.finish(output)
+            }
+
+            /// Number of bytes passed to [`update`](Self::update) so far
+            pub fn total_input(&self) -> usize {
+                self.0.total_input
+            }
+
+            /// Number of bytes written to the output so far
+            pub fn total_output(&self) -> usize {
+                self.0.total_output
+            }
+        }
+    };
+}
+
+cipher_stream!(
+    /// Streaming encryption, created with [`Se05X::encrypt_stream`]
+    EncryptStream
+);
+cipher_stream!(
+    /// Streaming decryption, created with [`Se05X::decrypt_stream`]
+    DecryptStream
+);
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Start encrypting with `key_id`, using the crypto object `cipher_id` created with `mode`
+    pub fn encrypt_stream(
+        &mut self,
+        key_id: ObjectId,
+        cipher_id: CryptoObjectId,
+        mode: CipherMode,
+        initialization_vector: Option<&[u8]>,
+    ) -> Result<EncryptStream<'_, T>, Error> {
+        CipherStream::new(self, key_id, cipher_id, mode, initialization_vector, true)
+            .map(EncryptStream)
+    }
+
+    /// Start decrypting with `key_id`, using the crypto object `cipher_id` created with `mode`
+    pub fn decrypt_stream(
+        &mut self,
+        key_id: ObjectId,
+        cipher_id: CryptoObjectId,
+        mode: CipherMode,
+        initialization_vector: Option<&[u8]>,
+    ) -> Result<DecryptStream<'_, T>, Error> {
+        CipherStream::new(self, key_id, cipher_id, mode, initialization_vector, false)
+            .map(DecryptStream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use hex_literal::hex;
+
+    const CREATE_CTR: [u8; 15] = hex!("80 01 10 00 0A 41 02 0001 42 01 02 43 01 F0");
+    const CREATE_CBC_PKCS5: [u8; 15] = hex!("80 01 10 00 0A 41 02 0001 42 01 02 43 01 18");
+    const CREATE_CBC_NOPAD: [u8; 15] = hex!("80 01 10 00 0A 41 02 0001 42 01 02 43 01 0D");
+    const DECRYPT_INIT: [u8; 15] = hex!("80 03 0E 43 0A 41 04 01020304 42 02 0001");
+    const DELETE: [u8; 9] = hex!("80 04 10 28 04 41 02 0001");
+    const SUCCESS: [u8; 2] = hex!("90 00");
+
+    /// Expect a [`CipherUpdate`](crate::se05x::commands::CipherUpdate) (`p2` is `0C`) or a [`CipherFinal`] (`p2` is `0D`) of `data`, answered with the inverted bits of `data`
+    fn expect_cipher(script: &mut ScriptedTransport, p2: u8, data: &[u8]) {
+        assert!(data.len() < 0x80);
+        let inverted: Vec<u8> = data.iter().map(|b| !b).collect();
+        script.expect(
+            &[
+                &[0x80, 0x03, 0x0E, p2, 0x00, 0x00, data.len() as u8 + 6][..],
+                &hex!("42 02 0001"),
+                &[0x43, data.len() as u8],
+                data,
+                &hex!("00 00"),
+            ]
+            .concat(),
+            &[
+                &[0x41, 0x82, 0x00, data.len() as u8][..],
+                &inverted,
+                &SUCCESS,
+            ]
+            .concat(),
+        );
+    }
+
+    #[test]
+    fn encrypt_stream() {
+        let input: Vec<u8> = (0..1100).map(|i| i as u8).collect();
+        let streamed = [&input[..], &input[..3]].concat();
+        let (blocks, last) = streamed.split_at(streamed.len() / 16 * 16);
+        let mut script = ScriptedTransport::new();
+        script.expect(&CREATE_CTR, &SUCCESS).expect(
+            &[
+                &hex!("80 03 0E 42 1C 41 04 01020304 42 02 0001 44 10")[..],
+                &[0; 16],
+            ]
+            .concat(),
+            &SUCCESS,
+        );
+        // Each update of 7 bytes completes at most one block
+        for block in blocks.chunks(16) {
+            expect_cipher(&mut script, 0x0C, block);
+        }
+        expect_cipher(&mut script, 0x0D, last);
+        script.expect(&DELETE, &SUCCESS);
+        let mut se05x = script.enable();
+        let mut output = vec![0; 1200];
+        let mut stream = se05x
+            .encrypt_stream(
+                ObjectId([1, 2, 3, 4]),
+                CryptoObjectId([0, 1]),
+                CipherMode::AesCtr,
+                Some(&[0; 16]),
+            )
+            .unwrap();
+        let mut written = 0;
+        for chunk in input.chunks(7).chain(input[..3].chunks(3)) {
+            written += stream.update(chunk, &mut output[written..]).unwrap();
+        }
+        assert_eq!(stream.total_input(), 1103);
+        let end = stream.finish(&mut output[written..]).unwrap();
+        assert_eq!(end.total_input, 1103);
+        assert_eq!(end.total_output, 1103);
+        assert_eq!(written + end.written, 1103);
+        assert!(output[..1100].iter().zip(&input).all(|(o, i)| *o == !i));
+        assert!(se05x.into_transport().into_inner().is_done());
+    }
+
+    #[test]
+    fn decrypt_stream_holds_back_padding() {
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(&CREATE_CBC_PKCS5, &SUCCESS)
+            .expect(&DECRYPT_INIT, &SUCCESS);
+        expect_cipher(&mut script, 0x0C, &[0; 16]);
+        expect_cipher(&mut script, 0x0D, &[0; 16]);
+        script
+            .expect(&DELETE, &SUCCESS)
+            .expect(&CREATE_CBC_NOPAD, &SUCCESS)
+            .expect(&DECRYPT_INIT, &SUCCESS)
+            .expect(&DELETE, &SUCCESS);
+        let mut se05x = script.enable();
+        let mut output = [0; 64];
+        let mut stream = se05x
+            .decrypt_stream(
+                ObjectId([1, 2, 3, 4]),
+                CryptoObjectId([0, 1]),
+                CipherMode::AesCbcPkcs5,
+                None,
+            )
+            .unwrap();
+        assert_eq!(stream.update(&[0; 16], &mut output).unwrap(), 0);
+        assert_eq!(stream.update(&[0; 16], &mut output).unwrap(), 16);
+        assert_eq!(stream.finish(&mut output).unwrap().written, 16);
+
+        // Dropping an unfinished stream deletes the crypto object
+        let stream = se05x
+            .decrypt_stream(
+                ObjectId([1, 2, 3, 4]),
+                CryptoObjectId([0, 1]),
+                CipherMode::AesCbcNopad,
+                None,
+            )
+            .unwrap();
+        drop(stream);
+        assert!(se05x.into_transport().into_inner().is_done());
+    }
+}
@@ -8,8 +8,22 @@ use super::*;
 
 // ************* CreateSession ************* //
 
+/// Open a session authenticated with the object `object_id`, returning the ID to send in the following commands of the session.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+///
+/// The response is parsed into [`CreateSessionResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `session_id` | `SessionId` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "CreateSession"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CreateSession {
     /// Serialized to TLV tag [`TAG_1`]()
     pub object_id: ObjectId,
@@ -50,6 +64,7 @@ impl<W: Writer> DataStream<W> for CreateSession {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CreateSessionResponse {
     /// Parsed from TLV tag [`TAG_1`]()
     pub session_id: SessionId,
@@ -59,7 +74,7 @@ impl<'data> Se05XResponse<'data> for CreateSessionResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (session_id, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { session_id })
     }
 }
@@ -70,12 +85,28 @@ impl<W: Writer> Se05XCommand<W> for CreateSession {
 
 // ************* ExchangeSessionData ************* //
 
+/// Set the policy of the current session. `c_mac` is the MAC of the command when the session uses secure messaging.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `session_policy` | `SessionPolicy` | no |
+/// | remaining data | `c_mac` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`ExchangeSessionDataResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | remaining data | `r_mac` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ExchangeSessionData"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExchangeSessionData<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub session_policy: SessionPolicy,
     /// Serialized to remaining data
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub c_mac: &'data [u8],
 }
 
@@ -116,8 +147,10 @@ impl<W: Writer> DataStream<W> for ExchangeSessionData<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExchangeSessionDataResponse<'data> {
     /// Parsed from remaining data
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub r_mac: &'data [u8],
 }
 
@@ -130,14 +163,40 @@ impl<'data> Se05XResponse<'data> for ExchangeSessionDataResponse<'data> {
     }
 }
 
+/// Owned version of [`ExchangeSessionDataResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExchangeSessionDataResponseOwned<const N: usize> {
+    /// Parsed from remaining data
+    pub r_mac: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl ExchangeSessionDataResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<ExchangeSessionDataResponseOwned<N>, Error> {
+        Ok(ExchangeSessionDataResponseOwned {
+            r_mac: owned_bytes(self.r_mac)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for ExchangeSessionData<'_> {
     type Response<'rdata> = ExchangeSessionDataResponse<'rdata>;
 }
 
 // ************* RefreshSession ************* //
 
+/// Refresh the current session, replacing its policy by `policy`. Only possible if the session policy allows it.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_POLICY` | `policy` | `SessionPolicy` | yes |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "RefreshSession"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RefreshSession {
     /// Serialized to TLV tag [`TAG_POLICY`]()
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = policy_opt))))]
@@ -179,12 +238,13 @@ impl<W: Writer> DataStream<W> for RefreshSession {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RefreshSessionResponse {}
 
 impl<'data> Se05XResponse<'data> for RefreshSessionResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self {})
     }
 }
@@ -195,8 +255,12 @@ impl<W: Writer> Se05XCommand<W> for RefreshSession {
 
 // ************* CloseSession ************* //
 
+/// Close the current session.
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "CloseSession"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CloseSession {}
 
 impl DataSource for CloseSession {
@@ -220,12 +284,13 @@ impl<W: Writer> DataStream<W> for CloseSession {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CloseSessionResponse {}
 
 impl<'data> Se05XResponse<'data> for CloseSessionResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self {})
     }
 }
@@ -236,10 +301,19 @@ impl<W: Writer> Se05XCommand<W> for CloseSession {
 
 // ************* VerifySessionUserId ************* //
 
+/// Authenticate the current session opened with a user ID object by sending its value.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `user_id` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "VerifySessionUserID"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VerifySessionUserId<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub user_id: &'data [u8],
 }
 
@@ -278,12 +352,13 @@ impl<W: Writer> DataStream<W> for VerifySessionUserId<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VerifySessionUserIdResponse {}
 
 impl<'data> Se05XResponse<'data> for VerifySessionUserIdResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self {})
     }
 }
@@ -294,10 +369,25 @@ impl<W: Writer> Se05XCommand<W> for VerifySessionUserId<'_> {
 
 // ************* ScpInitializeUpdate ************* //
 
+/// Open an SCP03 session by sending the challenge of the host. The response holds the challenge and the cryptogram of the se05x.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | remaining data | `host_challenge` | `[u8; 8]` | no |
+///
+/// The response is parsed into [`ScpInitializeUpdateResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | remaining data | `se05x_challenge` | `Se05xChallenge` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "SCPInitializeUpdate"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScpInitializeUpdate {
     /// Serialized to remaining data
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
     pub host_challenge: [u8; 8],
 }
 
@@ -336,6 +426,7 @@ impl<W: Writer> DataStream<W> for ScpInitializeUpdate {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScpInitializeUpdateResponse {
     /// Parsed from remaining data
     pub se05x_challenge: Se05xChallenge,
@@ -356,12 +447,27 @@ impl<W: Writer> Se05XCommand<W> for ScpInitializeUpdate {
 
 // ************* ScpExternalAuthenticate ************* //
 
+/// Authenticate the host to the SCP03 session opened with `ScpInitializeUpdate`.
+///
+/// The secure messaging of the following commands of the session is set by `security_level`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | P1 | `security_level` | `SecurityLevel` | no |
+/// | remaining data | `host_cryptogram` | `[u8; 8]` | no |
+/// | remaining data | `mac` | `[u8; 8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "SCPExternalAuthenticate"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScpExternalAuthenticate {
+    pub security_level: SecurityLevel,
     /// Serialized to remaining data
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
     pub host_cryptogram: [u8; 8],
     /// Serialized to remaining data
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
     pub mac: [u8; 8],
 }
 
@@ -370,14 +476,10 @@ impl DataSource for ScpExternalAuthenticate {
         let host_cryptogram = &self.host_cryptogram;
         let mac = &self.mac;
         let __data: &[&dyn DataSource] = &[host_cryptogram, mac];
-        let command = CommandBuilder::new(
-            SM_CLA,
-            INS_EXTERNAL_AUTHENTICATE,
-            P1_DEFAULT,
-            P2_DEFAULT,
-            __data,
-            0,
-        );
+        let p1: u8 = self.security_level.into();
+
+        let command =
+            CommandBuilder::new(SM_CLA, INS_EXTERNAL_AUTHENTICATE, p1, P2_DEFAULT, __data, 0);
         command.len()
     }
     fn is_empty(&self) -> bool {
@@ -390,24 +492,21 @@ impl<W: Writer> DataStream<W> for ScpExternalAuthenticate {
         let host_cryptogram = &self.host_cryptogram;
         let mac = &self.mac;
         let __data: &[&dyn DataStream<W>] = &[host_cryptogram, mac];
-        let command = CommandBuilder::new(
-            SM_CLA,
-            INS_EXTERNAL_AUTHENTICATE,
-            P1_DEFAULT,
-            P2_DEFAULT,
-            __data,
-            0,
-        );
+        let p1: u8 = self.security_level.into();
+
+        let command =
+            CommandBuilder::new(SM_CLA, INS_EXTERNAL_AUTHENTICATE, p1, P2_DEFAULT, __data, 0);
         command.to_writer(writer)
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScpExternalAuthenticateResponse {}
 
 impl<'data> Se05XResponse<'data> for ScpExternalAuthenticateResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self {})
     }
 }
@@ -418,20 +517,40 @@ impl<W: Writer> Se05XCommand<W> for ScpExternalAuthenticate {
 
 // ************* SetLockState ************* //
 
+/// Lock or unlock the applet. Unlocking a transport-locked applet requires the `unlock_response` to the challenge from `GetUnlockChallenge`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `lock_indicator` | `TransientIndicator` | no |
+/// | `TAG_2` | `lock_state` | `LockState` | no |
+/// | `TAG_3` | `unlock_response` | `&'data [u8; 16]` | yes |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "SetLockState"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
-pub struct SetLockState {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetLockState<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub lock_indicator: TransientIndicator,
     /// Serialized to TLV tag [`TAG_2`]()
     pub lock_state: LockState,
+    /// Response to the challenge from `GetUnlockChallenge`, required to unlock a transport-locked applet
+    ///
+    /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_byte_array_ref::option", borrow)
+    )]
+    #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = unlock_response_opt))))]
+    pub unlock_response: Option<&'data [u8; 16]>,
 }
 
-impl DataSource for SetLockState {
+impl DataSource for SetLockState<'_> {
     fn len(&self) -> usize {
         let lock_indicator = &Tlv::new(TAG_1, self.lock_indicator);
         let lock_state = &Tlv::new(TAG_2, self.lock_state);
-        let __data: &[&dyn DataSource] = &[lock_indicator, lock_state];
+        let unlock_response = &self.unlock_response.map(|data| Tlv::new(TAG_3, data));
+        let __data: &[&dyn DataSource] = &[lock_indicator, lock_state, unlock_response];
         let command = CommandBuilder::new(NO_SM_CLA, INS_MGMT, P1_DEFAULT, P2_TRANSPORT, __data, 0);
         command.len()
     }
@@ -440,24 +559,170 @@ impl DataSource for SetLockState {
         false
     }
 }
-impl<W: Writer> DataStream<W> for SetLockState {
+impl<W: Writer> DataStream<W> for SetLockState<'_> {
     fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
         let lock_indicator = &Tlv::new(TAG_1, self.lock_indicator);
         let lock_state = &Tlv::new(TAG_2, self.lock_state);
-        let __data: &[&dyn DataStream<W>] = &[lock_indicator, lock_state];
+        let unlock_response = &self.unlock_response.map(|data| Tlv::new(TAG_3, data));
+        let __data: &[&dyn DataStream<W>] = &[lock_indicator, lock_state, unlock_response];
         let command = CommandBuilder::new(NO_SM_CLA, INS_MGMT, P1_DEFAULT, P2_TRANSPORT, __data, 0);
         command.to_writer(writer)
     }
 }
 
-impl<W: Writer> Se05XCommand<W> for SetLockState {
+impl<W: Writer> Se05XCommand<W> for SetLockState<'_> {
+    type Response<'rdata> = ();
+}
+
+// ************* GetUnlockChallenge ************* //
+
+/// Get the challenge to answer to unlock a transport-locked applet with `SetLockState`.
+///
+/// The response is parsed into [`GetUnlockChallengeResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `challenge` | `&'data [u8; 16]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "SetLockState"
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GetUnlockChallenge {}
+
+impl DataSource for GetUnlockChallenge {
+    fn len(&self) -> usize {
+        let __data: &[&dyn DataSource] = &[];
+        let command = CommandBuilder::new(
+            NO_SM_CLA,
+            INS_MGMT,
+            P1_DEFAULT,
+            P2_UNLOCK_CHALLENGE,
+            __data,
+            ExpectedLen::Max,
+        );
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+impl<W: Writer> DataStream<W> for GetUnlockChallenge {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let __data: &[&dyn DataStream<W>] = &[];
+        let command = CommandBuilder::new(
+            NO_SM_CLA,
+            INS_MGMT,
+            P1_DEFAULT,
+            P2_UNLOCK_CHALLENGE,
+            __data,
+            ExpectedLen::Max,
+        );
+        command.to_writer(writer)
+    }
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GetUnlockChallengeResponse<'data> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_byte_array_ref", borrow))]
+    pub challenge: &'data [u8; 16],
+}
+
+impl<'data> Se05XResponse<'data> for GetUnlockChallengeResponse<'data> {
+    #[inline(never)]
+    fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
+        let (challenge, rem) = take_do_until(TAG_1, rem)?;
+        check_trailing_data(rem)?;
+        Ok(Self { challenge })
+    }
+}
+
+/// Owned version of [`GetUnlockChallengeResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GetUnlockChallengeResponseOwned {
+    /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    pub challenge: [u8; 16],
+}
+
+#[cfg(feature = "owned")]
+impl GetUnlockChallengeResponse<'_> {
+    pub fn to_owned(&self) -> Result<GetUnlockChallengeResponseOwned, Error> {
+        Ok(GetUnlockChallengeResponseOwned {
+            challenge: *self.challenge,
+        })
+    }
+}
+
+impl<W: Writer> Se05XCommand<W> for GetUnlockChallenge {
+    type Response<'rdata> = GetUnlockChallengeResponse<'rdata>;
+}
+
+// ************* SetAppletFeatures ************* //
+
+/// Permanently disable the applet features missing from `features`. Disabled features cannot be enabled again.
+///
+/// The command must be sent in a session authenticated with [`ObjectId::FEATURE`].
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `features` | `AppletConfig` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "SetAppletFeatures"
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetAppletFeatures {
+    /// Serialized to TLV tag [`TAG_1`]()
+    pub features: AppletConfig,
+}
+
+impl DataSource for SetAppletFeatures {
+    fn len(&self) -> usize {
+        let features = &Tlv::new(TAG_1, self.features);
+        let __data: &[&dyn DataSource] = &[features];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_MGMT, P1_DEFAULT, P2_VARIANT, __data, 0);
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+impl<W: Writer> DataStream<W> for SetAppletFeatures {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let features = &Tlv::new(TAG_1, self.features);
+        let __data: &[&dyn DataStream<W>] = &[features];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_MGMT, P1_DEFAULT, P2_VARIANT, __data, 0);
+        command.to_writer(writer)
+    }
+}
+
+impl<W: Writer> Se05XCommand<W> for SetAppletFeatures {
     type Response<'rdata> = ();
 }
 
 // ************* WriteEcKey ************* //
 
+/// Create or update an EC key object. A new key requires a `curve`. Without key values, the key pair is generated in the se05x.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_POLICY` | `policy` | `PolicySet<'data>` | yes |
+/// | `TAG_MAX_ATTEMPTS` | `max_attempts` | `Be<u16>` | yes |
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `curve` | `EcCurve` | yes |
+/// | `TAG_3` | `private_key` | `&'data [u8]` | yes |
+/// | `TAG_4` | `public_key` | `&'data [u8]` | yes |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "WriteECKey"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct WriteEcKey<'data> {
     #[cfg_attr(feature = "builder", builder(default))]
     pub transient: bool,
@@ -477,9 +742,11 @@ pub struct WriteEcKey<'data> {
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = curve_opt))))]
     pub curve: Option<EcCurve>,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = private_key_opt))))]
     pub private_key: Option<&'data [u8]>,
     /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = public_key_opt))))]
     pub public_key: Option<&'data [u8]>,
 }
@@ -559,12 +826,134 @@ impl<W: Writer> DataStream<W> for WriteEcKey<'_> {
 
 impl<W: Writer> Se05XCommand<W> for WriteEcKey<'_> {
     type Response<'rdata> = ();
+    fn validate(&self) -> Result<(), Error> {
+        WriteEcKey::validate(self)
+    }
+}
+
+// ************* GenEcKey ************* //
+
+/// Generate an EC key pair in the se05x.
+///
+/// Unlike `WriteEcKey`, no key value can be given, so a key cannot be imported by mistake. A new key requires a `curve`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_POLICY` | `policy` | `PolicySet<'data>` | yes |
+/// | `TAG_MAX_ATTEMPTS` | `max_attempts` | `Be<u16>` | yes |
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `curve` | `EcCurve` | yes |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "WriteECKey"
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GenEcKey<'data> {
+    #[cfg_attr(feature = "builder", builder(default))]
+    pub transient: bool,
+    #[cfg_attr(feature = "builder", builder(default))]
+    pub is_auth: bool,
+    /// Serialized to TLV tag [`TAG_POLICY`]()
+    #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = policy_opt))))]
+    pub policy: Option<PolicySet<'data>>,
+    /// Serialized to TLV tag [`TAG_MAX_ATTEMPTS`]()
+    #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = max_attempts_opt))))]
+    pub max_attempts: Option<Be<u16>>,
+    /// Serialized to TLV tag [`TAG_1`]()
+    pub object_id: ObjectId,
+    /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = curve_opt))))]
+    pub curve: Option<EcCurve>,
+}
+
+impl DataSource for GenEcKey<'_> {
+    fn len(&self) -> usize {
+        let policy = &self.policy.map(|data| Tlv::new(TAG_POLICY, data));
+        let max_attempts = &self
+            .max_attempts
+            .map(|data| Tlv::new(TAG_MAX_ATTEMPTS, data));
+        let object_id = &Tlv::new(TAG_1, self.object_id);
+        let curve = &self.curve.map(|data| Tlv::new(TAG_2, data));
+        let __data: &[&dyn DataSource] = &[policy, max_attempts, object_id, curve];
+        let ins = if self.transient {
+            INS_WRITE | INS_TRANSIENT
+        } else {
+            INS_WRITE
+        };
+        let ins = if self.is_auth {
+            ins | INS_AUTH_OBJECT
+        } else {
+            ins
+        };
+
+        let command =
+            CommandBuilder::new(NO_SM_CLA, ins, P1_EC | P1_KEY_PAIR, P2_DEFAULT, __data, 0);
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+impl<W: Writer> DataStream<W> for GenEcKey<'_> {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let policy = &self.policy.map(|data| Tlv::new(TAG_POLICY, data));
+        let max_attempts = &self
+            .max_attempts
+            .map(|data| Tlv::new(TAG_MAX_ATTEMPTS, data));
+        let object_id = &Tlv::new(TAG_1, self.object_id);
+        let curve = &self.curve.map(|data| Tlv::new(TAG_2, data));
+        let __data: &[&dyn DataStream<W>] = &[policy, max_attempts, object_id, curve];
+        let ins = if self.transient {
+            INS_WRITE | INS_TRANSIENT
+        } else {
+            INS_WRITE
+        };
+        let ins = if self.is_auth {
+            ins | INS_AUTH_OBJECT
+        } else {
+            ins
+        };
+
+        let command =
+            CommandBuilder::new(NO_SM_CLA, ins, P1_EC | P1_KEY_PAIR, P2_DEFAULT, __data, 0);
+        command.to_writer(writer)
+    }
+}
+
+impl<W: Writer> Se05XCommand<W> for GenEcKey<'_> {
+    type Response<'rdata> = ();
+    fn validate(&self) -> Result<(), Error> {
+        GenEcKey::validate(self)
+    }
 }
 
 // ************* WriteRsaKey ************* //
 
+/// Create or update an RSA key object, component by component. A new key requires a `key_size`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_POLICY` | `policy` | `PolicySet<'data>` | yes |
+/// | `TAG_MAX_ATTEMPTS` | `max_attempts` | `Be<u16>` | yes |
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `key_size` | `Be<u16>` | yes |
+/// | `TAG_3` | `p` | `&'data [u8]` | yes |
+/// | `TAG_4` | `q` | `&'data [u8]` | yes |
+/// | `TAG_5` | `dp` | `&'data [u8]` | yes |
+/// | `TAG_6` | `dq` | `&'data [u8]` | yes |
+/// | `TAG_7` | `inv_q` | `&'data [u8]` | yes |
+/// | `TAG_8` | `e` | `&'data [u8]` | yes |
+/// | `TAG_9` | `d` | `&'data [u8]` | yes |
+/// | `TAG_10` | `n` | `&'data [u8]` | yes |
+///
+/// Requires the applet features [`RSA_ALL`](AppletConfig::RSA_ALL), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "WriteRSAKey"
+#[cfg(feature = "rsa")]
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct WriteRsaKey<'data> {
     #[cfg_attr(feature = "builder", builder(default))]
     pub transient: bool,
@@ -586,31 +975,40 @@ pub struct WriteRsaKey<'data> {
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = key_size_opt))))]
     pub key_size: Option<Be<u16>>,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = p_opt))))]
     pub p: Option<&'data [u8]>,
     /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = q_opt))))]
     pub q: Option<&'data [u8]>,
     /// Serialized to TLV tag [`TAG_5`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = dp_opt))))]
     pub dp: Option<&'data [u8]>,
     /// Serialized to TLV tag [`TAG_6`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = dq_opt))))]
     pub dq: Option<&'data [u8]>,
     /// Serialized to TLV tag [`TAG_7`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = inv_q_opt))))]
     pub inv_q: Option<&'data [u8]>,
     /// Serialized to TLV tag [`TAG_8`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = e_opt))))]
     pub e: Option<&'data [u8]>,
     /// Serialized to TLV tag [`TAG_9`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = d_opt))))]
     pub d: Option<&'data [u8]>,
     /// Serialized to TLV tag [`TAG_10`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = n_opt))))]
     pub n: Option<&'data [u8]>,
 }
 
+#[cfg(feature = "rsa")]
 impl DataSource for WriteRsaKey<'_> {
     fn len(&self) -> usize {
         let policy = &self.policy.map(|data| Tlv::new(TAG_POLICY, data));
@@ -665,6 +1063,7 @@ impl DataSource for WriteRsaKey<'_> {
         false
     }
 }
+#[cfg(feature = "rsa")]
 impl<W: Writer> DataStream<W> for WriteRsaKey<'_> {
     fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
         let policy = &self.policy.map(|data| Tlv::new(TAG_POLICY, data));
@@ -716,14 +1115,32 @@ impl<W: Writer> DataStream<W> for WriteRsaKey<'_> {
     }
 }
 
+#[cfg(feature = "rsa")]
 impl<W: Writer> Se05XCommand<W> for WriteRsaKey<'_> {
     type Response<'rdata> = ();
+    fn validate(&self) -> Result<(), Error> {
+        WriteRsaKey::validate(self)
+    }
 }
 
 // ************* GenRsaKey ************* //
 
+/// Generate an RSA key pair of `key_size` bits in the se05x, in raw format.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_POLICY` | `policy` | `PolicySet<'data>` | yes |
+/// | `TAG_MAX_ATTEMPTS` | `max_attempts` | `Be<u16>` | yes |
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `key_size` | `Be<u16>` | yes |
+///
+/// Requires the applet features [`RSA_ALL`](AppletConfig::RSA_ALL), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "WriteRSAKey"
+#[cfg(feature = "rsa")]
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct GenRsaKey<'data> {
     #[cfg_attr(feature = "builder", builder(default))]
     pub transient: bool,
@@ -742,6 +1159,7 @@ pub struct GenRsaKey<'data> {
     pub key_size: Option<Be<u16>>,
 }
 
+#[cfg(feature = "rsa")]
 impl DataSource for GenRsaKey<'_> {
     fn len(&self) -> usize {
         let policy = &self.policy.map(|data| Tlv::new(TAG_POLICY, data));
@@ -770,6 +1188,7 @@ impl DataSource for GenRsaKey<'_> {
         false
     }
 }
+#[cfg(feature = "rsa")]
 impl<W: Writer> DataStream<W> for GenRsaKey<'_> {
     fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
         let policy = &self.policy.map(|data| Tlv::new(TAG_POLICY, data));
@@ -795,14 +1214,28 @@ impl<W: Writer> DataStream<W> for GenRsaKey<'_> {
     }
 }
 
+#[cfg(feature = "rsa")]
 impl<W: Writer> Se05XCommand<W> for GenRsaKey<'_> {
     type Response<'rdata> = ();
 }
 
 // ************* WriteSymmKey ************* //
 
+/// Create or update an AES, DES or HMAC key object. With `kek_id`, `value` is wrapped with that key (RFC 3394).
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | P1 | `key_type` | `SymmKeyType` | no |
+/// | `TAG_POLICY` | `policy` | `PolicySet<'data>` | yes |
+/// | `TAG_MAX_ATTEMPTS` | `max_attempts` | `Be<u16>` | yes |
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `kek_id` | `ObjectId` | yes |
+/// | `TAG_3` | `value` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "WriteSymmKey"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct WriteSymmKey<'data> {
     #[cfg_attr(feature = "builder", builder(default))]
     pub transient: bool,
@@ -821,6 +1254,7 @@ pub struct WriteSymmKey<'data> {
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = kek_id_opt))))]
     pub kek_id: Option<ObjectId>,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub value: &'data [u8],
 }
 
@@ -883,12 +1317,27 @@ impl<W: Writer> DataStream<W> for WriteSymmKey<'_> {
 
 impl<W: Writer> Se05XCommand<W> for WriteSymmKey<'_> {
     type Response<'rdata> = ();
+    fn validate(&self) -> Result<(), Error> {
+        WriteSymmKey::validate(self)
+    }
 }
 
 // ************* WriteBinary ************* //
 
+/// Create or update a binary file. A new file requires a `file_length`, `data` is written at `offset`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_POLICY` | `policy` | `PolicySet<'data>` | yes |
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `offset` | `Be<u16>` | yes |
+/// | `TAG_3` | `file_length` | `Be<u16>` | yes |
+/// | `TAG_4` | `data` | `&'data [u8]` | yes |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "WriteBinary"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct WriteBinary<'data> {
     #[cfg_attr(feature = "builder", builder(default))]
     pub transient: bool,
@@ -906,6 +1355,7 @@ pub struct WriteBinary<'data> {
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = file_length_opt))))]
     pub file_length: Option<Be<u16>>,
     /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = data_opt))))]
     pub data: Option<&'data [u8]>,
 }
@@ -953,12 +1403,26 @@ impl<W: Writer> DataStream<W> for WriteBinary<'_> {
 
 impl<W: Writer> Se05XCommand<W> for WriteBinary<'_> {
     type Response<'rdata> = ();
+    fn validate(&self) -> Result<(), Error> {
+        WriteBinary::validate(self)
+    }
 }
 
 // ************* WriteUserId ************* //
 
+/// Create a user ID object, an authentication object holding the value sent by `VerifySessionUserId`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_POLICY` | `policy` | `PolicySet<'data>` | yes |
+/// | `TAG_MAX_ATTEMPTS` | `max_attempts` | `Be<u8>` | yes |
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `data` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "WriteUserID"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct WriteUserId<'data> {
     /// Serialized to TLV tag [`TAG_POLICY`]()
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = policy_opt))))]
@@ -969,6 +1433,7 @@ pub struct WriteUserId<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub object_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
@@ -1023,8 +1488,19 @@ impl<W: Writer> Se05XCommand<W> for WriteUserId<'_> {
 
 // ************* WriteCounter ************* //
 
+/// Create a monotonic counter of `data` bytes, or increment it. With `value`, the counter is set to that value, which cannot be lower than the current one.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_POLICY` | `policy` | `PolicySet<'data>` | yes |
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `data` | `CounterSize` | yes |
+/// | `TAG_3` | `value` | `Be<u64>` | yes |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "WriteCounter"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct WriteCounter<'data> {
     #[cfg_attr(feature = "builder", builder(default))]
     pub transient: bool,
@@ -1086,8 +1562,19 @@ impl<W: Writer> Se05XCommand<W> for WriteCounter<'_> {
 
 // ************* WritePcr ************* //
 
+/// Create a PCR with `initial_value`, or extend it by hashing `extend` into its value. Without value, the PCR is reset.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_POLICY` | `policy` | `PolicySet<'data>` | yes |
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `initial_value` | `&'data [u8]` | yes |
+/// | `TAG_3` | `extend` | `&'data [u8]` | yes |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "WritePCR"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct WritePcr<'data> {
     #[cfg_attr(feature = "builder", builder(default))]
     pub transient: bool,
@@ -1097,9 +1584,11 @@ pub struct WritePcr<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub object_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = initial_value_opt))))]
     pub initial_value: Option<&'data [u8]>,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = extend_opt))))]
     pub extend: Option<&'data [u8]>,
 }
@@ -1149,8 +1638,18 @@ impl<W: Writer> Se05XCommand<W> for WritePcr<'_> {
 
 // ************* ImportObject ************* //
 
+/// Import a transient object exported with `ExportObject`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `rsa_key_component` | `RsaKeyComponent` | yes |
+/// | `TAG_3` | `serialized_object` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ImportObject"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImportObject<'data> {
     #[cfg_attr(feature = "builder", builder(default))]
     pub transient: bool,
@@ -1162,6 +1661,7 @@ pub struct ImportObject<'data> {
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = rsa_key_component_opt))))]
     pub rsa_key_component: Option<RsaKeyComponent>,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub serialized_object: &'data [u8],
 }
 
@@ -1208,8 +1708,25 @@ impl<W: Writer> Se05XCommand<W> for ImportObject<'_> {
 
 // ************* ReadObject ************* //
 
+/// Read the value of an object. Private and secret key values cannot be read, only public keys, binary files, counters and PCRs.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `offset` | `Be<u16>` | yes |
+/// | `TAG_3` | `length` | `Be<u16>` | yes |
+/// | `TAG_4` | `rsa_key_component` | `RsaKeyComponent` | yes |
+///
+/// The response is parsed into [`ReadObjectResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ReadObject"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadObject {
     /// Serialized to TLV tag [`TAG_1`]()
     pub object_id: ObjectId,
@@ -1265,8 +1782,10 @@ impl<W: Writer> DataStream<W> for ReadObject {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadObjectResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
@@ -1274,19 +1793,65 @@ impl<'data> Se05XResponse<'data> for ReadObjectResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (data, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { data })
     }
 }
 
+/// Owned version of [`ReadObjectResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReadObjectResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub data: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl ReadObjectResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<ReadObjectResponseOwned<N>, Error> {
+        Ok(ReadObjectResponseOwned {
+            data: owned_bytes(self.data)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for ReadObject {
     type Response<'rdata> = ReadObjectResponse<'rdata>;
+    fn validate(&self) -> Result<(), Error> {
+        ReadObject::validate(self)
+    }
 }
 
 // ************* ReadAttestObject ************* //
 
+/// Read the value of an object together with its attributes, a timestamp and `freshness_random`, signed with `attestation_object`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `offset` | `Be<u16>` | yes |
+/// | `TAG_3` | `length` | `Be<u16>` | yes |
+/// | `TAG_4` | `rsa_key_component` | `RsaKeyComponent` | yes |
+/// | `TAG_5` | `attestation_object` | `ObjectId` | no |
+/// | `TAG_6` | `attestation_algo` | `AttestationAlgo` | no |
+/// | `TAG_7` | `freshness_random` | `&'data [u8; 16]` | no |
+///
+/// The response is parsed into [`ReadAttestObjectResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | yes |
+/// | `TAG_2` | `attributes` | `ObjectAttributes` | no |
+/// | `TAG_3` | `timestamp` | `&'data [u8; 12]` | no |
+/// | `TAG_4` | `freshness_random` | `&'data [u8; 16]` | no |
+/// | `TAG_5` | `chip_unique_id` | `&'data [u8; 18]` | no |
+/// | `TAG_6` | `signature` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ReadObject"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadAttestObject<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub object_id: ObjectId,
@@ -1306,6 +1871,7 @@ pub struct ReadAttestObject<'data> {
     /// Serialized to TLV tag [`TAG_6`]()
     pub attestation_algo: AttestationAlgo,
     /// Serialized to TLV tag [`TAG_7`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_byte_array_ref", borrow))]
     pub freshness_random: &'data [u8; 16],
 }
 
@@ -1372,20 +1938,26 @@ impl<W: Writer> DataStream<W> for ReadAttestObject<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadAttestObjectResponse<'data> {
     /// Is None when the object is a private key
     ///
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: Option<&'data [u8]>,
     /// Parsed from TLV tag [`TAG_2`]()
     pub attributes: ObjectAttributes,
     /// Parsed from TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_byte_array_ref", borrow))]
     pub timestamp: &'data [u8; 12],
     /// Parsed from TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_byte_array_ref", borrow))]
     pub freshness_random: &'data [u8; 16],
     /// Parsed from TLV tag [`TAG_5`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_byte_array_ref", borrow))]
     pub chip_unique_id: &'data [u8; 18],
     /// Parsed from TLV tag [`TAG_6`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub signature: &'data [u8],
 }
 
@@ -1399,7 +1971,7 @@ impl<'data> Se05XResponse<'data> for ReadAttestObjectResponse<'data> {
         let (freshness_random, rem) = take_do_until(TAG_4, rem)?;
         let (chip_unique_id, rem) = take_do_until(TAG_5, rem)?;
         let (signature, rem) = take_do_until(TAG_6, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self {
             data,
             attributes,
@@ -1411,14 +1983,67 @@ impl<'data> Se05XResponse<'data> for ReadAttestObjectResponse<'data> {
     }
 }
 
+/// Owned version of [`ReadAttestObjectResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReadAttestObjectResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub data: Option<heapless::Vec<u8, N>>,
+    /// Parsed from TLV tag [`TAG_2`]()
+    pub attributes: ObjectAttributes,
+    /// Parsed from TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    pub timestamp: [u8; 12],
+    /// Parsed from TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    pub freshness_random: [u8; 16],
+    /// Parsed from TLV tag [`TAG_5`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    pub chip_unique_id: [u8; 18],
+    /// Parsed from TLV tag [`TAG_6`]()
+    pub signature: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl ReadAttestObjectResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<ReadAttestObjectResponseOwned<N>, Error> {
+        Ok(ReadAttestObjectResponseOwned {
+            data: self.data.map(owned_bytes).transpose()?,
+            attributes: self.attributes.clone(),
+            timestamp: *self.timestamp,
+            freshness_random: *self.freshness_random,
+            chip_unique_id: *self.chip_unique_id,
+            signature: owned_bytes(self.signature)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for ReadAttestObject<'_> {
     type Response<'rdata> = ReadAttestObjectResponse<'rdata>;
 }
 
 // ************* ReadAttributes ************* //
 
+/// Read the attributes of an object: its identifier, type, authentication status, policies and origin.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `offset` | `Be<u16>` | yes |
+/// | `TAG_3` | `length` | `Be<u16>` | yes |
+/// | `TAG_4` | `rsa_key_component` | `&'data [u8]` | yes |
+///
+/// The response is parsed into [`ReadAttributesResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_2` | `attributes` | `ObjectAttributes` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ReadObjectAttributes"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadAttributes<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub object_id: ObjectId,
@@ -1429,6 +2054,7 @@ pub struct ReadAttributes<'data> {
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = length_opt))))]
     pub length: Option<Be<u16>>,
     /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = rsa_key_component_opt))))]
     pub rsa_key_component: Option<&'data [u8]>,
 }
@@ -1462,6 +2088,7 @@ impl<W: Writer> DataStream<W> for ReadAttributes<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadAttributesResponse {
     /// Parsed from TLV tag [`TAG_2`]()
     pub attributes: ObjectAttributes,
@@ -1471,7 +2098,7 @@ impl<'data> Se05XResponse<'data> for ReadAttributesResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (attributes, rem) = take_do_until(TAG_2, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { attributes })
     }
 }
@@ -1482,8 +2109,32 @@ impl<W: Writer> Se05XCommand<W> for ReadAttributes<'_> {
 
 // ************* ReadAttributesAttest ************* //
 
+/// Read the attributes of an object together with a timestamp and `freshness_random`, signed with `attestation_object`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `offset` | `Be<u16>` | yes |
+/// | `TAG_3` | `length` | `Be<u16>` | yes |
+/// | `TAG_4` | `rsa_key_component` | `&'data [u8]` | yes |
+/// | `TAG_5` | `attestation_object` | `ObjectId` | no |
+/// | `TAG_6` | `attestation_algo` | `AttestationAlgo` | no |
+/// | `TAG_7` | `freshness_random` | `&'data [u8; 16]` | yes |
+///
+/// The response is parsed into [`ReadAttributesAttestResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_2` | `attributes` | `ObjectAttributes` | no |
+/// | `TAG_3` | `timestamp` | `&'data [u8; 12]` | no |
+/// | `TAG_4` | `freshness_random` | `&'data [u8; 16]` | no |
+/// | `TAG_5` | `chip_unique_id` | `&'data [u8; 18]` | no |
+/// | `TAG_6` | `signature` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ReadObjectAttributes"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadAttributesAttest<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub object_id: ObjectId,
@@ -1494,6 +2145,7 @@ pub struct ReadAttributesAttest<'data> {
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = length_opt))))]
     pub length: Option<Be<u16>>,
     /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = rsa_key_component_opt))))]
     pub rsa_key_component: Option<&'data [u8]>,
     /// Serialized to TLV tag [`TAG_5`]()
@@ -1501,6 +2153,10 @@ pub struct ReadAttributesAttest<'data> {
     /// Serialized to TLV tag [`TAG_6`]()
     pub attestation_algo: AttestationAlgo,
     /// Serialized to TLV tag [`TAG_7`]()
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "serde_byte_array_ref::option", borrow)
+    )]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = freshness_random_opt))))]
     pub freshness_random: Option<&'data [u8; 16]>,
 }
@@ -1568,16 +2224,21 @@ impl<W: Writer> DataStream<W> for ReadAttributesAttest<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadAttributesAttestResponse<'data> {
     /// Parsed from TLV tag [`TAG_2`]()
     pub attributes: ObjectAttributes,
     /// Parsed from TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_byte_array_ref", borrow))]
     pub timestamp: &'data [u8; 12],
     /// Parsed from TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_byte_array_ref", borrow))]
     pub freshness_random: &'data [u8; 16],
     /// Parsed from TLV tag [`TAG_5`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_byte_array_ref", borrow))]
     pub chip_unique_id: &'data [u8; 18],
     /// Parsed from TLV tag [`TAG_6`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub signature: &'data [u8],
 }
 
@@ -1589,7 +2250,7 @@ impl<'data> Se05XResponse<'data> for ReadAttributesAttestResponse<'data> {
         let (freshness_random, rem) = take_do_until(TAG_4, rem)?;
         let (chip_unique_id, rem) = take_do_until(TAG_5, rem)?;
         let (signature, rem) = take_do_until(TAG_6, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self {
             attributes,
             timestamp,
@@ -1600,14 +2261,62 @@ impl<'data> Se05XResponse<'data> for ReadAttributesAttestResponse<'data> {
     }
 }
 
+/// Owned version of [`ReadAttributesAttestResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReadAttributesAttestResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_2`]()
+    pub attributes: ObjectAttributes,
+    /// Parsed from TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    pub timestamp: [u8; 12],
+    /// Parsed from TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    pub freshness_random: [u8; 16],
+    /// Parsed from TLV tag [`TAG_5`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    pub chip_unique_id: [u8; 18],
+    /// Parsed from TLV tag [`TAG_6`]()
+    pub signature: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl ReadAttributesAttestResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<ReadAttributesAttestResponseOwned<N>, Error> {
+        Ok(ReadAttributesAttestResponseOwned {
+            attributes: self.attributes.clone(),
+            timestamp: *self.timestamp,
+            freshness_random: *self.freshness_random,
+            chip_unique_id: *self.chip_unique_id,
+            signature: owned_bytes(self.signature)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for ReadAttributesAttest<'_> {
     type Response<'rdata> = ReadAttributesAttestResponse<'rdata>;
 }
 
 // ************* ExportObject ************* //
 
+/// Export a transient object, encrypted and authenticated by the se05x, to restore it later with `ImportObject`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+/// | `TAG_2` | `rsa_key_component` | `RsaKeyComponent` | no |
+///
+/// The response is parsed into [`ExportObjectResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ExportObject"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExportObject {
     /// Serialized to TLV tag [`TAG_1`]()
     pub object_id: ObjectId,
@@ -1643,8 +2352,10 @@ impl<W: Writer> DataStream<W> for ExportObject {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExportObjectResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
@@ -1652,19 +2363,52 @@ impl<'data> Se05XResponse<'data> for ExportObjectResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (data, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { data })
     }
 }
 
+/// Owned version of [`ExportObjectResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExportObjectResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub data: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl ExportObjectResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<ExportObjectResponseOwned<N>, Error> {
+        Ok(ExportObjectResponseOwned {
+            data: owned_bytes(self.data)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for ExportObject {
     type Response<'rdata> = ExportObjectResponse<'rdata>;
 }
 
 // ************* ReadType ************* //
 
+/// Read the type of an object and whether it is transient.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+///
+/// The response is parsed into [`ReadTypeResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `ty` | `SecureObjectType` | no |
+/// | `TAG_2` | `transient_indicator` | `TransientIndicator` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ReadType"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadType {
     /// Serialized to TLV tag [`TAG_1`]()
     pub object_id: ObjectId,
@@ -1705,6 +2449,7 @@ impl<W: Writer> DataStream<W> for ReadType {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadTypeResponse {
     /// Parsed from TLV tag [`TAG_1`]()
     pub ty: SecureObjectType,
@@ -1717,7 +2462,7 @@ impl<'data> Se05XResponse<'data> for ReadTypeResponse {
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (ty, rem) = take_do_until(TAG_1, rem)?;
         let (transient_indicator, rem) = take_do_until(TAG_2, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self {
             ty,
             transient_indicator,
@@ -1731,8 +2476,22 @@ impl<W: Writer> Se05XCommand<W> for ReadType {
 
 // ************* ReadSize ************* //
 
+/// Read the size of an object: the length of a key in bytes, or of a binary file.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+///
+/// The response is parsed into [`ReadSizeResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `size` | `Be<u64>` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ReadSize"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadSize {
     /// Serialized to TLV tag [`TAG_1`]()
     pub object_id: ObjectId,
@@ -1773,6 +2532,7 @@ impl<W: Writer> DataStream<W> for ReadSize {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadSizeResponse {
     /// Parsed from TLV tag [`TAG_1`]()
     pub size: Be<u64>,
@@ -1782,7 +2542,7 @@ impl<'data> Se05XResponse<'data> for ReadSizeResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (size, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { size })
     }
 }
@@ -1793,8 +2553,24 @@ impl<W: Writer> Se05XCommand<W> for ReadSize {
 
 // ************* ReadIdList ************* //
 
+/// List the identifiers of the objects matching `filter`, starting at `offset`. `more` is set when the list continues past this response.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `offset` | `Be<u16>` | no |
+/// | `TAG_2` | `filter` | `SecureObjectFilter` | no |
+///
+/// The response is parsed into [`ReadIdListResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `more` | `MoreIndicator` | no |
+/// | `TAG_2` | `ids` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ReadIDList"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadIdList {
     /// Serialized to TLV tag [`TAG_1`]()
     pub offset: Be<u16>,
@@ -1839,10 +2615,12 @@ impl<W: Writer> DataStream<W> for ReadIdList {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadIdListResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
     pub more: MoreIndicator,
     /// Parsed from TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub ids: &'data [u8],
 }
 
@@ -1851,19 +2629,54 @@ impl<'data> Se05XResponse<'data> for ReadIdListResponse<'data> {
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (more, rem) = take_do_until(TAG_1, rem)?;
         let (ids, rem) = take_do_until(TAG_2, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { more, ids })
     }
 }
 
+/// Owned version of [`ReadIdListResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReadIdListResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub more: MoreIndicator,
+    /// Parsed from TLV tag [`TAG_2`]()
+    pub ids: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl ReadIdListResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<ReadIdListResponseOwned<N>, Error> {
+        Ok(ReadIdListResponseOwned {
+            more: self.more,
+            ids: owned_bytes(self.ids)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for ReadIdList {
     type Response<'rdata> = ReadIdListResponse<'rdata>;
 }
 
 // ************* CheckObjectExists ************* //
 
+/// Check whether an object exists.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+///
+/// The response is parsed into [`CheckObjectExistsResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `result` | `Se05XResult` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "CheckObjectExists"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CheckObjectExists {
     /// Serialized to TLV tag [`TAG_1`]()
     pub object_id: ObjectId,
@@ -1904,6 +2717,7 @@ impl<W: Writer> DataStream<W> for CheckObjectExists {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CheckObjectExistsResponse {
     /// Parsed from TLV tag [`TAG_1`]()
     pub result: Se05XResult,
@@ -1913,7 +2727,7 @@ impl<'data> Se05XResponse<'data> for CheckObjectExistsResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (result, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { result })
     }
 }
@@ -1924,8 +2738,16 @@ impl<W: Writer> Se05XCommand<W> for CheckObjectExists {
 
 // ************* DeleteSecureObject ************* //
 
+/// Delete an object and its value.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "DeleteSecureObject"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeleteSecureObject {
     /// Serialized to TLV tag [`TAG_1`]()
     pub object_id: ObjectId,
@@ -1960,8 +2782,16 @@ impl<W: Writer> Se05XCommand<W> for DeleteSecureObject {
 
 // ************* CreateEcCurve ************* //
 
+/// Create a curve object, whose parameters are then set with `SetEcCurveParam`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `curve` | `EcCurve` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "CreateECCurve"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CreateEcCurve {
     /// Serialized to TLV tag [`TAG_1`]()
     pub curve: EcCurve,
@@ -1994,14 +2824,25 @@ impl<W: Writer> Se05XCommand<W> for CreateEcCurve {
 
 // ************* SetEcCurveParam ************* //
 
+/// Set a parameter of a curve object created with `CreateEcCurve`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `curve` | `EcCurve` | no |
+/// | `TAG_2` | `param` | `EcCurveParam` | no |
+/// | `TAG_3` | `value` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "SetECCurveParam"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetEcCurveParam<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub curve: EcCurve,
     /// Serialized to TLV tag [`TAG_2`]()
     pub param: EcCurveParam,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub value: &'data [u8],
 }
 
@@ -2036,8 +2877,22 @@ impl<W: Writer> Se05XCommand<W> for SetEcCurveParam<'_> {
 
 // ************* GetEcCurveId ************* //
 
+/// Get the curve of an EC key object.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `object_id` | `ObjectId` | no |
+///
+/// The response is parsed into [`GetEcCurveIdResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `curve` | `EcCurve` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "GetECCurveID"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetEcCurveId {
     /// Serialized to TLV tag [`TAG_1`]()
     pub object_id: ObjectId,
@@ -2064,6 +2919,7 @@ impl<W: Writer> DataStream<W> for GetEcCurveId {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetEcCurveIdResponse {
     /// Parsed from TLV tag [`TAG_1`]()
     pub curve: EcCurve,
@@ -2073,7 +2929,7 @@ impl<'data> Se05XResponse<'data> for GetEcCurveIdResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (curve, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { curve })
     }
 }
@@ -2084,8 +2940,18 @@ impl<W: Writer> Se05XCommand<W> for GetEcCurveId {
 
 // ************* ReadEcCurveList ************* //
 
+/// List the curves, with one byte per curve telling whether it is set.
+///
+/// The response is parsed into [`ReadEcCurveListResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `ids` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ReadECCurveList"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadEcCurveList {}
 
 impl DataSource for ReadEcCurveList {
@@ -2107,8 +2973,10 @@ impl<W: Writer> DataStream<W> for ReadEcCurveList {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadEcCurveListResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub ids: &'data [u8],
 }
 
@@ -2116,19 +2984,45 @@ impl<'data> Se05XResponse<'data> for ReadEcCurveListResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (ids, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { ids })
     }
 }
 
+/// Owned version of [`ReadEcCurveListResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReadEcCurveListResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub ids: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl ReadEcCurveListResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<ReadEcCurveListResponseOwned<N>, Error> {
+        Ok(ReadEcCurveListResponseOwned {
+            ids: owned_bytes(self.ids)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for ReadEcCurveList {
     type Response<'rdata> = ReadEcCurveListResponse<'rdata>;
 }
 
 // ************* DeleteEcCurve ************* //
 
+/// Delete a curve object. The curve must not be used by any key.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `curve` | `EcCurve` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "DeleteECCurve"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeleteEcCurve {
     /// Serialized to TLV tag [`TAG_1`]()
     pub curve: EcCurve,
@@ -2163,8 +3057,18 @@ impl<W: Writer> Se05XCommand<W> for DeleteEcCurve {
 
 // ************* CreateDigestObject ************* //
 
+/// Create a crypto object to hash data with `DigestInit`, `DigestUpdate` and `DigestFinal`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `id` | `CryptoObjectId` | no |
+/// | `TAG_2` | fixed to `CryptoContext::Digest` | `CryptoContext` | no |
+/// | `TAG_3` | `subtype` | `Digest` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "CreateCryptoObject"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CreateDigestObject {
     /// Serialized to TLV tag [`TAG_1`]()
     pub id: CryptoObjectId,
@@ -2205,8 +3109,18 @@ impl<W: Writer> Se05XCommand<W> for CreateDigestObject {
 
 // ************* CreateCipherObject ************* //
 
+/// Create a crypto object to encrypt or decrypt data with `CipherEncryptInit` or `CipherDecryptInit`, `CipherUpdate` and `CipherFinal`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `id` | `CryptoObjectId` | no |
+/// | `TAG_2` | fixed to `CryptoContext::Cipher` | `CryptoContext` | no |
+/// | `TAG_3` | `subtype` | `CipherMode` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "CreateCryptoObject"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CreateCipherObject {
     /// Serialized to TLV tag [`TAG_1`]()
     pub id: CryptoObjectId,
@@ -2247,8 +3161,18 @@ impl<W: Writer> Se05XCommand<W> for CreateCipherObject {
 
 // ************* CreateSignatureObject ************* //
 
+/// Create a crypto object to compute or validate a MAC with `MacGenerateInit` or `MacValidateInit`, `MacUpdate` and the final commands.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `id` | `CryptoObjectId` | no |
+/// | `TAG_2` | fixed to `CryptoContext::Signature` | `CryptoContext` | no |
+/// | `TAG_3` | `subtype` | `MacAlgo` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "CreateCryptoObject"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CreateSignatureObject {
     /// Serialized to TLV tag [`TAG_1`]()
     pub id: CryptoObjectId,
@@ -2289,8 +3213,18 @@ impl<W: Writer> Se05XCommand<W> for CreateSignatureObject {
 
 // ************* ReadCryptoObjList ************* //
 
+/// List the crypto objects with their context and subtype.
+///
+/// The response is parsed into [`ReadCryptoObjListResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `list` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ReadCryptoObjectList"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadCryptoObjList {}
 
 impl DataSource for ReadCryptoObjList {
@@ -2312,8 +3246,12 @@ impl<W: Writer> DataStream<W> for ReadCryptoObjList {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadCryptoObjListResponse<'data> {
+    /// Parsed by [`entries`](Self::entries)
+    ///
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub list: &'data [u8],
 }
 
@@ -2321,19 +3259,45 @@ impl<'data> Se05XResponse<'data> for ReadCryptoObjListResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (list, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { list })
     }
 }
 
-impl<W: Writer> Se05XCommand<W> for ReadCryptoObjList {
-    type Response<'rdata> = ReadCryptoObjListResponse<'rdata>;
+/// Owned version of [`ReadCryptoObjListResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReadCryptoObjListResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub list: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl ReadCryptoObjListResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<ReadCryptoObjListResponseOwned<N>, Error> {
+        Ok(ReadCryptoObjListResponseOwned {
+            list: owned_bytes(self.list)?,
+        })
+    }
+}
+
+impl<W: Writer> Se05XCommand<W> for ReadCryptoObjList {
+    type Response<'rdata> = ReadCryptoObjListResponse<'rdata>;
 }
 
 // ************* DeleteCryptoObj ************* //
 
+/// Delete a crypto object.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `id` | `CryptoObjectId` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "DeleteCryptoObject"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeleteCryptoObj {
     /// Serialized to TLV tag [`TAG_1`]()
     pub id: CryptoObjectId,
@@ -2380,14 +3344,35 @@ impl<W: Writer> Se05XCommand<W> for DeleteCryptoObj {
 
 // ************* EcdsaSign ************* //
 
+/// Sign pre-hashed data with an EC key pair. The length of `data` must match the digest of `algo`.
+///
+/// The signature is returned DER encoded.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `algo` | `EcDsaSignatureAlgo` | no |
+/// | `TAG_3` | `data` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`EcdsaSignResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `signature` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`ECDSA_ECDH_ECDHE`](AppletConfig::ECDSA_ECDH_ECDHE), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ECDSASign"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EcdsaSign<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
     pub algo: EcDsaSignatureAlgo,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
@@ -2430,8 +3415,10 @@ impl<W: Writer> DataStream<W> for EcdsaSign<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EcdsaSignResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub signature: &'data [u8],
 }
 
@@ -2439,30 +3426,72 @@ impl<'data> Se05XResponse<'data> for EcdsaSignResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (signature, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { signature })
     }
 }
 
+/// Owned version of [`EcdsaSignResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EcdsaSignResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub signature: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl EcdsaSignResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<EcdsaSignResponseOwned<N>, Error> {
+        Ok(EcdsaSignResponseOwned {
+            signature: owned_bytes(self.signature)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for EcdsaSign<'_> {
     type Response<'rdata> = EcdsaSignResponse<'rdata>;
 }
 
 // ************* EddsaSign ************* //
 
+/// Sign a message with an Ed25519 key pair, the message is passed as is and must fit in a single APDU.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `algo` | `EdDsaSignatureAlgo` | no |
+/// | `TAG_3` | `data` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`EddsaSignResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `signature` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`EDDSA`](AppletConfig::EDDSA), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "EdDSASign"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EddsaSign<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
+    /// [`Pure`][EdDsaSignatureAlgo::Pure] unless the data is pre-hashed. It is the default value with the builder API
+    ///
+    /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "builder", builder(default=EdDsaSignatureAlgo::Pure))]
+    pub algo: EdDsaSignatureAlgo,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
 impl DataSource for EddsaSign<'_> {
     fn len(&self) -> usize {
         let key_id = &Tlv::new(TAG_1, self.key_id);
-        let algo = &Tlv::new(TAG_2, EdDsaSignatureAlgo::Pure);
+        let algo = &Tlv::new(TAG_2, self.algo);
         let data = &Tlv::new(TAG_3, self.data);
         let __data: &[&dyn DataSource] = &[key_id, algo, data];
         let command = CommandBuilder::new(
@@ -2483,7 +3512,7 @@ impl DataSource for EddsaSign<'_> {
 impl<W: Writer> DataStream<W> for EddsaSign<'_> {
     fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
         let key_id = &Tlv::new(TAG_1, self.key_id);
-        let algo = &Tlv::new(TAG_2, EdDsaSignatureAlgo::Pure);
+        let algo = &Tlv::new(TAG_2, self.algo);
         let data = &Tlv::new(TAG_3, self.data);
         let __data: &[&dyn DataStream<W>] = &[key_id, algo, data];
         let command = CommandBuilder::new(
@@ -2498,8 +3527,10 @@ impl<W: Writer> DataStream<W> for EddsaSign<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EddsaSignResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub signature: &'data [u8],
 }
 
@@ -2507,25 +3538,68 @@ impl<'data> Se05XResponse<'data> for EddsaSignResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (signature, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { signature })
     }
 }
 
+/// Owned version of [`EddsaSignResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EddsaSignResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub signature: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl EddsaSignResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<EddsaSignResponseOwned<N>, Error> {
+        Ok(EddsaSignResponseOwned {
+            signature: owned_bytes(self.signature)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for EddsaSign<'_> {
     type Response<'rdata> = EddsaSignResponse<'rdata>;
 }
 
 // ************* EcdaaSign ************* //
 
+/// Sign a 32-byte digest with a key on the `TPM_ECC_BN_P256` curve.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | fixed to `EcDaaSignatureAlgo::EcDaa` | `EcDaaSignatureAlgo` | no |
+/// | `TAG_3` | `data` | `[u8; 32]` | no |
+/// | `TAG_4` | `random_data` | `[u8; 32]` | no |
+///
+/// The response is parsed into [`EcdaaSignResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `signature` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`ECDAA`](AppletConfig::ECDAA), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ECDAASign"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EcdaaSign {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
+    /// SHA-256 digest of the message
+    ///
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
     pub data: [u8; 32],
+    /// Random nonce chosen by the host, see [`EcdaaNonce`](super::ecdaa::EcdaaNonce)
+    ///
     /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
     pub random_data: [u8; 32],
 }
 
@@ -2570,8 +3644,12 @@ impl<W: Writer> DataStream<W> for EcdaaSign {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EcdaaSignResponse<'data> {
+    /// Concatenation of the two 32-byte components, see [`EcdaaSignature`](super::ecdaa::EcdaaSignature)
+    ///
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub signature: &'data [u8],
 }
 
@@ -2579,27 +3657,66 @@ impl<'data> Se05XResponse<'data> for EcdaaSignResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (signature, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { signature })
     }
 }
 
+/// Owned version of [`EcdaaSignResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EcdaaSignResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub signature: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl EcdaaSignResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<EcdaaSignResponseOwned<N>, Error> {
+        Ok(EcdaaSignResponseOwned {
+            signature: owned_bytes(self.signature)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for EcdaaSign {
     type Response<'rdata> = EcdaaSignResponse<'rdata>;
 }
 
 // ************* EcdsaVerify ************* //
 
+/// Verify a DER encoded signature over pre-hashed data with an EC public key or key pair.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `algo` | `EcDsaSignatureAlgo` | no |
+/// | `TAG_3` | `data` | `&'data [u8]` | no |
+/// | `TAG_5` | `signature` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`EcdsaVerifyResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `result` | `Se05XResult` | no |
+///
+/// Requires the applet features [`ECDSA_ECDH_ECDHE`](AppletConfig::ECDSA_ECDH_ECDHE), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ECDSAVerify"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EcdsaVerify<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
     pub algo: EcDsaSignatureAlgo,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
     /// Serialized to TLV tag [`TAG_5`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub signature: &'data [u8],
 }
 
@@ -2632,6 +3749,7 @@ impl<W: Writer> DataStream<W> for EcdsaVerify<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EcdsaVerifyResponse {
     /// Parsed from TLV tag [`TAG_1`]()
     pub result: Se05XResult,
@@ -2641,7 +3759,7 @@ impl<'data> Se05XResponse<'data> for EcdsaVerifyResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (result, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { result })
     }
 }
@@ -2652,21 +3770,47 @@ impl<W: Writer> Se05XCommand<W> for EcdsaVerify<'_> {
 
 // ************* EddsaVerify ************* //
 
+/// Verify an Ed25519 signature with an EC public key or key pair.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `algo` | `EdDsaSignatureAlgo` | no |
+/// | `TAG_3` | `data` | `&'data [u8]` | no |
+/// | `TAG_5` | `signature` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`EddsaVerifyResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `result` | `Se05XResult` | no |
+///
+/// Requires the applet features [`EDDSA`](AppletConfig::EDDSA), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "EdDSAVerify"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EddsaVerify<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
+    /// [`Pure`][EdDsaSignatureAlgo::Pure] unless the data is pre-hashed. It is the default value with the builder API
+    ///
+    /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "builder", builder(default=EdDsaSignatureAlgo::Pure))]
+    pub algo: EdDsaSignatureAlgo,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
     /// Serialized to TLV tag [`TAG_5`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub signature: &'data [u8],
 }
 
 impl DataSource for EddsaVerify<'_> {
     fn len(&self) -> usize {
         let key_id = &Tlv::new(TAG_1, self.key_id);
-        let algo = &Tlv::new(TAG_2, EdDsaSignatureAlgo::Pure);
+        let algo = &Tlv::new(TAG_2, self.algo);
         let data = &Tlv::new(TAG_3, self.data);
         let signature = &Tlv::new(TAG_5, self.signature);
         let __data: &[&dyn DataSource] = &[key_id, algo, data, signature];
@@ -2682,7 +3826,7 @@ impl DataSource for EddsaVerify<'_> {
 impl<W: Writer> DataStream<W> for EddsaVerify<'_> {
     fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
         let key_id = &Tlv::new(TAG_1, self.key_id);
-        let algo = &Tlv::new(TAG_2, EdDsaSignatureAlgo::Pure);
+        let algo = &Tlv::new(TAG_2, self.algo);
         let data = &Tlv::new(TAG_3, self.data);
         let signature = &Tlv::new(TAG_5, self.signature);
         let __data: &[&dyn DataStream<W>] = &[key_id, algo, data, signature];
@@ -2692,6 +3836,7 @@ impl<W: Writer> DataStream<W> for EddsaVerify<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EddsaVerifyResponse {
     /// Parsed from TLV tag [`TAG_1`]()
     pub result: Se05XResult,
@@ -2701,7 +3846,7 @@ impl<'data> Se05XResponse<'data> for EddsaVerifyResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (result, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { result })
     }
 }
@@ -2712,12 +3857,30 @@ impl<W: Writer> Se05XCommand<W> for EddsaVerify<'_> {
 
 // ************* EcdhGenerateSharedSecret ************* //
 
+/// Perform a Diffie-Hellman key agreement. `public_key` is the uncompressed point of the peer, on the same curve as `key_id`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `public_key` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`EcdhGenerateSharedSecretResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `shared_secret` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`ECDSA_ECDH_ECDHE`](AppletConfig::ECDSA_ECDH_ECDHE), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ECDHGenerateSharedSecret"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EcdhGenerateSharedSecret<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub public_key: &'data [u8],
 }
 
@@ -2758,8 +3921,10 @@ impl<W: Writer> DataStream<W> for EcdhGenerateSharedSecret<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EcdhGenerateSharedSecretResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub shared_secret: &'data [u8],
 }
 
@@ -2767,28 +3932,126 @@ impl<'data> Se05XResponse<'data> for EcdhGenerateSharedSecretResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (shared_secret, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { shared_secret })
     }
 }
 
+/// Owned version of [`EcdhGenerateSharedSecretResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EcdhGenerateSharedSecretResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub shared_secret: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl EcdhGenerateSharedSecretResponse<'_> {
+    pub fn to_owned<const N: usize>(
+        &self,
+    ) -> Result<EcdhGenerateSharedSecretResponseOwned<N>, Error> {
+        Ok(EcdhGenerateSharedSecretResponseOwned {
+            shared_secret: owned_bytes(self.shared_secret)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for EcdhGenerateSharedSecret<'_> {
     type Response<'rdata> = EcdhGenerateSharedSecretResponse<'rdata>;
 }
 
+// ************* EcdhStoreSharedSecret ************* //
+
+/// Perform a Diffie-Hellman key agreement and store the shared secret in an HMAC key object.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `public_key` | `&'data [u8]` | no |
+/// | `TAG_7` | `target` | `ObjectId` | no |
+///
+/// Requires the applet features [`ECDSA_ECDH_ECDHE`](AppletConfig::ECDSA_ECDH_ECDHE), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "ECDHGenerateSharedSecret"
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EcdhStoreSharedSecret<'data> {
+    /// Serialized to TLV tag [`TAG_1`]()
+    pub key_id: ObjectId,
+    /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub public_key: &'data [u8],
+    /// HMAC key object receiving the shared secret
+    ///
+    /// Serialized to TLV tag [`TAG_7`]()
+    pub target: ObjectId,
+}
+
+impl DataSource for EcdhStoreSharedSecret<'_> {
+    fn len(&self) -> usize {
+        let key_id = &Tlv::new(TAG_1, self.key_id);
+        let public_key = &Tlv::new(TAG_2, self.public_key);
+        let target = &Tlv::new(TAG_7, self.target);
+        let __data: &[&dyn DataSource] = &[key_id, public_key, target];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_EC, P2_DH, __data, 0);
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+impl<W: Writer> DataStream<W> for EcdhStoreSharedSecret<'_> {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let key_id = &Tlv::new(TAG_1, self.key_id);
+        let public_key = &Tlv::new(TAG_2, self.public_key);
+        let target = &Tlv::new(TAG_7, self.target);
+        let __data: &[&dyn DataStream<W>] = &[key_id, public_key, target];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_EC, P2_DH, __data, 0);
+        command.to_writer(writer)
+    }
+}
+
+impl<W: Writer> Se05XCommand<W> for EcdhStoreSharedSecret<'_> {
+    type Response<'rdata> = ();
+}
+
 // ************* RsaSign ************* //
 
+/// Sign data with an RSA key pair. PKCS#1 v1.5 and PSS padding are computed by the applet.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `algo` | `RsaSignatureAlgo` | no |
+/// | `TAG_3` | `data` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`RsaSignResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `signature` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`RSA_ALL`](AppletConfig::RSA_ALL), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "RSASign"
+#[cfg(feature = "rsa")]
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RsaSign<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
     pub algo: RsaSignatureAlgo,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
+#[cfg(feature = "rsa")]
 impl DataSource for RsaSign<'_> {
     fn len(&self) -> usize {
         let key_id = &Tlv::new(TAG_1, self.key_id);
@@ -2810,6 +4073,7 @@ impl DataSource for RsaSign<'_> {
         false
     }
 }
+#[cfg(feature = "rsa")]
 impl<W: Writer> DataStream<W> for RsaSign<'_> {
     fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
         let key_id = &Tlv::new(TAG_1, self.key_id);
@@ -2827,40 +4091,88 @@ impl<W: Writer> DataStream<W> for RsaSign<'_> {
         command.to_writer(writer)
     }
 }
+#[cfg(feature = "rsa")]
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RsaSignResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub signature: &'data [u8],
 }
 
+#[cfg(feature = "rsa")]
 impl<'data> Se05XResponse<'data> for RsaSignResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (signature, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { signature })
     }
 }
 
+/// Owned version of [`RsaSignResponse`]
+#[cfg(feature = "rsa")]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RsaSignResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub signature: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "rsa")]
+#[cfg(feature = "owned")]
+impl RsaSignResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<RsaSignResponseOwned<N>, Error> {
+        Ok(RsaSignResponseOwned {
+            signature: owned_bytes(self.signature)?,
+        })
+    }
+}
+
+#[cfg(feature = "rsa")]
 impl<W: Writer> Se05XCommand<W> for RsaSign<'_> {
     type Response<'rdata> = RsaSignResponse<'rdata>;
 }
 
 // ************* RsaVerify ************* //
 
+/// Verify an RSA signature with an RSA public key or key pair.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `algo` | `RsaSignatureAlgo` | no |
+/// | `TAG_3` | `data` | `&'data [u8]` | no |
+/// | `TAG_5` | `signature` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`RsaVerifyResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `result` | `Se05XResult` | no |
+///
+/// Requires the applet features [`RSA_ALL`](AppletConfig::RSA_ALL), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "RSAVerify"
+#[cfg(feature = "rsa")]
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RsaVerify<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
     pub algo: RsaSignatureAlgo,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
     /// Serialized to TLV tag [`TAG_5`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub signature: &'data [u8],
 }
 
+#[cfg(feature = "rsa")]
 impl DataSource for RsaVerify<'_> {
     fn len(&self) -> usize {
         let key_id = &Tlv::new(TAG_1, self.key_id);
@@ -2877,6 +4189,7 @@ impl DataSource for RsaVerify<'_> {
         false
     }
 }
+#[cfg(feature = "rsa")]
 impl<W: Writer> DataStream<W> for RsaVerify<'_> {
     fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
         let key_id = &Tlv::new(TAG_1, self.key_id);
@@ -2889,38 +4202,63 @@ impl<W: Writer> DataStream<W> for RsaVerify<'_> {
         command.to_writer(writer)
     }
 }
+#[cfg(feature = "rsa")]
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RsaVerifyResponse {
     /// Parsed from TLV tag [`TAG_1`]()
     pub result: Se05XResult,
 }
 
+#[cfg(feature = "rsa")]
 impl<'data> Se05XResponse<'data> for RsaVerifyResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (result, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { result })
     }
 }
 
+#[cfg(feature = "rsa")]
 impl<W: Writer> Se05XCommand<W> for RsaVerify<'_> {
     type Response<'rdata> = RsaVerifyResponse;
 }
 
 // ************* RsaEncrypt ************* //
 
+/// Encrypt data with an RSA public key or key pair. The plaintext must be shorter than the modulus minus the padding overhead.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `algo` | `RsaEncryptionAlgo` | no |
+/// | `TAG_3` | `plaintext` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`RsaEncryptResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `ciphertext` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`RSA_ALL`](AppletConfig::RSA_ALL), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "RSAEncrypt"
+#[cfg(feature = "rsa")]
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RsaEncrypt<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
     pub algo: RsaEncryptionAlgo,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub plaintext: &'data [u8],
 }
 
+#[cfg(feature = "rsa")]
 impl DataSource for RsaEncrypt<'_> {
     fn len(&self) -> usize {
         let key_id = &Tlv::new(TAG_1, self.key_id);
@@ -2942,6 +4280,7 @@ impl DataSource for RsaEncrypt<'_> {
         false
     }
 }
+#[cfg(feature = "rsa")]
 impl<W: Writer> DataStream<W> for RsaEncrypt<'_> {
     fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
         let key_id = &Tlv::new(TAG_1, self.key_id);
@@ -2959,38 +4298,84 @@ impl<W: Writer> DataStream<W> for RsaEncrypt<'_> {
         command.to_writer(writer)
     }
 }
+#[cfg(feature = "rsa")]
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RsaEncryptResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub ciphertext: &'data [u8],
 }
 
+#[cfg(feature = "rsa")]
 impl<'data> Se05XResponse<'data> for RsaEncryptResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (ciphertext, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { ciphertext })
     }
 }
 
+/// Owned version of [`RsaEncryptResponse`]
+#[cfg(feature = "rsa")]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RsaEncryptResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub ciphertext: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "rsa")]
+#[cfg(feature = "owned")]
+impl RsaEncryptResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<RsaEncryptResponseOwned<N>, Error> {
+        Ok(RsaEncryptResponseOwned {
+            ciphertext: owned_bytes(self.ciphertext)?,
+        })
+    }
+}
+
+#[cfg(feature = "rsa")]
 impl<W: Writer> Se05XCommand<W> for RsaEncrypt<'_> {
     type Response<'rdata> = RsaEncryptResponse<'rdata>;
 }
 
 // ************* RsaDecrypt ************* //
 
+/// Decrypt data with an RSA key pair. The ciphertext has the length of the modulus.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `algo` | `RsaEncryptionAlgo` | no |
+/// | `TAG_3` | `ciphertext` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`RsaDecryptResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `plaintext` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`RSA_ALL`](AppletConfig::RSA_ALL), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "RSADecrypt"
+#[cfg(feature = "rsa")]
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RsaDecrypt<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
     pub algo: RsaEncryptionAlgo,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub ciphertext: &'data [u8],
 }
 
+#[cfg(feature = "rsa")]
 impl DataSource for RsaDecrypt<'_> {
     fn len(&self) -> usize {
         let key_id = &Tlv::new(TAG_1, self.key_id);
@@ -3012,6 +4397,7 @@ impl DataSource for RsaDecrypt<'_> {
         false
     }
 }
+#[cfg(feature = "rsa")]
 impl<W: Writer> DataStream<W> for RsaDecrypt<'_> {
     fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
         let key_id = &Tlv::new(TAG_1, self.key_id);
@@ -3029,35 +4415,71 @@ impl<W: Writer> DataStream<W> for RsaDecrypt<'_> {
         command.to_writer(writer)
     }
 }
+#[cfg(feature = "rsa")]
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RsaDecryptResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub plaintext: &'data [u8],
 }
 
+#[cfg(feature = "rsa")]
 impl<'data> Se05XResponse<'data> for RsaDecryptResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (plaintext, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { plaintext })
     }
 }
 
+/// Owned version of [`RsaDecryptResponse`]
+#[cfg(feature = "rsa")]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RsaDecryptResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub plaintext: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "rsa")]
+#[cfg(feature = "owned")]
+impl RsaDecryptResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<RsaDecryptResponseOwned<N>, Error> {
+        Ok(RsaDecryptResponseOwned {
+            plaintext: owned_bytes(self.plaintext)?,
+        })
+    }
+}
+
+#[cfg(feature = "rsa")]
 impl<W: Writer> Se05XCommand<W> for RsaDecrypt<'_> {
     type Response<'rdata> = RsaDecryptResponse<'rdata>;
 }
 
 // ************* CipherEncryptInit ************* //
 
+/// Start an encryption with the cipher object `cipher_id` and the key `key_id`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `cipher_id` | `CryptoObjectId` | no |
+/// | `TAG_4` | `initialization_vector` | `&'data [u8]` | yes |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "CipherInit"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CipherEncryptInit<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
     pub cipher_id: CryptoObjectId,
     /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = initialization_vector_opt))))]
     pub initialization_vector: Option<&'data [u8]>,
 }
@@ -3093,14 +4515,25 @@ impl<W: Writer> Se05XCommand<W> for CipherEncryptInit<'_> {
 
 // ************* CipherDecryptInit ************* //
 
+/// Start a decryption with the cipher object `cipher_id` and the key `key_id`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `cipher_id` | `CryptoObjectId` | no |
+/// | `TAG_4` | `initialization_vector` | `&'data [u8]` | yes |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "CipherInit"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CipherDecryptInit<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
     pub cipher_id: CryptoObjectId,
     /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = initialization_vector_opt))))]
     pub initialization_vector: Option<&'data [u8]>,
 }
@@ -3136,14 +4569,30 @@ impl<W: Writer> Se05XCommand<W> for CipherDecryptInit<'_> {
 
 // ************* CipherUpdate ************* //
 
+/// Encrypt or decrypt the next part of the data. The output can be shorter than the input for padded modes.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_2` | `cipher_id` | `CryptoObjectId` | no |
+/// | `TAG_3` | `data` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`CipherUpdateResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "CipherUpdate"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CipherUpdate<'data> {
     /// Serialized to TLV tag [`TAG_2`]()
     pub cipher_id: CryptoObjectId,
     /// input data, can be either plaintext or ciphertext depending on whether cipher_decrypt_init or cipher_encrypt_init was used
     ///
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
@@ -3184,10 +4633,12 @@ impl<W: Writer> DataStream<W> for CipherUpdate<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CipherUpdateResponse<'data> {
     /// output data
     ///
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
@@ -3195,25 +4646,59 @@ impl<'data> Se05XResponse<'data> for CipherUpdateResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (data, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { data })
     }
 }
 
+/// Owned version of [`CipherUpdateResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CipherUpdateResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub data: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl CipherUpdateResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<CipherUpdateResponseOwned<N>, Error> {
+        Ok(CipherUpdateResponseOwned {
+            data: owned_bytes(self.data)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for CipherUpdate<'_> {
     type Response<'rdata> = CipherUpdateResponse<'rdata>;
 }
 
 // ************* CipherFinal ************* //
 
+/// Encrypt or decrypt the last part of the data, ending the operation of the cipher object.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_2` | `cipher_id` | `CryptoObjectId` | no |
+/// | `TAG_3` | `data` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`CipherFinalResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "CipherFinal"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CipherFinal<'data> {
     /// Serialized to TLV tag [`TAG_2`]()
     pub cipher_id: CryptoObjectId,
     /// input data, can be either plaintext or ciphertext depending on whether cipher_decrypt_init or cipher_encrypt_init was used
     ///
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
@@ -3254,10 +4739,12 @@ impl<W: Writer> DataStream<W> for CipherFinal<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CipherFinalResponse<'data> {
     /// output data
     ///
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
@@ -3265,27 +4752,64 @@ impl<'data> Se05XResponse<'data> for CipherFinalResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (data, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { data })
     }
 }
 
+/// Owned version of [`CipherFinalResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CipherFinalResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub data: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl CipherFinalResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<CipherFinalResponseOwned<N>, Error> {
+        Ok(CipherFinalResponseOwned {
+            data: owned_bytes(self.data)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for CipherFinal<'_> {
     type Response<'rdata> = CipherFinalResponse<'rdata>;
 }
 
 // ************* CipherOneShotEncrypt ************* //
 
+/// Encrypt data in a single command. Unpadded modes require the data to be a multiple of the block size: 16 bytes for AES and 8 bytes for DES.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `mode` | `CipherMode` | no |
+/// | `TAG_3` | `plaintext` | `&'data [u8]` | no |
+/// | `TAG_4` | `initialization_vector` | `&'data [u8]` | yes |
+///
+/// The response is parsed into [`CipherOneShotEncryptResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `ciphertext` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "CipherOneShot"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CipherOneShotEncrypt<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
     pub mode: CipherMode,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub plaintext: &'data [u8],
     /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = initialization_vector_opt))))]
     pub initialization_vector: Option<&'data [u8]>,
 }
@@ -3331,8 +4855,10 @@ impl<W: Writer> DataStream<W> for CipherOneShotEncrypt<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CipherOneShotEncryptResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub ciphertext: &'data [u8],
 }
 
@@ -3340,27 +4866,64 @@ impl<'data> Se05XResponse<'data> for CipherOneShotEncryptResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (ciphertext, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { ciphertext })
     }
 }
 
+/// Owned version of [`CipherOneShotEncryptResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CipherOneShotEncryptResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub ciphertext: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl CipherOneShotEncryptResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<CipherOneShotEncryptResponseOwned<N>, Error> {
+        Ok(CipherOneShotEncryptResponseOwned {
+            ciphertext: owned_bytes(self.ciphertext)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for CipherOneShotEncrypt<'_> {
     type Response<'rdata> = CipherOneShotEncryptResponse<'rdata>;
 }
 
 // ************* CipherOneShotDecrypt ************* //
 
+/// Decrypt data in a single command. The data must be a multiple of the block size: 16 bytes for AES and 8 bytes for DES.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `mode` | `CipherMode` | no |
+/// | `TAG_3` | `ciphertext` | `&'data [u8]` | no |
+/// | `TAG_4` | `initialization_vector` | `&'data [u8]` | yes |
+///
+/// The response is parsed into [`CipherOneShotDecryptResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `plaintext` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "CipherOneShot"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CipherOneShotDecrypt<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
     pub mode: CipherMode,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub ciphertext: &'data [u8],
     /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = initialization_vector_opt))))]
     pub initialization_vector: Option<&'data [u8]>,
 }
@@ -3406,8 +4969,10 @@ impl<W: Writer> DataStream<W> for CipherOneShotDecrypt<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CipherOneShotDecryptResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub plaintext: &'data [u8],
 }
 
@@ -3415,19 +4980,46 @@ impl<'data> Se05XResponse<'data> for CipherOneShotDecryptResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (plaintext, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { plaintext })
     }
 }
 
+/// Owned version of [`CipherOneShotDecryptResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CipherOneShotDecryptResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub plaintext: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl CipherOneShotDecryptResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<CipherOneShotDecryptResponseOwned<N>, Error> {
+        Ok(CipherOneShotDecryptResponseOwned {
+            plaintext: owned_bytes(self.plaintext)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for CipherOneShotDecrypt<'_> {
     type Response<'rdata> = CipherOneShotDecryptResponse<'rdata>;
 }
 
 // ************* MacGenerateInit ************* //
 
+/// Start computing a MAC with the signature object `mac_id` and the key `key_id`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `mac_id` | `CryptoObjectId` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "MACInit"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MacGenerateInit {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
@@ -3464,8 +5056,17 @@ impl<W: Writer> Se05XCommand<W> for MacGenerateInit {
 
 // ************* MacValidateInit ************* //
 
+/// Start validating a MAC with the signature object `mac_id` and the key `key_id`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `mac_id` | `CryptoObjectId` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "MACInit"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MacValidateInit {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
@@ -3502,10 +5103,20 @@ impl<W: Writer> Se05XCommand<W> for MacValidateInit {
 
 // ************* MacUpdate ************* //
 
+/// Add the next part of the data to the MAC of the signature object `mac_id`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | no |
+/// | `TAG_2` | `mac_id` | `CryptoObjectId` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "MACUpdate"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MacUpdate<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
     /// Serialized to TLV tag [`TAG_2`]()
     pub mac_id: CryptoObjectId,
@@ -3540,10 +5151,26 @@ impl<W: Writer> Se05XCommand<W> for MacUpdate<'_> {
 
 // ************* MacGenerateFinal ************* //
 
+/// Add the last part of the data and return the MAC, ending the operation.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | no |
+/// | `TAG_2` | `mac_id` | `CryptoObjectId` | no |
+///
+/// The response is parsed into [`MacGenerateFinalResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `tag` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "MACFinal"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MacGenerateFinal<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
     /// Serialized to TLV tag [`TAG_2`]()
     pub mac_id: CryptoObjectId,
@@ -3586,8 +5213,10 @@ impl<W: Writer> DataStream<W> for MacGenerateFinal<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MacGenerateFinalResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub tag: &'data [u8],
 }
 
@@ -3595,27 +5224,63 @@ impl<'data> Se05XResponse<'data> for MacGenerateFinalResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (tag, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { tag })
     }
 }
 
+/// Owned version of [`MacGenerateFinalResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MacGenerateFinalResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub tag: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl MacGenerateFinalResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<MacGenerateFinalResponseOwned<N>, Error> {
+        Ok(MacGenerateFinalResponseOwned {
+            tag: owned_bytes(self.tag)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for MacGenerateFinal<'_> {
     type Response<'rdata> = MacGenerateFinalResponse<'rdata>;
 }
 
 // ************* MacValidateFinal ************* //
 
+/// Add the last part of the data and compare the MAC with `tag`, ending the operation.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | no |
+/// | `TAG_2` | `mac_id` | `CryptoObjectId` | no |
+/// | `TAG_3` | `tag` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`MacValidateFinalResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `result` | `Se05XResult` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "MACFinal"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MacValidateFinal<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
     /// Serialized to TLV tag [`TAG_2`]()
     pub mac_id: CryptoObjectId,
     /// Tag to validate
     ///
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub tag: &'data [u8],
 }
 
@@ -3658,6 +5323,7 @@ impl<W: Writer> DataStream<W> for MacValidateFinal<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MacValidateFinalResponse {
     /// Parsed from TLV tag [`TAG_1`]()
     pub result: Se05XResult,
@@ -3667,7 +5333,7 @@ impl<'data> Se05XResponse<'data> for MacValidateFinalResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (result, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { result })
     }
 }
@@ -3678,14 +5344,31 @@ impl<W: Writer> Se05XCommand<W> for MacValidateFinal<'_> {
 
 // ************* MacOneShotGenerate ************* //
 
+/// Compute a MAC in a single command. HMAC algorithms require an HMAC key, CMAC and CBC-MAC algorithms an AES or DES key.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `algo` | `MacAlgo` | no |
+/// | `TAG_3` | `data` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`MacOneShotGenerateResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `tag` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "MACOneShot"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MacOneShotGenerate<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
     pub algo: MacAlgo,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
@@ -3728,8 +5411,10 @@ impl<W: Writer> DataStream<W> for MacOneShotGenerate<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MacOneShotGenerateResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub tag: &'data [u8],
 }
 
@@ -3737,29 +5422,66 @@ impl<'data> Se05XResponse<'data> for MacOneShotGenerateResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (tag, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { tag })
     }
 }
 
+/// Owned version of [`MacOneShotGenerateResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MacOneShotGenerateResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub tag: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl MacOneShotGenerateResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<MacOneShotGenerateResponseOwned<N>, Error> {
+        Ok(MacOneShotGenerateResponseOwned {
+            tag: owned_bytes(self.tag)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for MacOneShotGenerate<'_> {
     type Response<'rdata> = MacOneShotGenerateResponse<'rdata>;
 }
 
 // ************* MacOneShotValidate ************* //
 
+/// Validate a MAC in a single command.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_id` | `ObjectId` | no |
+/// | `TAG_2` | `algo` | `MacAlgo` | no |
+/// | `TAG_3` | `data` | `&'data [u8]` | no |
+/// | `TAG_5` | `tag` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`MacOneShotValidateResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `result` | `Se05XResult` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "MACOneShot"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MacOneShotValidate<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub key_id: ObjectId,
     /// Serialized to TLV tag [`TAG_2`]()
     pub algo: MacAlgo,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
     /// tag to validate
     ///
     /// Serialized to TLV tag [`TAG_5`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub tag: &'data [u8],
 }
 
@@ -3804,6 +5526,7 @@ impl<W: Writer> DataStream<W> for MacOneShotValidate<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MacOneShotValidateResponse {
     /// Parsed from TLV tag [`TAG_1`]()
     pub result: Se05XResult,
@@ -3813,7 +5536,7 @@ impl<'data> Se05XResponse<'data> for MacOneShotValidateResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (result, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { result })
     }
 }
@@ -3824,8 +5547,29 @@ impl<W: Writer> Se05XCommand<W> for MacOneShotValidate<'_> {
 
 // ************* Hkdf ************* //
 
+/// Derive key material with HKDF (RFC 5869) from an HMAC key object.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `ikm` | `ObjectId` | no |
+/// | `TAG_2` | `digest` | `Digest` | no |
+/// | `TAG_3` | `salt` | `&'data [u8]` | yes |
+/// | `TAG_4` | `info` | `&'data [u8]` | yes |
+/// | `TAG_5` | `requested_len` | `Be<u16>` | no |
+/// | `TAG_8` | `target` | `ObjectId` | yes |
+///
+/// The response is parsed into [`HkdfResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | yes |
+///
+/// Requires the applet features [`HMAC`](AppletConfig::HMAC), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "HKDF"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hkdf<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub ikm: ObjectId,
@@ -3834,15 +5578,22 @@ pub struct Hkdf<'data> {
     /// up to 64 bytes
     ///
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = salt_opt))))]
     pub salt: Option<&'data [u8]>,
     /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = info_opt))))]
     pub info: Option<&'data [u8]>,
     /// Up to MAX_APDU_PAYLOAD_LENGTH (= 889)
     ///
     /// Serialized to TLV tag [`TAG_5`]()
     pub requested_len: Be<u16>,
+    /// HMAC key object receiving the derived key, instead of returning it
+    ///
+    /// Serialized to TLV tag [`TAG_8`]()
+    #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = target_opt))))]
+    pub target: Option<ObjectId>,
 }
 
 impl DataSource for Hkdf<'_> {
@@ -3852,7 +5603,8 @@ impl DataSource for Hkdf<'_> {
         let salt = &self.salt.map(|data| Tlv::new(TAG_3, data));
         let info = &self.info.map(|data| Tlv::new(TAG_4, data));
         let requested_len = &Tlv::new(TAG_5, self.requested_len);
-        let __data: &[&dyn DataSource] = &[ikm, digest, salt, info, requested_len];
+        let target = &self.target.map(|data| Tlv::new(TAG_8, data));
+        let __data: &[&dyn DataSource] = &[ikm, digest, salt, info, requested_len, target];
         let command = CommandBuilder::new(
             NO_SM_CLA,
             INS_CRYPTO,
@@ -3875,7 +5627,8 @@ impl<W: Writer> DataStream<W> for Hkdf<'_> {
         let salt = &self.salt.map(|data| Tlv::new(TAG_3, data));
         let info = &self.info.map(|data| Tlv::new(TAG_4, data));
         let requested_len = &Tlv::new(TAG_5, self.requested_len);
-        let __data: &[&dyn DataStream<W>] = &[ikm, digest, salt, info, requested_len];
+        let target = &self.target.map(|data| Tlv::new(TAG_8, data));
+        let __data: &[&dyn DataStream<W>] = &[ikm, digest, salt, info, requested_len, target];
         let command = CommandBuilder::new(
             NO_SM_CLA,
             INS_CRYPTO,
@@ -3888,34 +5641,79 @@ impl<W: Writer> DataStream<W> for Hkdf<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HkdfResponse<'data> {
+    /// Is None when the derived key is stored in the target object
+    ///
     /// Parsed from TLV tag [`TAG_1`]()
-    pub data: &'data [u8],
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub data: Option<&'data [u8]>,
 }
 
 impl<'data> Se05XResponse<'data> for HkdfResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
-        let (data, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        let (data, rem) = take_opt_do_until_end(TAG_1, &[TAG_1], rem)?;
+        check_trailing_data(rem)?;
         Ok(Self { data })
     }
 }
 
+/// Owned version of [`HkdfResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HkdfResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub data: Option<heapless::Vec<u8, N>>,
+}
+
+#[cfg(feature = "owned")]
+impl HkdfResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<HkdfResponseOwned<N>, Error> {
+        Ok(HkdfResponseOwned {
+            data: self.data.map(owned_bytes).transpose()?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for Hkdf<'_> {
     type Response<'rdata> = HkdfResponse<'rdata>;
+    fn validate(&self) -> Result<(), Error> {
+        Hkdf::validate(self)
+    }
 }
 
 // ************* Pbkdf2 ************* //
 
+/// Derive key material with PBKDF2-HMAC-SHA1 (RFC 8018) from a password stored in an HMAC key object.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `password` | `ObjectId` | no |
+/// | `TAG_2` | `salt` | `&'data [u8]` | yes |
+/// | `TAG_3` | `iterations` | `Be<u16>` | no |
+/// | `TAG_4` | `requested_len` | `Be<u16>` | no |
+///
+/// The response is parsed into [`Pbkdf2Response`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`PBKDF`](AppletConfig::PBKDF), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "PBKDF2"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pbkdf2<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub password: ObjectId,
     /// up to 64 bytes
     ///
     /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = salt_opt))))]
     pub salt: Option<&'data [u8]>,
     /// Up to 0x7FFF
@@ -3969,8 +5767,10 @@ impl<W: Writer> DataStream<W> for Pbkdf2<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pbkdf2Response<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
@@ -3978,29 +5778,73 @@ impl<'data> Se05XResponse<'data> for Pbkdf2Response<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (data, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { data })
     }
 }
 
+/// Owned version of [`Pbkdf2Response`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pbkdf2ResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub data: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl Pbkdf2Response<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<Pbkdf2ResponseOwned<N>, Error> {
+        Ok(Pbkdf2ResponseOwned {
+            data: owned_bytes(self.data)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for Pbkdf2<'_> {
     type Response<'rdata> = Pbkdf2Response<'rdata>;
 }
 
-// ************* DigestInit ************* //
-
+// ************* I2CMasterExecuteCommandSet ************* //
+
+/// Execute a set of I2C master commands on the bus of the se05x, encoded with [`encode_command_set`](super::i2cm::encode_command_set).
+///
+/// The command must be sent in a session authenticated with [`ObjectId::I2CM_ACCESS`] if the applet is configured so.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `command_set` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`I2CMasterExecuteCommandSetResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`I2CM`](AppletConfig::I2CM), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "I2CM_ExecuteCommandSet"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
-pub struct DigestInit {
-    /// Serialized to TLV tag [`TAG_2`]()
-    pub digest_id: CryptoObjectId,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct I2CMasterExecuteCommandSet<'data> {
+    /// Serialized to TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub command_set: &'data [u8],
 }
 
-impl DataSource for DigestInit {
+impl DataSource for I2CMasterExecuteCommandSet<'_> {
     fn len(&self) -> usize {
-        let digest_id = &Tlv::new(TAG_2, self.digest_id);
-        let __data: &[&dyn DataSource] = &[digest_id];
-        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_DEFAULT, P2_INIT, __data, 0);
+        let command_set = &Tlv::new(TAG_1, self.command_set);
+        let __data: &[&dyn DataSource] = &[command_set];
+        let command = CommandBuilder::new(
+            NO_SM_CLA,
+            INS_CRYPTO,
+            P1_DEFAULT,
+            P2_I2CM,
+            __data,
+            ExpectedLen::Max,
+        );
         command.len()
     }
     fn is_empty(&self) -> bool {
@@ -4008,34 +5852,995 @@ impl DataSource for DigestInit {
         false
     }
 }
-impl<W: Writer> DataStream<W> for DigestInit {
+impl<W: Writer> DataStream<W> for I2CMasterExecuteCommandSet<'_> {
     fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
-        let digest_id = &Tlv::new(TAG_2, self.digest_id);
-        let __data: &[&dyn DataStream<W>] = &[digest_id];
-        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_DEFAULT, P2_INIT, __data, 0);
+        let command_set = &Tlv::new(TAG_1, self.command_set);
+        let __data: &[&dyn DataStream<W>] = &[command_set];
+        let command = CommandBuilder::new(
+            NO_SM_CLA,
+            INS_CRYPTO,
+            P1_DEFAULT,
+            P2_I2CM,
+            __data,
+            ExpectedLen::Max,
+        );
         command.to_writer(writer)
     }
 }
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct I2CMasterExecuteCommandSetResponse<'data> {
+    /// Results of the commands, parsed by [`I2cmResults`](super::i2cm::I2cmResults)
+    ///
+    /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub data: &'data [u8],
+}
 
-impl<W: Writer> Se05XCommand<W> for DigestInit {
-    type Response<'rdata> = ();
+impl<'data> Se05XResponse<'data> for I2CMasterExecuteCommandSetResponse<'data> {
+    #[inline(never)]
+    fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
+        let (data, rem) = take_do_until(TAG_1, rem)?;
+        check_trailing_data(rem)?;
+        Ok(Self { data })
+    }
 }
 
-// ************* DigestUpdate ************* //
+/// Owned version of [`I2CMasterExecuteCommandSetResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct I2CMasterExecuteCommandSetResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub data: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl I2CMasterExecuteCommandSetResponse<'_> {
+    pub fn to_owned<const N: usize>(
+        &self,
+    ) -> Result<I2CMasterExecuteCommandSetResponseOwned<N>, Error> {
+        Ok(I2CMasterExecuteCommandSetResponseOwned {
+            data: owned_bytes(self.data)?,
+        })
+    }
+}
 
+impl<W: Writer> Se05XCommand<W> for I2CMasterExecuteCommandSet<'_> {
+    type Response<'rdata> = I2CMasterExecuteCommandSetResponse<'rdata>;
+}
+
+// ************* I2CMasterExecuteCommandSetAttested ************* //
+
+/// Execute a set of I2C master commands and sign their results with `attestation_object`, together with a timestamp and the freshness random chosen by the host.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `command_set` | `&'data [u8]` | no |
+/// | `TAG_2` | `attestation_object` | `ObjectId` | no |
+/// | `TAG_3` | `attestation_algo` | `AttestationAlgo` | no |
+/// | `TAG_7` | `freshness_random` | `&'data [u8; 16]` | no |
+///
+/// The response is parsed into [`I2CMasterExecuteCommandSetAttestedResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | no |
+/// | `TAG_3` | `timestamp` | `&'data [u8; 12]` | no |
+/// | `TAG_4` | `freshness_random` | `&'data [u8; 16]` | no |
+/// | `TAG_5` | `chip_unique_id` | `&'data [u8; 18]` | no |
+/// | `TAG_6` | `signature` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`I2CM`](AppletConfig::I2CM), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "I2CM_ExecuteCommandSet"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
-pub struct DigestUpdate<'data> {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct I2CMasterExecuteCommandSetAttested<'data> {
+    /// Serialized to TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub command_set: &'data [u8],
     /// Serialized to TLV tag [`TAG_2`]()
-    pub digest_id: CryptoObjectId,
+    pub attestation_object: ObjectId,
     /// Serialized to TLV tag [`TAG_3`]()
-    pub data: &'data [u8],
+    pub attestation_algo: AttestationAlgo,
+    /// Serialized to TLV tag [`TAG_7`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_byte_array_ref", borrow))]
+    pub freshness_random: &'data [u8; 16],
 }
 
-impl DataSource for DigestUpdate<'_> {
+impl DataSource for I2CMasterExecuteCommandSetAttested<'_> {
     fn len(&self) -> usize {
-        let digest_id = &Tlv::new(TAG_2, self.digest_id);
-        let data = &Tlv::new(TAG_3, self.data);
+        let command_set = &Tlv::new(TAG_1, self.command_set);
+        let attestation_object = &Tlv::new(TAG_2, self.attestation_object);
+        let attestation_algo = &Tlv::new(TAG_3, self.attestation_algo);
+        let freshness_random = &Tlv::new(TAG_7, self.freshness_random);
+        let __data: &[&dyn DataSource] = &[
+            command_set,
+            attestation_object,
+            attestation_algo,
+            freshness_random,
+        ];
+        let command = CommandBuilder::new(
+            NO_SM_CLA,
+            INS_CRYPTO,
+            P1_DEFAULT,
+            P2_I2CM_ATTESTED,
+            __data,
+            ExpectedLen::Max,
+        );
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+impl<W: Writer> DataStream<W> for I2CMasterExecuteCommandSetAttested<'_> {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let command_set = &Tlv::new(TAG_1, self.command_set);
+        let attestation_object = &Tlv::new(TAG_2, self.attestation_object);
+        let attestation_algo = &Tlv::new(TAG_3, self.attestation_algo);
+        let freshness_random = &Tlv::new(TAG_7, self.freshness_random);
+        let __data: &[&dyn DataStream<W>] = &[
+            command_set,
+            attestation_object,
+            attestation_algo,
+            freshness_random,
+        ];
+        let command = CommandBuilder::new(
+            NO_SM_CLA,
+            INS_CRYPTO,
+            P1_DEFAULT,
+            P2_I2CM_ATTESTED,
+            __data,
+            ExpectedLen::Max,
+        );
+        command.to_writer(writer)
+    }
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct I2CMasterExecuteCommandSetAttestedResponse<'data> {
+    /// Results of the commands, parsed by [`I2cmResults`](super::i2cm::I2cmResults)
+    ///
+    /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub data: &'data [u8],
+    /// Parsed from TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_byte_array_ref", borrow))]
+    pub timestamp: &'data [u8; 12],
+    /// Parsed from TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_byte_array_ref", borrow))]
+    pub freshness_random: &'data [u8; 16],
+    /// Parsed from TLV tag [`TAG_5`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_byte_array_ref", borrow))]
+    pub chip_unique_id: &'data [u8; 18],
+    /// Parsed from TLV tag [`TAG_6`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub signature: &'data [u8],
+}
+
+impl<'data> Se05XResponse<'data> for I2CMasterExecuteCommandSetAttestedResponse<'data> {
+    #[inline(never)]
+    fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
+        let (data, rem) = take_do_until(TAG_1, rem)?;
+        let (timestamp, rem) = take_do_until(TAG_3, rem)?;
+        let (freshness_random, rem) = take_do_until(TAG_4, rem)?;
+        let (chip_unique_id, rem) = take_do_until(TAG_5, rem)?;
+        let (signature, rem) = take_do_until(TAG_6, rem)?;
+        check_trailing_data(rem)?;
+        Ok(Self {
+            data,
+            timestamp,
+            freshness_random,
+            chip_unique_id,
+            signature,
+        })
+    }
+}
+
+/// Owned version of [`I2CMasterExecuteCommandSetAttestedResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct I2CMasterExecuteCommandSetAttestedResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub data: heapless::Vec<u8, N>,
+    /// Parsed from TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    pub timestamp: [u8; 12],
+    /// Parsed from TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    pub freshness_random: [u8; 16],
+    /// Parsed from TLV tag [`TAG_5`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    pub chip_unique_id: [u8; 18],
+    /// Parsed from TLV tag [`TAG_6`]()
+    pub signature: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl I2CMasterExecuteCommandSetAttestedResponse<'_> {
+    pub fn to_owned<const N: usize>(
+        &self,
+    ) -> Result<I2CMasterExecuteCommandSetAttestedResponseOwned<N>, Error> {
+        Ok(I2CMasterExecuteCommandSetAttestedResponseOwned {
+            data: owned_bytes(self.data)?,
+            timestamp: *self.timestamp,
+            freshness_random: *self.freshness_random,
+            chip_unique_id: *self.chip_unique_id,
+            signature: owned_bytes(self.signature)?,
+        })
+    }
+}
+
+impl<W: Writer> Se05XCommand<W> for I2CMasterExecuteCommandSetAttested<'_> {
+    type Response<'rdata> = I2CMasterExecuteCommandSetAttestedResponse<'rdata>;
+}
+
+// ************* TlsCalculatePreMasterSecret ************* //
+
+/// Compute a TLS PSK or ECDHE-PSK pre-master secret and store it in an HMAC key object.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `psk_id` | `ObjectId` | no |
+/// | `TAG_2` | `key_pair_id` | `ObjectId` | yes |
+/// | `TAG_3` | `hmac_key_id` | `ObjectId` | no |
+/// | `TAG_4` | `input_data` | `&'data [u8]` | yes |
+///
+/// Requires the applet features [`TLS`](AppletConfig::TLS), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "TLSCalculatePreMasterSecret"
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TlsCalculatePreMasterSecret<'data> {
+    /// Pre-shared key
+    ///
+    /// Serialized to TLV tag [`TAG_1`]()
+    pub psk_id: ObjectId,
+    /// EC key pair, for ECDHE-PSK
+    ///
+    /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = key_pair_id_opt))))]
+    pub key_pair_id: Option<ObjectId>,
+    /// HMAC key object receiving the pre-master secret
+    ///
+    /// Serialized to TLV tag [`TAG_3`]()
+    pub hmac_key_id: ObjectId,
+    /// Public key of the peer, for ECDHE-PSK
+    ///
+    /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = input_data_opt))))]
+    pub input_data: Option<&'data [u8]>,
+}
+
+impl DataSource for TlsCalculatePreMasterSecret<'_> {
+    fn len(&self) -> usize {
+        let psk_id = &Tlv::new(TAG_1, self.psk_id);
+        let key_pair_id = &self.key_pair_id.map(|data| Tlv::new(TAG_2, data));
+        let hmac_key_id = &Tlv::new(TAG_3, self.hmac_key_id);
+        let input_data = &self.input_data.map(|data| Tlv::new(TAG_4, data));
+        let __data: &[&dyn DataSource] = &[psk_id, key_pair_id, hmac_key_id, input_data];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_TLS, P2_TLS_PMS, __data, 0);
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+impl<W: Writer> DataStream<W> for TlsCalculatePreMasterSecret<'_> {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let psk_id = &Tlv::new(TAG_1, self.psk_id);
+        let key_pair_id = &self.key_pair_id.map(|data| Tlv::new(TAG_2, data));
+        let hmac_key_id = &Tlv::new(TAG_3, self.hmac_key_id);
+        let input_data = &self.input_data.map(|data| Tlv::new(TAG_4, data));
+        let __data: &[&dyn DataStream<W>] = &[psk_id, key_pair_id, hmac_key_id, input_data];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_TLS, P2_TLS_PMS, __data, 0);
+        command.to_writer(writer)
+    }
+}
+
+impl<W: Writer> Se05XCommand<W> for TlsCalculatePreMasterSecret<'_> {
+    type Response<'rdata> = ();
+}
+
+// ************* TlsPerformPrf ************* //
+
+/// Run the TLS 1.2 PRF on a secret held by the applet. `digest` must be SHA-256 or SHA-384.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | P2 | `prf_type` | `TlsPrfType` | no |
+/// | `TAG_1` | `hmac_key_id` | `ObjectId` | no |
+/// | `TAG_2` | `digest` | `Digest` | no |
+/// | `TAG_3` | `label` | `&'data [u8]` | no |
+/// | `TAG_4` | `random` | `&'data [u8]` | no |
+/// | `TAG_5` | `requested_len` | `Be<u16>` | no |
+///
+/// The response is parsed into [`TlsPerformPrfResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`TLS`](AppletConfig::TLS), see [`Atr::applet_config`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "TLSPerformPRF"
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TlsPerformPrf<'data> {
+    pub prf_type: TlsPrfType,
+    /// HMAC key object holding the pre-master or master secret
+    ///
+    /// Serialized to TLV tag [`TAG_1`]()
+    pub hmac_key_id: ObjectId,
+    /// Serialized to TLV tag [`TAG_2`]()
+    pub digest: Digest,
+    /// 1 to 64 bytes
+    ///
+    /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub label: &'data [u8],
+    /// 32 or 64 bytes
+    ///
+    /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub random: &'data [u8],
+    /// Up to MAX_APDU_PAYLOAD_LENGTH (= 889)
+    ///
+    /// Serialized to TLV tag [`TAG_5`]()
+    pub requested_len: Be<u16>,
+}
+
+impl DataSource for TlsPerformPrf<'_> {
+    fn len(&self) -> usize {
+        let hmac_key_id = &Tlv::new(TAG_1, self.hmac_key_id);
+        let digest = &Tlv::new(TAG_2, self.digest);
+        let label = &Tlv::new(TAG_3, self.label);
+        let random = &Tlv::new(TAG_4, self.random);
+        let requested_len = &Tlv::new(TAG_5, self.requested_len);
+        let __data: &[&dyn DataSource] = &[hmac_key_id, digest, label, random, requested_len];
+        let p2: u8 = self.prf_type.into();
+
+        let command =
+            CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_TLS, p2, __data, ExpectedLen::Max);
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+impl<W: Writer> DataStream<W> for TlsPerformPrf<'_> {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let hmac_key_id = &Tlv::new(TAG_1, self.hmac_key_id);
+        let digest = &Tlv::new(TAG_2, self.digest);
+        let label = &Tlv::new(TAG_3, self.label);
+        let random = &Tlv::new(TAG_4, self.random);
+        let requested_len = &Tlv::new(TAG_5, self.requested_len);
+        let __data: &[&dyn DataStream<W>] = &[hmac_key_id, digest, label, random, requested_len];
+        let p2: u8 = self.prf_type.into();
+
+        let command =
+            CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_TLS, p2, __data, ExpectedLen::Max);
+        command.to_writer(writer)
+    }
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TlsPerformPrfResponse<'data> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub data: &'data [u8],
+}
+
+impl<'data> Se05XResponse<'data> for TlsPerformPrfResponse<'data> {
+    #[inline(never)]
+    fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
+        let (data, rem) = take_do_until(TAG_1, rem)?;
+        check_trailing_data(rem)?;
+        Ok(Self { data })
+    }
+}
+
+/// Owned version of [`TlsPerformPrfResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TlsPerformPrfResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub data: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl TlsPerformPrfResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<TlsPerformPrfResponseOwned<N>, Error> {
+        Ok(TlsPerformPrfResponseOwned {
+            data: owned_bytes(self.data)?,
+        })
+    }
+}
+
+impl<W: Writer> Se05XCommand<W> for TlsPerformPrf<'_> {
+    type Response<'rdata> = TlsPerformPrfResponse<'rdata>;
+    fn validate(&self) -> Result<(), Error> {
+        TlsPerformPrf::validate(self)
+    }
+}
+
+// ************* PakeConfigDevice ************* //
+
+/// Set the SPAKE2+ role of the crypto object `id`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `id` | `CryptoObjectId` | no |
+/// | `TAG_2` | `device_type` | `Spake2PlusDeviceType` | no |
+///
+/// Requires the applet features [`PAKE`](AppletConfig::PAKE), see [`Atr::applet_config`].
+///
+/// Reference: SE051 IoT applet APDU Specification, section "PAKEConfigDevice"
+#[cfg(feature = "se051")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PakeConfigDevice {
+    /// Serialized to TLV tag [`TAG_1`]()
+    pub id: CryptoObjectId,
+    /// Serialized to TLV tag [`TAG_2`]()
+    pub device_type: Spake2PlusDeviceType,
+}
+
+#[cfg(feature = "se051")]
+impl DataSource for PakeConfigDevice {
+    fn len(&self) -> usize {
+        let id = &Tlv::new(TAG_1, self.id);
+        let device_type = &Tlv::new(TAG_2, self.device_type);
+        let __data: &[&dyn DataSource] = &[id, device_type];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_PAKE, P2_CREATE, __data, 0);
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+#[cfg(feature = "se051")]
+impl<W: Writer> DataStream<W> for PakeConfigDevice {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let id = &Tlv::new(TAG_1, self.id);
+        let device_type = &Tlv::new(TAG_2, self.device_type);
+        let __data: &[&dyn DataStream<W>] = &[id, device_type];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_PAKE, P2_CREATE, __data, 0);
+        command.to_writer(writer)
+    }
+}
+
+#[cfg(feature = "se051")]
+impl<W: Writer> Se05XCommand<W> for PakeConfigDevice {
+    type Response<'rdata> = ();
+}
+
+// ************* PakeInitDevice ************* //
+
+/// Set the context and the identities of the parties of a SPAKE2+ exchange.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `id` | `CryptoObjectId` | no |
+/// | `TAG_2` | `context` | `&'data [u8]` | no |
+/// | `TAG_3` | `prover_id` | `&'data [u8]` | no |
+/// | `TAG_4` | `verifier_id` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`PAKE`](AppletConfig::PAKE), see [`Atr::applet_config`].
+///
+/// Reference: SE051 IoT applet APDU Specification, section "PAKEInitDevice"
+#[cfg(feature = "se051")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PakeInitDevice<'data> {
+    /// Serialized to TLV tag [`TAG_1`]()
+    pub id: CryptoObjectId,
+    /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub context: &'data [u8],
+    /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub prover_id: &'data [u8],
+    /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub verifier_id: &'data [u8],
+}
+
+#[cfg(feature = "se051")]
+impl DataSource for PakeInitDevice<'_> {
+    fn len(&self) -> usize {
+        let id = &Tlv::new(TAG_1, self.id);
+        let context = &Tlv::new(TAG_2, self.context);
+        let prover_id = &Tlv::new(TAG_3, self.prover_id);
+        let verifier_id = &Tlv::new(TAG_4, self.verifier_id);
+        let __data: &[&dyn DataSource] = &[id, context, prover_id, verifier_id];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_PAKE, P2_INIT, __data, 0);
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+#[cfg(feature = "se051")]
+impl<W: Writer> DataStream<W> for PakeInitDevice<'_> {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let id = &Tlv::new(TAG_1, self.id);
+        let context = &Tlv::new(TAG_2, self.context);
+        let prover_id = &Tlv::new(TAG_3, self.prover_id);
+        let verifier_id = &Tlv::new(TAG_4, self.verifier_id);
+        let __data: &[&dyn DataStream<W>] = &[id, context, prover_id, verifier_id];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_PAKE, P2_INIT, __data, 0);
+        command.to_writer(writer)
+    }
+}
+
+#[cfg(feature = "se051")]
+impl<W: Writer> Se05XCommand<W> for PakeInitDevice<'_> {
+    type Response<'rdata> = ();
+}
+
+// ************* PakeInitCredentials ************* //
+
+/// Set the objects holding the SPAKE2+ credentials. The prover uses `w0` and `w1`, the verifier `w0` and `l`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `id` | `CryptoObjectId` | no |
+/// | `TAG_2` | `w0` | `ObjectId` | no |
+/// | `TAG_3` | `w1` | `ObjectId` | yes |
+/// | `TAG_4` | `l` | `ObjectId` | yes |
+///
+/// Requires the applet features [`PAKE`](AppletConfig::PAKE), see [`Atr::applet_config`].
+///
+/// Reference: SE051 IoT applet APDU Specification, section "PAKEInitCredentials"
+#[cfg(feature = "se051")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PakeInitCredentials {
+    /// Serialized to TLV tag [`TAG_1`]()
+    pub id: CryptoObjectId,
+    /// Serialized to TLV tag [`TAG_2`]()
+    pub w0: ObjectId,
+    /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = w1_opt))))]
+    pub w1: Option<ObjectId>,
+    /// Serialized to TLV tag [`TAG_4`]()
+    #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = l_opt))))]
+    pub l: Option<ObjectId>,
+}
+
+#[cfg(feature = "se051")]
+impl DataSource for PakeInitCredentials {
+    fn len(&self) -> usize {
+        let id = &Tlv::new(TAG_1, self.id);
+        let w0 = &Tlv::new(TAG_2, self.w0);
+        let w1 = &self.w1.map(|data| Tlv::new(TAG_3, data));
+        let l = &self.l.map(|data| Tlv::new(TAG_4, data));
+        let __data: &[&dyn DataSource] = &[id, w0, w1, l];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_PAKE, P2_PARAM, __data, 0);
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+#[cfg(feature = "se051")]
+impl<W: Writer> DataStream<W> for PakeInitCredentials {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let id = &Tlv::new(TAG_1, self.id);
+        let w0 = &Tlv::new(TAG_2, self.w0);
+        let w1 = &self.w1.map(|data| Tlv::new(TAG_3, data));
+        let l = &self.l.map(|data| Tlv::new(TAG_4, data));
+        let __data: &[&dyn DataStream<W>] = &[id, w0, w1, l];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_PAKE, P2_PARAM, __data, 0);
+        command.to_writer(writer)
+    }
+}
+
+#[cfg(feature = "se051")]
+impl<W: Writer> Se05XCommand<W> for PakeInitCredentials {
+    type Response<'rdata> = ();
+}
+
+// ************* PakeComputeKeyShare ************* //
+
+/// Generate the SPAKE2+ key share of the device. The verifier passes the key share of the prover.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `id` | `CryptoObjectId` | no |
+/// | `TAG_2` | `peer_key_share` | `&'data [u8]` | yes |
+///
+/// The response is parsed into [`PakeComputeKeyShareResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `key_share` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`PAKE`](AppletConfig::PAKE), see [`Atr::applet_config`].
+///
+/// Reference: SE051 IoT applet APDU Specification, section "PAKEComputeKeyShare"
+#[cfg(feature = "se051")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PakeComputeKeyShare<'data> {
+    /// Serialized to TLV tag [`TAG_1`]()
+    pub id: CryptoObjectId,
+    /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    #[cfg_attr(feature = "builder", builder(default, setter(strip_option(fallback = peer_key_share_opt))))]
+    pub peer_key_share: Option<&'data [u8]>,
+}
+
+#[cfg(feature = "se051")]
+impl DataSource for PakeComputeKeyShare<'_> {
+    fn len(&self) -> usize {
+        let id = &Tlv::new(TAG_1, self.id);
+        let peer_key_share = &self.peer_key_share.map(|data| Tlv::new(TAG_2, data));
+        let __data: &[&dyn DataSource] = &[id, peer_key_share];
+        let command = CommandBuilder::new(
+            NO_SM_CLA,
+            INS_CRYPTO,
+            P1_PAKE,
+            P2_GENERATE,
+            __data,
+            ExpectedLen::Max,
+        );
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+#[cfg(feature = "se051")]
+impl<W: Writer> DataStream<W> for PakeComputeKeyShare<'_> {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let id = &Tlv::new(TAG_1, self.id);
+        let peer_key_share = &self.peer_key_share.map(|data| Tlv::new(TAG_2, data));
+        let __data: &[&dyn DataStream<W>] = &[id, peer_key_share];
+        let command = CommandBuilder::new(
+            NO_SM_CLA,
+            INS_CRYPTO,
+            P1_PAKE,
+            P2_GENERATE,
+            __data,
+            ExpectedLen::Max,
+        );
+        command.to_writer(writer)
+    }
+}
+#[cfg(feature = "se051")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PakeComputeKeyShareResponse<'data> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub key_share: &'data [u8],
+}
+
+#[cfg(feature = "se051")]
+impl<'data> Se05XResponse<'data> for PakeComputeKeyShareResponse<'data> {
+    #[inline(never)]
+    fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
+        let (key_share, rem) = take_do_until(TAG_1, rem)?;
+        check_trailing_data(rem)?;
+        Ok(Self { key_share })
+    }
+}
+
+/// Owned version of [`PakeComputeKeyShareResponse`]
+#[cfg(feature = "se051")]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PakeComputeKeyShareResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub key_share: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "se051")]
+#[cfg(feature = "owned")]
+impl PakeComputeKeyShareResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<PakeComputeKeyShareResponseOwned<N>, Error> {
+        Ok(PakeComputeKeyShareResponseOwned {
+            key_share: owned_bytes(self.key_share)?,
+        })
+    }
+}
+
+#[cfg(feature = "se051")]
+impl<W: Writer> Se05XCommand<W> for PakeComputeKeyShare<'_> {
+    type Response<'rdata> = PakeComputeKeyShareResponse<'rdata>;
+}
+
+// ************* PakeComputeSessionKeys ************* //
+
+/// Derive the SPAKE2+ shared secret from the key share of the peer, and the key confirmation message for the peer.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `id` | `CryptoObjectId` | no |
+/// | `TAG_2` | `peer_key_share` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`PakeComputeSessionKeysResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `shared_secret` | `&'data [u8]` | no |
+/// | `TAG_2` | `confirmation` | `&'data [u8]` | no |
+///
+/// Requires the applet features [`PAKE`](AppletConfig::PAKE), see [`Atr::applet_config`].
+///
+/// Reference: SE051 IoT applet APDU Specification, section "PAKEComputeSessionKeys"
+#[cfg(feature = "se051")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PakeComputeSessionKeys<'data> {
+    /// Serialized to TLV tag [`TAG_1`]()
+    pub id: CryptoObjectId,
+    /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub peer_key_share: &'data [u8],
+}
+
+#[cfg(feature = "se051")]
+impl DataSource for PakeComputeSessionKeys<'_> {
+    fn len(&self) -> usize {
+        let id = &Tlv::new(TAG_1, self.id);
+        let peer_key_share = &Tlv::new(TAG_2, self.peer_key_share);
+        let __data: &[&dyn DataSource] = &[id, peer_key_share];
+        let command = CommandBuilder::new(
+            NO_SM_CLA,
+            INS_CRYPTO,
+            P1_PAKE,
+            P2_DH,
+            __data,
+            ExpectedLen::Max,
+        );
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+#[cfg(feature = "se051")]
+impl<W: Writer> DataStream<W> for PakeComputeSessionKeys<'_> {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let id = &Tlv::new(TAG_1, self.id);
+        let peer_key_share = &Tlv::new(TAG_2, self.peer_key_share);
+        let __data: &[&dyn DataStream<W>] = &[id, peer_key_share];
+        let command = CommandBuilder::new(
+            NO_SM_CLA,
+            INS_CRYPTO,
+            P1_PAKE,
+            P2_DH,
+            __data,
+            ExpectedLen::Max,
+        );
+        command.to_writer(writer)
+    }
+}
+#[cfg(feature = "se051")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PakeComputeSessionKeysResponse<'data> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub shared_secret: &'data [u8],
+    /// Parsed from TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub confirmation: &'data [u8],
+}
+
+#[cfg(feature = "se051")]
+impl<'data> Se05XResponse<'data> for PakeComputeSessionKeysResponse<'data> {
+    #[inline(never)]
+    fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
+        let (shared_secret, rem) = take_do_until(TAG_1, rem)?;
+        let (confirmation, rem) = take_do_until(TAG_2, rem)?;
+        check_trailing_data(rem)?;
+        Ok(Self {
+            shared_secret,
+            confirmation,
+        })
+    }
+}
+
+/// Owned version of [`PakeComputeSessionKeysResponse`]
+#[cfg(feature = "se051")]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PakeComputeSessionKeysResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub shared_secret: heapless::Vec<u8, N>,
+    /// Parsed from TLV tag [`TAG_2`]()
+    pub confirmation: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "se051")]
+#[cfg(feature = "owned")]
+impl PakeComputeSessionKeysResponse<'_> {
+    pub fn to_owned<const N: usize>(
+        &self,
+    ) -> Result<PakeComputeSessionKeysResponseOwned<N>, Error> {
+        Ok(PakeComputeSessionKeysResponseOwned {
+            shared_secret: owned_bytes(self.shared_secret)?,
+            confirmation: owned_bytes(self.confirmation)?,
+        })
+    }
+}
+
+#[cfg(feature = "se051")]
+impl<W: Writer> Se05XCommand<W> for PakeComputeSessionKeys<'_> {
+    type Response<'rdata> = PakeComputeSessionKeysResponse<'rdata>;
+}
+
+// ************* PakeVerifySessionKeys ************* //
+
+/// Verify the SPAKE2+ key confirmation message of the peer.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `id` | `CryptoObjectId` | no |
+/// | `TAG_2` | `peer_confirmation` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`PakeVerifySessionKeysResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `result` | `Se05XResult` | no |
+///
+/// Requires the applet features [`PAKE`](AppletConfig::PAKE), see [`Atr::applet_config`].
+///
+/// Reference: SE051 IoT applet APDU Specification, section "PAKEVerifySessionKeys"
+#[cfg(feature = "se051")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PakeVerifySessionKeys<'data> {
+    /// Serialized to TLV tag [`TAG_1`]()
+    pub id: CryptoObjectId,
+    /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub peer_confirmation: &'data [u8],
+}
+
+#[cfg(feature = "se051")]
+impl DataSource for PakeVerifySessionKeys<'_> {
+    fn len(&self) -> usize {
+        let id = &Tlv::new(TAG_1, self.id);
+        let peer_confirmation = &Tlv::new(TAG_2, self.peer_confirmation);
+        let __data: &[&dyn DataSource] = &[id, peer_confirmation];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_PAKE, P2_VERIFY, __data, 3);
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+#[cfg(feature = "se051")]
+impl<W: Writer> DataStream<W> for PakeVerifySessionKeys<'_> {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let id = &Tlv::new(TAG_1, self.id);
+        let peer_confirmation = &Tlv::new(TAG_2, self.peer_confirmation);
+        let __data: &[&dyn DataStream<W>] = &[id, peer_confirmation];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_PAKE, P2_VERIFY, __data, 3);
+        command.to_writer(writer)
+    }
+}
+#[cfg(feature = "se051")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PakeVerifySessionKeysResponse {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub result: Se05XResult,
+}
+
+#[cfg(feature = "se051")]
+impl<'data> Se05XResponse<'data> for PakeVerifySessionKeysResponse {
+    #[inline(never)]
+    fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
+        let (result, rem) = take_do_until(TAG_1, rem)?;
+        check_trailing_data(rem)?;
+        Ok(Self { result })
+    }
+}
+
+#[cfg(feature = "se051")]
+impl<W: Writer> Se05XCommand<W> for PakeVerifySessionKeys<'_> {
+    type Response<'rdata> = PakeVerifySessionKeysResponse;
+}
+
+// ************* DigestInit ************* //
+
+/// Start hashing data with the digest object `digest_id`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_2` | `digest_id` | `CryptoObjectId` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "DigestInit"
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DigestInit {
+    /// Serialized to TLV tag [`TAG_2`]()
+    pub digest_id: CryptoObjectId,
+}
+
+impl DataSource for DigestInit {
+    fn len(&self) -> usize {
+        let digest_id = &Tlv::new(TAG_2, self.digest_id);
+        let __data: &[&dyn DataSource] = &[digest_id];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_DEFAULT, P2_INIT, __data, 0);
+        command.len()
+    }
+    fn is_empty(&self) -> bool {
+        // Command always has a header
+        false
+    }
+}
+impl<W: Writer> DataStream<W> for DigestInit {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as iso7816::command::Writer>::Error> {
+        let digest_id = &Tlv::new(TAG_2, self.digest_id);
+        let __data: &[&dyn DataStream<W>] = &[digest_id];
+        let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_DEFAULT, P2_INIT, __data, 0);
+        command.to_writer(writer)
+    }
+}
+
+impl<W: Writer> Se05XCommand<W> for DigestInit {
+    type Response<'rdata> = ();
+}
+
+// ************* DigestUpdate ************* //
+
+/// Hash the next part of the data with the digest object `digest_id`.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_2` | `digest_id` | `CryptoObjectId` | no |
+/// | `TAG_3` | `data` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "DigestUpdate"
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DigestUpdate<'data> {
+    /// Serialized to TLV tag [`TAG_2`]()
+    pub digest_id: CryptoObjectId,
+    /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
+    pub data: &'data [u8],
+}
+
+impl DataSource for DigestUpdate<'_> {
+    fn len(&self) -> usize {
+        let digest_id = &Tlv::new(TAG_2, self.digest_id);
+        let data = &Tlv::new(TAG_3, self.data);
         let __data: &[&dyn DataSource] = &[digest_id, data];
         let command = CommandBuilder::new(NO_SM_CLA, INS_CRYPTO, P1_DEFAULT, P2_UPDATE, __data, 0);
         command.len()
@@ -4061,12 +6866,28 @@ impl<W: Writer> Se05XCommand<W> for DigestUpdate<'_> {
 
 // ************* DigestFinal ************* //
 
+/// Hash the last part of the data and return the digest, ending the operation.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_2` | `digest_id` | `CryptoObjectId` | no |
+/// | `TAG_3` | `data` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`DigestFinalResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `digest` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "DigestFinal"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DigestFinal<'data> {
     /// Serialized to TLV tag [`TAG_2`]()
     pub digest_id: CryptoObjectId,
     /// Serialized to TLV tag [`TAG_3`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
@@ -4107,8 +6928,10 @@ impl<W: Writer> DataStream<W> for DigestFinal<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DigestFinalResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub digest: &'data [u8],
 }
 
@@ -4116,23 +6939,57 @@ impl<'data> Se05XResponse<'data> for DigestFinalResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (digest, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { digest })
     }
 }
 
+/// Owned version of [`DigestFinalResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DigestFinalResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub digest: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl DigestFinalResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<DigestFinalResponseOwned<N>, Error> {
+        Ok(DigestFinalResponseOwned {
+            digest: owned_bytes(self.digest)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for DigestFinal<'_> {
     type Response<'rdata> = DigestFinalResponse<'rdata>;
 }
 
 // ************* DigestOneShot ************* //
 
+/// Hash data in a single command. The data must fit in a single APDU, use [`DigestInit`] and [`DigestUpdate`] for longer inputs.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `algo` | `Digest` | no |
+/// | `TAG_2` | `data` | `&'data [u8]` | no |
+///
+/// The response is parsed into [`DigestOneShotResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `digest` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "DigestOneShot"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DigestOneShot<'data> {
     /// Serialized to TLV tag [`TAG_1`]()
     pub algo: Digest,
     /// Serialized to TLV tag [`TAG_2`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
@@ -4173,8 +7030,10 @@ impl<W: Writer> DataStream<W> for DigestOneShot<'_> {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DigestOneShotResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub digest: &'data [u8],
 }
 
@@ -4182,19 +7041,47 @@ impl<'data> Se05XResponse<'data> for DigestOneShotResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (digest, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { digest })
     }
 }
 
+/// Owned version of [`DigestOneShotResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DigestOneShotResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub digest: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl DigestOneShotResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<DigestOneShotResponseOwned<N>, Error> {
+        Ok(DigestOneShotResponseOwned {
+            digest: owned_bytes(self.digest)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for DigestOneShot<'_> {
     type Response<'rdata> = DigestOneShotResponse<'rdata>;
 }
 
 // ************* GetVersion ************* //
 
+/// Get the version of the applet and its enabled features.
+///
+/// The response is parsed into [`GetVersionResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `version_info` | `VersionInfo` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "GetVersion"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetVersion {}
 
 impl DataSource for GetVersion {
@@ -4216,6 +7103,7 @@ impl<W: Writer> DataStream<W> for GetVersion {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetVersionResponse {
     /// Parsed from TLV tag [`TAG_1`]()
     pub version_info: VersionInfo,
@@ -4225,7 +7113,7 @@ impl<'data> Se05XResponse<'data> for GetVersionResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (version_info, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { version_info })
     }
 }
@@ -4236,8 +7124,18 @@ impl<W: Writer> Se05XCommand<W> for GetVersion {
 
 // ************* GetTimestamp ************* //
 
+/// Get the timestamp of the se05x: a counter increased at each boot and a time since boot.
+///
+/// The response is parsed into [`GetTimestampResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `timestamp` | `&'data [u8; 12]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "GetTimestamp"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetTimestamp {}
 
 impl DataSource for GetTimestamp {
@@ -4259,8 +7157,10 @@ impl<W: Writer> DataStream<W> for GetTimestamp {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetTimestampResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_byte_array_ref", borrow))]
     pub timestamp: &'data [u8; 12],
 }
 
@@ -4268,19 +7168,52 @@ impl<'data> Se05XResponse<'data> for GetTimestampResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (timestamp, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { timestamp })
     }
 }
 
+/// Owned version of [`GetTimestampResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GetTimestampResponseOwned {
+    /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    pub timestamp: [u8; 12],
+}
+
+#[cfg(feature = "owned")]
+impl GetTimestampResponse<'_> {
+    pub fn to_owned(&self) -> Result<GetTimestampResponseOwned, Error> {
+        Ok(GetTimestampResponseOwned {
+            timestamp: *self.timestamp,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for GetTimestamp {
     type Response<'rdata> = GetTimestampResponse<'rdata>;
 }
 
 // ************* GetFreeMemory ************* //
 
+/// Get the available bytes of `memory`, saturated at 0x7FFF.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `memory` | `Memory` | no |
+///
+/// The response is parsed into [`GetFreeMemoryResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `available` | `Be<u16>` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "GetFreeMemory"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetFreeMemory {
     /// Serialized to TLV tag [`TAG_1`]()
     pub memory: Memory,
@@ -4307,6 +7240,7 @@ impl<W: Writer> DataStream<W> for GetFreeMemory {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetFreeMemoryResponse {
     /// Parsed from TLV tag [`TAG_1`]()
     pub available: Be<u16>,
@@ -4316,7 +7250,7 @@ impl<'data> Se05XResponse<'data> for GetFreeMemoryResponse {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (available, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { available })
     }
 }
@@ -4327,8 +7261,22 @@ impl<W: Writer> Se05XCommand<W> for GetFreeMemory {
 
 // ************* GetRandom ************* //
 
+/// Get random bytes from the applet, `length` must fit in the response APDU.
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `length` | `Be<u16>` | no |
+///
+/// The response is parsed into [`GetRandomResponse`]:
+///
+/// | Tag | Field | Type | Optional |
+/// |-----|-------|------|----------|
+/// | `TAG_1` | `data` | `&'data [u8]` | no |
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "GetRandom"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetRandom {
     /// Serialized to TLV tag [`TAG_1`]()
     pub length: Be<u16>,
@@ -4369,8 +7317,10 @@ impl<W: Writer> DataStream<W> for GetRandom {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetRandomResponse<'data> {
     /// Parsed from TLV tag [`TAG_1`]()
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes", borrow))]
     pub data: &'data [u8],
 }
 
@@ -4378,19 +7328,43 @@ impl<'data> Se05XResponse<'data> for GetRandomResponse<'data> {
     #[inline(never)]
     fn from_response(rem: &'data [u8]) -> Result<Self, Error> {
         let (data, rem) = take_do_until(TAG_1, rem)?;
-        let _ = rem;
+        check_trailing_data(rem)?;
         Ok(Self { data })
     }
 }
 
+/// Owned version of [`GetRandomResponse`]
+#[cfg(feature = "owned")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GetRandomResponseOwned<const N: usize> {
+    /// Parsed from TLV tag [`TAG_1`]()
+    pub data: heapless::Vec<u8, N>,
+}
+
+#[cfg(feature = "owned")]
+impl GetRandomResponse<'_> {
+    pub fn to_owned<const N: usize>(&self) -> Result<GetRandomResponseOwned<N>, Error> {
+        Ok(GetRandomResponseOwned {
+            data: owned_bytes(self.data)?,
+        })
+    }
+}
+
 impl<W: Writer> Se05XCommand<W> for GetRandom {
     type Response<'rdata> = GetRandomResponse<'rdata>;
 }
 
 // ************* DeleteAll ************* //
 
+/// Delete all objects except the ones created by NXP, and reset the applet to its initial state.
+///
+/// The command must be sent in a session authenticated with [`ObjectId::FACTORY_RESET`].
+///
+/// Reference: AN12413 "SE05x IoT applet APDU Specification", section "DeleteAll"
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "builder", derive(typed_builder::TypedBuilder))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeleteAll {}
 
 impl DataSource for DeleteAll {
@@ -4429,3 +7403,982 @@ impl<W: Writer> DataStream<W> for DeleteAll {
 impl<W: Writer> Se05XCommand<W> for DeleteAll {
     type Response<'rdata> = ();
 }
+
+// ************* Test vectors ************* //
+
+/// Sample of each command with its expected APDU, computed from the constants of `se05x.rs`
+#[cfg(feature = "test-vectors")]
+pub const TEST_VECTORS: &[test_vectors::TestVector] = &[
+    test_vectors::TestVector {
+        name: "CreateSession",
+        encode: || {
+            test_vectors::encode(&CreateSession {
+                object_id: ObjectId([1; 4]),
+            })
+        },
+        expected: &hex!("8004001B064104010101010C"),
+    },
+    test_vectors::TestVector {
+        name: "ExchangeSessionData",
+        encode: || {
+            test_vectors::encode(&ExchangeSessionData {
+                session_policy: SessionPolicy::new().max_apdu(Some(1)),
+                c_mac: &[2, 2, 2],
+            })
+        },
+        expected: &hex!("8004001F09410480000001020202"),
+    },
+    test_vectors::TestVector {
+        name: "RefreshSession",
+        encode: || {
+            test_vectors::encode(&RefreshSession {
+                policy: Some(SessionPolicy::new().max_apdu(Some(1))),
+            })
+        },
+        expected: &hex!("8004001E06110480000001"),
+    },
+    test_vectors::TestVector {
+        name: "CloseSession",
+        encode: || test_vectors::encode(&CloseSession {}),
+        expected: &hex!("8004001C"),
+    },
+    test_vectors::TestVector {
+        name: "VerifySessionUserId",
+        encode: || {
+            test_vectors::encode(&VerifySessionUserId {
+                user_id: &[1, 1, 1],
+            })
+        },
+        expected: &hex!("8004002C054103010101"),
+    },
+    test_vectors::TestVector {
+        name: "ScpInitializeUpdate",
+        encode: || {
+            test_vectors::encode(&ScpInitializeUpdate {
+                host_challenge: [1; 8],
+            })
+        },
+        expected: &hex!("8050000008010101010101010100"),
+    },
+    test_vectors::TestVector {
+        name: "ScpExternalAuthenticate",
+        encode: || {
+            test_vectors::encode(&ScpExternalAuthenticate {
+                security_level: SecurityLevel::None,
+                host_cryptogram: [1; 8],
+                mac: [2; 8],
+            })
+        },
+        expected: &hex!("848200001001010101010101010202020202020202"),
+    },
+    test_vectors::TestVector {
+        name: "SetLockState",
+        encode: || {
+            test_vectors::encode(&SetLockState {
+                lock_indicator: TransientIndicator::Transient,
+                lock_state: LockState::Locked,
+                unlock_response: Some(&[3; 16]),
+            })
+        },
+        expected: &hex!("8004003E18410101420101431003030303030303030303030303030303"),
+    },
+    test_vectors::TestVector {
+        name: "GetUnlockChallenge",
+        encode: || test_vectors::encode(&GetUnlockChallenge {}),
+        expected: &hex!("80040033000000"),
+    },
+    test_vectors::TestVector {
+        name: "SetAppletFeatures",
+        encode: || {
+            test_vectors::encode(&SetAppletFeatures {
+                features: AppletConfig::from_bits_retain(1),
+            })
+        },
+        expected: &hex!("8004003F0441020001"),
+    },
+    test_vectors::TestVector {
+        name: "WriteEcKey",
+        encode: || {
+            test_vectors::encode(&WriteEcKey {
+                transient: true,
+                is_auth: true,
+                key_type: Some(P1KeyType::Public),
+                policy: Some(PolicySet(&[Policy {
+                    object_id: ObjectId([1; 4]),
+                    access_rule: ObjectAccessRule::from_flags(ObjectPolicyFlags::ALLOW_READ),
+                }])),
+                max_attempts: Some(Be(2)),
+                object_id: ObjectId([3; 4]),
+                curve: Some(EcCurve::NistP192),
+                private_key: Some(&[5, 5, 5]),
+                public_key: Some(&[6, 6, 6]),
+            })
+        },
+        expected: &hex!(
+            "80C1210022110908010101010020000012020002410403030303420101430305"
+            "05054403060606"
+        ),
+    },
+    test_vectors::TestVector {
+        name: "GenEcKey",
+        encode: || {
+            test_vectors::encode(&GenEcKey {
+                transient: true,
+                is_auth: true,
+                policy: Some(PolicySet(&[Policy {
+                    object_id: ObjectId([1; 4]),
+                    access_rule: ObjectAccessRule::from_flags(ObjectPolicyFlags::ALLOW_READ),
+                }])),
+                max_attempts: Some(Be(2)),
+                object_id: ObjectId([3; 4]),
+                curve: Some(EcCurve::NistP192),
+            })
+        },
+        expected: &hex!("80C1610018110908010101010020000012020002410403030303420101"),
+    },
+    #[cfg(feature = "rsa")]
+    test_vectors::TestVector {
+        name: "WriteRsaKey",
+        encode: || {
+            test_vectors::encode(&WriteRsaKey {
+                transient: true,
+                is_auth: true,
+                key_type: Some(P1KeyType::Public),
+                key_format: Some(RsaFormat::Raw),
+                policy: Some(PolicySet(&[Policy {
+                    object_id: ObjectId([1; 4]),
+                    access_rule: ObjectAccessRule::from_flags(ObjectPolicyFlags::ALLOW_READ),
+                }])),
+                max_attempts: Some(Be(2)),
+                object_id: ObjectId([3; 4]),
+                key_size: Some(Be(4)),
+                p: Some(&[5, 5, 5]),
+                q: Some(&[6, 6, 6]),
+                dp: Some(&[7, 7, 7]),
+                dq: Some(&[8, 8, 8]),
+                inv_q: Some(&[9, 9, 9]),
+                e: Some(&[10, 10, 10]),
+                d: Some(&[11, 11, 11]),
+                n: Some(&[12, 12, 12]),
+            })
+        },
+        expected: &hex!(
+            "80C1224F41110908010101010020000012020002410403030303420200044303"
+            "050505440306060645030707074603080808470309090948030A0A0A49030B0B"
+            "0B4A030C0C0C"
+        ),
+    },
+    #[cfg(feature = "rsa")]
+    test_vectors::TestVector {
+        name: "GenRsaKey",
+        encode: || {
+            test_vectors::encode(&GenRsaKey {
+                transient: true,
+                is_auth: true,
+                policy: Some(PolicySet(&[Policy {
+                    object_id: ObjectId([1; 4]),
+                    access_rule: ObjectAccessRule::from_flags(ObjectPolicyFlags::ALLOW_READ),
+                }])),
+                max_attempts: Some(Be(2)),
+                object_id: ObjectId([3; 4]),
+                key_size: Some(Be(4)),
+            })
+        },
+        expected: &hex!("80C1624F1911090801010101002000001202000241040303030342020004"),
+    },
+    test_vectors::TestVector {
+        name: "WriteSymmKey",
+        encode: || {
+            test_vectors::encode(&WriteSymmKey {
+                key_type: SymmKeyType::Aes,
+                transient: true,
+                is_auth: true,
+                policy: Some(PolicySet(&[Policy {
+                    object_id: ObjectId([1; 4]),
+                    access_rule: ObjectAccessRule::from_flags(ObjectPolicyFlags::ALLOW_READ),
+                }])),
+                max_attempts: Some(Be(2)),
+                object_id: ObjectId([3; 4]),
+                kek_id: Some(ObjectId([4; 4])),
+                value: &[5, 5, 5],
+            })
+        },
+        expected: &hex!(
+            "80C1030020110908010101010020000012020002410403030303420404040404"
+            "4303050505"
+        ),
+    },
+    test_vectors::TestVector {
+        name: "WriteBinary",
+        encode: || {
+            test_vectors::encode(&WriteBinary {
+                transient: true,
+                policy: Some(PolicySet(&[Policy {
+                    object_id: ObjectId([1; 4]),
+                    access_rule: ObjectAccessRule::from_flags(ObjectPolicyFlags::ALLOW_READ),
+                }])),
+                object_id: ObjectId([2; 4]),
+                offset: Some(Be(3)),
+                file_length: Some(Be(4)),
+                data: Some(&[5, 5, 5]),
+            })
+        },
+        expected: &hex!(
+            "808106001E110908010101010020000041040202020242020003430200044403"
+            "050505"
+        ),
+    },
+    test_vectors::TestVector {
+        name: "WriteUserId",
+        encode: || {
+            test_vectors::encode(&WriteUserId {
+                policy: Some(PolicySet(&[Policy {
+                    object_id: ObjectId([1; 4]),
+                    access_rule: ObjectAccessRule::from_flags(ObjectPolicyFlags::ALLOW_READ),
+                }])),
+                max_attempts: Some(Be(2)),
+                object_id: ObjectId([3; 4]),
+                data: &[4, 4, 4],
+            })
+        },
+        expected: &hex!("804107001911090801010101002000001201024104030303034203040404"),
+    },
+    test_vectors::TestVector {
+        name: "WriteCounter",
+        encode: || {
+            test_vectors::encode(&WriteCounter {
+                transient: true,
+                policy: Some(PolicySet(&[Policy {
+                    object_id: ObjectId([1; 4]),
+                    access_rule: ObjectAccessRule::from_flags(ObjectPolicyFlags::ALLOW_READ),
+                }])),
+                object_id: ObjectId([2; 4]),
+                data: Some(CounterSize::B1),
+                value: Some(Be(4)),
+            })
+        },
+        expected: &hex!(
+            "808108001F110908010101010020000041040202020242020001430800000000"
+            "00000004"
+        ),
+    },
+    test_vectors::TestVector {
+        name: "WritePcr",
+        encode: || {
+            test_vectors::encode(&WritePcr {
+                transient: true,
+                policy: Some(PolicySet(&[Policy {
+                    object_id: ObjectId([1; 4]),
+                    access_rule: ObjectAccessRule::from_flags(ObjectPolicyFlags::ALLOW_READ),
+                }])),
+                object_id: ObjectId([2; 4]),
+                initial_value: Some(&[3, 3, 3]),
+                extend: Some(&[4, 4, 4]),
+            })
+        },
+        expected: &hex!("808109001B110908010101010020000041040202020242030303034303040404"),
+    },
+    test_vectors::TestVector {
+        name: "ImportObject",
+        encode: || {
+            test_vectors::encode(&ImportObject {
+                transient: true,
+                object_id: ObjectId([1; 4]),
+                rsa_key_component: Some(RsaKeyComponent::Mod),
+                serialized_object: &[3, 3, 3],
+            })
+        },
+        expected: &hex!("808100180E4104010101014201004303030303"),
+    },
+    test_vectors::TestVector {
+        name: "ReadObject",
+        encode: || {
+            test_vectors::encode(&ReadObject {
+                object_id: ObjectId([1; 4]),
+                offset: Some(Be(2)),
+                length: Some(Be(3)),
+                rsa_key_component: Some(RsaKeyComponent::Mod),
+            })
+        },
+        expected: &hex!("8002000000001141040101010142020002430200034401000000"),
+    },
+    test_vectors::TestVector {
+        name: "ReadAttestObject",
+        encode: || {
+            test_vectors::encode(&ReadAttestObject {
+                object_id: ObjectId([1; 4]),
+                offset: Some(Be(2)),
+                length: Some(Be(3)),
+                rsa_key_component: Some(RsaKeyComponent::Mod),
+                attestation_object: ObjectId([5; 4]),
+                attestation_algo: AttestationAlgo::ECdsaSha,
+                freshness_random: &[7; 16],
+            })
+        },
+        expected: &hex!(
+            "802200002C410401010101420200024302000344010045040505050546011147"
+            "1007070707070707070707070707070707"
+        ),
+    },
+    test_vectors::TestVector {
+        name: "ReadAttributes",
+        encode: || {
+            test_vectors::encode(&ReadAttributes {
+                object_id: ObjectId([1; 4]),
+                offset: Some(Be(2)),
+                length: Some(Be(3)),
+                rsa_key_component: Some(&[4, 4, 4]),
+            })
+        },
+        expected: &hex!("8002003B1341040101010142020002430200034403040404"),
+    },
+    test_vectors::TestVector {
+        name: "ReadAttributesAttest",
+        encode: || {
+            test_vectors::encode(&ReadAttributesAttest {
+                object_id: ObjectId([1; 4]),
+                offset: Some(Be(2)),
+                length: Some(Be(3)),
+                rsa_key_component: Some(&[4, 4, 4]),
+                attestation_object: ObjectId([5; 4]),
+                attestation_algo: AttestationAlgo::ECdsaSha,
+                freshness_random: Some(&[7; 16]),
+            })
+        },
+        expected: &hex!(
+            "8022003B2E410401010101420200024302000344030404044504050505054601"
+            "11471007070707070707070707070707070707"
+        ),
+    },
+    test_vectors::TestVector {
+        name: "ExportObject",
+        encode: || {
+            test_vectors::encode(&ExportObject {
+                object_id: ObjectId([1; 4]),
+                rsa_key_component: RsaKeyComponent::Mod,
+            })
+        },
+        expected: &hex!("800200190000094104010101014201000100"),
+    },
+    test_vectors::TestVector {
+        name: "ReadType",
+        encode: || {
+            test_vectors::encode(&ReadType {
+                object_id: ObjectId([1; 4]),
+            })
+        },
+        expected: &hex!("800200260000064104010101010000"),
+    },
+    test_vectors::TestVector {
+        name: "ReadSize",
+        encode: || {
+            test_vectors::encode(&ReadSize {
+                object_id: ObjectId([1; 4]),
+            })
+        },
+        expected: &hex!("800200070000064104010101010000"),
+    },
+    test_vectors::TestVector {
+        name: "ReadIdList",
+        encode: || {
+            test_vectors::encode(&ReadIdList {
+                offset: Be(1),
+                filter: SecureObjectFilter::EcKeyPair,
+            })
+        },
+        expected: &hex!("80020025000007410200014201010000"),
+    },
+    test_vectors::TestVector {
+        name: "CheckObjectExists",
+        encode: || {
+            test_vectors::encode(&CheckObjectExists {
+                object_id: ObjectId([1; 4]),
+            })
+        },
+        expected: &hex!("800400270000064104010101010000"),
+    },
+    test_vectors::TestVector {
+        name: "DeleteSecureObject",
+        encode: || {
+            test_vectors::encode(&DeleteSecureObject {
+                object_id: ObjectId([1; 4]),
+            })
+        },
+        expected: &hex!("8004002806410401010101"),
+    },
+    test_vectors::TestVector {
+        name: "CreateEcCurve",
+        encode: || {
+            test_vectors::encode(&CreateEcCurve {
+                curve: EcCurve::NistP192,
+            })
+        },
+        expected: &hex!("80010B0403410101"),
+    },
+    test_vectors::TestVector {
+        name: "SetEcCurveParam",
+        encode: || {
+            test_vectors::encode(&SetEcCurveParam {
+                curve: EcCurve::NistP192,
+                param: EcCurveParam::ParamA,
+                value: &[3, 3, 3],
+            })
+        },
+        expected: &hex!("80010B400B4101014201014303030303"),
+    },
+    test_vectors::TestVector {
+        name: "GetEcCurveId",
+        encode: || {
+            test_vectors::encode(&GetEcCurveId {
+                object_id: ObjectId([1; 4]),
+            })
+        },
+        expected: &hex!("80020B3606410401010101"),
+    },
+    test_vectors::TestVector {
+        name: "ReadEcCurveList",
+        encode: || test_vectors::encode(&ReadEcCurveList {}),
+        expected: &hex!("80020B25"),
+    },
+    test_vectors::TestVector {
+        name: "DeleteEcCurve",
+        encode: || {
+            test_vectors::encode(&DeleteEcCurve {
+                curve: EcCurve::NistP192,
+            })
+        },
+        expected: &hex!("80040B2803410101"),
+    },
+    test_vectors::TestVector {
+        name: "CreateDigestObject",
+        encode: || {
+            test_vectors::encode(&CreateDigestObject {
+                id: CryptoObjectId([1; 2]),
+                subtype: Digest::NoHash,
+            })
+        },
+        expected: &hex!("800110000A41020101420101430100"),
+    },
+    test_vectors::TestVector {
+        name: "CreateCipherObject",
+        encode: || {
+            test_vectors::encode(&CreateCipherObject {
+                id: CryptoObjectId([1; 2]),
+                subtype: CipherMode::AesEcbNopad,
+            })
+        },
+        expected: &hex!("800110000A4102010142010243010E"),
+    },
+    test_vectors::TestVector {
+        name: "CreateSignatureObject",
+        encode: || {
+            test_vectors::encode(&CreateSignatureObject {
+                id: CryptoObjectId([1; 2]),
+                subtype: MacAlgo::HmacSha1,
+            })
+        },
+        expected: &hex!("800110000A41020101420103430118"),
+    },
+    test_vectors::TestVector {
+        name: "ReadCryptoObjList",
+        encode: || test_vectors::encode(&ReadCryptoObjList {}),
+        expected: &hex!("80021025"),
+    },
+    test_vectors::TestVector {
+        name: "DeleteCryptoObj",
+        encode: || {
+            test_vectors::encode(&DeleteCryptoObj {
+                id: CryptoObjectId([1; 2]),
+            })
+        },
+        expected: &hex!("800410280441020101"),
+    },
+    test_vectors::TestVector {
+        name: "EcdsaSign",
+        encode: || {
+            test_vectors::encode(&EcdsaSign {
+                key_id: ObjectId([1; 4]),
+                algo: EcDsaSignatureAlgo::Plain,
+                data: &[3, 3, 3],
+            })
+        },
+        expected: &hex!("80030C0900000E41040101010142010943030303030000"),
+    },
+    test_vectors::TestVector {
+        name: "EddsaSign",
+        encode: || {
+            test_vectors::encode(&EddsaSign {
+                key_id: ObjectId([1; 4]),
+                algo: EdDsaSignatureAlgo::Pure,
+                data: &[3, 3, 3],
+            })
+        },
+        expected: &hex!("80030C0900000E4104010101014201A343030303030000"),
+    },
+    test_vectors::TestVector {
+        name: "EcdaaSign",
+        encode: || {
+            test_vectors::encode(&EcdaaSign {
+                key_id: ObjectId([1; 4]),
+                data: [2; 32],
+                random_data: [3; 32],
+            })
+        },
+        expected: &hex!(
+            "80030C0900004D4104010101014201F443200202020202020202020202020202"
+            "0202020202020202020202020202020202024420030303030303030303030303"
+            "03030303030303030303030303030303030303030000"
+        ),
+    },
+    test_vectors::TestVector {
+        name: "EcdsaVerify",
+        encode: || {
+            test_vectors::encode(&EcdsaVerify {
+                key_id: ObjectId([1; 4]),
+                algo: EcDsaSignatureAlgo::Plain,
+                data: &[3, 3, 3],
+                signature: &[4, 4, 4],
+            })
+        },
+        expected: &hex!("80030C0A134104010101014201094303030303450304040403"),
+    },
+    test_vectors::TestVector {
+        name: "EddsaVerify",
+        encode: || {
+            test_vectors::encode(&EddsaVerify {
+                key_id: ObjectId([1; 4]),
+                algo: EdDsaSignatureAlgo::Pure,
+                data: &[3, 3, 3],
+                signature: &[4, 4, 4],
+            })
+        },
+        expected: &hex!("80030C0A134104010101014201A34303030303450304040403"),
+    },
+    test_vectors::TestVector {
+        name: "EcdhGenerateSharedSecret",
+        encode: || {
+            test_vectors::encode(&EcdhGenerateSharedSecret {
+                key_id: ObjectId([1; 4]),
+                public_key: &[2, 2, 2],
+            })
+        },
+        expected: &hex!("8003010F00000B41040101010142030202020000"),
+    },
+    test_vectors::TestVector {
+        name: "EcdhStoreSharedSecret",
+        encode: || {
+            test_vectors::encode(&EcdhStoreSharedSecret {
+                key_id: ObjectId([1; 4]),
+                public_key: &[2, 2, 2],
+                target: ObjectId([3; 4]),
+            })
+        },
+        expected: &hex!("8003010F114104010101014203020202470403030303"),
+    },
+    #[cfg(feature = "rsa")]
+    test_vectors::TestVector {
+        name: "RsaSign",
+        encode: || {
+            test_vectors::encode(&RsaSign {
+                key_id: ObjectId([1; 4]),
+                algo: RsaSignatureAlgo::RsaSha1Pkcs1Pss,
+                data: &[3, 3, 3],
+            })
+        },
+        expected: &hex!("80030C0900000E41040101010142011543030303030000"),
+    },
+    #[cfg(feature = "rsa")]
+    test_vectors::TestVector {
+        name: "RsaVerify",
+        encode: || {
+            test_vectors::encode(&RsaVerify {
+                key_id: ObjectId([1; 4]),
+                algo: RsaSignatureAlgo::RsaSha1Pkcs1Pss,
+                data: &[3, 3, 3],
+                signature: &[4, 4, 4],
+            })
+        },
+        expected: &hex!("80030C0A134104010101014201154303030303450304040403"),
+    },
+    #[cfg(feature = "rsa")]
+    test_vectors::TestVector {
+        name: "RsaEncrypt",
+        encode: || {
+            test_vectors::encode(&RsaEncrypt {
+                key_id: ObjectId([1; 4]),
+                algo: RsaEncryptionAlgo::NoPad,
+                plaintext: &[3, 3, 3],
+            })
+        },
+        expected: &hex!("8003023700000E41040101010142010C43030303030000"),
+    },
+    #[cfg(feature = "rsa")]
+    test_vectors::TestVector {
+        name: "RsaDecrypt",
+        encode: || {
+            test_vectors::encode(&RsaDecrypt {
+                key_id: ObjectId([1; 4]),
+                algo: RsaEncryptionAlgo::NoPad,
+                ciphertext: &[3, 3, 3],
+            })
+        },
+        expected: &hex!("8003023800000E41040101010142010C43030303030000"),
+    },
+    test_vectors::TestVector {
+        name: "CipherEncryptInit",
+        encode: || {
+            test_vectors::encode(&CipherEncryptInit {
+                key_id: ObjectId([1; 4]),
+                cipher_id: CryptoObjectId([2; 2]),
+                initialization_vector: Some(&[3, 3, 3]),
+            })
+        },
+        expected: &hex!("80030E420F410401010101420202024403030303"),
+    },
+    test_vectors::TestVector {
+        name: "CipherDecryptInit",
+        encode: || {
+            test_vectors::encode(&CipherDecryptInit {
+                key_id: ObjectId([1; 4]),
+                cipher_id: CryptoObjectId([2; 2]),
+                initialization_vector: Some(&[3, 3, 3]),
+            })
+        },
+        expected: &hex!("80030E430F410401010101420202024403030303"),
+    },
+    test_vectors::TestVector {
+        name: "CipherUpdate",
+        encode: || {
+            test_vectors::encode(&CipherUpdate {
+                cipher_id: CryptoObjectId([1; 2]),
+                data: &[2, 2, 2],
+            })
+        },
+        expected: &hex!("80030E0C0000094202010143030202020000"),
+    },
+    test_vectors::TestVector {
+        name: "CipherFinal",
+        encode: || {
+            test_vectors::encode(&CipherFinal {
+                cipher_id: CryptoObjectId([1; 2]),
+                data: &[2, 2, 2],
+            })
+        },
+        expected: &hex!("80030E0D0000094202010143030202020000"),
+    },
+    test_vectors::TestVector {
+        name: "CipherOneShotEncrypt",
+        encode: || {
+            test_vectors::encode(&CipherOneShotEncrypt {
+                key_id: ObjectId([1; 4]),
+                mode: CipherMode::AesEcbNopad,
+                plaintext: &[3, 3, 3],
+                initialization_vector: Some(&[4, 4, 4]),
+            })
+        },
+        expected: &hex!("80030E3700001341040101010142010E430303030344030404040000"),
+    },
+    test_vectors::TestVector {
+        name: "CipherOneShotDecrypt",
+        encode: || {
+            test_vectors::encode(&CipherOneShotDecrypt {
+                key_id: ObjectId([1; 4]),
+                mode: CipherMode::AesEcbNopad,
+                ciphertext: &[3, 3, 3],
+                initialization_vector: Some(&[4, 4, 4]),
+            })
+        },
+        expected: &hex!("80030E3800001341040101010142010E430303030344030404040000"),
+    },
+    test_vectors::TestVector {
+        name: "MacGenerateInit",
+        encode: || {
+            test_vectors::encode(&MacGenerateInit {
+                key_id: ObjectId([1; 4]),
+                mac_id: CryptoObjectId([2; 2]),
+            })
+        },
+        expected: &hex!("80030D030A41040101010142020202"),
+    },
+    test_vectors::TestVector {
+        name: "MacValidateInit",
+        encode: || {
+            test_vectors::encode(&MacValidateInit {
+                key_id: ObjectId([1; 4]),
+                mac_id: CryptoObjectId([2; 2]),
+            })
+        },
+        expected: &hex!("80030D440A41040101010142020202"),
+    },
+    test_vectors::TestVector {
+        name: "MacUpdate",
+        encode: || {
+            test_vectors::encode(&MacUpdate {
+                data: &[1, 1, 1],
+                mac_id: CryptoObjectId([2; 2]),
+            })
+        },
+        expected: &hex!("80030D0C09410301010142020202"),
+    },
+    test_vectors::TestVector {
+        name: "MacGenerateFinal",
+        encode: || {
+            test_vectors::encode(&MacGenerateFinal {
+                data: &[1, 1, 1],
+                mac_id: CryptoObjectId([2; 2]),
+            })
+        },
+        expected: &hex!("80030D0D0000094103010101420202020000"),
+    },
+    test_vectors::TestVector {
+        name: "MacValidateFinal",
+        encode: || {
+            test_vectors::encode(&MacValidateFinal {
+                data: &[1, 1, 1],
+                mac_id: CryptoObjectId([2; 2]),
+                tag: &[3, 3, 3],
+            })
+        },
+        expected: &hex!("80030D0D00000E41030101014202020243030303030000"),
+    },
+    test_vectors::TestVector {
+        name: "MacOneShotGenerate",
+        encode: || {
+            test_vectors::encode(&MacOneShotGenerate {
+                key_id: ObjectId([1; 4]),
+                algo: MacAlgo::HmacSha1,
+                data: &[3, 3, 3],
+            })
+        },
+        expected: &hex!("80030D4500000E41040101010142011843030303030000"),
+    },
+    test_vectors::TestVector {
+        name: "MacOneShotValidate",
+        encode: || {
+            test_vectors::encode(&MacOneShotValidate {
+                key_id: ObjectId([1; 4]),
+                algo: MacAlgo::HmacSha1,
+                data: &[3, 3, 3],
+                tag: &[4, 4, 4],
+            })
+        },
+        expected: &hex!("80030D46000013410401010101420118430303030345030404040000"),
+    },
+    test_vectors::TestVector {
+        name: "Hkdf",
+        encode: || {
+            test_vectors::encode(&Hkdf {
+                ikm: ObjectId([1; 4]),
+                digest: Digest::NoHash,
+                salt: Some(&[3, 3, 3]),
+                info: Some(&[4, 4, 4]),
+                requested_len: Be(5),
+                target: Some(ObjectId([6; 4])),
+            })
+        },
+        expected: &hex!(
+            "8003002D00001D41040101010142010043030303034403040404450200054804"
+            "060606060000"
+        ),
+    },
+    test_vectors::TestVector {
+        name: "Pbkdf2",
+        encode: || {
+            test_vectors::encode(&Pbkdf2 {
+                password: ObjectId([1; 4]),
+                salt: Some(&[2, 2, 2]),
+                iterations: Be(3),
+                requested_len: Be(4),
+            })
+        },
+        expected: &hex!("8003002E000013410401010101420302020243020003440200040000"),
+    },
+    test_vectors::TestVector {
+        name: "I2CMasterExecuteCommandSet",
+        encode: || {
+            test_vectors::encode(&I2CMasterExecuteCommandSet {
+                command_set: &[1, 1, 1],
+            })
+        },
+        expected: &hex!("8003003000000541030101010000"),
+    },
+    test_vectors::TestVector {
+        name: "I2CMasterExecuteCommandSetAttested",
+        encode: || {
+            test_vectors::encode(&I2CMasterExecuteCommandSetAttested {
+                command_set: &[1, 1, 1],
+                attestation_object: ObjectId([2; 4]),
+                attestation_algo: AttestationAlgo::ECdsaSha,
+                freshness_random: &[4; 16],
+            })
+        },
+        expected: &hex!(
+            "8003003100002041030101014204020202024301114710040404040404040404"
+            "040404040404040000"
+        ),
+    },
+    test_vectors::TestVector {
+        name: "TlsCalculatePreMasterSecret",
+        encode: || {
+            test_vectors::encode(&TlsCalculatePreMasterSecret {
+                psk_id: ObjectId([1; 4]),
+                key_pair_id: Some(ObjectId([2; 4])),
+                hmac_key_id: ObjectId([3; 4]),
+                input_data: Some(&[4, 4, 4]),
+            })
+        },
+        expected: &hex!("80030F4A174104010101014204020202024304030303034403040404"),
+    },
+    test_vectors::TestVector {
+        name: "TlsPerformPrf",
+        encode: || {
+            test_vectors::encode(&TlsPerformPrf {
+                prf_type: TlsPrfType::ClientHello,
+                hmac_key_id: ObjectId([1; 4]),
+                digest: Digest::NoHash,
+                label: &[3, 3, 3],
+                random: &[4, 4, 4],
+                requested_len: Be(5),
+            })
+        },
+        expected: &hex!("80030F4B00001741040101010142010043030303034403040404450200050000"),
+    },
+    #[cfg(feature = "se051")]
+    test_vectors::TestVector {
+        name: "PakeConfigDevice",
+        encode: || {
+            test_vectors::encode(&PakeConfigDevice {
+                id: CryptoObjectId([1; 2]),
+                device_type: Spake2PlusDeviceType::A,
+            })
+        },
+        expected: &hex!("800313040741020101420101"),
+    },
+    #[cfg(feature = "se051")]
+    test_vectors::TestVector {
+        name: "PakeInitDevice",
+        encode: || {
+            test_vectors::encode(&PakeInitDevice {
+                id: CryptoObjectId([1; 2]),
+                context: &[2, 2, 2],
+                prover_id: &[3, 3, 3],
+                verifier_id: &[4, 4, 4],
+            })
+        },
+        expected: &hex!("8003130B1341020101420302020243030303034403040404"),
+    },
+    #[cfg(feature = "se051")]
+    test_vectors::TestVector {
+        name: "PakeInitCredentials",
+        encode: || {
+            test_vectors::encode(&PakeInitCredentials {
+                id: CryptoObjectId([1; 2]),
+                w0: ObjectId([2; 4]),
+                w1: Some(ObjectId([3; 4])),
+                l: Some(ObjectId([4; 4])),
+            })
+        },
+        expected: &hex!("800313401641020101420402020202430403030303440404040404"),
+    },
+    #[cfg(feature = "se051")]
+    test_vectors::TestVector {
+        name: "PakeComputeKeyShare",
+        encode: || {
+            test_vectors::encode(&PakeComputeKeyShare {
+                id: CryptoObjectId([1; 2]),
+                peer_key_share: Some(&[2, 2, 2]),
+            })
+        },
+        expected: &hex!("800313030000094102010142030202020000"),
+    },
+    #[cfg(feature = "se051")]
+    test_vectors::TestVector {
+        name: "PakeComputeSessionKeys",
+        encode: || {
+            test_vectors::encode(&PakeComputeSessionKeys {
+                id: CryptoObjectId([1; 2]),
+                peer_key_share: &[2, 2, 2],
+            })
+        },
+        expected: &hex!("8003130F0000094102010142030202020000"),
+    },
+    #[cfg(feature = "se051")]
+    test_vectors::TestVector {
+        name: "PakeVerifySessionKeys",
+        encode: || {
+            test_vectors::encode(&PakeVerifySessionKeys {
+                id: CryptoObjectId([1; 2]),
+                peer_confirmation: &[2, 2, 2],
+            })
+        },
+        expected: &hex!("8003130A0941020101420302020203"),
+    },
+    test_vectors::TestVector {
+        name: "DigestInit",
+        encode: || {
+            test_vectors::encode(&DigestInit {
+                digest_id: CryptoObjectId([1; 2]),
+            })
+        },
+        expected: &hex!("8003000B0442020101"),
+    },
+    test_vectors::TestVector {
+        name: "DigestUpdate",
+        encode: || {
+            test_vectors::encode(&DigestUpdate {
+                digest_id: CryptoObjectId([1; 2]),
+                data: &[2, 2, 2],
+            })
+        },
+        expected: &hex!("8003000C09420201014303020202"),
+    },
+    test_vectors::TestVector {
+        name: "DigestFinal",
+        encode: || {
+            test_vectors::encode(&DigestFinal {
+                digest_id: CryptoObjectId([1; 2]),
+                data: &[2, 2, 2],
+            })
+        },
+        expected: &hex!("8003000D0000094202010143030202020000"),
+    },
+    test_vectors::TestVector {
+        name: "DigestOneShot",
+        encode: || {
+            test_vectors::encode(&DigestOneShot {
+                algo: Digest::NoHash,
+                data: &[2, 2, 2],
+            })
+        },
+        expected: &hex!("8003000E00000841010042030202020000"),
+    },
+    test_vectors::TestVector {
+        name: "GetVersion",
+        encode: || test_vectors::encode(&GetVersion {}),
+        expected: &hex!("800400200B"),
+    },
+    test_vectors::TestVector {
+        name: "GetTimestamp",
+        encode: || test_vectors::encode(&GetTimestamp {}),
+        expected: &hex!("8004003D14"),
+    },
+    test_vectors::TestVector {
+        name: "GetFreeMemory",
+        encode: || {
+            test_vectors::encode(&GetFreeMemory {
+                memory: Memory::Persistent,
+            })
+        },
+        expected: &hex!("800400220341010106"),
+    },
+    test_vectors::TestVector {
+        name: "GetRandom",
+        encode: || test_vectors::encode(&GetRandom { length: Be(1) }),
+        expected: &hex!("80040049000004410200010000"),
+    },
+    test_vectors::TestVector {
+        name: "DeleteAll",
+        encode: || test_vectors::encode(&DeleteAll {}),
+        expected: &hex!("8004002A000000"),
+    },
+];
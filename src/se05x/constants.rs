@@ -2,6 +2,9 @@
 // SPDX-License-Identifier: LGPL-3.0-only
 
 //! Copied from [plug-and-trust/se05x_ecc_curves_values.h](https://github.com/NXP/plug-and-trust/blob/master/hostlib/hostLib/inc/se05x_ecc_curves_values.h)
+//!
+//! Each `*_INITIALIZER` is only available with the `curve-*` feature of its curve.
+//! The `all-curves` default feature enables all of them: disable it and select the curves in use to keep the other parameters out of [`EcCurve::params`].
 
 use hex_literal::hex;
 
@@ -26,70 +29,87 @@ pub struct CurveInitializer {
     pub curve: EcCurve,
 }
 
+#[cfg(feature = "curve-nist-p192")]
 pub const PRIME192V1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::NistP192,
     constants: PRIME192V1,
 };
+#[cfg(feature = "curve-nist-p224")]
 pub const SECP224R1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::NistP224,
     constants: SECP224R1,
 };
+#[cfg(feature = "curve-nist-p256")]
 pub const PRIME256V1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::NistP256,
     constants: PRIME256V1,
 };
+#[cfg(feature = "curve-nist-p384")]
 pub const SECP384R1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::NistP384,
     constants: SECP384R1,
 };
+#[cfg(feature = "curve-nist-p521")]
 pub const SECP521R1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::NistP521,
     constants: SECP521R1,
 };
+#[cfg(feature = "curve-brainpool160")]
 pub const BRAINPOOL_P160R1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::Brainpool160,
     constants: BRAINPOOL_P160R1,
 };
+#[cfg(feature = "curve-brainpool192")]
 pub const BRAINPOOL_P192R1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::Brainpool192,
     constants: BRAINPOOL_P192R1,
 };
+#[cfg(feature = "curve-brainpool224")]
 pub const BRAINPOOL_P224R1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::Brainpool224,
     constants: BRAINPOOL_P224R1,
 };
+#[cfg(feature = "curve-brainpool256")]
 pub const BRAINPOOL_P256R1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::Brainpool256,
     constants: BRAINPOOL_P256R1,
 };
+#[cfg(feature = "curve-brainpool320")]
 pub const BRAINPOOL_P320R1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::Brainpool320,
     constants: BRAINPOOL_P320R1,
 };
+#[cfg(feature = "curve-brainpool384")]
 pub const BRAINPOOL_P384R1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::Brainpool384,
     constants: BRAINPOOL_P384R1,
 };
+#[cfg(feature = "curve-brainpool512")]
 pub const BRAINPOOL_P512R1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::Brainpool512,
     constants: BRAINPOOL_P512R1,
 };
+#[cfg(feature = "curve-secp160k1")]
 pub const SECP160K1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::Secp160k1,
     constants: SECP160K1,
 };
+#[cfg(feature = "curve-secp192k1")]
 pub const SECP192K1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::Secp192k1,
     constants: SECP192K1,
 };
+#[cfg(feature = "curve-secp224k1")]
 pub const SECP224K1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::Secp224k1,
     constants: SECP224K1,
 };
+#[cfg(feature = "curve-secp256k1")]
 pub const SECP256K1_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::Secp256k1,
     constants: SECP256K1,
 };
+#[cfg(feature = "curve-tpm-bn-p256")]
 pub const TPM_BN_P256_INITIALIZER: CurveInitializer = CurveInitializer {
     curve: EcCurve::TpmEccBnP256,
     constants: TPM_BN_P256,
@@ -0,0 +1,110 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Incremental hashing on the se05x
+//!
+//! [`DigestStream`] manages the crypto object holding the state of the computation.
+//! Short messages can be hashed in a single command with [`DigestOneShot`](super::commands::DigestOneShot).
+
+use super::commands::{CreateDigestObject, DeleteCryptoObj, DigestFinal, DigestInit, DigestUpdate};
+use super::transport::Se05xTransport;
+use super::{CryptoObjectId, Digest, Error, Se05X, CHUNK_LEN};
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Start a digest computation using the crypto object `digest_id`
+    ///
+    /// `digest_id` must not be in use. It is created here and deleted when the stream is finished or dropped.
+    pub fn digest_stream(
+        &mut self,
+        algo: Digest,
+        digest_id: CryptoObjectId,
+    ) -> Result<DigestStream<'_, T>, Error> {
+        self.run_command(
+            &CreateDigestObject {
+                id: digest_id,
+                subtype: algo,
+            },
+            &mut [0; 2],
+        )?;
+        let stream = DigestStream {
+            se: self,
+            digest_id,
+            finished: false,
+        };
+        stream
+            .se
+            .run_command(&DigestInit { digest_id }, &mut [0; 2])?;
+        Ok(stream)
+    }
+}
+
+/// Incremental digest computation
+///
+/// Created with [`Se05X::digest_stream`]. The crypto object is deleted by [`finish`](Self::finish) and [`abort`](Self::abort),
+/// or when the stream is dropped, in which case errors are ignored.
+pub struct DigestStream<'se, T: Se05xTransport> {
+    se: &'se mut Se05X<T>,
+    digest_id: CryptoObjectId,
+    finished: bool,
+}
+
+impl<T: Se05xTransport> DigestStream<'_, T> {
+    pub fn digest_id(&self) -> CryptoObjectId {
+        self.digest_id
+    }
+
+    /// Feed `data` to the computation, in chunks of [`CHUNK_LEN`]
+    pub fn update(&mut self, data: &[u8]) -> Result<(), Error> {
+        for chunk in data.chunks(CHUNK_LEN) {
+            self.se.run_command(
+                &DigestUpdate {
+                    digest_id: self.digest_id,
+                    data: chunk,
+                },
+                &mut [0; 2],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Finish the computation and return the digest
+    pub fn finish(mut self, response_buf: &mut [u8]) -> Result<&[u8], Error> {
+        let digest = self
+            .se
+            .run_command(
+                &DigestFinal {
+                    digest_id: self.digest_id,
+                    data: &[],
+                },
+                response_buf,
+            )
+            .map(|response| response.digest);
+        self.finished = true;
+        let deleted = self.delete();
+        let digest = digest?;
+        deleted?;
+        Ok(digest)
+    }
+
+    /// Abandon the computation, deleting the crypto object
+    pub fn abort(mut self) -> Result<(), Error> {
+        self.finished = true;
+        self.delete()
+    }
+
+    fn delete(&mut self) -> Result<(), Error> {
+        self.se
+            .run_command(&DeleteCryptoObj { id: self.digest_id }, &mut [0; 2])
+    }
+}
+
+impl<T: Se05xTransport> Drop for DigestStream<'_, T> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        if let Err(_err) = self.delete() {
+            warn!("Failed to delete digest object on drop: {_err:?}");
+        }
+    }
+}
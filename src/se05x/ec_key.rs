@@ -0,0 +1,162 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Encodings of EC public keys
+//!
+//! For short Weierstrass curves, the se05x expects and returns public keys as SEC1 uncompressed points: `0x04 || X || Y`, each coordinate left-padded to the byte length of the field.
+//! For curve 25519, public keys are the 32 bytes of the encoded point.
+//!
+//! These are the values of the `public_key` of [`WriteEcKey`](super::commands::WriteEcKey), and the data read with [`ReadObject`](super::commands::ReadObject).
+
+use super::{EcCurve, Error};
+
+/// Leading byte of SEC1 uncompressed points
+pub const SEC1_UNCOMPRESSED: u8 = 0x04;
+
+/// Maximum length of a public key in the format of the se05x, reached with P-521
+pub const MAX_EC_PUBLIC_KEY_LEN: usize = 1 + 2 * 66;
+
+impl EcCurve {
+    /// Length in bytes of the coordinates of points on this curve
+    pub fn coordinate_len(&self) -> usize {
+        match self {
+            Self::Brainpool160 | Self::Secp160k1 => 20,
+            Self::NistP192 | Self::Brainpool192 | Self::Secp192k1 => 24,
+            Self::NistP224 | Self::Brainpool224 | Self::Secp224k1 => 28,
+            Self::NistP256 | Self::Brainpool256 | Self::Secp256k1 | Self::TpmEccBnP256 => 32,
+            Self::IdEccEd25519 | Self::IdEccMontDh25519 => 32,
+            Self::Brainpool320 => 40,
+            Self::NistP384 | Self::Brainpool384 => 48,
+            Self::Brainpool512 => 64,
+            Self::NistP521 => 66,
+        }
+    }
+
+    /// Whether public keys on this curve are SEC1 points, and not curve 25519 encodings
+    pub fn is_weierstrass(&self) -> bool {
+        !matches!(self, Self::IdEccEd25519 | Self::IdEccMontDh25519)
+    }
+
+    /// Length in bytes of public keys on this curve, in the format of the se05x
+    pub fn public_key_len(&self) -> usize {
+        if self.is_weierstrass() {
+            1 + 2 * self.coordinate_len()
+        } else {
+            self.coordinate_len()
+        }
+    }
+}
+
+fn weierstrass(curve: EcCurve) -> Result<usize, Error> {
+    if !curve.is_weierstrass() {
+        error!("Curve does not use SEC1 points: {curve:?}");
+        return Err(Error::Line(line!()));
+    }
+    Ok(curve.coordinate_len())
+}
+
+/// Check that `public_key` is a public key on `curve` in the format of the se05x
+pub fn check_public_key(curve: EcCurve, public_key: &[u8]) -> Result<(), Error> {
+    if public_key.len() != curve.public_key_len() {
+        error!(
+            "Public key for {curve:?} has length {}, expected {}",
+            public_key.len(),
+            curve.public_key_len()
+        );
+        return Err(Error::Line(line!()));
+    }
+    if curve.is_weierstrass() && public_key[0] != SEC1_UNCOMPRESSED {
+        error!(
+            "Public key is not an uncompressed point: {:02x}",
+            public_key[0]
+        );
+        return Err(Error::Line(line!()));
+    }
+    Ok(())
+}
+
+/// Strip the SEC1 uncompressed point `sec1` on `curve` to `X || Y`
+pub fn sec1_to_raw(curve: EcCurve, sec1: &[u8]) -> Result<&[u8], Error> {
+    weierstrass(curve)?;
+    check_public_key(curve, sec1)?;
+    Ok(&sec1[1..])
+}
+
+/// Encode the point `X || Y` on `curve` as a SEC1 uncompressed point
+///
+/// `buf` must hold at least [`public_key_len`](EcCurve::public_key_len) bytes.
+pub fn raw_to_sec1<'buf>(
+    curve: EcCurve,
+    raw: &[u8],
+    buf: &'buf mut [u8],
+) -> Result<&'buf [u8], Error> {
+    let len = weierstrass(curve)?;
+    if raw.len() != 2 * len {
+        error!(
+            "Point for {curve:?} has length {}, expected {}",
+            raw.len(),
+            2 * len
+        );
+        return Err(Error::Line(line!()));
+    }
+    let Some(sec1) = buf.get_mut(..1 + raw.len()) else {
+        error!("Buffer too small for SEC1 point");
+        return Err(Error::Line(line!()));
+    };
+    sec1[0] = SEC1_UNCOMPRESSED;
+    sec1[1..].copy_from_slice(raw);
+    Ok(sec1)
+}
+
+/// Split a public key on `curve` in the format of the se05x into its coordinates
+///
+/// Returns `(X, Y)`. Curve 25519 keys only have a single coordinate, returned as `X` with an empty `Y`.
+pub fn coordinates(curve: EcCurve, public_key: &[u8]) -> Result<(&[u8], &[u8]), Error> {
+    check_public_key(curve, public_key)?;
+    if curve.is_weierstrass() {
+        Ok(public_key[1..].split_at(curve.coordinate_len()))
+    } else {
+        Ok((public_key, &[]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        let raw: Vec<u8> = (0..132).collect();
+        let buf = &mut [0; MAX_EC_PUBLIC_KEY_LEN];
+        let sec1 = raw_to_sec1(EcCurve::NistP521, &raw, buf).unwrap();
+        assert_eq!(sec1.len(), EcCurve::NistP521.public_key_len());
+        assert_eq!(sec1[0], SEC1_UNCOMPRESSED);
+        assert_eq!(sec1_to_raw(EcCurve::NistP521, sec1).unwrap(), raw);
+        let (x, y) = coordinates(EcCurve::NistP521, sec1).unwrap();
+        assert_eq!((x, y), raw.split_at(66));
+
+        assert_eq!(
+            coordinates(EcCurve::IdEccEd25519, &[7; 32]).unwrap(),
+            (&[7; 32][..], &[][..])
+        );
+    }
+
+    #[test]
+    fn mismatches() {
+        let buf = &mut [0; MAX_EC_PUBLIC_KEY_LEN];
+        assert!(raw_to_sec1(EcCurve::NistP256, &[1; 63], buf).is_err());
+        assert!(raw_to_sec1(EcCurve::NistP256, &[1; 64], &mut [0; 64]).is_err());
+        assert!(raw_to_sec1(EcCurve::IdEccEd25519, &[1; 64], buf).is_err());
+
+        let mut sec1 = [1; 65];
+        assert!(sec1_to_raw(EcCurve::NistP256, &sec1).is_err());
+        sec1[0] = SEC1_UNCOMPRESSED;
+        assert!(sec1_to_raw(EcCurve::NistP256, &sec1).is_ok());
+        assert!(sec1_to_raw(EcCurve::NistP384, &sec1).is_err());
+        assert!(sec1_to_raw(EcCurve::IdEccMontDh25519, &sec1[..32]).is_err());
+
+        assert!(check_public_key(EcCurve::IdEccEd25519, &[1; 32]).is_ok());
+        assert!(check_public_key(EcCurve::IdEccEd25519, &[1; 33]).is_err());
+        assert!(check_public_key(EcCurve::NistP256, &sec1[..33]).is_err());
+    }
+}
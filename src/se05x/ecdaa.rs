@@ -0,0 +1,150 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! ECDAA signatures
+//!
+//! [`EcdaaSign`] signs with an EC key on the [`TpmEccBnP256`](super::EcCurve::TpmEccBnP256) curve.
+//! The host provides the SHA-256 digest of the message, as an [`EcdaaDigest`], and a random nonce, as an [`EcdaaNonce`].
+//! The se05x only computes its part of the signature: the host combines it with the credential of the key to complete the ECDAA signature.
+//!
+//! [`Se05X::ecdaa_sign`] runs the command with these typed parameters, and splits the result into an [`EcdaaSignature`].
+
+use super::commands::{EcdaaSign, EcdaaSignResponse};
+use super::rng::HostRng;
+use super::transport::Se05xTransport;
+use super::{AppletConfig, Error, ObjectId, Se05X};
+
+/// Length of the digest, the nonce and each component of the signature
+pub const ECDAA_LEN: usize = 32;
+
+/// SHA-256 digest of the message signed with [`Se05X::ecdaa_sign`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EcdaaDigest(pub [u8; ECDAA_LEN]);
+
+impl EcdaaDigest {
+    /// Hash `message` with SHA-256 on the host
+    #[cfg(feature = "host-digest")]
+    pub fn hash(message: &[u8]) -> Self {
+        use sha2::Digest;
+        Self(sha2::Sha256::digest(message).into())
+    }
+}
+
+/// Random nonce chosen by the host for [`Se05X::ecdaa_sign`]
+///
+/// A nonce must never be used for two signatures. Generate it with [`Se05X::ecdaa_nonce`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EcdaaNonce(pub [u8; ECDAA_LEN]);
+
+/// Part of the ECDAA signature computed by the se05x
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EcdaaSignature<'buf> {
+    /// First 32-byte component of the signature
+    pub r: &'buf [u8; ECDAA_LEN],
+    /// Second 32-byte component of the signature
+    pub s: &'buf [u8; ECDAA_LEN],
+}
+
+impl<'data> EcdaaSignResponse<'data> {
+    /// Split the signature into its components
+    pub fn ecdaa_signature(&self) -> Result<EcdaaSignature<'data>, Error> {
+        if self.signature.len() != 2 * ECDAA_LEN {
+            error!(
+                "Unexpected ECDAA signature length: {}",
+                self.signature.len()
+            );
+            return Err(Error::Line(line!()));
+        }
+        let (r, s) = self.signature.split_at(ECDAA_LEN);
+        Ok(EcdaaSignature {
+            r: r.try_into()?,
+            s: s.try_into()?,
+        })
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Generate a nonce for [`ecdaa_sign`](Self::ecdaa_sign) with `rng`
+    pub fn ecdaa_nonce<R: HostRng>(&mut self, rng: &mut R) -> Result<EcdaaNonce, Error> {
+        let mut nonce = [0; ECDAA_LEN];
+        rng.fill_random(self, &mut nonce)?;
+        Ok(EcdaaNonce(nonce))
+    }
+
+    /// Compute the part of the ECDAA signature of `digest` held by the key `key_id`
+    ///
+    /// `response_buf` must hold at least 70 bytes.
+    pub fn ecdaa_sign<'buf>(
+        &mut self,
+        key_id: ObjectId,
+        digest: &EcdaaDigest,
+        nonce: &EcdaaNonce,
+        response_buf: &'buf mut [u8],
+    ) -> Result<EcdaaSignature<'buf>, Error> {
+        self.require_feature(AppletConfig::ECDAA)?;
+        self.run_command(
+            &EcdaaSign {
+                key_id,
+                data: digest.0,
+                random_data: nonce.0,
+            },
+            response_buf,
+        )?
+        .ecdaa_signature()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use crate::se05x::rng::DeviceRng;
+    use hex_literal::hex;
+
+    #[test]
+    fn ecdaa_sign() {
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(
+                &hex!("80 04 00 49 00 00 04 41 02 0020 00 00"),
+                &[&hex!("41 20")[..], &[0x5A; ECDAA_LEN], &hex!("90 00")].concat(),
+            )
+            .expect(
+                &[
+                    &hex!("80 03 0C 09 00 00 4D 41 04 01020304 42 01 F4 43 20")[..],
+                    &[1; ECDAA_LEN],
+                    &hex!("44 20"),
+                    &[0x5A; ECDAA_LEN],
+                    &hex!("00 00"),
+                ]
+                .concat(),
+                &[
+                    &hex!("41 40")[..],
+                    &[0x5A; ECDAA_LEN],
+                    &[0xEE; ECDAA_LEN],
+                    &hex!("90 00"),
+                ]
+                .concat(),
+            );
+        let mut se05x = script.enable();
+        let nonce = se05x.ecdaa_nonce(&mut DeviceRng).unwrap();
+        assert_eq!(nonce, EcdaaNonce([0x5A; ECDAA_LEN]));
+        let buf = &mut [0; 70];
+        let signature = se05x
+            .ecdaa_sign(
+                ObjectId([1, 2, 3, 4]),
+                &EcdaaDigest([1; ECDAA_LEN]),
+                &nonce,
+                buf,
+            )
+            .unwrap();
+        assert_eq!(signature.r, &[0x5A; ECDAA_LEN]);
+        assert_eq!(signature.s, &[0xEE; ECDAA_LEN]);
+        assert!(se05x.into_transport().into_inner().is_done());
+
+        let truncated = EcdaaSignResponse {
+            signature: &[0; 2 * ECDAA_LEN - 1],
+        };
+        assert!(truncated.ecdaa_signature().is_err());
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Key agreement with EC keys stored in the se05x
+//!
+//! [`Se05X::ecdh`] returns the shared secret to the host.
+//! [`Se05X::ecdh_store`] keeps it in an HMAC key object of the se05x instead, for later HKDF or HMAC computations.
+//! [`Se05X::ecdh_hkdf`] keeps the shared secret in an HMAC key object of the se05x, and derives a key from it with [`Hkdf`].
+//!
+//! The public key of the peer must be in the format described in [`ec_key`].
+
+use core::fmt;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use super::commands::{EcdhGenerateSharedSecret, EcdhStoreSharedSecret, GetEcCurveId, Hkdf};
+use super::kdf::HkdfOutput;
+use super::transport::Se05xTransport;
+use super::{ec_key, AppletConfig, EcCurve, Error, ObjectId, Se05X};
+
+/// Maximum length of a shared secret, reached with P-521
+pub const MAX_SHARED_SECRET_LEN: usize = 66;
+
+/// Shared secret computed by [`Se05X::ecdh`]
+///
+/// The secret is overwritten with zeroes when dropped.
+#[derive(Clone)]
+pub struct SharedSecret {
+    bytes: [u8; MAX_SHARED_SECRET_LEN],
+    len: usize,
+}
+
+impl SharedSecret {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl AsRef<[u8]> for SharedSecret {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedSecret")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        self.bytes.fill(0);
+        compiler_fence(Ordering::SeqCst);
+    }
+}
+
+/// Applet feature required for key agreement on `curve`
+fn required_feature(curve: EcCurve) -> AppletConfig {
+    match curve {
+        EcCurve::IdEccMontDh25519 => AppletConfig::DH_MONT,
+        _ => AppletConfig::ECDSA_ECDH_ECDHE,
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Compute the shared secret of the EC key `key_id` and the public key of the peer
+    ///
+    /// The curve of `key_id` is read first, to check the length of `peer_public_sec1`.
+    pub fn ecdh(
+        &mut self,
+        key_id: ObjectId,
+        peer_public_sec1: &[u8],
+    ) -> Result<SharedSecret, Error> {
+        self.check_peer_public(key_id, peer_public_sec1)?;
+        let mut secret = SharedSecret {
+            bytes: [0; MAX_SHARED_SECRET_LEN],
+            len: 0,
+        };
+        let buf = &mut [0; MAX_SHARED_SECRET_LEN + 6];
+        let shared_secret = self
+            .run_command(
+                &EcdhGenerateSharedSecret {
+                    key_id,
+                    public_key: peer_public_sec1,
+                },
+                buf,
+            )?
+            .shared_secret;
+        let Some(bytes) = secret.bytes.get_mut(..shared_secret.len()) else {
+            error!("Shared secret too long: {}", shared_secret.len());
+            return Err(Error::Line(line!()));
+        };
+        bytes.copy_from_slice(shared_secret);
+        secret.len = shared_secret.len();
+        buf.fill(0);
+        Ok(secret)
+    }
+
+    /// Store the shared secret of the EC key `key_id` and the public key of the peer in the HMAC key object `target`
+    ///
+    /// The shared secret does not leave the se05x, and can be used later with [`Hkdf`] or for HMAC computations.
+    pub fn ecdh_store(
+        &mut self,
+        key_id: ObjectId,
+        peer_public_sec1: &[u8],
+        target: ObjectId,
+    ) -> Result<(), Error> {
+        if target == key_id {
+            error!("The shared secret cannot replace the EC key");
+            return Err(Error::Line(line!()));
+        }
+        self.check_peer_public(key_id, peer_public_sec1)?;
+        self.require_feature(AppletConfig::HMAC)?;
+        self.run_command(
+            &EcdhStoreSharedSecret {
+                key_id,
+                public_key: peer_public_sec1,
+                target,
+            },
+            &mut [0; 2],
+        )?;
+        Ok(())
+    }
+
+    /// Derive a key with HKDF from the shared secret of the EC key `key_id` and the public key of the peer
+    ///
+    /// The shared secret is stored in the HMAC key object `hkdf.ikm` with [`ecdh_store`](Self::ecdh_store), which is then used to run `hkdf`.
+    /// With a `target`, the derived key is stored too, see [`hkdf`](Self::hkdf).
+    pub fn ecdh_hkdf<'buf>(
+        &mut self,
+        key_id: ObjectId,
+        peer_public_sec1: &[u8],
+        hkdf: &Hkdf<'_>,
+        response_buf: &'buf mut [u8],
+    ) -> Result<HkdfOutput<'buf>, Error> {
+        self.ecdh_store(key_id, peer_public_sec1, hkdf.ikm)?;
+        self.hkdf(hkdf, response_buf)
+    }
+
+    fn check_peer_public(&mut self, key_id: ObjectId, peer_public: &[u8]) -> Result<(), Error> {
+        let curve = self
+            .run_command(&GetEcCurveId { object_id: key_id }, &mut [0; 8])?
+            .curve;
+        self.require_feature(required_feature(curve))?;
+        ec_key::check_public_key(curve, peer_public)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use hex_literal::hex;
+
+    const SECRET_ID: [u8; 4] = [5, 6, 7, 8];
+    const DERIVED_ID: [u8; 4] = [9, 9, 9, 9];
+
+    #[test]
+    fn ecdh() {
+        let key_id = ObjectId([1, 2, 3, 4]);
+        let mut peer = [0x11; 65];
+        peer[0] = ec_key::SEC1_UNCOMPRESSED;
+        // GetEcCurveId answering P-256
+        let curve_id = (
+            hex!("80 02 0B 36 06 41 04 01020304"),
+            hex!("41 01 03 90 00"),
+        );
+        let store_secret = [
+            &hex!("80 03 01 0F 4F 41 04 01020304 42 41")[..],
+            &peer,
+            &hex!("47 04 05060708"),
+        ]
+        .concat();
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(&curve_id.0, &curve_id.1)
+            .expect(
+                &[
+                    &hex!("80 03 01 0F 00 00 49 41 04 01020304 42 41")[..],
+                    &peer,
+                    &hex!("00 00"),
+                ]
+                .concat(),
+                &[&hex!("41 20")[..], &[0xAB; 32], &hex!("90 00")].concat(),
+            )
+            .expect(&curve_id.0, &curve_id.1)
+            .expect(&curve_id.0, &curve_id.1)
+            .expect(&curve_id.0, &curve_id.1)
+            .expect(&store_secret, &hex!("90 00"))
+            .expect(
+                &hex!("80 03 00 2D 00 00 16 41 04 05060708 42 01 04 44 07 73657373696F6E 45 02 0002 00 00"),
+                &hex!("41 02 CDEF 90 00"),
+            )
+            .expect(&curve_id.0, &curve_id.1)
+            .expect(&store_secret, &hex!("90 00"))
+            .expect(
+                &hex!("80 03 00 2D 00 00 1C 41 04 05060708 42 01 04 44 07 73657373696F6E 45 02 0002 48 04 09090909 00 00"),
+                &hex!("90 00"),
+            );
+        let mut se05x = script.enable();
+        let secret = se05x.ecdh(key_id, &peer).unwrap();
+        assert_eq!(secret.as_bytes(), [0xAB; 32]);
+        // Not a P-256 point
+        assert!(se05x.ecdh(key_id, &peer[..64]).is_err());
+        assert!(se05x.ecdh(key_id, &[0x11; 65]).is_err());
+
+        let hkdf = Hkdf {
+            ikm: ObjectId(SECRET_ID),
+            digest: crate::se05x::Digest::Sha256,
+            salt: None,
+            info: Some(b"session"),
+            requested_len: 2.into(),
+            target: None,
+        };
+        let buf = &mut [0; 16];
+        assert_eq!(
+            se05x.ecdh_hkdf(key_id, &peer, &hkdf, buf).unwrap(),
+            HkdfOutput::Returned(&[0xCD, 0xEF])
+        );
+        let hkdf = Hkdf {
+            target: Some(ObjectId(DERIVED_ID)),
+            ..hkdf
+        };
+        assert_eq!(
+            se05x.ecdh_hkdf(key_id, &peer, &hkdf, buf).unwrap(),
+            HkdfOutput::Stored(ObjectId(DERIVED_ID))
+        );
+        let hkdf = Hkdf {
+            target: Some(ObjectId(SECRET_ID)),
+            ..hkdf
+        };
+        assert!(se05x.hkdf(&hkdf, buf).is_err());
+        assert!(se05x.ecdh_store(key_id, &peer, key_id).is_err());
+        assert!(se05x.into_transport().into_inner().is_done());
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Overriding the expected response length of commands
+//!
+//! Generated commands set the Le field of their APDU to a fixed value, often [`ExpectedLen::Max`](iso7816::command::ExpectedLen::Max).
+//! Some readers and stacks handle large Le values poorly.
+//! [`Se05X::run_command_with_le`] sends a command with a different Le, re-encoding the APDU in its short or extended form as needed.
+
+use iso7816::command::{DataSource, DataStream, Writer};
+
+use super::transport::{ApduSink, ApduWriter, Se05xTransport};
+use super::{Error, Se05X, Se05XCommand, MAX_APDU_OVERHEAD, MAX_APDU_PAYLOAD_LENGTH};
+
+/// Largest Le of short APDUs
+const MAX_SHORT_LE: u16 = 256;
+/// Largest Lc of short APDUs
+const MAX_SHORT_LC: usize = 255;
+
+/// [`ApduSink`] writing a command APDU to a buffer
+pub(super) struct BufferSink<'a> {
+    pub(super) buf: &'a mut [u8],
+    pub(super) len: usize,
+}
+
+impl ApduSink for BufferSink<'_> {
+    fn write_apdu(&mut self, data: &[u8]) -> Result<usize, Error> {
+        let Some(dst) = self.buf.get_mut(self.len..self.len + data.len()) else {
+            error!("Command too long");
+            return Err(Error::CommandTooLong);
+        };
+        dst.copy_from_slice(data);
+        self.len += data.len();
+        Ok(data.len())
+    }
+}
+
+/// Command APDU with the header and data of a serialized command, and a new Le
+struct WithLe<'a> {
+    header: &'a [u8],
+    data: &'a [u8],
+    le: u16,
+}
+
+/// Fields of a serialized command APDU, returned by [`split_apdu`]
+pub(super) struct SplitApdu<'a> {
+    pub(super) header: &'a [u8],
+    pub(super) data: &'a [u8],
+    /// Le field as encoded: empty, 1 byte in the short form, 2 or 3 bytes in the extended form
+    #[cfg_attr(not(feature = "aes-session"), allow(dead_code))]
+    pub(super) le: &'a [u8],
+}
+
+/// Split the serialized command `apdu` into its header, its data and its Le field
+pub(super) fn split_apdu(apdu: &[u8]) -> Result<SplitApdu<'_>, Error> {
+    /// Data of `lc` bytes, followed by an Le field of `le_len` bytes or none
+    fn split(rest: &[u8], lc: usize, le_len: usize) -> Result<(&[u8], &[u8]), Error> {
+        if rest.len() == lc || rest.len() == lc + le_len {
+            Ok(rest.split_at(lc))
+        } else {
+            Err(invalid_apdu())
+        }
+    }
+    let (data, le) = match apdu {
+        // No data, with or without a short or extended Le
+        [_, _, _, _, rest @ ..] if matches!(rest, [] | [_] | [0, _, _]) => (&[][..], rest),
+        [_, _, _, _, 0, lc1, lc2, rest @ ..] => {
+            split(rest, u16::from_be_bytes([*lc1, *lc2]).into(), 2)?
+        }
+        [_, _, _, _, lc, rest @ ..] => split(rest, usize::from(*lc), 1)?,
+        _ => return Err(invalid_apdu()),
+    };
+    Ok(SplitApdu {
+        header: &apdu[..4],
+        data,
+        le,
+    })
+}
+
+impl<'a> WithLe<'a> {
+    /// Split the serialized command `apdu`, to send it with `le` instead of its own Le
+    fn parse(apdu: &'a [u8], le: u16) -> Result<Self, Error> {
+        let SplitApdu { header, data, .. } = split_apdu(apdu)?;
+        Ok(Self { header, data, le })
+    }
+
+    fn extended(&self) -> bool {
+        self.data.len() > MAX_SHORT_LC || self.le > MAX_SHORT_LE
+    }
+}
+
+fn invalid_apdu() -> Error {
+    error!("Failed to parse serialized command");
+    Error::Line(line!())
+}
+
+impl DataSource for WithLe<'_> {
+    fn len(&self) -> usize {
+        let lc_len = match (self.data.is_empty(), self.extended()) {
+            (true, _) => 0,
+            (false, true) => 3,
+            (false, false) => 1,
+        };
+        let le_len = match (self.data.is_empty(), self.extended()) {
+            (true, true) => 3,
+            (false, true) => 2,
+            (_, false) => 1,
+        };
+        self.header.len() + lc_len + self.data.len() + le_len
+    }
+    fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+impl<W: Writer> DataStream<W> for WithLe<'_> {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as Writer>::Error> {
+        writer.write_all(self.header)?;
+        if self.extended() {
+            writer.write_all(&[0])?;
+            if !self.data.is_empty() {
+                writer.write_all(&(self.data.len() as u16).to_be_bytes())?;
+                writer.write_all(self.data)?;
+            }
+            writer.write_all(&self.le.to_be_bytes())
+        } else {
+            if !self.data.is_empty() {
+                writer.write_all(&[self.data.len() as u8])?;
+                writer.write_all(self.data)?;
+            }
+            // 256 is encoded as 0
+            writer.write_all(&[self.le as u8])
+        }
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Run `command`, expecting at most `le` bytes of response data
+    ///
+    /// The Le field set by the command is replaced by `le`, which must not be 0.
+    /// The APDU uses the extended form if `le` is above 256 or the data longer than 255 bytes.
+    pub fn run_command_with_le<'buf, C: for<'a> Se05XCommand<ApduWriter<'a>>>(
+        &mut self,
+        command: &C,
+        le: u16,
+        response_buf: &'buf mut [u8],
+    ) -> Result<<C as Se05XCommand<ApduWriter<'_>>>::Response<'buf>, Error> {
+        if le == 0 {
+            error!("Le must not be 0");
+            return Err(Error::Line(line!()));
+        }
+        command.validate()?;
+        let mut apdu = [0; MAX_APDU_PAYLOAD_LENGTH + MAX_APDU_OVERHEAD];
+        let mut sink = BufferSink {
+            buf: &mut apdu,
+            len: 0,
+        };
+        command.to_writer(&mut ApduWriter::new(&mut sink))?;
+        let len = sink.len;
+        let command = WithLe::parse(&apdu[..len], le)?;
+        self.run_command_internal(&command, response_buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::se05x::apdu::ScriptedTransport;
+    use crate::se05x::commands::{GetRandom, WriteBinary};
+    use crate::se05x::ObjectId;
+    use hex_literal::hex;
+
+    #[test]
+    fn override_le() {
+        let random = GetRandom { length: 16.into() };
+        let data = [0xAB; 300];
+        let write = WriteBinary {
+            transient: false,
+            policy: None,
+            object_id: ObjectId([1, 2, 3, 4]),
+            offset: None,
+            file_length: None,
+            data: Some(&data),
+        };
+        let mut script = ScriptedTransport::new();
+        // The script returns no random data, so only the command is checked
+        script
+            .expect(&hex!("80 04 00 49 04 41 02 0010 14"), &hex!("90 00"))
+            .expect(
+                &hex!("80 04 00 49 00 00 04 41 02 0010 01 2C"),
+                &hex!("90 00"),
+            );
+        // Long data keeps the extended form
+        let mut apdu = hex!("80 01 06 00 00 01 36 41 04 01020304 44 82 01 2C").to_vec();
+        apdu.extend_from_slice(&data);
+        apdu.extend_from_slice(&[0x00, 0x10]);
+        script.expect(&apdu, &hex!("90 00"));
+        let mut se05x = script.enable();
+        assert!(se05x.run_command_with_le(&random, 0, &mut [0; 32]).is_err());
+        se05x
+            .run_command_with_le(&random, 20, &mut [0; 32])
+            .map(|_| ())
+            .unwrap_err();
+        se05x
+            .run_command_with_le(&random, 300, &mut [0; 32])
+            .map(|_| ())
+            .unwrap_err();
+        se05x.run_command_with_le(&write, 16, &mut [0; 32]).unwrap();
+        assert!(se05x.into_transport().into_inner().is_done());
+    }
+}
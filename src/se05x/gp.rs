@@ -0,0 +1,145 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Minimal GlobalPlatform card manager commands
+//!
+//! The Issuer Security Domain (ISD) holds data about the chip, such as the Card Production Life Cycle data (CPLC), and the templates of its keys.
+//! The ISD is selected with [`SelectAid`] and [`ISD_AID`], see [`Se05X::select_isd`].
+//! This deselects the IoT applet: call [`Se05X::enable`] to select it again before sending applet commands.
+
+use iso7816::command::class::NO_SM_CLA;
+use iso7816::command::{CommandBuilder, DataSource, DataStream, Writer};
+
+use super::transport::Se05xTransport;
+use super::{Error, Se05X, Se05XCommand, Se05XResponse, SelectAid, ISD_AID};
+
+/// Card Production Life Cycle data
+pub const TAG_CPLC: u16 = 0x9F7F;
+/// Card data, including the GlobalPlatform and secure channel versions
+pub const TAG_CARD_DATA: u16 = 0x0066;
+/// Key information template
+pub const TAG_KEY_INFORMATION: u16 = 0x00E0;
+/// Issuer identification number
+pub const TAG_IIN: u16 = 0x0042;
+/// Card image number
+pub const TAG_CIN: u16 = 0x0045;
+
+const INS_GET_DATA: u8 = 0xCA;
+/// Maximum length of a short response
+const LE_MAX_SHORT: u16 = 256;
+
+/// GlobalPlatform GET DATA of the data object `tag`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GetData {
+    pub tag: u16,
+}
+
+impl GetData {
+    fn command(&self) -> CommandBuilder<&'static [u8]> {
+        let [p1, p2] = self.tag.to_be_bytes();
+        CommandBuilder::new(NO_SM_CLA, INS_GET_DATA.into(), p1, p2, &[], LE_MAX_SHORT)
+    }
+}
+
+impl DataSource for GetData {
+    fn len(&self) -> usize {
+        self.command().len()
+    }
+    fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+impl<W: Writer> DataStream<W> for GetData {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as Writer>::Error> {
+        self.command().to_writer(writer)
+    }
+}
+
+impl<W: Writer> Se05XCommand<W> for GetData {
+    type Response<'a> = GpResponse<'a>;
+}
+
+/// Unparsed response to a GlobalPlatform command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpResponse<'a> {
+    pub data: &'a [u8],
+}
+
+impl<'a> Se05XResponse<'a> for GpResponse<'a> {
+    fn from_response(data: &'a [u8]) -> Result<Self, Error> {
+        Ok(Self { data })
+    }
+}
+
+impl<'a> GpResponse<'a> {
+    /// Value of the data object `tag` that makes up the whole response
+    ///
+    /// GET DATA returns the requested data object with its tag and length.
+    pub fn value(&self, tag: u16) -> Result<&'a [u8], Error> {
+        let rest = match tag.to_be_bytes() {
+            [0, tag] => self.data.strip_prefix(&[tag]),
+            tag => self.data.strip_prefix(&tag),
+        };
+        let Some(rest) = rest else {
+            error!("Unexpected data object, expected {tag:04x}");
+            return Err(Error::Tlv);
+        };
+        let (len, value) = match rest {
+            [len @ 0..=0x7F, value @ ..] => (usize::from(*len), value),
+            [0x81, len, value @ ..] => (usize::from(*len), value),
+            [0x82, len1, len2, value @ ..] => (u16::from_be_bytes([*len1, *len2]).into(), value),
+            _ => return Err(Error::Tlv),
+        };
+        if value.len() != len {
+            error!("Invalid length of data object {tag:04x}: {len}");
+            return Err(Error::Tlv);
+        }
+        Ok(value)
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Select the Issuer Security Domain, returning its File Control Information
+    ///
+    /// The IoT applet is deselected, see the [module documentation](super::gp).
+    pub fn select_isd<'buf>(&mut self, buf: &'buf mut [u8]) -> Result<&'buf [u8], Error> {
+        // The response is not an ATR, so it is returned unparsed
+        self.run_command_buf_response(&SelectAid { aid: &ISD_AID }, buf)
+    }
+
+    /// Read the data object `tag` of the selected security domain, returning its value
+    ///
+    /// For example, [`TAG_CPLC`] reads the Card Production Life Cycle data after [`select_isd`](Self::select_isd).
+    pub fn get_data<'buf>(&mut self, tag: u16, buf: &'buf mut [u8]) -> Result<&'buf [u8], Error> {
+        self.run_command(&GetData { tag }, buf)?.value(tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::{ApduLink, ApduSe05X, ScriptedTransport};
+    use hex_literal::hex;
+
+    #[test]
+    fn get_data() {
+        let mut script = ScriptedTransport::new();
+        // ISD answering GET DATA of the CPLC and of the IIN, and returning a truncated CIN
+        script
+            .expect(
+                &hex!("00 A4 04 00 08 A000000151000000 00"),
+                &hex!("6F 02 84 00 90 00"),
+            )
+            .expect(&hex!("80 CA 9F 7F 00"), &hex!("9F 7F 03 479091 90 00"))
+            .expect(&hex!("80 CA 00 42 00"), &hex!("42 02 0102 90 00"))
+            .expect(&hex!("80 CA 00 45 00"), &hex!("45 03 01 90 00"));
+        let mut se05x = ApduSe05X::from_transport(ApduLink::new(script));
+        let buf = &mut [0; 64];
+        assert_eq!(se05x.select_isd(buf).unwrap(), [0x6F, 0x02, 0x84, 0x00]);
+        assert_eq!(se05x.get_data(TAG_CPLC, buf).unwrap(), [0x47, 0x90, 0x91]);
+        assert_eq!(se05x.get_data(TAG_IIN, buf).unwrap(), [0x01, 0x02]);
+        assert_eq!(se05x.get_data(TAG_CIN, buf), Err(Error::Tlv));
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+}
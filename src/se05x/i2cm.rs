@@ -0,0 +1,285 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! I2C master
+//!
+//! The se05x can drive a secondary I2C bus, for example to read a sensor.
+//! A command set is a sequence of [`I2cmCommand`]s encoded with [`encode_command_set`], and executed with [`I2CMasterExecuteCommandSet`].
+//! Each command yields a result, read from the response with [`I2cmResults`].
+//!
+//! [`Se05X::i2cm_execute_attested`] executes the command set with [`I2CMasterExecuteCommandSetAttested`], which signs the results
+//! together with a timestamp and a freshness random chosen by the host, to prove the provenance of sensor readings.
+
+use super::attestation::FRESHNESS_RANDOM_LEN;
+use super::commands::{
+    I2CMasterExecuteCommandSet, I2CMasterExecuteCommandSetAttested,
+    I2CMasterExecuteCommandSetAttestedResponse,
+};
+use super::rng::HostRng;
+use super::transport::Se05xTransport;
+use super::{AppletConfig, AttestationAlgo, Error, ObjectId, Se05X};
+
+/// Tag of the configuration of the bus
+pub const TAG_I2CM_CONFIG: u8 = 0x01;
+/// Tag of a write to the bus
+pub const TAG_I2CM_WRITE: u8 = 0x03;
+/// Tag of a read from the bus
+pub const TAG_I2CM_READ: u8 = 0x04;
+/// Status of a successful command
+pub const I2CM_SUCCESS: u8 = 0x5A;
+
+/// Speed of the I2C bus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum I2cmSpeed {
+    Khz100,
+    Khz400,
+}
+
+/// Command of a command set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum I2cmCommand<'a> {
+    /// Configure the 7-bit address of the device and the speed of the bus for the next commands
+    Configure { address: u8, speed: I2cmSpeed },
+    /// Write up to 255 bytes
+    Write(&'a [u8]),
+    /// Read `n` bytes
+    Read(u16),
+}
+
+/// Encode `commands` into `buf`, returning the command set
+pub fn encode_command_set<'buf>(
+    commands: &[I2cmCommand<'_>],
+    buf: &'buf mut [u8],
+) -> Result<&'buf [u8], Error> {
+    let mut written = 0;
+    for command in commands {
+        let mut header = [0; 4];
+        let (header, data): (&[u8], &[u8]) = match *command {
+            I2cmCommand::Configure { address, speed } => {
+                if address > 0x7F {
+                    error!("Invalid I2C address: {address:02x}");
+                    return Err(Error::Line(line!()));
+                }
+                let speed = match speed {
+                    I2cmSpeed::Khz100 => 0x00,
+                    I2cmSpeed::Khz400 => 0x01,
+                };
+                header = [TAG_I2CM_CONFIG, 2, address, speed];
+                (&header, &[])
+            }
+            I2cmCommand::Write(data) => {
+                let Ok(len) = u8::try_from(data.len()) else {
+                    error!("I2C write too long: {}", data.len());
+                    return Err(Error::Line(line!()));
+                };
+                header[..2].copy_from_slice(&[TAG_I2CM_WRITE, len]);
+                (&header[..2], data)
+            }
+            I2cmCommand::Read(len) => {
+                let [len1, len2] = len.to_be_bytes();
+                header = [TAG_I2CM_READ, 2, len1, len2];
+                (&header, &[])
+            }
+        };
+        let end = written + header.len() + data.len();
+        let Some(dest) = buf.get_mut(written..end) else {
+            error!("Buffer too small for the I2C command set");
+            return Err(Error::Line(line!()));
+        };
+        let (dest_header, dest_data) = dest.split_at_mut(header.len());
+        dest_header.copy_from_slice(header);
+        dest_data.copy_from_slice(data);
+        written = end;
+    }
+    Ok(&buf[..written])
+}
+
+/// Result of a command of a command set
+///
+/// The tag is the one of the command. The value of a successful read holds the data read,
+/// the value of the other results holds a status, [`I2CM_SUCCESS`] if the command succeeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct I2cmResult<'a> {
+    pub tag: u8,
+    pub value: &'a [u8],
+}
+
+impl I2cmResult<'_> {
+    /// Returns `true` if the value is a single status byte other than [`I2CM_SUCCESS`]
+    ///
+    /// Reads of a single byte can't be told apart from a status: check the length expected instead.
+    pub fn is_error(&self) -> bool {
+        matches!(self.value, [status] if *status != I2CM_SUCCESS)
+    }
+}
+
+/// Iterator over the results of a command set, in the `data` of the responses
+#[derive(Debug, Clone)]
+pub struct I2cmResults<'a> {
+    rem: &'a [u8],
+}
+
+impl<'a> I2cmResults<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { rem: data }
+    }
+}
+
+impl<'a> Iterator for I2cmResults<'a> {
+    type Item = Result<I2cmResult<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (tag, len, value) = match *self.rem {
+            [] => return None,
+            [tag, len @ 0..=0x7F, ref value @ ..] => (tag, usize::from(len), value),
+            [tag, 0x81, len, ref value @ ..] => (tag, usize::from(len), value),
+            [tag, 0x82, len1, len2, ref value @ ..] => {
+                (tag, u16::from_be_bytes([len1, len2]).into(), value)
+            }
+            _ => {
+                self.rem = &[];
+                return Some(Err(Error::Tlv));
+            }
+        };
+        if value.len() < len {
+            error!("Truncated I2C result: {len}");
+            self.rem = &[];
+            return Some(Err(Error::Tlv));
+        }
+        let (value, rem) = value.split_at(len);
+        self.rem = rem;
+        Some(Ok(I2cmResult { tag, value }))
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Execute the I2C master `command_set`, returning its results
+    pub fn i2cm_execute<'buf>(
+        &mut self,
+        command_set: &[u8],
+        buf: &'buf mut [u8],
+    ) -> Result<I2cmResults<'buf>, Error> {
+        self.require_feature(AppletConfig::I2CM)?;
+        let response = self.run_command(&I2CMasterExecuteCommandSet { command_set }, buf)?;
+        Ok(I2cmResults::new(response.data))
+    }
+
+    /// Execute the I2C master `command_set`, attested by `attestation_object`
+    ///
+    /// The freshness random is generated with `rng`. If the attestation does not echo it, [`Error::FreshnessMismatch`] is returned.
+    /// The results are in the `data` of the response, see [`I2cmResults`].
+    pub fn i2cm_execute_attested<'buf, R: HostRng>(
+        &mut self,
+        command_set: &[u8],
+        attestation_object: ObjectId,
+        attestation_algo: AttestationAlgo,
+        rng: &mut R,
+        buf: &'buf mut [u8],
+    ) -> Result<I2CMasterExecuteCommandSetAttestedResponse<'buf>, Error> {
+        self.require_feature(AppletConfig::I2CM)?;
+        let mut freshness_random = [0; FRESHNESS_RANDOM_LEN];
+        rng.fill_random(self, &mut freshness_random)?;
+        let attestation = self.run_command(
+            &I2CMasterExecuteCommandSetAttested {
+                command_set,
+                attestation_object,
+                attestation_algo,
+                freshness_random: &freshness_random,
+            },
+            buf,
+        )?;
+        if attestation.freshness_random != &freshness_random {
+            error!("Attestation freshness mismatch");
+            return Err(Error::FreshnessMismatch);
+        }
+        Ok(attestation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use crate::se05x::rng::DeviceRng;
+    use hex_literal::hex;
+
+    const COMMANDS: &[I2cmCommand<'static>] = &[
+        I2cmCommand::Configure {
+            address: 0x48,
+            speed: I2cmSpeed::Khz400,
+        },
+        I2cmCommand::Write(&[0x00]),
+        I2cmCommand::Read(2),
+    ];
+
+    #[test]
+    fn attested_read() {
+        let buf = &mut [0; 32];
+        let command_set = encode_command_set(COMMANDS, buf).unwrap();
+        assert!(encode_command_set(COMMANDS, &mut [0; 10]).is_err());
+
+        // The freshness random is all zeroes, so the script can echo it
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(
+                &hex!("80 04 00 49 00 00 04 41 02 0010 00 00"),
+                &hex!("41 10 00000000000000000000000000000000 9000"),
+            )
+            .expect(
+                &hex!(
+                    "80 03 00 31 00 00 28"
+                    "41 0B 01 02 48 01 03 01 00 04 02 00 02"
+                    "42 04 F0000012"
+                    "43 01 21"
+                    "47 10 00000000000000000000000000000000"
+                    "0000"
+                ),
+                &hex!(
+                    "41 0A 01 01 5A 03 01 5A 04 02 12 34"
+                    "43 0C 000000000000000000000001"
+                    "44 10 00000000000000000000000000000000"
+                    "45 12 000000000000000000000000000000000000"
+                    "46 02 3000"
+                    "9000"
+                ),
+            );
+        let mut se05x = script.enable();
+        let response_buf = &mut [0; 128];
+        let attestation = se05x
+            .i2cm_execute_attested(
+                command_set,
+                ObjectId(hex!("F0000012")),
+                AttestationAlgo::ECdsaSha256,
+                &mut DeviceRng,
+                response_buf,
+            )
+            .unwrap();
+        assert_eq!(attestation.signature, [0x30, 0x00]);
+        assert!(se05x.transport().inner_mut().is_done());
+        let results: heapless::Vec<_, 4> = I2cmResults::new(attestation.data)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            results,
+            [
+                I2cmResult {
+                    tag: TAG_I2CM_CONFIG,
+                    value: &[I2CM_SUCCESS],
+                },
+                I2cmResult {
+                    tag: TAG_I2CM_WRITE,
+                    value: &[I2CM_SUCCESS],
+                },
+                I2cmResult {
+                    tag: TAG_I2CM_READ,
+                    value: &[0x12, 0x34],
+                },
+            ]
+        );
+        assert!(!results.iter().any(I2cmResult::is_error));
+        assert!(I2cmResults::new(&[0x04, 0x02, 0x12])
+            .next()
+            .unwrap()
+            .is_err());
+    }
+}
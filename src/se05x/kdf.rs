@@ -0,0 +1,55 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Key derivation in the se05x
+//!
+//! [`Hkdf`] either returns the derived key to the host, or stores it in the HMAC key object given as its `target`.
+//! Storing it allows building key hierarchies in the se05x, where derived keys never leave it.
+
+use super::commands::Hkdf;
+use super::transport::Se05xTransport;
+use super::{Error, ObjectId, Se05X};
+
+/// Result of [`Se05X::hkdf`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HkdfOutput<'buf> {
+    /// The derived key, returned when the command has no `target`
+    Returned(&'buf [u8]),
+    /// The object in which the derived key was stored
+    Stored(ObjectId),
+}
+
+impl<'buf> HkdfOutput<'buf> {
+    /// The derived key, if it was returned
+    pub fn returned(self) -> Option<&'buf [u8]> {
+        match self {
+            Self::Returned(data) => Some(data),
+            Self::Stored(_) => None,
+        }
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Run `hkdf`, returning the derived key or storing it depending on its `target`
+    ///
+    /// When the key is returned, `response_buf` must hold the requested length and 4 bytes of overhead.
+    pub fn hkdf<'buf>(
+        &mut self,
+        hkdf: &Hkdf<'_>,
+        response_buf: &'buf mut [u8],
+    ) -> Result<HkdfOutput<'buf>, Error> {
+        let data = self.run_command(hkdf, response_buf)?.data;
+        match (hkdf.target, data) {
+            (None, Some(data)) => Ok(HkdfOutput::Returned(data)),
+            (Some(target), None) => Ok(HkdfOutput::Stored(target)),
+            (None, None) => {
+                error!("Missing derived key in response");
+                Err(Error::Line(line!()))
+            }
+            (Some(_), Some(_)) => {
+                error!("Derived key returned instead of being stored");
+                Err(Error::Line(line!()))
+            }
+        }
+    }
+}
@@ -0,0 +1,332 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Key URIs for desktop tooling
+//!
+//! Tools built around OpenSSL engines or providers address keys with URIs. This module parses URIs of the form
+//! `se05x:objectid=0x12345678`, optionally followed by `;type=ec` or `;type=rsa`, into a [`KeyUri`].
+//! [`KeyUri::resolve`] checks that the object holds a private key of the expected type and returns a [`KeyHandle`],
+//! whose [`sign`](KeyHandle::sign) and [`decrypt`](KeyHandle::decrypt) methods can back the callbacks of such tools.
+//!
+//! ```rust,no_run
+//! # use se05x::se05x::{Se05X, key_uri::KeyUri};
+//! # fn sign<T: se05x::se05x::transport::Se05xTransport>(se05x: &mut Se05X<T>, digest: &[u8]) -> Result<(), se05x::se05x::Error> {
+//! let uri: KeyUri = "se05x:objectid=0x20000001;type=ec".parse()?;
+//! let key = uri.resolve(se05x)?;
+//! let buf = &mut [0; 128];
+//! let _signature = key.sign(se05x, digest, buf)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::commands::{EcdsaSign, ReadType};
+#[cfg(feature = "rsa")]
+use super::commands::{RsaDecrypt, RsaSign};
+use super::transport::Se05xTransport;
+use super::{EcDsaSignatureAlgo, Error, ObjectId, Se05X, SecureObjectType};
+#[cfg(feature = "rsa")]
+use super::{RsaEncryptionAlgo, RsaSignatureAlgo};
+
+/// Scheme of the key URIs
+pub const KEY_URI_SCHEME: &str = "se05x";
+
+/// Type of the private key addressed by a [`KeyUri`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
+    Ec,
+    #[cfg(feature = "rsa")]
+    Rsa,
+}
+
+impl KeyType {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Ec => "ec",
+            #[cfg(feature = "rsa")]
+            Self::Rsa => "rsa",
+        }
+    }
+
+    /// Type of the private key held by an object of type `ty`, if any
+    pub fn of_object(ty: SecureObjectType) -> Option<Self> {
+        match ty {
+            SecureObjectType::EcKeyPair | SecureObjectType::EcPrivKey => Some(Self::Ec),
+            #[cfg(feature = "rsa")]
+            SecureObjectType::RsaKeyPair
+            | SecureObjectType::RsaPrivKey
+            | SecureObjectType::RsaKeyPairCrt
+            | SecureObjectType::RsaPrivKeyCrt => Some(Self::Rsa),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for KeyType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "ec" => Ok(Self::Ec),
+            #[cfg(feature = "rsa")]
+            "rsa" => Ok(Self::Rsa),
+            _ => {
+                error!("Unsupported key type: {s}");
+                Err(Error::Line(line!()))
+            }
+        }
+    }
+}
+
+/// Parsed key URI, see the [module documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyUri {
+    pub object_id: ObjectId,
+    /// Expected type of the key, checked by [`resolve`](Self::resolve)
+    pub key_type: Option<KeyType>,
+}
+
+/// Parse an object ID, in hexadecimal with a `0x` prefix or in decimal
+fn parse_object_id(s: &str) -> Result<ObjectId, Error> {
+    let id = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+    match id {
+        Ok(id) => Ok(ObjectId(id.to_be_bytes())),
+        Err(_) => {
+            error!("Invalid object ID: {s}");
+            Err(Error::Line(line!()))
+        }
+    }
+}
+
+impl FromStr for KeyUri {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let Some(attributes) = s
+            .strip_prefix(KEY_URI_SCHEME)
+            .and_then(|rest| rest.strip_prefix(':'))
+        else {
+            error!("Not an se05x key URI: {s}");
+            return Err(Error::Line(line!()));
+        };
+        let mut object_id = None;
+        let mut key_type = None;
+        for attribute in attributes.split(';') {
+            let Some((name, value)) = attribute.split_once('=') else {
+                error!("Invalid attribute in key URI: {attribute}");
+                return Err(Error::Line(line!()));
+            };
+            let duplicate = match name {
+                "objectid" => object_id.replace(parse_object_id(value)?).is_some(),
+                "type" => key_type.replace(value.parse()?).is_some(),
+                _ => {
+                    error!("Unknown attribute in key URI: {name}");
+                    return Err(Error::Line(line!()));
+                }
+            };
+            if duplicate {
+                error!("Duplicate attribute in key URI: {name}");
+                return Err(Error::Line(line!()));
+            }
+        }
+        let Some(object_id) = object_id else {
+            error!("Key URI without object ID: {s}");
+            return Err(Error::Line(line!()));
+        };
+        Ok(Self {
+            object_id,
+            key_type,
+        })
+    }
+}
+
+impl fmt::Display for KeyUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{KEY_URI_SCHEME}:objectid=0x{}", self.object_id)?;
+        if let Some(key_type) = self.key_type {
+            write!(f, ";type={}", key_type.name())?;
+        }
+        Ok(())
+    }
+}
+
+impl KeyUri {
+    /// Check that the object exists and holds a private key of the expected type
+    pub fn resolve<T: Se05xTransport>(&self, se05x: &mut Se05X<T>) -> Result<KeyHandle, Error> {
+        let ty = se05x
+            .run_command(
+                &ReadType {
+                    object_id: self.object_id,
+                },
+                &mut [0; 16],
+            )?
+            .ty;
+        let Some(key_type) = KeyType::of_object(ty) else {
+            error!("{:?} is not a private key: {ty:?}", self.object_id);
+            return Err(Error::Line(line!()));
+        };
+        if self.key_type.is_some_and(|expected| expected != key_type) {
+            error!("{:?} is a {key_type:?} key", self.object_id);
+            return Err(Error::Line(line!()));
+        }
+        Ok(KeyHandle {
+            object_id: self.object_id,
+            key_type,
+        })
+    }
+}
+
+/// Private key resolved from a [`KeyUri`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyHandle {
+    pub object_id: ObjectId,
+    pub key_type: KeyType,
+}
+
+impl KeyHandle {
+    /// Sign `digest`, returning the DER encoded ECDSA signature or the PKCS#1 v1.5 RSA signature
+    ///
+    /// The digest algorithm is deduced from the length of `digest`: SHA-1, SHA-224, SHA-256, SHA-384 or SHA-512.
+    pub fn sign<'buf, T: Se05xTransport>(
+        &self,
+        se05x: &mut Se05X<T>,
+        digest: &[u8],
+        buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        let Some(index) = [20, 28, 32, 48, 64].iter().position(|&l| l == digest.len()) else {
+            error!("Invalid digest length: {}", digest.len());
+            return Err(Error::Line(line!()));
+        };
+        match self.key_type {
+            KeyType::Ec => {
+                use EcDsaSignatureAlgo::*;
+                let algo = [Sha, Sha224, Sha256, Sha384, Sha512][index];
+                let command = EcdsaSign {
+                    key_id: self.object_id,
+                    algo,
+                    data: digest,
+                };
+                Ok(se05x.run_command(&command, buf)?.signature)
+            }
+            #[cfg(feature = "rsa")]
+            KeyType::Rsa => {
+                use RsaSignatureAlgo::*;
+                let algo = [
+                    RsaSha1Pkcs1,
+                    RsaSha224Pkcs1,
+                    RsaSha256Pkcs1,
+                    RsaSha384Pkcs1,
+                    RsaSha512Pkcs1,
+                ][index];
+                let command = RsaSign {
+                    key_id: self.object_id,
+                    algo,
+                    data: digest,
+                };
+                Ok(se05x.run_command(&command, buf)?.signature)
+            }
+        }
+    }
+
+    /// Decrypt `ciphertext` with an RSA key, returning the plaintext
+    ///
+    /// EC keys can't decrypt and return an error.
+    #[cfg(feature = "rsa")]
+    pub fn decrypt<'buf, T: Se05xTransport>(
+        &self,
+        se05x: &mut Se05X<T>,
+        padding: RsaEncryptionAlgo,
+        ciphertext: &[u8],
+        buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        if self.key_type != KeyType::Rsa {
+            error!("{:?} is not an RSA key", self.object_id);
+            return Err(Error::Line(line!()));
+        }
+        let command = RsaDecrypt {
+            key_id: self.object_id,
+            algo: padding,
+            ciphertext,
+        };
+        Ok(se05x.run_command(&command, buf)?.plaintext)
+    }
+}
+
+#[cfg(all(test, feature = "rsa"))]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use crate::se05x::{TYPE_EC_KEY_PAIR, TYPE_RSA_KEY_PAIR};
+    use hex_literal::hex;
+
+    #[test]
+    fn parse_uri() {
+        let uri: KeyUri = "se05x:objectid=0x12345678;type=rsa".parse().unwrap();
+        assert_eq!(
+            uri,
+            KeyUri {
+                object_id: ObjectId([0x12, 0x34, 0x56, 0x78]),
+                key_type: Some(KeyType::Rsa),
+            }
+        );
+        assert_eq!(uri.to_string(), "se05x:objectid=0x12345678;type=rsa");
+        assert_eq!(
+            "se05x:objectid=16".parse::<KeyUri>().unwrap().object_id,
+            ObjectId([0, 0, 0, 0x10])
+        );
+        for invalid in [
+            "pkcs11:objectid=0x1",
+            "se05x:",
+            "se05x:type=ec",
+            "se05x:objectid=0x1;objectid=0x2",
+            "se05x:objectid=0xZZ",
+            "se05x:objectid=0x1;label=key",
+            "se05x:objectid=0x1;type=dsa",
+        ] {
+            assert!(invalid.parse::<KeyUri>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn resolve_and_sign() {
+        // `00000001` is an EC key pair and `00000002` an RSA key pair
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(
+                &hex!("80 02 00 26 00 00 06 41 04 00000001 00 00"),
+                &[0x41, 0x01, TYPE_EC_KEY_PAIR, 0x42, 0x01, 0x01, 0x90, 0x00],
+            )
+            .expect(
+                &[
+                    &hex!("80 03 0C 09 00 00 2B 41 04 00000001 42 01 21 43 20")[..],
+                    &[0; 32],
+                    &hex!("00 00"),
+                ]
+                .concat(),
+                &hex!("41 02 3000 90 00"),
+            )
+            .expect(
+                &hex!("80 02 00 26 00 00 06 41 04 00000002 00 00"),
+                &[0x41, 0x01, TYPE_RSA_KEY_PAIR, 0x42, 0x01, 0x01, 0x90, 0x00],
+            );
+        let mut se05x = script.enable();
+        let ec: KeyUri = "se05x:objectid=0x00000001".parse().unwrap();
+        let key = ec.resolve(&mut se05x).unwrap();
+        assert_eq!(key.key_type, KeyType::Ec);
+        let buf = &mut [0; 64];
+        assert_eq!(key.sign(&mut se05x, &[0; 32], buf).unwrap(), [0x30, 0x00]);
+        assert!(key.sign(&mut se05x, &[0; 31], buf).is_err());
+        assert!(key
+            .decrypt(&mut se05x, RsaEncryptionAlgo::Pkcs1Oaep, &[0; 256], buf)
+            .is_err());
+
+        let mismatch: KeyUri = "se05x:objectid=0x00000002;type=ec".parse().unwrap();
+        assert!(mismatch.resolve(&mut se05x).is_err());
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+}
@@ -0,0 +1,460 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Label based key management
+//!
+//! Middleware such as PKCS#11 addresses keys through handles and labels rather than raw 4-byte object IDs.
+//! [`KeyStore`] provides these semantics, and [`Se05xKeyStore`] implements it on top of the se05x.
+//!
+//! [`Se05xKeyStore`] keeps a label → [`ObjectId`] table in a binary file object on the secure element.
+//! The table holds [`KEYSTORE_CAPACITY`] fixed-size slots. Each slot is made of the length of the label followed by the label, padded to [`LABEL_MAX_LEN`].
+//! The key stored in slot `n` has the object ID `key_id_base + n`, so keys need no separate allocation.
+//! The table is read once and then cached, so it must not be modified by other means while a [`Se05xKeyStore`] uses it.
+//!
+//! Keys are persistent objects. The se05x only exports transient objects, so the keys cannot be wrapped out of the secure element.
+
+use core::fmt;
+
+use super::commands::{
+    CheckObjectExists, CipherOneShotDecrypt, DeleteSecureObject, EcdsaSign, GenEcKey, ReadObject,
+    WriteBinary,
+};
+use super::transport::Se05xTransport;
+use super::{CipherMode, EcCurve, EcDsaSignatureAlgo, Error, ObjectId, Se05X};
+
+/// Maximum length of a label
+pub const LABEL_MAX_LEN: usize = 32;
+/// Number of keys the table can hold
+pub const KEYSTORE_CAPACITY: usize = 32;
+
+const SLOT_LEN: usize = 1 + LABEL_MAX_LEN;
+const INDEX_LEN: usize = SLOT_LEN * KEYSTORE_CAPACITY;
+/// Size of the chunks in which the table is read
+const READ_CHUNK_LEN: usize = 256;
+
+/// Error of the [`Se05xKeyStore`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStoreError {
+    /// The key IDs overflow or include [reserved](ObjectId::is_reserved) IDs
+    InvalidKeyIds,
+    /// The label is empty or longer than [`LABEL_MAX_LEN`]
+    InvalidLabel,
+    /// No key is registered under the label
+    NotFound,
+    /// A key is already registered under the label
+    LabelInUse,
+    /// All [`KEYSTORE_CAPACITY`] slots are used
+    Full,
+    /// Running a command failed
+    Se05x(Error),
+}
+
+impl From<Error> for KeyStoreError {
+    fn from(value: Error) -> Self {
+        Self::Se05x(value)
+    }
+}
+
+impl fmt::Display for KeyStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKeyIds => f.write_str("Invalid key IDs"),
+            Self::InvalidLabel => f.write_str("Invalid label length"),
+            Self::NotFound => f.write_str("No key with this label"),
+            Self::LabelInUse => f.write_str("Label already in use"),
+            Self::Full => f.write_str("Key table is full"),
+            Self::Se05x(err) => err.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for KeyStoreError {}
+
+/// Key management with handle and label semantics
+pub trait KeyStore {
+    type Error;
+
+    /// Find the key with the given label
+    fn find(&mut self, label: &[u8]) -> Result<Option<ObjectId>, Self::Error>;
+
+    /// Generate an EC key pair on `curve` and register it under `label`
+    fn create_ec_key(&mut self, label: &[u8], curve: EcCurve) -> Result<ObjectId, Self::Error>;
+
+    /// Delete the key registered under `label`
+    fn delete(&mut self, label: &[u8]) -> Result<(), Self::Error>;
+
+    /// Sign `digest` with the key registered under `label`. Returns the DER encoded signature
+    fn sign<'buf>(
+        &mut self,
+        label: &[u8],
+        algo: EcDsaSignatureAlgo,
+        digest: &[u8],
+        buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Self::Error>;
+
+    /// Decrypt `ciphertext` with the key registered under `label`
+    fn decrypt<'buf>(
+        &mut self,
+        label: &[u8],
+        mode: CipherMode,
+        initialization_vector: Option<&[u8]>,
+        ciphertext: &[u8],
+        buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Self::Error>;
+}
+
+/// [`KeyStore`] backed by the se05x
+pub struct Se05xKeyStore<'se, T> {
+    se: &'se mut Se05X<T>,
+    index_id: ObjectId,
+    key_id_base: u32,
+    /// Copy of the table, read on first use
+    index: Option<[u8; INDEX_LEN]>,
+}
+
+impl<'se, T: Se05xTransport> Se05xKeyStore<'se, T> {
+    /// The table is stored in the binary file `index_id`. Keys use the object IDs `key_id_base..key_id_base + KEYSTORE_CAPACITY`
+    ///
+    /// Fails if one of the key IDs is [reserved](ObjectId::is_reserved).
+    pub fn new(
+        se: &'se mut Se05X<T>,
+        index_id: ObjectId,
+        key_id_base: ObjectId,
+    ) -> Result<Self, KeyStoreError> {
+        let base = u32::from_be_bytes(key_id_base.0);
+        let valid = (0..KEYSTORE_CAPACITY as u32).all(|slot| {
+            base.checked_add(slot)
+                .is_some_and(|id| !ObjectId(id.to_be_bytes()).is_reserved())
+        });
+        if !valid {
+            error!("Invalid key ID base: {base:08x}");
+            return Err(KeyStoreError::InvalidKeyIds);
+        }
+        Ok(Self {
+            se,
+            index_id,
+            key_id_base: base,
+            index: None,
+        })
+    }
+
+    /// Create the table if it does not exist yet
+    pub fn init(&mut self) -> Result<(), KeyStoreError> {
+        let exists = self
+            .se
+            .run_command(
+                &CheckObjectExists {
+                    object_id: self.index_id,
+                },
+                &mut [0; 16],
+            )?
+            .result
+            .is_success();
+        if exists {
+            return Ok(());
+        }
+        self.se.run_command(
+            &WriteBinary {
+                transient: false,
+                policy: None,
+                object_id: self.index_id,
+                offset: None,
+                file_length: Some((INDEX_LEN as u16).into()),
+                data: None,
+            },
+            &mut [0; 2],
+        )?;
+        self.se
+            .write_binary_chunked(self.index_id, 0, &[0; INDEX_LEN])?;
+        self.index = Some([0; INDEX_LEN]);
+        Ok(())
+    }
+
+    /// Object ID of the key in `slot`
+    pub fn slot_id(&self, slot: usize) -> ObjectId {
+        ObjectId((self.key_id_base + slot as u32).to_be_bytes())
+    }
+
+    fn index(&mut self) -> Result<&[u8; INDEX_LEN], Error> {
+        if self.index.is_none() {
+            self.index = Some(self.read_index()?);
+        }
+        Ok(self.index.as_ref().unwrap())
+    }
+
+    fn read_index(&mut self) -> Result<[u8; INDEX_LEN], Error> {
+        let mut index = [0; INDEX_LEN];
+        let buf = &mut [0; READ_CHUNK_LEN + 8];
+        for (i, chunk) in index.chunks_mut(READ_CHUNK_LEN).enumerate() {
+            let offset = (i * READ_CHUNK_LEN) as u16;
+            let response = self.se.run_command(
+                &ReadObject {
+                    object_id: self.index_id,
+                    offset: Some(offset.into()),
+                    length: Some((chunk.len() as u16).into()),
+                    rsa_key_component: None,
+                },
+                buf,
+            )?;
+            if response.data.len() != chunk.len() {
+                error!("Unexpected key table length");
+                return Err(Error::Line(line!()));
+            }
+            chunk.copy_from_slice(response.data);
+        }
+        Ok(index)
+    }
+
+    fn write_slot(&mut self, slot: usize, label: &[u8]) -> Result<(), Error> {
+        let mut data = [0; SLOT_LEN];
+        data[0] = label.len() as u8;
+        data[1..][..label.len()].copy_from_slice(label);
+        // The cached table no longer matches if the write fails halfway
+        let cached = self.index.take();
+        self.se
+            .write_binary_chunked(self.index_id, (slot * SLOT_LEN) as u16, &data)?;
+        if let Some(mut index) = cached {
+            index[slot * SLOT_LEN..][..SLOT_LEN].copy_from_slice(&data);
+            self.index = Some(index);
+        }
+        Ok(())
+    }
+
+    fn find_slot(&mut self, label: &[u8]) -> Result<Option<usize>, Error> {
+        let index = self.index()?;
+        Ok(index.chunks_exact(SLOT_LEN).position(|slot| {
+            let len = slot[0] as usize;
+            len != 0 && slot[1..].get(..len) == Some(label)
+        }))
+    }
+
+    fn require(&mut self, label: &[u8]) -> Result<ObjectId, KeyStoreError> {
+        match self.find_slot(label)? {
+            Some(slot) => Ok(self.slot_id(slot)),
+            None => {
+                debug!("No key with label {:02x?}", label);
+                Err(KeyStoreError::NotFound)
+            }
+        }
+    }
+}
+
+impl<T: Se05xTransport> KeyStore for Se05xKeyStore<'_, T> {
+    type Error = KeyStoreError;
+
+    fn find(&mut self, label: &[u8]) -> Result<Option<ObjectId>, KeyStoreError> {
+        Ok(self.find_slot(label)?.map(|slot| self.slot_id(slot)))
+    }
+
+    /// If the key cannot be registered in the table, it is deleted again
+    fn create_ec_key(&mut self, label: &[u8], curve: EcCurve) -> Result<ObjectId, KeyStoreError> {
+        if label.is_empty() || label.len() > LABEL_MAX_LEN {
+            error!("Invalid label length: {}", label.len());
+            return Err(KeyStoreError::InvalidLabel);
+        }
+        let index = self.index()?;
+        let mut free = None;
+        for (i, slot) in index.chunks_exact(SLOT_LEN).enumerate() {
+            let len = slot[0] as usize;
+            if len == 0 {
+                free = free.or(Some(i));
+            } else if slot[1..].get(..len) == Some(label) {
+                error!("Label already in use");
+                return Err(KeyStoreError::LabelInUse);
+            }
+        }
+        let Some(slot) = free else {
+            error!("Key table is full");
+            return Err(KeyStoreError::Full);
+        };
+        let object_id = self.slot_id(slot);
+        self.se.run_command(
+            &GenEcKey {
+                transient: false,
+                is_auth: false,
+                policy: None,
+                max_attempts: None,
+                object_id,
+                curve: Some(curve),
+            },
+            &mut [0; 2],
+        )?;
+        if let Err(err) = self.write_slot(slot, label) {
+            error!("Failed to register the key, deleting it");
+            self.se
+                .run_command(&DeleteSecureObject { object_id }, &mut [0; 2])
+                .ok();
+            return Err(err.into());
+        }
+        Ok(object_id)
+    }
+
+    fn delete(&mut self, label: &[u8]) -> Result<(), KeyStoreError> {
+        let Some(slot) = self.find_slot(label)? else {
+            debug!("No key with label {:02x?}", label);
+            return Err(KeyStoreError::NotFound);
+        };
+        self.se.run_command(
+            &DeleteSecureObject {
+                object_id: self.slot_id(slot),
+            },
+            &mut [0; 2],
+        )?;
+        Ok(self.write_slot(slot, &[])?)
+    }
+
+    fn sign<'buf>(
+        &mut self,
+        label: &[u8],
+        algo: EcDsaSignatureAlgo,
+        digest: &[u8],
+        buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], KeyStoreError> {
+        let key_id = self.require(label)?;
+        Ok(self
+            .se
+            .run_command(
+                &EcdsaSign {
+                    key_id,
+                    algo,
+                    data: digest,
+                },
+                buf,
+            )?
+            .signature)
+    }
+
+    fn decrypt<'buf>(
+        &mut self,
+        label: &[u8],
+        mode: CipherMode,
+        initialization_vector: Option<&[u8]>,
+        ciphertext: &[u8],
+        buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], KeyStoreError> {
+        let key_id = self.require(label)?;
+        Ok(self
+            .se
+            .run_command(
+                &CipherOneShotDecrypt {
+                    key_id,
+                    mode,
+                    ciphertext,
+                    initialization_vector,
+                },
+                buf,
+            )?
+            .plaintext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::{ApduLink, ApduSe05X, ScriptedTransport};
+    use crate::se05x::{APPLET_RESERVED_RANGE, EPHEMERAL_PUBLIC_KEY_ID};
+    use hex_literal::hex;
+
+    #[test]
+    fn key_id_base() {
+        // No command is sent
+        let mut se05x = ApduSe05X::from_transport(ApduLink::new(ScriptedTransport::new()));
+        let index_id = ObjectId([0x10, 0, 0, 0]);
+        let store = Se05xKeyStore::new(&mut se05x, index_id, ObjectId([0x20, 0, 0, 0])).unwrap();
+        assert_eq!(store.slot_id(31), ObjectId([0x20, 0, 0, 0x1F]));
+
+        let last_free = u32::from_be_bytes(EPHEMERAL_PUBLIC_KEY_ID.0) - KEYSTORE_CAPACITY as u32;
+        assert!(
+            Se05xKeyStore::new(&mut se05x, index_id, ObjectId(last_free.to_be_bytes())).is_ok()
+        );
+        for base in [
+            // Reaches the ephemeral public key ID
+            last_free + 1,
+            // Reaches the range reserved for the applet
+            APPLET_RESERVED_RANGE.start() - 1,
+            // Overflows
+            u32::MAX - 4,
+        ] {
+            assert_eq!(
+                Se05xKeyStore::new(&mut se05x, index_id, ObjectId(base.to_be_bytes())).err(),
+                Some(KeyStoreError::InvalidKeyIds)
+            );
+        }
+    }
+
+    /// Expect the table in `10000000` to be read in chunks, and answer that it is empty
+    fn expect_empty_index(script: &mut ScriptedTransport) {
+        for offset in (0..INDEX_LEN).step_by(READ_CHUNK_LEN) {
+            let len = READ_CHUNK_LEN.min(INDEX_LEN - offset) as u16;
+            let read = [
+                &hex!("80 02 00 00 00 00 0E 41 04 10000000 42 02")[..],
+                &(offset as u16).to_be_bytes(),
+                &hex!("43 02"),
+                &len.to_be_bytes(),
+                &hex!("00 00"),
+            ]
+            .concat();
+            let response = [
+                &hex!("41 82")[..],
+                &len.to_be_bytes(),
+                &vec![0; len.into()],
+                &hex!("90 00"),
+            ]
+            .concat();
+            script.expect(&read, &response);
+        }
+    }
+
+    /// Generates a NIST P-256 key pair in the first slot
+    const GEN_EC_KEY: [u8; 14] = hex!("80 01 61 00 09 41 04 20000000 42 01 03");
+
+    #[test]
+    fn create_ec_key() {
+        let mut script = ScriptedTransport::new();
+        expect_empty_index(&mut script);
+        let write_slot = [
+            &hex!("80 01 06 00 2D 41 04 10000000 42 02 0000 44 21 03")[..],
+            b"key",
+            &[0; LABEL_MAX_LEN - 3],
+        ]
+        .concat();
+        script
+            // The key is deleted if it cannot be registered
+            .expect(&GEN_EC_KEY, &hex!("90 00"))
+            .expect(&write_slot, &hex!("69 85"))
+            .expect(&hex!("80 04 00 28 06 41 04 20000000"), &hex!("90 00"));
+        // The table is read again after the failed write, and then kept
+        expect_empty_index(&mut script);
+        script
+            .expect(&GEN_EC_KEY, &hex!("90 00"))
+            .expect(&write_slot, &hex!("90 00"));
+        let mut se05x = script.enable();
+        let mut store = Se05xKeyStore::new(
+            &mut se05x,
+            ObjectId([0x10, 0, 0, 0]),
+            ObjectId([0x20, 0, 0, 0]),
+        )
+        .unwrap();
+        assert_eq!(
+            store.create_ec_key(b"key", EcCurve::NistP256),
+            Err(KeyStoreError::Se05x(Error::Status(0x6985.into())))
+        );
+        assert_eq!(store.find(b"key"), Ok(None));
+        assert_eq!(
+            store.create_ec_key(b"", EcCurve::NistP256),
+            Err(KeyStoreError::InvalidLabel)
+        );
+        assert_eq!(
+            store.create_ec_key(b"key", EcCurve::NistP256),
+            Ok(ObjectId([0x20, 0, 0, 0]))
+        );
+        assert_eq!(
+            store.create_ec_key(b"key", EcCurve::NistP256),
+            Err(KeyStoreError::LabelInUse)
+        );
+        assert_eq!(store.find(b"key"), Ok(Some(ObjectId([0x20, 0, 0, 0]))));
+        assert_eq!(store.delete(b"other"), Err(KeyStoreError::NotFound));
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+}
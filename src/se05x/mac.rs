@@ -0,0 +1,345 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! MAC computation with keys stored in the se05x
+//!
+//! Short messages can be authenticated with [`Se05X::hmac`], or [`Se05X::hmac_sha256`] with a key imported with [`Se05X::import_hmac_key`].
+//! Longer messages are fed incrementally to a [`HmacStream`], which manages the crypto object holding the state of the computation.
+
+use core::ops::RangeInclusive;
+
+use super::commands::{
+    CreateSignatureObject, DeleteCryptoObj, MacGenerateFinal, MacGenerateInit, MacOneShotGenerate,
+    MacUpdate, WriteSymmKey,
+};
+use super::policies::PolicySet;
+use super::transport::Se05xTransport;
+use super::{
+    AppletConfig, Atr, CryptoObjectId, Error, MacAlgo, ObjectId, Se05X, SymmKeyType, CHUNK_LEN,
+};
+
+/// Maximum length of the HMAC keys accepted by the applet
+pub const MAX_HMAC_KEY_LEN: usize = 256;
+/// Minimum length of the HMAC keys accepted by applets in FIPS mode: 112 bits, following NIST SP 800-131A
+pub const MIN_FIPS_HMAC_KEY_LEN: usize = 14;
+/// Length of HMAC-SHA256 tags
+pub const HMAC_SHA256_LEN: usize = 32;
+
+/// Applet feature required by `algo`
+fn required_feature(algo: MacAlgo) -> AppletConfig {
+    match algo {
+        MacAlgo::HmacSha1 | MacAlgo::HmacSha256 | MacAlgo::HmacSha384 | MacAlgo::HmacSha512 => {
+            AppletConfig::HMAC
+        }
+        MacAlgo::Cmac128 | MacAlgo::AesCmac16 => AppletConfig::AES,
+        #[cfg(feature = "legacy-des")]
+        _ => AppletConfig::DES,
+    }
+}
+
+impl Atr {
+    /// Lengths of the HMAC keys accepted by the applet, empty if it does not offer [`AppletConfig::HMAC`]
+    ///
+    /// Applets in FIPS mode, without [`AppletConfig::FIPS_MODE_DISABLED`], reject keys shorter than [`MIN_FIPS_HMAC_KEY_LEN`].
+    pub fn hmac_key_lens(&self) -> RangeInclusive<usize> {
+        if !self.applet_config.contains(AppletConfig::HMAC) {
+            return RangeInclusive::new(1, 0);
+        }
+        if self
+            .applet_config
+            .contains(AppletConfig::FIPS_MODE_DISABLED)
+        {
+            1..=MAX_HMAC_KEY_LEN
+        } else {
+            MIN_FIPS_HMAC_KEY_LEN..=MAX_HMAC_KEY_LEN
+        }
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Compute the MAC of `data` in a single command with [`MacOneShotGenerate`]
+    ///
+    /// `data` must fit in a single command. Use [`hmac_stream`](Self::hmac_stream) for longer data.
+    pub fn hmac<'buf>(
+        &mut self,
+        key_id: ObjectId,
+        algo: MacAlgo,
+        data: &[u8],
+        response_buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        self.require_feature(required_feature(algo))?;
+        Ok(self
+            .run_command(&MacOneShotGenerate { key_id, algo, data }, response_buf)?
+            .tag)
+    }
+
+    /// Store `secret` in a new HMAC key object
+    ///
+    /// The length of the secret is checked against the [lengths accepted by the applet](Atr::hmac_key_lens) before the key is sent.
+    /// Before the applet is [enabled](Self::enable), keys of 1 to [`MAX_HMAC_KEY_LEN`] bytes are accepted.
+    pub fn import_hmac_key(
+        &mut self,
+        object_id: ObjectId,
+        secret: &[u8],
+        policy: Option<PolicySet<'_>>,
+    ) -> Result<(), Error> {
+        self.require_feature(AppletConfig::HMAC)?;
+        let lens = self
+            .atr()
+            .map_or(1..=MAX_HMAC_KEY_LEN, |atr| atr.hmac_key_lens());
+        if !lens.contains(&secret.len()) {
+            error!(
+                "HMAC key length not accepted by the applet: {}",
+                secret.len()
+            );
+            return Err(Error::InvalidKeyLength {
+                key_type: SymmKeyType::Hmac,
+                len: secret.len(),
+            });
+        }
+        self.run_command(
+            &WriteSymmKey {
+                transient: false,
+                is_auth: false,
+                key_type: SymmKeyType::Hmac,
+                policy,
+                max_attempts: None,
+                object_id,
+                kek_id: None,
+                value: secret,
+            },
+            &mut [0; 2],
+        )
+    }
+
+    /// Compute the HMAC-SHA256 of `data` with the HMAC key `key_id`
+    ///
+    /// `data` must fit in a single command. Use [`hmac_stream`](Self::hmac_stream) for longer data.
+    pub fn hmac_sha256(
+        &mut self,
+        key_id: ObjectId,
+        data: &[u8],
+    ) -> Result<[u8; HMAC_SHA256_LEN], Error> {
+        let buf = &mut [0; HMAC_SHA256_LEN + 8];
+        let tag = self.hmac(key_id, MacAlgo::HmacSha256, data, buf)?;
+        tag.try_into().map_err(|_| {
+            error!("Unexpected HMAC length: {}", tag.len());
+            Error::Tlv
+        })
+    }
+
+    /// Start a MAC computation with `key_id`, using the crypto object `mac_id`
+    ///
+    /// `mac_id` must not be in use. It is created here and deleted when the stream is finished, aborted or dropped.
+    pub fn hmac_stream(
+        &mut self,
+        key_id: ObjectId,
+        algo: MacAlgo,
+        mac_id: CryptoObjectId,
+    ) -> Result<HmacStream<'_, T>, Error> {
+        self.require_feature(required_feature(algo))?;
+        self.run_command(
+            &CreateSignatureObject {
+                id: mac_id,
+                subtype: algo,
+            },
+            &mut [0; 2],
+        )?;
+        let stream = HmacStream {
+            se: self,
+            mac_id,
+            finished: false,
+        };
+        stream
+            .se
+            .run_command(&MacGenerateInit { key_id, mac_id }, &mut [0; 2])?;
+        Ok(stream)
+    }
+}
+
+/// Incremental MAC computation
+///
+/// Created with [`Se05X::hmac_stream`]. The crypto object is deleted by [`finish`](Self::finish) and [`abort`](Self::abort),
+/// or when the stream is dropped, in which case errors are ignored.
+pub struct HmacStream<'se, T: Se05xTransport> {
+    se: &'se mut Se05X<T>,
+    mac_id: CryptoObjectId,
+    finished: bool,
+}
+
+impl<T: Se05xTransport> HmacStream<'_, T> {
+    pub fn mac_id(&self) -> CryptoObjectId {
+        self.mac_id
+    }
+
+    /// Feed `data` to the computation, in chunks of [`CHUNK_LEN`]
+    pub fn update(&mut self, data: &[u8]) -> Result<(), Error> {
+        for chunk in data.chunks(CHUNK_LEN) {
+            self.se.run_command(
+                &MacUpdate {
+                    data: chunk,
+                    mac_id: self.mac_id,
+                },
+                &mut [0; 2],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Finish the computation and return the MAC
+    pub fn finish(mut self, response_buf: &mut [u8]) -> Result<&[u8], Error> {
+        let tag = self
+            .se
+            .run_command(
+                &MacGenerateFinal {
+                    data: &[],
+                    mac_id: self.mac_id,
+                },
+                response_buf,
+            )
+            .map(|response| response.tag);
+        self.finished = true;
+        let deleted = self.delete();
+        let tag = tag?;
+        deleted?;
+        Ok(tag)
+    }
+
+    /// Abandon the computation, deleting the crypto object
+    pub fn abort(mut self) -> Result<(), Error> {
+        self.finished = true;
+        self.delete()
+    }
+
+    fn delete(&mut self) -> Result<(), Error> {
+        self.se
+            .run_command(&DeleteCryptoObj { id: self.mac_id }, &mut [0; 2])
+    }
+}
+
+impl<T: Se05xTransport> Drop for HmacStream<'_, T> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        if let Err(_err) = self.delete() {
+            warn!("Failed to delete MAC object on drop: {_err:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::{ApduLink, ApduSe05X, ScriptedTransport};
+    use hex_literal::hex;
+
+    #[test]
+    fn hmac_key() {
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(
+                &[
+                    &hex!("80 01 05 00 28 41 04 01020304 43 20")[..],
+                    &[0x55; 32],
+                ]
+                .concat(),
+                &hex!("90 00"),
+            )
+            .expect(
+                &hex!("80 03 0D 45 00 00 0F 41 04 01020304 42 01 19 43 04 64617461 00 00"),
+                &[&hex!("41 20")[..], &[0xAB; 32], &hex!("90 00")].concat(),
+            );
+        let mut se05x = script.enable();
+        let key_id = ObjectId([1, 2, 3, 4]);
+        assert_eq!(
+            se05x.import_hmac_key(key_id, &[], None),
+            Err(Error::InvalidKeyLength {
+                key_type: SymmKeyType::Hmac,
+                len: 0
+            })
+        );
+        assert_eq!(
+            se05x.import_hmac_key(key_id, &[0; MAX_HMAC_KEY_LEN + 1], None),
+            Err(Error::InvalidKeyLength {
+                key_type: SymmKeyType::Hmac,
+                len: MAX_HMAC_KEY_LEN + 1
+            })
+        );
+        se05x.import_hmac_key(key_id, &[0x55; 32], None).unwrap();
+        assert_eq!(se05x.hmac_sha256(key_id, b"data").unwrap(), [0xAB; 32]);
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn hmac_key_fips() {
+        let mut script = ScriptedTransport::new();
+        // Applet in FIPS mode, and without HMAC
+        script
+            .expect(
+                &hex!("00 A4 04 00 10 A0000003965453000000010300000000 00"),
+                &hex!("03 01 00 2F FF 01 00 90 00"),
+            )
+            .expect(
+                &[
+                    &hex!("80 01 05 00 16 41 04 01020304 43 0E")[..],
+                    &[0x55; 14],
+                ]
+                .concat(),
+                &hex!("90 00"),
+            )
+            .expect(
+                &hex!("00 A4 04 00 10 A0000003965453000000010300000000 00"),
+                &hex!("03 01 00 3F EF 01 00 90 00"),
+            );
+        let mut se05x = ApduSe05X::from_transport(ApduLink::new(script));
+        se05x.enable().unwrap();
+        let key_id = ObjectId([1, 2, 3, 4]);
+        assert_eq!(
+            se05x.import_hmac_key(key_id, &[0x55; 13], None),
+            Err(Error::InvalidKeyLength {
+                key_type: SymmKeyType::Hmac,
+                len: 13
+            })
+        );
+        se05x.import_hmac_key(key_id, &[0x55; 14], None).unwrap();
+
+        se05x.enable().unwrap();
+        assert!(se05x.atr().unwrap().hmac_key_lens().is_empty());
+        assert_eq!(
+            se05x.import_hmac_key(key_id, &[0x55; 14], None),
+            Err(Error::Unsupported(AppletConfig::HMAC))
+        );
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+    #[test]
+    fn hmac_stream_abort() {
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(
+                &hex!("80 01 10 00 0A 41 02 0001 42 01 03 43 01 19"),
+                &hex!("90 00"),
+            )
+            .expect(
+                &hex!("80 03 0D 03 0A 41 04 01020304 42 02 0001"),
+                &hex!("90 00"),
+            )
+            .expect(
+                &hex!("80 03 0D 0C 0A 41 04 64617461 42 02 0001"),
+                &hex!("90 00"),
+            )
+            // The crypto object is deleted
+            .expect(&hex!("80 04 10 28 04 41 02 0001"), &hex!("90 00"));
+        let mut se05x = script.enable();
+        let mut stream = se05x
+            .hmac_stream(
+                ObjectId([1, 2, 3, 4]),
+                MacAlgo::HmacSha256,
+                CryptoObjectId([0, 1]),
+            )
+            .unwrap();
+        stream.update(b"data").unwrap();
+        stream.abort().unwrap();
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Command latency metrics
+//!
+//! With a clock set by [`Se05X::set_metrics_clock`], the latency of each exchange with the se05x is recorded in a [`LatencyHistogram`] per instruction byte.
+//! Exchanges are timed from the start of the command to the end of the response, so the `GET RESPONSE` commands reassembling long responses are recorded separately,
+//! and retries are recorded as separate exchanges.
+//!
+//! The metrics help quantifying the overhead of the se05x and tuning the [retry policy](Se05X::set_retry_policy) and the timeouts of the transport.
+
+use super::transport::Se05xTransport;
+use super::Se05X;
+
+/// Upper bounds of the buckets of [`LatencyHistogram::buckets`], in microseconds
+///
+/// The last bucket holds the latencies above the last bound.
+pub const LATENCY_BUCKET_BOUNDS_US: [u64; 9] = [
+    1_000, 2_000, 5_000, 10_000, 20_000, 50_000, 100_000, 200_000, 500_000,
+];
+
+/// Source of the timestamps of the [`CommandMetrics`]
+///
+/// Implemented by closures returning a timestamp, so that a clock can own a hardware timer or keep state between calls.
+pub trait MetricsClock {
+    /// Current time in microseconds, monotonic
+    fn now_us(&mut self) -> u64;
+}
+
+impl<F: FnMut() -> u64> MetricsClock for F {
+    fn now_us(&mut self) -> u64 {
+        self()
+    }
+}
+
+/// Number of distinct instructions tracked by [`CommandMetrics`]
+pub const TRACKED_INSTRUCTIONS: usize = 12;
+
+/// Distribution of the latencies of the exchanges with one instruction
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    pub count: u32,
+    pub total_us: u64,
+    pub max_us: u64,
+    /// Number of exchanges per bucket of [`LATENCY_BUCKET_BOUNDS_US`]
+    pub buckets: [u32; LATENCY_BUCKET_BOUNDS_US.len() + 1],
+}
+
+impl LatencyHistogram {
+    fn record(&mut self, latency_us: u64) {
+        self.count = self.count.saturating_add(1);
+        self.total_us = self.total_us.saturating_add(latency_us);
+        self.max_us = self.max_us.max(latency_us);
+        let bucket = LATENCY_BUCKET_BOUNDS_US
+            .iter()
+            .position(|&bound| latency_us <= bound)
+            .unwrap_or(LATENCY_BUCKET_BOUNDS_US.len());
+        self.buckets[bucket] = self.buckets[bucket].saturating_add(1);
+    }
+
+    /// Mean latency, `None` if nothing was recorded
+    pub fn mean_us(&self) -> Option<u64> {
+        self.total_us.checked_div(self.count.into())
+    }
+}
+
+/// Latencies per instruction byte, returned by [`Se05X::metrics`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CommandMetrics {
+    entries: heapless::Vec<(u8, LatencyHistogram), TRACKED_INSTRUCTIONS>,
+    /// Number of exchanges not recorded because [`TRACKED_INSTRUCTIONS`] other instructions were already tracked
+    pub untracked: u32,
+}
+
+impl CommandMetrics {
+    /// Latencies of the exchanges with the instruction byte `ins`
+    pub fn get(&self, ins: u8) -> Option<&LatencyHistogram> {
+        self.entries
+            .iter()
+            .find(|(tracked, _)| *tracked == ins)
+            .map(|(_, histogram)| histogram)
+    }
+
+    /// Instruction bytes and their latencies, in the order they were first exchanged
+    pub fn iter(&self) -> impl Iterator<Item = (u8, &LatencyHistogram)> {
+        self.entries
+            .iter()
+            .map(|(ins, histogram)| (*ins, histogram))
+    }
+
+    pub(crate) fn record(&mut self, ins: u8, latency_us: u64) {
+        if let Some((_, histogram)) = self.entries.iter_mut().find(|(tracked, _)| *tracked == ins) {
+            histogram.record(latency_us);
+            return;
+        }
+        let mut histogram = LatencyHistogram::default();
+        histogram.record(latency_us);
+        if self.entries.push((ins, histogram)).is_err() {
+            self.untracked = self.untracked.saturating_add(1);
+        }
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Record the latency of the exchanges with the se05x, using `clock` to read timestamps in microseconds
+    ///
+    /// `clock` must be monotonic, for example backed by a hardware timer or by `std::time::Instant`. `None` stops recording.
+    /// The clock is borrowed for the lifetime of the driver: allocate it in a `static` or with `Box::leak`. It must be `Send`, like the driver.
+    pub fn set_metrics_clock(&mut self, clock: Option<&'static mut (dyn MetricsClock + Send)>) {
+        self.metrics_clock = clock;
+    }
+
+    /// Latencies recorded since the clock was set or since the last [`reset_metrics`](Self::reset_metrics)
+    pub fn metrics(&self) -> &CommandMetrics {
+        &self.metrics
+    }
+
+    pub fn reset_metrics(&mut self) {
+        self.metrics = CommandMetrics::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use crate::se05x::commands::GetRandom;
+    use hex_literal::hex;
+
+    #[test]
+    fn command_latency() {
+        let random = hex!("80 04 00 49 00 00 04 41 02 0002 00 00");
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(&random, &hex!("41 02 1234 90 00"))
+            .expect(&random, &hex!("41 02 1234 90 00"));
+        let mut se05x = script.enable();
+        assert_eq!(se05x.metrics(), &CommandMetrics::default());
+
+        // Each call advances the time by 1.5 ms
+        let mut now = 0;
+        let clock = move || {
+            now += 1_500;
+            now
+        };
+        se05x.set_metrics_clock(Some(Box::leak(Box::new(clock))));
+        se05x.enable().unwrap();
+        for _ in 0..2 {
+            se05x
+                .run_command(&GetRandom { length: 2.into() }, &mut [0; 8])
+                .unwrap();
+        }
+        let metrics = se05x.metrics();
+        assert_eq!(
+            metrics
+                .iter()
+                .map(|(ins, _)| ins)
+                .collect::<heapless::Vec<_, 4>>(),
+            [0xA4, 0x04]
+        );
+        let random = metrics.get(0x04).unwrap();
+        assert_eq!(random.count, 2);
+        assert_eq!(random.mean_us(), Some(1_500));
+        assert_eq!(random.max_us, 1_500);
+        assert_eq!(random.buckets[1], 2);
+        assert_eq!(metrics.get(0x03), None);
+        assert!(se05x.transport().inner_mut().is_done());
+
+        let mut metrics = CommandMetrics::default();
+        for ins in 0..=TRACKED_INSTRUCTIONS as u8 {
+            metrics.record(ins, 1_000_000);
+        }
+        assert_eq!(metrics.untracked, 1);
+        assert_eq!(
+            metrics.get(0).unwrap().buckets[LATENCY_BUCKET_BOUNDS_US.len()],
+            1
+        );
+
+        se05x.reset_metrics();
+        assert_eq!(se05x.metrics().get(0x04), None);
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Platform Configuration Registers
+//!
+//! A PCR holds a SHA-256 digest. Extending it with a measurement replaces the value with `SHA-256(value || measurement)`.
+//! Objects can require a PCR to hold a given value through [`ObjectAccessRule::require_pcr_value`](super::policies::ObjectAccessRule::require_pcr_value).
+
+use super::commands::{ReadObject, WritePcr};
+use super::transport::Se05xTransport;
+use super::{Error, ObjectId, Se05X};
+
+/// Size of a PCR value
+pub const PCR_LEN: usize = 32;
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Create a PCR with the given initial value
+    pub fn pcr_create(&mut self, id: ObjectId, initial: &[u8; PCR_LEN]) -> Result<(), Error> {
+        self.run_command(
+            &WritePcr {
+                transient: false,
+                policy: None,
+                object_id: id,
+                initial_value: Some(initial),
+                extend: None,
+            },
+            &mut [0; 2],
+        )
+    }
+
+    /// Extend a PCR with a measurement
+    pub fn pcr_extend(&mut self, id: ObjectId, measurement: &[u8]) -> Result<(), Error> {
+        self.run_command(
+            &WritePcr {
+                transient: false,
+                policy: None,
+                object_id: id,
+                initial_value: None,
+                extend: Some(measurement),
+            },
+            &mut [0; 2],
+        )
+    }
+
+    pub fn pcr_read(&mut self, id: ObjectId) -> Result<[u8; PCR_LEN], Error> {
+        let buf = &mut [0; PCR_LEN + 8];
+        let response = self.run_command(
+            &ReadObject {
+                object_id: id,
+                offset: None,
+                length: None,
+                rsa_key_component: None,
+            },
+            buf,
+        )?;
+        Ok(response.data.try_into()?)
+    }
+
+    /// Check that a PCR holds the value obtained by extending `initial` with `measurements`
+    ///
+    /// See [`pcr_expected`].
+    #[cfg(feature = "host-digest")]
+    pub fn pcr_verify<'m>(
+        &mut self,
+        id: ObjectId,
+        initial: &[u8; PCR_LEN],
+        measurements: impl IntoIterator<Item = &'m [u8]>,
+    ) -> Result<bool, Error> {
+        Ok(self.pcr_read(id)? == pcr_expected(initial, measurements))
+    }
+}
+
+/// Compute on the host the value of a PCR created with `initial` and extended with `measurements`, in order
+#[cfg(feature = "host-digest")]
+pub fn pcr_expected<'m>(
+    initial: &[u8; PCR_LEN],
+    measurements: impl IntoIterator<Item = &'m [u8]>,
+) -> [u8; PCR_LEN] {
+    use sha2::{Digest, Sha256};
+
+    measurements
+        .into_iter()
+        .fold(*initial, |value, measurement| {
+            Sha256::new()
+                .chain_update(value)
+                .chain_update(measurement)
+                .finalize()
+                .into()
+        })
+}
+
+#[cfg(all(test, feature = "host-digest"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extend_chain() {
+        let initial = [0; PCR_LEN];
+        assert_eq!(pcr_expected(&initial, []), initial);
+        // SHA-256 of 32 zero bytes followed by "abc"
+        let once = pcr_expected(&initial, [&b"abc"[..]]);
+        let mut data = [0; PCR_LEN + 3];
+        data[PCR_LEN..].copy_from_slice(b"abc");
+        assert_eq!(
+            once,
+            <[u8; PCR_LEN]>::from(<sha2::Sha256 as sha2::Digest>::digest(data))
+        );
+        assert_eq!(
+            pcr_expected(&initial, [&b"abc"[..], &b"def"[..]]),
+            pcr_expected(&once, [&b"def"[..]])
+        );
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Detection of reads of stale response data
+//!
+//! Responses borrow from the buffer passed to [`run_command`](super::Se05X::run_command).
+//! When that buffer is reused, any offset or length kept from a previous response points to data that was not written by the last command.
+//! Filling the buffer with a poison pattern makes such reads stand out.
+//!
+//! See [`Se05X::set_response_poison`](super::Se05X::set_response_poison) and [`PoisonedBuf`].
+
+use core::ops::Range;
+
+/// Default poison pattern, unlikely to be mistaken for TLV data
+pub const DEFAULT_POISON: u8 = 0xA5;
+
+/// Response buffer filled with a poison pattern that detects reads of regions that were not written
+///
+/// The written region is inferred as ending with the last byte that differs from the pattern.
+/// A response ending with bytes equal to the pattern is therefore seen as shorter than it is.
+#[derive(Debug)]
+pub struct PoisonedBuf<'buf> {
+    buf: &'buf mut [u8],
+    pattern: u8,
+}
+
+impl<'buf> PoisonedBuf<'buf> {
+    pub fn new(buf: &'buf mut [u8], pattern: u8) -> Self {
+        buf.fill(pattern);
+        Self { buf, pattern }
+    }
+
+    /// Fill the buffer with the poison pattern again before reusing it
+    pub fn repoison(&mut self) {
+        self.buf.fill(self.pattern);
+    }
+
+    /// The buffer to pass to [`run_command`](super::Se05X::run_command)
+    pub fn buf_mut(&mut self) -> &mut [u8] {
+        self.buf
+    }
+
+    /// Length of the region written since the buffer was last poisoned
+    pub fn written_len(&self) -> usize {
+        self.buf
+            .iter()
+            .rposition(|b| *b != self.pattern)
+            .map(|i| i + 1)
+            .unwrap_or(0)
+    }
+
+    /// The region written since the buffer was last poisoned
+    pub fn written(&self) -> &[u8] {
+        &self.buf[..self.written_len()]
+    }
+
+    /// Read `range` from the buffer
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` extends past the written region
+    pub fn get(&self, range: Range<usize>) -> &[u8] {
+        let written = self.written_len();
+        assert!(
+            range.end <= written,
+            "read of {range:?} past the {written} bytes written to the response buffer"
+        );
+        &self.buf[range]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn written_region() {
+        let mut buf = [0; 16];
+        let mut poisoned = PoisonedBuf::new(&mut buf, DEFAULT_POISON);
+        assert_eq!(poisoned.written_len(), 0);
+        poisoned.buf_mut()[..4].copy_from_slice(&[1, 2, 3, 4]);
+        assert_eq!(poisoned.written(), &[1, 2, 3, 4]);
+        assert_eq!(poisoned.get(1..3), &[2, 3]);
+        poisoned.repoison();
+        poisoned.buf_mut()[..2].copy_from_slice(&[5, 6]);
+        assert_eq!(poisoned.written(), &[5, 6]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn stale_read() {
+        let mut buf = [0; 16];
+        let mut poisoned = PoisonedBuf::new(&mut buf, DEFAULT_POISON);
+        poisoned.buf_mut()[..4].copy_from_slice(&[1, 2, 3, 4]);
+        poisoned.repoison();
+        poisoned.buf_mut()[..2].copy_from_slice(&[5, 6]);
+        poisoned.get(0..4);
+    }
+}
@@ -4,10 +4,11 @@
 use bitflags::bitflags;
 use iso7816::command::{DataSource, DataStream, Writer};
 
-use crate::se05x::ObjectId;
+use crate::se05x::{Error, ObjectId};
 
 bitflags! {
     #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct ObjectPolicyFlags: u32 {
         /// Reserved for future use
         const RFU1                            = 0b10000000_00000000_00000000_00000000;
@@ -55,12 +56,15 @@ bitflags! {
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PcrExtension {
     object_id: ObjectId,
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
     pcr_value: [u8; 32],
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ObjectAccessRule {
     flags: ObjectPolicyFlags,
     require_pcr_value: Option<PcrExtension>,
@@ -75,6 +79,16 @@ impl ObjectAccessRule {
         }
     }
 
+    pub fn flags(&self) -> ObjectPolicyFlags {
+        self.flags
+    }
+
+    /// Returns `true` if the rule allows `operation`
+    pub fn allows(&self, operation: Operation) -> bool {
+        !self.flags.contains(ObjectPolicyFlags::FORBID_ALL)
+            && self.flags.contains(operation.required_flag())
+    }
+
     /// Indicates that access to the object is allowed only if the given PCR object contains a certain value
     pub fn require_pcr_value(self, extension: Option<PcrExtension>) -> Self {
         Self {
@@ -103,6 +117,7 @@ impl ObjectAccessRule {
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Policy {
     pub object_id: ObjectId,
     pub access_rule: ObjectAccessRule,
@@ -115,7 +130,9 @@ impl Policy {
     }
 }
 
+/// With the `serde` feature, only `Serialize` is implemented since the policies are borrowed
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PolicySet<'a>(pub &'a [Policy]);
 
 impl PolicySet<'_> {
@@ -153,8 +170,127 @@ impl<W: Writer> DataStream<W> for PolicySet<'_> {
     }
 }
 
+/// Length of a policy without extension: authentication object ID and access rule
+const POLICY_LEN: usize = 8;
+/// Length of the PCR extension of an access rule: PCR object ID and value
+const PCR_EXTENSION_LEN: usize = 36;
+
+/// Iterator over the policies of a serialized policy set
+///
+/// The single trailing byte of [`ObjectAttributes`](super::ObjectAttributes), holding the origin of the object, is not a policy: it is left in the [`remainder`](Self::remainder).
+#[derive(Clone, Debug)]
+pub struct PolicyIter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> PolicyIter<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Bytes following the policies, once the iterator returned `None`
+    ///
+    /// In object attributes, this is the origin of the object.
+    /// Empty if the policies were invalid.
+    pub fn remainder(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+impl Iterator for PolicyIter<'_> {
+    type Item = Result<Policy, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let [len, rest @ ..] = self.data else {
+            return None;
+        };
+        if rest.is_empty() {
+            // Origin of the object attributes, left in the remainder
+            return None;
+        }
+        let len = usize::from(*len);
+        if len < POLICY_LEN || rest.len() < len {
+            error!("Invalid policy length: {len}");
+            self.data = &[];
+            return Some(Err(Error::Tlv));
+        }
+        let (policy, rest) = rest.split_at(len);
+        self.data = rest;
+        let [id0, id1, id2, id3, ar0, ar1, ar2, ar3, extension @ ..] = policy else {
+            unreachable!();
+        };
+        let flags =
+            ObjectPolicyFlags::from_bits_retain(u32::from_be_bytes([*ar0, *ar1, *ar2, *ar3]));
+        let require_pcr_value = match extension {
+            [pcr0, pcr1, pcr2, pcr3, pcr_value @ ..]
+                if flags.contains(ObjectPolicyFlags::REQUIRE_PCR_VALUE)
+                    && extension.len() == PCR_EXTENSION_LEN =>
+            {
+                Some(PcrExtension {
+                    object_id: ObjectId([*pcr0, *pcr1, *pcr2, *pcr3]),
+                    pcr_value: pcr_value.try_into().ok()?,
+                })
+            }
+            _ => None,
+        };
+        Some(Ok(Policy {
+            object_id: ObjectId([*id0, *id1, *id2, *id3]),
+            access_rule: ObjectAccessRule {
+                flags,
+                require_pcr_value,
+            },
+        }))
+    }
+}
+
+/// Operation on a secure object, checked against its policy
+///
+/// See [`Se05X::can_perform`](super::Se05X::can_perform).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Operation {
+    /// Signature or MAC generation
+    Sign,
+    /// Signature or MAC verification
+    Verify,
+    KeyAgreement,
+    Encrypt,
+    Decrypt,
+    KeyDerivation,
+    /// Wrapping of other keys
+    Wrap,
+    Read,
+    Write,
+    Generate,
+    Delete,
+    /// Attestation of other objects
+    Attest,
+    ImportExport,
+}
+
+impl Operation {
+    /// Flag of the access rule allowing the operation
+    pub fn required_flag(self) -> ObjectPolicyFlags {
+        match self {
+            Self::Sign => ObjectPolicyFlags::ALLOW_SIGN,
+            Self::Verify => ObjectPolicyFlags::ALLOW_VERIFY,
+            Self::KeyAgreement => ObjectPolicyFlags::ALLOW_KA,
+            Self::Encrypt => ObjectPolicyFlags::ALLOW_ENC,
+            Self::Decrypt => ObjectPolicyFlags::ALLOW_DEC,
+            Self::KeyDerivation => ObjectPolicyFlags::ALLOW_KDF,
+            Self::Wrap => ObjectPolicyFlags::ALLOW_WRAP,
+            Self::Read => ObjectPolicyFlags::ALLOW_READ,
+            Self::Write => ObjectPolicyFlags::ALLOW_WRITE,
+            Self::Generate => ObjectPolicyFlags::ALLOW_GEN,
+            Self::Delete => ObjectPolicyFlags::ALLOW_DELETE,
+            Self::Attest => ObjectPolicyFlags::ALLOW_ATTESTATION,
+            Self::ImportExport => ObjectPolicyFlags::ALLOW_IMPORT_EXPORT,
+        }
+    }
+}
+
 bitflags! {
     #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct SessionPolicyFlags: u16 {
         ///  Defines the maximum number of APDUs allowed within the session. Note that the ExchangeSessionData command itself is also counted as APDU within the session.
         #[doc(hidden)]
@@ -166,13 +302,35 @@ bitflags! {
     }
 }
 
+/// Policy of an authenticated session, set with [`ExchangeSessionData`](super::commands::ExchangeSessionData) or [`RefreshSession`](super::commands::RefreshSession)
+///
+/// The applet bounds the lifetime of a session only by its number of APDUs, there is no time based expiry.
+///
+/// ```
+/// # use se05x::se05x::policies::SessionPolicy;
+/// let policy = SessionPolicy::new().allow_refresh(true).max_apdu(Some(100));
+/// assert_eq!(&*policy.to_bytes(), [0xA0, 0x00, 0x00, 0x64]);
+/// assert_eq!(SessionPolicy::try_from(&*policy.to_bytes()), Ok(policy));
+/// ```
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SessionPolicy {
     flags: SessionPolicyFlags,
     max_apdu: Option<u16>,
 }
 
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SessionPolicy {
+    /// Policy without any restriction, that does not allow refreshing the session
+    pub const fn new() -> Self {
+        Self::from_flags(SessionPolicyFlags::empty())
+    }
+
     pub const fn from_flags(flags: SessionPolicyFlags) -> Self {
         assert!(!flags.contains(SessionPolicyFlags::MAX_APDU));
         Self {
@@ -192,6 +350,32 @@ impl SessionPolicy {
         }
     }
 
+    /// Allow or forbid refreshing the session with [`RefreshSession`](super::commands::RefreshSession)
+    pub fn allow_refresh(self, allow: bool) -> Self {
+        Self {
+            flags: self.flags.difference(SessionPolicyFlags::ALLOW_REFRESH)
+                | if allow {
+                    SessionPolicyFlags::ALLOW_REFRESH
+                } else {
+                    SessionPolicyFlags::empty()
+                },
+            ..self
+        }
+    }
+
+    pub fn flags(&self) -> SessionPolicyFlags {
+        self.flags
+    }
+
+    /// Maximum number of APDUs in the session, including the `ExchangeSessionData` command
+    pub fn max_apdu_count(&self) -> Option<u16> {
+        self.max_apdu
+    }
+
+    pub fn allows_refresh(&self) -> bool {
+        self.flags.contains(SessionPolicyFlags::ALLOW_REFRESH)
+    }
+
     pub fn to_bytes(self) -> heapless::Vec<u8, 7> {
         if let Some(max_apdu) = self.max_apdu {
             self.flags
@@ -206,6 +390,29 @@ impl SessionPolicy {
     }
 }
 
+impl TryFrom<&[u8]> for SessionPolicy {
+    type Error = Error;
+    fn try_from(value: &[u8]) -> Result<Self, Error> {
+        let (flags, max_apdu) = match *value {
+            [f0, f1] => (u16::from_be_bytes([f0, f1]), None),
+            [f0, f1, m0, m1] => (
+                u16::from_be_bytes([f0, f1]),
+                Some(u16::from_be_bytes([m0, m1])),
+            ),
+            _ => {
+                error!("Invalid session policy length: {}", value.len());
+                return Err(Error::Tlv);
+            }
+        };
+        let flags = SessionPolicyFlags::from_bits_retain(flags);
+        if flags.contains(SessionPolicyFlags::MAX_APDU) != max_apdu.is_some() {
+            error!("Session policy length does not match its flags: {flags:?}");
+            return Err(Error::Tlv);
+        }
+        Ok(Self { flags, max_apdu })
+    }
+}
+
 impl DataSource for SessionPolicy {
     fn len(&self) -> usize {
         self.to_bytes().len()
@@ -238,4 +445,66 @@ mod tests {
         let res = policy.to_bytes(&mut buf).unwrap();
         assert_eq!(res, hex_literal::hex!("08 00000000 00040000"));
     }
+
+    #[test]
+    fn parse_policies() {
+        let policies = [
+            Policy {
+                object_id: ObjectId::INVALID,
+                access_rule: ObjectAccessRule::from_flags(ObjectPolicyFlags::ALLOW_DELETE),
+            },
+            Policy {
+                object_id: ObjectId([0, 0, 0, 1]),
+                access_rule: ObjectAccessRule::from_flags(ObjectPolicyFlags::ALLOW_SIGN)
+                    .require_pcr_value(Some(PcrExtension {
+                        object_id: ObjectId([1, 2, 3, 4]),
+                        pcr_value: [0xAA; 32],
+                    })),
+            },
+        ];
+        // Followed by the origin of the object attributes
+        let data = hex_literal::hex!(
+            "08 00000000 00040000"
+            "2C 00000001 10010000 01020304"
+            "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"
+            "01"
+        );
+        let parsed: Result<Vec<Policy>, Error> = PolicyIter::new(&data).collect();
+        assert_eq!(parsed.unwrap(), policies);
+        assert!(policies[1].access_rule.allows(Operation::Sign));
+        assert!(!policies[1].access_rule.allows(Operation::Delete));
+
+        let mut invalid = PolicyIter::new(&hex_literal::hex!("08 00000000 0004"));
+        assert_eq!(invalid.next(), Some(Err(Error::Tlv)));
+        assert_eq!(invalid.next(), None);
+    }
+
+    #[test]
+    fn session_policy() {
+        let policy = SessionPolicy::new()
+            .max_apdu(Some(0x0102))
+            .allow_refresh(true);
+        assert_eq!(policy.max_apdu_count(), Some(0x0102));
+        assert!(policy.allows_refresh());
+        assert_eq!(&*policy.to_bytes(), hex_literal::hex!("A000 0102"));
+        assert_eq!(SessionPolicy::try_from(&*policy.to_bytes()), Ok(policy));
+
+        let policy = policy.max_apdu(None).allow_refresh(false);
+        assert_eq!(policy, SessionPolicy::default());
+        assert_eq!(&*policy.to_bytes(), hex_literal::hex!("0000"));
+        assert_eq!(SessionPolicy::try_from(&*policy.to_bytes()), Ok(policy));
+
+        assert_eq!(
+            SessionPolicy::try_from(&hex_literal::hex!("8000")[..]),
+            Err(Error::Tlv)
+        );
+        assert_eq!(
+            SessionPolicy::try_from(&hex_literal::hex!("2000 0102")[..]),
+            Err(Error::Tlv)
+        );
+        assert_eq!(
+            SessionPolicy::try_from(&hex_literal::hex!("20")[..]),
+            Err(Error::Tlv)
+        );
+    }
 }
@@ -0,0 +1,520 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Declarative provisioning
+//!
+//! A manifest is a list of [`ProvisionItem`]s: curves to configure, keys to generate and binary files to write.
+//! [`Se05X::provision`] executes it in order, skipping the items that are already present, so that the same manifest can be applied again after an interrupted provisioning.
+//!
+//! ```rust,no_run
+//! # use se05x::se05x::{ObjectId, EcCurve, Se05X, provision::ProvisionItem};
+//! # fn provision<T: se05x::se05x::transport::Se05xTransport>(se05x: &mut Se05X<T>) -> Result<(), se05x::se05x::Error> {
+//! const MANIFEST: &[ProvisionItem<'static>] = &[
+//!     ProvisionItem::Curve(EcCurve::NistP256),
+//!     ProvisionItem::EcKey {
+//!         object_id: ObjectId(hex_literal::hex!("00000001")),
+//!         curve: EcCurve::NistP256,
+//!         policy: None,
+//!     },
+//!     ProvisionItem::Binary {
+//!         object_id: ObjectId(hex_literal::hex!("00000002")),
+//!         data: b"device configuration",
+//!         policy: None,
+//!     },
+//! ];
+//! let summary = se05x.provision(MANIFEST, |_item, _result| {});
+//! assert_eq!(summary.failed, 0);
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "rsa")]
+use super::commands::GenRsaKey;
+use super::commands::{
+    CheckObjectExists, DeleteSecureObject, GenEcKey, ReadEcCurveList, ReadSize, ReadType,
+    WriteBinary, WriteSymmKey,
+};
+use super::policies::PolicySet;
+use super::transport::Se05xTransport;
+use super::{
+    constants, EcCurve, Error, Memory, ObjectId, Se05X, Se05XResult, SecureObjectType, SymmKeyType,
+    CHUNK_LEN,
+};
+
+/// Item of a provisioning manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProvisionItem<'a> {
+    /// Configure the parameters of a curve
+    ///
+    /// Curves must be configured before the keys using them, so they should come first in the manifest.
+    Curve(EcCurve),
+    /// Generate an EC key pair
+    EcKey {
+        object_id: ObjectId,
+        curve: EcCurve,
+        policy: Option<PolicySet<'a>>,
+    },
+    /// Generate an RSA key pair of `key_size` bits
+    #[cfg(feature = "rsa")]
+    RsaKey {
+        object_id: ObjectId,
+        key_size: u16,
+        policy: Option<PolicySet<'a>>,
+    },
+    /// Write a binary file holding `data`
+    Binary {
+        object_id: ObjectId,
+        data: &'a [u8],
+        policy: Option<PolicySet<'a>>,
+    },
+}
+
+/// Result of a successful [`ProvisionItem`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProvisionOutcome {
+    /// The item was created
+    Created,
+    /// The item was already present and was left unchanged
+    AlreadyPresent,
+}
+
+/// Number of items per result, returned by [`Se05X::provision`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ProvisionSummary {
+    pub created: usize,
+    pub already_present: usize,
+    pub failed: usize,
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Execute the provisioning `manifest`, passing the result of each item to `on_result`
+    ///
+    /// Items that are already present are skipped. An existing key is only checked to be of the expected type:
+    /// its policies and curve are not compared with the manifest.
+    /// An existing binary file is read back and compared with the manifest. If its size or content differs, for example after an interrupted write,
+    /// it is deleted and written again, with the outcome [`Created`](ProvisionOutcome::Created).
+    /// Binary files must therefore be readable without authentication.
+    /// A failed item does not stop the provisioning of the next ones.
+    pub fn provision<'a>(
+        &mut self,
+        manifest: &[ProvisionItem<'a>],
+        mut on_result: impl FnMut(&ProvisionItem<'a>, Result<ProvisionOutcome, Error>),
+    ) -> ProvisionSummary {
+        let mut summary = ProvisionSummary::default();
+        for item in manifest {
+            let result = self.provision_item(item);
+            match result {
+                Ok(ProvisionOutcome::Created) => summary.created += 1,
+                Ok(ProvisionOutcome::AlreadyPresent) => summary.already_present += 1,
+                Err(_) => summary.failed += 1,
+            }
+            on_result(item, result);
+        }
+        summary
+    }
+
+    fn provision_item(&mut self, item: &ProvisionItem<'_>) -> Result<ProvisionOutcome, Error> {
+        match *item {
+            ProvisionItem::Curve(curve) => {
+                let Some(constants) = curve.required_params()? else {
+                    return Ok(ProvisionOutcome::AlreadyPresent);
+                };
+                let buf = &mut [0; 64];
+                if self.run_command(&ReadEcCurveList {}, buf)?.is_set(curve) {
+                    return Ok(ProvisionOutcome::AlreadyPresent);
+                }
+                self.create_and_set_curve_params(&constants::CurveInitializer {
+                    constants,
+                    curve,
+                })?;
+            }
+            ProvisionItem::EcKey {
+                object_id,
+                curve,
+                policy,
+            } => return self.ensure_ec_key(object_id, curve, policy),
+            #[cfg(feature = "rsa")]
+            ProvisionItem::RsaKey {
+                object_id,
+                key_size,
+                policy,
+            } => {
+                if self.check_existing(object_id, SecureObjectType::RsaKeyPair)? {
+                    return Ok(ProvisionOutcome::AlreadyPresent);
+                }
+                self.run_command(
+                    &GenRsaKey {
+                        transient: false,
+                        is_auth: false,
+                        policy,
+                        max_attempts: None,
+                        object_id,
+                        key_size: Some(key_size.into()),
+                    },
+                    &mut [0; 2],
+                )?;
+            }
+            ProvisionItem::Binary {
+                object_id,
+                data,
+                policy,
+            } => {
+                if self.check_existing(object_id, SecureObjectType::BinaryFile)? {
+                    if self.binary_matches(object_id, data)? {
+                        return Ok(ProvisionOutcome::AlreadyPresent);
+                    }
+                    // Left by an interrupted provisioning, or with other content
+                    self.run_command(&DeleteSecureObject { object_id }, &mut [0; 2])?;
+                }
+                let Ok(file_length) = u16::try_from(data.len()) else {
+                    error!("Binary file too long: {}", data.len());
+                    return Err(Error::Line(line!()));
+                };
+                let (first, rest) = data.split_at(data.len().min(CHUNK_LEN));
+                self.run_command(
+                    &WriteBinary {
+                        transient: false,
+                        policy,
+                        object_id,
+                        offset: None,
+                        file_length: Some(file_length.into()),
+                        data: Some(first),
+                    },
+                    &mut [0; 2],
+                )?;
+                self.write_binary_chunked(object_id, first.len() as u16, rest)?;
+            }
+        }
+        Ok(ProvisionOutcome::Created)
+    }
+
+    /// Returns `true` if the binary file `object_id` has the size and content of `data`
+    fn binary_matches(&mut self, object_id: ObjectId, data: &[u8]) -> Result<bool, Error> {
+        let size = self
+            .run_command(&ReadSize { object_id }, &mut [0; 16])?
+            .size
+            .0;
+        if size != data.len() as u64 {
+            return Ok(false);
+        }
+        let buf = &mut [0; CHUNK_LEN];
+        let offsets = (0..).step_by(CHUNK_LEN);
+        for (offset, chunk) in offsets.zip(data.chunks(CHUNK_LEN)) {
+            // Below the file length, which fits in a u16
+            self.read_binary_into(object_id, offset as u16, &mut buf[..chunk.len()])?;
+            if buf[..chunk.len()] != *chunk {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns `true` if `object_id` exists, and fails if it is not of type `expected`
+    fn check_existing(
+        &mut self,
+        object_id: ObjectId,
+        expected: SecureObjectType,
+    ) -> Result<bool, Error> {
+        if !self.exists(object_id)? {
+            return Ok(false);
+        }
+        let ty = self.run_command(&ReadType { object_id }, &mut [0; 16])?.ty;
+        if ty != expected {
+            error!("{object_id:?} exists with type {ty:?} instead of {expected:?}");
+            return Err(Error::Line(line!()));
+        }
+        Ok(true)
+    }
+
+    /// Returns `true` if `object_id` exists
+    pub fn exists(&mut self, object_id: ObjectId) -> Result<bool, Error> {
+        let response = self.run_command(&CheckObjectExists { object_id }, &mut [0; 16])?;
+        Ok(response.result == Se05XResult::Success)
+    }
+
+    /// Create an empty binary file of `len` bytes in `object_id`, unless it already exists
+    ///
+    /// Like with [`provision`](Self::provision), an existing object is only checked to be a binary file, its size and policy are not compared.
+    pub fn ensure_binary(
+        &mut self,
+        object_id: ObjectId,
+        len: u16,
+        policy: Option<PolicySet<'_>>,
+    ) -> Result<ProvisionOutcome, Error> {
+        if self.check_existing(object_id, SecureObjectType::BinaryFile)? {
+            return Ok(ProvisionOutcome::AlreadyPresent);
+        }
+        self.run_command(
+            &WriteBinary {
+                transient: false,
+                policy,
+                object_id,
+                offset: None,
+                file_length: Some(len.into()),
+                data: None,
+            },
+            &mut [0; 2],
+        )?;
+        Ok(ProvisionOutcome::Created)
+    }
+
+    /// Write the AES `key` to `object_id`, unless it already exists
+    ///
+    /// An existing key is left unchanged, even if its value differs from `key`.
+    pub fn ensure_aes_key(
+        &mut self,
+        object_id: ObjectId,
+        key: &[u8],
+        policy: Option<PolicySet<'_>>,
+    ) -> Result<ProvisionOutcome, Error> {
+        if self.check_existing(object_id, SecureObjectType::AesKey)? {
+            return Ok(ProvisionOutcome::AlreadyPresent);
+        }
+        self.run_command(
+            &WriteSymmKey {
+                transient: false,
+                is_auth: false,
+                key_type: SymmKeyType::Aes,
+                policy,
+                max_attempts: None,
+                object_id,
+                kek_id: None,
+                value: key,
+            },
+            &mut [0; 2],
+        )?;
+        Ok(ProvisionOutcome::Created)
+    }
+
+    /// Generate an EC key pair on `curve` in `object_id`, unless it already exists
+    ///
+    /// The curve of an existing key pair is not checked.
+    pub fn ensure_ec_key(
+        &mut self,
+        object_id: ObjectId,
+        curve: EcCurve,
+        policy: Option<PolicySet<'_>>,
+    ) -> Result<ProvisionOutcome, Error> {
+        if self.check_existing(object_id, SecureObjectType::EcKeyPair)? {
+            return Ok(ProvisionOutcome::AlreadyPresent);
+        }
+        self.generate_ec_key(object_id, curve, Memory::Persistent, policy)?;
+        Ok(ProvisionOutcome::Created)
+    }
+
+    /// Generate an EC key pair on `curve` in `object_id`, stored in `memory`
+    ///
+    /// See [`Memory::transient`] for the memories keys can be created in.
+    pub fn generate_ec_key(
+        &mut self,
+        object_id: ObjectId,
+        curve: EcCurve,
+        memory: Memory,
+        policy: Option<PolicySet<'_>>,
+    ) -> Result<(), Error> {
+        self.run_command(
+            &GenEcKey {
+                transient: memory.transient()?,
+                is_auth: false,
+                policy,
+                max_attempts: None,
+                object_id,
+                curve: Some(curve),
+            },
+            &mut [0; 2],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "rsa"))]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use hex_literal::hex;
+
+    const EXISTS_1: [u8; 15] = hex!("80 04 00 27 00 00 06 41 04 00000001 00 00");
+    const EXISTS_2: [u8; 15] = hex!("80 04 00 27 00 00 06 41 04 00000002 00 00");
+    const READ_TYPE_1: [u8; 15] = hex!("80 02 00 26 00 00 06 41 04 00000001 00 00");
+    const PRESENT: [u8; 5] = hex!("41 01 01 90 00");
+    const ABSENT: [u8; 5] = hex!("41 01 02 90 00");
+    const EC_KEY_PAIR: [u8; 8] = hex!("41 01 01 42 01 02 90 00");
+    const SUCCESS: [u8; 2] = hex!("90 00");
+
+    #[test]
+    fn provision_manifest() {
+        // All curves are set and `00000001` is an EC key pair
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(&hex!("80 02 0B 25"), &hex!("41 04 02020202 90 00"))
+            .expect(&EXISTS_1, &PRESENT)
+            .expect(&READ_TYPE_1, &EC_KEY_PAIR)
+            .expect(&EXISTS_2, &ABSENT)
+            // The binary file is written in two chunks
+            .expect(
+                &[
+                    &hex!("80 01 06 00 00 02 0E 41 04 00000002 43 02 0258 44 82 0200")[..],
+                    &[0xAB; 512],
+                ]
+                .concat(),
+                &SUCCESS,
+            )
+            .expect(
+                &[
+                    &hex!("80 01 06 00 64 41 04 00000002 42 02 0200 44 58")[..],
+                    &[0xAB; 88],
+                ]
+                .concat(),
+                &SUCCESS,
+            )
+            .expect(&EXISTS_1, &PRESENT)
+            .expect(&READ_TYPE_1, &EC_KEY_PAIR);
+        let mut se05x = script.enable();
+        let manifest = [
+            ProvisionItem::Curve(EcCurve::NistP256),
+            ProvisionItem::EcKey {
+                object_id: ObjectId([0, 0, 0, 1]),
+                curve: EcCurve::NistP256,
+                policy: None,
+            },
+            ProvisionItem::Binary {
+                object_id: ObjectId([0, 0, 0, 2]),
+                data: &[0xAB; 600],
+                policy: None,
+            },
+            ProvisionItem::RsaKey {
+                object_id: ObjectId([0, 0, 0, 1]),
+                key_size: 2048,
+                policy: None,
+            },
+        ];
+        let mut results: heapless::Vec<Result<ProvisionOutcome, Error>, 4> = heapless::Vec::new();
+        let summary = se05x.provision(&manifest, |_item, result| results.push(result).unwrap());
+        assert_eq!(
+            results[..3],
+            [
+                Ok(ProvisionOutcome::AlreadyPresent),
+                Ok(ProvisionOutcome::AlreadyPresent),
+                Ok(ProvisionOutcome::Created),
+            ]
+        );
+        // The existing key is not an RSA key
+        assert!(results[3].is_err());
+        assert_eq!(
+            summary,
+            ProvisionSummary {
+                created: 1,
+                already_present: 2,
+                failed: 1,
+            }
+        );
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn provision_interrupted_binary() {
+        let read_type = hex!("80 02 00 26 00 00 06 41 04 00000002 00 00");
+        let binary_file = hex!("41 01 0B 42 01 02 90 00");
+        let read_size = hex!("80 02 00 07 00 00 06 41 04 00000002 00 00");
+        let size = hex!("41 08 0000000000000004 90 00");
+        let read = hex!("80 02 00 00 00 00 0E 41 04 00000002 42 02 0000 43 02 0004 00 00");
+        let mut script = ScriptedTransport::new();
+        script
+            // Only the first half was written
+            .expect(&EXISTS_2, &PRESENT)
+            .expect(&read_type, &binary_file)
+            .expect(&read_size, &size)
+            .expect(&read, &hex!("41 04 ABAB0000 90 00"))
+            .expect(&hex!("80 04 00 28 06 41 04 00000002"), &SUCCESS)
+            .expect(
+                &hex!("80 01 06 00 10 41 04 00000002 43 02 0004 44 04 ABABABAB"),
+                &SUCCESS,
+            )
+            // Complete file
+            .expect(&EXISTS_2, &PRESENT)
+            .expect(&read_type, &binary_file)
+            .expect(&read_size, &size)
+            .expect(&read, &hex!("41 04 ABABABAB 90 00"));
+        let mut se05x = script.enable();
+        let manifest = [ProvisionItem::Binary {
+            object_id: ObjectId([0, 0, 0, 2]),
+            data: &[0xAB; 4],
+            policy: None,
+        }];
+        let mut outcomes: heapless::Vec<Result<ProvisionOutcome, Error>, 2> = heapless::Vec::new();
+        se05x.provision(&manifest, |_item, result| outcomes.push(result).unwrap());
+        se05x.provision(&manifest, |_item, result| outcomes.push(result).unwrap());
+        assert_eq!(
+            outcomes,
+            [
+                Ok(ProvisionOutcome::Created),
+                Ok(ProvisionOutcome::AlreadyPresent)
+            ]
+        );
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn ensure_objects() {
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(&EXISTS_1, &PRESENT)
+            .expect(&EXISTS_2, &ABSENT)
+            .expect(&EXISTS_1, &PRESENT)
+            .expect(&READ_TYPE_1, &EC_KEY_PAIR)
+            .expect(&EXISTS_2, &ABSENT)
+            .expect(&hex!("80 01 06 00 0A 41 04 00000002 43 02 0020"), &SUCCESS)
+            .expect(&hex!("80 04 00 27 00 00 06 41 04 00000003 00 00"), &ABSENT)
+            .expect(
+                &[
+                    &hex!("80 01 03 00 18 41 04 00000003 43 10")[..],
+                    &[0x42; 16],
+                ]
+                .concat(),
+                &SUCCESS,
+            )
+            .expect(&EXISTS_1, &PRESENT)
+            .expect(&READ_TYPE_1, &EC_KEY_PAIR);
+        let mut se05x = script.enable();
+        assert!(se05x.exists(ObjectId([0, 0, 0, 1])).unwrap());
+        assert!(!se05x.exists(ObjectId([0, 0, 0, 2])).unwrap());
+        assert_eq!(
+            se05x.ensure_ec_key(ObjectId([0, 0, 0, 1]), EcCurve::NistP256, None),
+            Ok(ProvisionOutcome::AlreadyPresent)
+        );
+        assert_eq!(
+            se05x.ensure_binary(ObjectId([0, 0, 0, 2]), 32, None),
+            Ok(ProvisionOutcome::Created)
+        );
+        assert_eq!(
+            se05x.ensure_aes_key(ObjectId([0, 0, 0, 3]), &[0x42; 16], None),
+            Ok(ProvisionOutcome::Created)
+        );
+        // The existing object is an EC key pair
+        assert!(se05x
+            .ensure_aes_key(ObjectId([0, 0, 0, 1]), &[0x42; 16], None)
+            .is_err());
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn generate_transient() {
+        let mut script = ScriptedTransport::new();
+        script.expect(&hex!("80 81 61 00 09 41 04 00000002 42 01 03"), &SUCCESS);
+        let mut se05x = script.enable();
+        let object_id = ObjectId([0, 0, 0, 2]);
+        se05x
+            .generate_ec_key(object_id, EcCurve::NistP256, Memory::TransientReset, None)
+            .unwrap();
+        // Rejected before any command is sent
+        assert!(se05x
+            .generate_ec_key(
+                object_id,
+                EcCurve::NistP256,
+                Memory::TransientDeselect,
+                None
+            )
+            .is_err());
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+}
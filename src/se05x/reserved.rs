@@ -0,0 +1,162 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Object IDs reserved by the applet and trust provisioned by NXP
+//!
+//! The applet reserves [`APPLET_RESERVED_RANGE`](super::APPLET_RESERVED_RANGE) for its own authentication objects, available as associated constants of [`ObjectId`].
+//! NXP provisions die-individual keys and certificates in [`NXP_PROVISIONED_RANGE`](super::NXP_PROVISIONED_RANGE) during manufacturing.
+//!
+//! Which provisioned objects are present depends on the product variant and its configuration, see AN12436 "SE050 configurations" and AN12973 "SE051 configurations".
+//! Use [`ReadIdList`](super::commands::ReadIdList) or [`CheckObjectExists`](super::commands::CheckObjectExists) to find out which ones a given secure element holds.
+
+use hex_literal::hex;
+
+use super::ObjectId;
+
+/// Die-individual RSA 2048 key pair used to attest objects
+pub const ATTESTATION_KEY_RSA: ObjectId = ObjectId(hex!("F0000010"));
+/// Certificate of [`ATTESTATION_KEY_RSA`], signed by the NXP attestation CA
+pub const ATTESTATION_CERT_RSA: ObjectId = ObjectId(hex!("F0000011"));
+/// Die-individual NIST P-256 key pair used to attest objects
+pub const ATTESTATION_KEY_EC: ObjectId = ObjectId(hex!("F0000012"));
+/// Certificate of [`ATTESTATION_KEY_EC`], signed by the NXP attestation CA
+pub const ATTESTATION_CERT_EC: ObjectId = ObjectId(hex!("F0000013"));
+/// Die-individual NIST P-256 key pair for cloud onboarding
+pub const DEVICE_KEY_EC: ObjectId = ObjectId(hex!("F0000100"));
+/// Certificate of [`DEVICE_KEY_EC`]
+pub const DEVICE_CERT_EC: ObjectId = ObjectId(hex!("F0000101"));
+/// Die-individual RSA 2048 key pair for cloud onboarding
+pub const DEVICE_KEY_RSA: ObjectId = ObjectId(hex!("F0000102"));
+/// Certificate of [`DEVICE_KEY_RSA`]
+pub const DEVICE_CERT_RSA: ObjectId = ObjectId(hex!("F0000103"));
+
+/// Kind of a reserved object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReservedKind {
+    /// Authentication object used to open a session
+    Authentication,
+    /// Key pair
+    KeyPair,
+    /// Binary file holding an X.509 certificate
+    Certificate,
+    /// Binary file holding other data
+    Binary,
+}
+
+/// Description of a reserved object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReservedObject {
+    pub id: ObjectId,
+    pub name: &'static str,
+    pub kind: ReservedKind,
+}
+
+const fn reserved(id: ObjectId, name: &'static str, kind: ReservedKind) -> ReservedObject {
+    ReservedObject { id, name, kind }
+}
+
+/// Objects reserved by the applet, see the associated constants of [`ObjectId`]
+pub const APPLET_OBJECTS: &[ReservedObject] = &[
+    reserved(
+        ObjectId::TRANSPORT,
+        "TRANSPORT",
+        ReservedKind::Authentication,
+    ),
+    reserved(
+        ObjectId::KP_ECKEY_USER,
+        "KP_ECKEY_USER",
+        ReservedKind::KeyPair,
+    ),
+    reserved(
+        ObjectId::KP_ECKEY_IMPORT,
+        "KP_ECKEY_IMPORT",
+        ReservedKind::KeyPair,
+    ),
+    reserved(ObjectId::FEATURE, "FEATURE", ReservedKind::Authentication),
+    reserved(
+        ObjectId::FACTORY_RESET,
+        "FACTORY_RESET",
+        ReservedKind::Authentication,
+    ),
+    reserved(ObjectId::UNIQUE_ID, "UNIQUE_ID", ReservedKind::Binary),
+    reserved(
+        ObjectId::PLATFORM_SCP,
+        "PLATFORM_SCP",
+        ReservedKind::Authentication,
+    ),
+    reserved(
+        ObjectId::I2CM_ACCESS,
+        "I2CM_ACCESS",
+        ReservedKind::Authentication,
+    ),
+    reserved(ObjectId::RESTRICT, "RESTRICT", ReservedKind::Authentication),
+];
+
+/// Objects that can be trust provisioned by NXP
+pub const NXP_OBJECTS: &[ReservedObject] = &[
+    reserved(
+        ATTESTATION_KEY_RSA,
+        "ATTESTATION_KEY_RSA",
+        ReservedKind::KeyPair,
+    ),
+    reserved(
+        ATTESTATION_CERT_RSA,
+        "ATTESTATION_CERT_RSA",
+        ReservedKind::Certificate,
+    ),
+    reserved(
+        ATTESTATION_KEY_EC,
+        "ATTESTATION_KEY_EC",
+        ReservedKind::KeyPair,
+    ),
+    reserved(
+        ATTESTATION_CERT_EC,
+        "ATTESTATION_CERT_EC",
+        ReservedKind::Certificate,
+    ),
+    reserved(DEVICE_KEY_EC, "DEVICE_KEY_EC", ReservedKind::KeyPair),
+    reserved(DEVICE_CERT_EC, "DEVICE_CERT_EC", ReservedKind::Certificate),
+    reserved(DEVICE_KEY_RSA, "DEVICE_KEY_RSA", ReservedKind::KeyPair),
+    reserved(
+        DEVICE_CERT_RSA,
+        "DEVICE_CERT_RSA",
+        ReservedKind::Certificate,
+    ),
+];
+
+/// Look up a known reserved object by its ID
+///
+/// Returns `None` for IDs that are reserved but not listed in [`APPLET_OBJECTS`] or [`NXP_OBJECTS`].
+pub fn lookup(id: ObjectId) -> Option<&'static ReservedObject> {
+    APPLET_OBJECTS
+        .iter()
+        .chain(NXP_OBJECTS)
+        .find(|object| object.id == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserved_objects() {
+        for object in APPLET_OBJECTS {
+            assert!(object.id.is_reserved());
+            assert!(!object.id.is_provisioned());
+        }
+        for object in NXP_OBJECTS {
+            assert!(object.id.is_reserved());
+            assert!(object.id.is_provisioned());
+        }
+        assert_eq!(
+            lookup(ATTESTATION_KEY_EC).unwrap().name,
+            "ATTESTATION_KEY_EC"
+        );
+        assert_eq!(
+            lookup(ObjectId::UNIQUE_ID).unwrap().kind,
+            ReservedKind::Binary
+        );
+        assert_eq!(lookup(ObjectId(hex!("00001000"))), None);
+        assert!(!ObjectId(hex!("00001000")).is_provisioned());
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Sources of randomness for the helpers of this crate
+//!
+//! Session authentication, attested reads and IV generation need random bytes on the host.
+//! They take a [`HostRng`], implemented for the RNGs of `rand` (with the `rand` feature, enabled by `aes-session`),
+//! and by [`DeviceRng`], which requests the random bytes from the se05x with [`GetRandom`].
+//! [`DeviceRng`] lets targets without an entropy source use these workflows.
+
+use super::commands::GetRandom;
+use super::transport::Se05xTransport;
+use super::{Error, Se05X};
+
+/// Maximum number of bytes requested from the se05x at once by [`DeviceRng`]
+const DEVICE_CHUNK_LEN: usize = 64;
+
+/// Source of random bytes
+pub trait HostRng {
+    /// Fill `dest` with random bytes
+    ///
+    /// `se05x` is the device the random bytes are used with, which implementations can use as their source.
+    fn fill_random<T: Se05xTransport>(
+        &mut self,
+        se05x: &mut Se05X<T>,
+        dest: &mut [u8],
+    ) -> Result<(), Error>;
+}
+
+#[cfg(feature = "rand")]
+impl<R: rand::CryptoRng + rand::RngCore> HostRng for R {
+    fn fill_random<T: Se05xTransport>(
+        &mut self,
+        _se05x: &mut Se05X<T>,
+        dest: &mut [u8],
+    ) -> Result<(), Error> {
+        self.try_fill_bytes(dest).map_err(|_err| {
+            error!("Failed to get random bytes: {_err:?}");
+            Error::Line(line!())
+        })
+    }
+}
+
+/// [`HostRng`] getting the random bytes from the se05x with [`GetRandom`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeviceRng;
+
+impl HostRng for DeviceRng {
+    fn fill_random<T: Se05xTransport>(
+        &mut self,
+        se05x: &mut Se05X<T>,
+        dest: &mut [u8],
+    ) -> Result<(), Error> {
+        for chunk in dest.chunks_mut(DEVICE_CHUNK_LEN) {
+            let buf = &mut [0; DEVICE_CHUNK_LEN + 8];
+            let data = se05x
+                .run_command(
+                    &GetRandom {
+                        length: (chunk.len() as u16).into(),
+                    },
+                    buf,
+                )?
+                .data;
+            if data.len() != chunk.len() {
+                error!("Got {} random bytes, expected {}", data.len(), chunk.len());
+                return Err(Error::Line(line!()));
+            }
+            chunk.copy_from_slice(data);
+        }
+        Ok(())
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Fill `dest` with random bytes from `rng`, for example to generate an IV
+    pub fn fill_random<R: HostRng>(&mut self, rng: &mut R, dest: &mut [u8]) -> Result<(), Error> {
+        rng.fill_random(self, dest)
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::{MockDelay, MockTransport};
+
+    #[test]
+    fn device_rng() {
+        let mut se05x = Se05X::new(MockTransport::new(), 0x48, MockDelay);
+        se05x.enable().unwrap();
+        let mut iv = [0; 16];
+        se05x.fill_random(&mut DeviceRng, &mut iv).unwrap();
+        assert_eq!(iv, core::array::from_fn(|i| i as u8 + 1));
+        // Requested in several chunks
+        let mut long = [0; DEVICE_CHUNK_LEN * 2 + 3];
+        se05x.fill_random(&mut DeviceRng, &mut long).unwrap();
+        assert_eq!(long, core::array::from_fn(|i| i as u8 + 17));
+    }
+}
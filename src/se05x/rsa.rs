@@ -0,0 +1,105 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Reading RSA keys
+//!
+//! The se05x returns the public components of RSA keys one at a time, with the `rsa_key_component` of [`ReadObject`].
+//! The helpers check that the object holds an RSA key with a public part before reading, so that mistakes are reported clearly instead of with a status word.
+
+use super::commands::{ReadAttributes, ReadObject};
+use super::transport::Se05xTransport;
+use super::{Error, ObjectId, RsaKeyComponent, Se05X, SecureObjectType, OBJECT_ATTRIBUTES_MAX_LEN};
+
+/// Public part of an RSA key, read with [`Se05X::read_rsa_public`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RsaPublicKey<'buf> {
+    /// Big-endian modulus
+    pub modulus: &'buf [u8],
+    /// Big-endian public exponent
+    pub public_exponent: &'buf [u8],
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Read `component` of the RSA key `object_id`
+    ///
+    /// Only [`RsaKeyComponent::Mod`] and [`RsaKeyComponent::PubExp`] can be read.
+    /// `buf` must hold the component and 6 bytes of overhead, for example 518 bytes for the modulus of a 4096-bit key.
+    pub fn read_rsa_component<'buf>(
+        &mut self,
+        object_id: ObjectId,
+        component: RsaKeyComponent,
+        buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        if !matches!(component, RsaKeyComponent::Mod | RsaKeyComponent::PubExp) {
+            error!("RSA key component cannot be read: {component:?}");
+            return Err(Error::Line(line!()));
+        }
+        self.check_rsa_public(object_id)?;
+        self.read_component(object_id, component, buf)
+    }
+
+    /// Read the modulus and public exponent of the RSA key `object_id`
+    ///
+    /// `buf` must hold both components and 6 bytes of overhead.
+    pub fn read_rsa_public<'buf>(
+        &mut self,
+        object_id: ObjectId,
+        buf: &'buf mut [u8],
+    ) -> Result<RsaPublicKey<'buf>, Error> {
+        self.check_rsa_public(object_id)?;
+        let base = buf.as_ptr() as usize;
+        let modulus = self.read_component(object_id, RsaKeyComponent::Mod, &mut *buf)?;
+        let (start, len) = (modulus.as_ptr() as usize - base, modulus.len());
+        // Keep the modulus at the start of the buffer, to read the exponent in the rest
+        buf.copy_within(start..start + len, 0);
+        let (modulus, rest) = buf.split_at_mut(len);
+        let public_exponent = self.read_component(object_id, RsaKeyComponent::PubExp, rest)?;
+        Ok(RsaPublicKey {
+            modulus,
+            public_exponent,
+        })
+    }
+
+    fn check_rsa_public(&mut self, object_id: ObjectId) -> Result<(), Error> {
+        let attributes = self
+            .run_command(
+                &ReadAttributes {
+                    object_id,
+                    offset: None,
+                    length: None,
+                    rsa_key_component: None,
+                },
+                &mut [0; OBJECT_ATTRIBUTES_MAX_LEN + 8],
+            )?
+            .attributes;
+        match attributes.class() {
+            SecureObjectType::RsaKeyPair
+            | SecureObjectType::RsaKeyPairCrt
+            | SecureObjectType::RsaPubKey => Ok(()),
+            _class => {
+                error!("Object is not an RSA key with a public part: {_class:?}");
+                Err(Error::Line(line!()))
+            }
+        }
+    }
+
+    fn read_component<'buf>(
+        &mut self,
+        object_id: ObjectId,
+        component: RsaKeyComponent,
+        buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        Ok(self
+            .run_command(
+                &ReadObject {
+                    object_id,
+                    offset: None,
+                    length: None,
+                    rsa_key_component: Some(component),
+                },
+                buf,
+            )
+            .map_err(|err| self.explain_policy_error(object_id, err))?
+            .data)
+    }
+}
@@ -0,0 +1,254 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Client certificates for [`rustls`] with keys stored in the se05x
+//!
+//! [`Se05xSigningKey`] implements [`SigningKey`] with an EC key pair, signing with [`EcdsaSign`](super::commands::EcdsaSign).
+//! rustls requires signing keys to be `Send + Sync`, so the se05x is shared in a [`SharedSe05X`].
+//!
+//! [`certified_key`] pairs the key with the DER certificate stored in a binary file, for example by [`Se05X::write_certificate`].
+//! The resulting [`CertifiedKey`] can be used as client certificate with [`SingleCertAndKey`](rustls::sign::SingleCertAndKey):
+//!
+//! ```rust,no_run
+//! # use std::sync::{Arc, Mutex};
+//! # use se05x::se05x::{ObjectId, EcCurve, Se05X, rustls_signer::certified_key};
+//! # fn config<T: se05x::se05x::transport::Se05xTransport + Send + 'static>(
+//! #     se05x: Se05X<T>,
+//! #     builder: rustls::ConfigBuilder<rustls::ClientConfig, rustls::client::WantsClientCert>,
+//! # ) -> Result<rustls::ClientConfig, se05x::se05x::Error> {
+//! let se05x = Arc::new(Mutex::new(se05x));
+//! let key = certified_key(
+//!     &se05x,
+//!     ObjectId(hex_literal::hex!("00000001")),
+//!     EcCurve::NistP256,
+//!     ObjectId(hex_literal::hex!("00000002")),
+//! )?;
+//! let config = builder.with_client_cert_resolver(Arc::new(rustls::sign::SingleCertAndKey::from(key)));
+//! # Ok(config)
+//! # }
+//! ```
+
+use std::boxed::Box;
+use std::fmt;
+use std::format;
+use std::sync::{Arc, Mutex};
+use std::vec;
+use std::vec::Vec;
+
+use rustls::pki_types::CertificateDer;
+use rustls::sign::{CertifiedKey, Signer, SigningKey};
+use rustls::{SignatureAlgorithm, SignatureScheme};
+
+use super::commands::ReadSize;
+use super::transport::Se05xTransport;
+use super::{EcCurve, EcDsaSignatureAlgo, Error, ObjectId, Se05X};
+
+/// Se05x shared between the signing keys and the rest of the application
+pub type SharedSe05X<T> = Arc<Mutex<Se05X<T>>>;
+
+/// Signature scheme and digest used with keys on `curve`
+fn scheme(curve: EcCurve) -> Result<(SignatureScheme, EcDsaSignatureAlgo), Error> {
+    match curve {
+        EcCurve::NistP256 => Ok((
+            SignatureScheme::ECDSA_NISTP256_SHA256,
+            EcDsaSignatureAlgo::Sha256,
+        )),
+        EcCurve::NistP384 => Ok((
+            SignatureScheme::ECDSA_NISTP384_SHA384,
+            EcDsaSignatureAlgo::Sha384,
+        )),
+        _curve => {
+            error!("Curve not supported by rustls: {_curve:?}");
+            Err(Error::Line(line!()))
+        }
+    }
+}
+
+/// EC key pair stored in the se05x, usable as a rustls [`SigningKey`]
+///
+/// Only keys on [`NistP256`](EcCurve::NistP256) and [`NistP384`](EcCurve::NistP384) are supported, as TLS 1.3 binds each curve to a digest.
+pub struct Se05xSigningKey<T> {
+    se: SharedSe05X<T>,
+    key_id: ObjectId,
+    scheme: SignatureScheme,
+    algo: EcDsaSignatureAlgo,
+}
+
+impl<T> Se05xSigningKey<T> {
+    /// Signing key for the key pair `key_id`, on `curve`
+    pub fn new(se: SharedSe05X<T>, key_id: ObjectId, curve: EcCurve) -> Result<Self, Error> {
+        let (scheme, algo) = scheme(curve)?;
+        Ok(Self {
+            se,
+            key_id,
+            scheme,
+            algo,
+        })
+    }
+
+    pub fn key_id(&self) -> ObjectId {
+        self.key_id
+    }
+}
+
+impl<T> Clone for Se05xSigningKey<T> {
+    fn clone(&self) -> Self {
+        Self {
+            se: self.se.clone(),
+            key_id: self.key_id,
+            scheme: self.scheme,
+            algo: self.algo,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Se05xSigningKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Se05xSigningKey")
+            .field("key_id", &self.key_id)
+            .field("scheme", &self.scheme)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Se05xTransport + Send + 'static> SigningKey for Se05xSigningKey<T> {
+    fn choose_scheme(&self, offered: &[SignatureScheme]) -> Option<Box<dyn Signer>> {
+        offered
+            .contains(&self.scheme)
+            .then(|| Box::new(self.clone()) as Box<dyn Signer>)
+    }
+
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::ECDSA
+    }
+}
+
+impl<T: Se05xTransport + Send + 'static> Signer for Se05xSigningKey<T> {
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, rustls::Error> {
+        let mut se = self
+            .se
+            .lock()
+            .map_err(|_| rustls::Error::General("se05x mutex poisoned".into()))?;
+        let buf = &mut [0; 128];
+        let response = se
+            .sign_message(self.key_id, self.algo, message, buf)
+            .map_err(|err| rustls::Error::General(format!("se05x signature failed: {err}")))?;
+        Ok(response.signature.to_vec())
+    }
+
+    fn scheme(&self) -> SignatureScheme {
+        self.scheme
+    }
+}
+
+/// Client certificate made of the certificate stored in the binary file `certificate_id` and the key pair `key_id`, on `curve`
+///
+/// The binary file must hold a single DER certificate. Intermediate certificates can be appended to [`CertifiedKey::cert`].
+pub fn certified_key<T: Se05xTransport + Send + 'static>(
+    se: &SharedSe05X<T>,
+    key_id: ObjectId,
+    curve: EcCurve,
+    certificate_id: ObjectId,
+) -> Result<CertifiedKey, Error> {
+    let key = Se05xSigningKey::new(se.clone(), key_id, curve)?;
+    let certificate = {
+        let Ok(mut se) = se.lock() else {
+            error!("se05x mutex poisoned");
+            return Err(Error::Line(line!()));
+        };
+        let size = se
+            .run_command(
+                &ReadSize {
+                    object_id: certificate_id,
+                },
+                &mut [0; 16],
+            )?
+            .size
+            .0;
+        let Ok(size) = usize::try_from(size) else {
+            error!("Certificate too large: {size}");
+            return Err(Error::Line(line!()));
+        };
+        let mut buf = vec![0; size];
+        let len = se.read_certificate(certificate_id, &mut buf)?.len();
+        buf.truncate(len);
+        buf
+    };
+    Ok(CertifiedKey::new(
+        vec![CertificateDer::from(certificate)],
+        Arc::new(key),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use hex_literal::hex;
+
+    /// DER `SEQUENCE` of 128 bytes, standing for a certificate
+    fn certificate() -> Vec<u8> {
+        let mut certificate = vec![0x30, 0x81, 0x80];
+        certificate.extend((0..0x80).map(|i| i as u8));
+        certificate
+    }
+
+    #[test]
+    fn client_certificate() {
+        let certificate = certificate();
+        // The certificate is in the binary file `00000002`, and the signature is made by `00000001`
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(
+                &hex!("80 02 00 07 00 00 06 41 04 00000002 00 00"),
+                &hex!("41 08 0000000000000083 90 00"),
+            )
+            .expect(
+                &hex!("80 02 00 00 00 00 0E 41 04 00000002 42 02 0000 43 02 0004 00 00"),
+                &[&hex!("41 04")[..], &certificate[..4], &hex!("90 00")].concat(),
+            )
+            .expect(
+                &hex!("80 02 00 00 00 00 0E 41 04 00000002 42 02 0004 43 02 007F 00 00"),
+                &[&hex!("41 7F")[..], &certificate[4..], &hex!("90 00")].concat(),
+            )
+            .expect(
+                &hex!(
+                    "80 03 0C 09 00 00 2B 41 04 00000001 42 01 21"
+                    // SHA-256 of `handshake`
+                    "43 20 DFFF30D188055F20AAA1610F29102805528465BBAD91709DED764E8899F5AD2B"
+                    "00 00"
+                ),
+                &hex!("41 02 3000 90 00"),
+            );
+        let se05x = script.enable();
+        let se05x = Arc::new(Mutex::new(se05x));
+        assert!(
+            Se05xSigningKey::new(se05x.clone(), ObjectId([0, 0, 0, 1]), EcCurve::Secp256k1)
+                .is_err()
+        );
+
+        let key = certified_key(
+            &se05x,
+            ObjectId([0, 0, 0, 1]),
+            EcCurve::NistP256,
+            ObjectId([0, 0, 0, 2]),
+        )
+        .unwrap();
+        assert_eq!(key.cert, [CertificateDer::from(certificate)]);
+
+        assert!(key
+            .key
+            .choose_scheme(&[SignatureScheme::ECDSA_NISTP384_SHA384])
+            .is_none());
+        let signer = key
+            .key
+            .choose_scheme(&[
+                SignatureScheme::ED25519,
+                SignatureScheme::ECDSA_NISTP256_SHA256,
+            ])
+            .unwrap();
+        assert_eq!(signer.scheme(), SignatureScheme::ECDSA_NISTP256_SHA256);
+        assert_eq!(signer.sign(b"handshake").unwrap(), [0x30, 0x00]);
+        assert!(se05x.lock().unwrap().transport().inner_mut().is_done());
+    }
+}
@@ -0,0 +1,289 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Rotation of the platform SCP03 keys
+//!
+//! The platform SCP03 keys authenticate the secure channel between the host and the issuer security domain (ISD) of the se05x.
+//! They are replaced with the GlobalPlatform PUT KEY command, built by [`PutScp03Keys`].
+//! The new keys are encrypted with the DEK of the current key set and sent with their key check values, which the ISD echoes in its response.
+//!
+//! This crate does not implement the platform secure channel.
+//! [`Se05X::rotate_scp03_keys`] must run over a transport that selected the ISD and wraps commands in a secure channel opened with the current keys.
+
+use core::fmt;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use aes::cipher::{BlockEncrypt, KeyInit};
+use aes::Aes128;
+use iso7816::command::class::NO_SM_CLA;
+use iso7816::command::{CommandBuilder, DataSource, DataStream, Writer};
+
+use super::transport::Se05xTransport;
+use super::{Error, Se05X, Se05XCommand, Se05XResponse};
+
+/// Length of the platform SCP03 keys
+pub const SCP03_KEY_LEN: usize = 16;
+/// Length of key check values
+pub const KCV_LEN: usize = 3;
+
+const INS_PUT_KEY: u8 = 0xD8;
+/// Identifier of the first key of the set, with the flag announcing several keys
+const P2_MULTIPLE_KEYS: u8 = 0x81;
+const KEY_TYPE_AES: u8 = 0x88;
+/// Key type, length of the key component, key length, encrypted key, KCV length and KCV
+const KEY_COMPONENT_LEN: usize = 3 + SCP03_KEY_LEN + 1 + KCV_LEN;
+const PUT_KEY_DATA_LEN: usize = 1 + 3 * KEY_COMPONENT_LEN;
+
+/// Platform SCP03 key set
+///
+/// The keys are overwritten with zeroes when dropped.
+#[derive(Clone)]
+pub struct Scp03Keys {
+    /// Key version number of the set
+    pub version: u8,
+    pub enc: [u8; SCP03_KEY_LEN],
+    pub mac: [u8; SCP03_KEY_LEN],
+    pub dek: [u8; SCP03_KEY_LEN],
+}
+
+impl Scp03Keys {
+    fn keys(&self) -> [&[u8; SCP03_KEY_LEN]; 3] {
+        [&self.enc, &self.mac, &self.dek]
+    }
+}
+
+impl fmt::Debug for Scp03Keys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scp03Keys")
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Scp03Keys {
+    fn drop(&mut self) {
+        self.enc.fill(0);
+        self.mac.fill(0);
+        self.dek.fill(0);
+        compiler_fence(Ordering::SeqCst);
+    }
+}
+
+/// Key check value of an AES key: the first bytes of a block of `0x01` bytes encrypted with the key
+pub fn key_check_value(key: &[u8; SCP03_KEY_LEN]) -> [u8; KCV_LEN] {
+    let mut block = [0x01; 16].into();
+    Aes128::new(key.into()).encrypt_block(&mut block);
+    let mut kcv = [0; KCV_LEN];
+    kcv.copy_from_slice(&block[..KCV_LEN]);
+    kcv
+}
+
+/// GlobalPlatform PUT KEY command replacing a platform SCP03 key set
+#[derive(Clone, PartialEq, Eq)]
+pub struct PutScp03Keys {
+    old_version: u8,
+    data: [u8; PUT_KEY_DATA_LEN],
+}
+
+impl PutScp03Keys {
+    /// Replace the key set `old_keys` with `new_keys`
+    pub fn new(old_keys: &Scp03Keys, new_keys: &Scp03Keys) -> Self {
+        let dek = Aes128::new((&old_keys.dek).into());
+        let mut data = [0; PUT_KEY_DATA_LEN];
+        data[0] = new_keys.version;
+        for (component, key) in data[1..]
+            .chunks_exact_mut(KEY_COMPONENT_LEN)
+            .zip(new_keys.keys())
+        {
+            let (header, rest) = component.split_at_mut(3);
+            header.copy_from_slice(&[KEY_TYPE_AES, 1 + SCP03_KEY_LEN as u8, SCP03_KEY_LEN as u8]);
+            let (encrypted, kcv) = rest.split_at_mut(SCP03_KEY_LEN);
+            let mut block = (*key).into();
+            dek.encrypt_block(&mut block);
+            encrypted.copy_from_slice(&block);
+            block.fill(0);
+            kcv[0] = KCV_LEN as u8;
+            kcv[1..].copy_from_slice(&key_check_value(key));
+        }
+        Self {
+            old_version: old_keys.version,
+            data,
+        }
+    }
+
+    /// The response expected from the ISD: the new key version followed by the key check values
+    fn expected_response(&self) -> PutKeyResponse {
+        let mut check_values = [[0; KCV_LEN]; 3];
+        for (kcv, component) in check_values
+            .iter_mut()
+            .zip(self.data[1..].chunks_exact(KEY_COMPONENT_LEN))
+        {
+            kcv.copy_from_slice(&component[KEY_COMPONENT_LEN - KCV_LEN..]);
+        }
+        PutKeyResponse {
+            version: self.data[0],
+            check_values,
+        }
+    }
+
+    fn command(&self) -> CommandBuilder<&[u8]> {
+        CommandBuilder::new(
+            NO_SM_CLA,
+            INS_PUT_KEY.into(),
+            self.old_version,
+            P2_MULTIPLE_KEYS,
+            &self.data,
+            0,
+        )
+    }
+}
+
+impl fmt::Debug for PutScp03Keys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PutScp03Keys")
+            .field("old_version", &self.old_version)
+            .field("new_version", &self.data[0])
+            .finish_non_exhaustive()
+    }
+}
+
+impl DataSource for PutScp03Keys {
+    fn len(&self) -> usize {
+        self.command().len()
+    }
+    fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+impl<W: Writer> DataStream<W> for PutScp03Keys {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as Writer>::Error> {
+        self.command().to_writer(writer)
+    }
+}
+
+impl<W: Writer> Se05XCommand<W> for PutScp03Keys {
+    type Response<'a> = PutKeyResponse;
+}
+
+/// Response to [`PutScp03Keys`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PutKeyResponse {
+    /// Key version number of the new set
+    pub version: u8,
+    /// Key check values of the ENC, MAC and DEK keys
+    pub check_values: [[u8; KCV_LEN]; 3],
+}
+
+impl<'a> Se05XResponse<'a> for PutKeyResponse {
+    fn from_response(data: &'a [u8]) -> Result<Self, Error> {
+        let [version, rest @ ..] = data else {
+            error!("Empty PUT KEY response");
+            return Err(Error::Line(line!()));
+        };
+        if rest.len() != 3 * KCV_LEN {
+            error!("Unexpected PUT KEY response length: {}", data.len());
+            return Err(Error::Line(line!()));
+        }
+        let mut check_values = [[0; KCV_LEN]; 3];
+        for (kcv, chunk) in check_values.iter_mut().zip(rest.chunks_exact(KCV_LEN)) {
+            kcv.copy_from_slice(chunk);
+        }
+        Ok(Self {
+            version: *version,
+            check_values,
+        })
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Replace the platform SCP03 key set `old_keys` with `new_keys`
+    ///
+    /// The transport must have selected the ISD and authenticated a secure channel with `old_keys`, see the [module documentation](self).
+    /// The key check values echoed by the ISD are compared with the ones of `new_keys`.
+    pub fn rotate_scp03_keys(
+        &mut self,
+        old_keys: &Scp03Keys,
+        new_keys: &Scp03Keys,
+    ) -> Result<(), Error> {
+        let command = PutScp03Keys::new(old_keys, new_keys);
+        let response = self.run_command(&command, &mut [0; 16])?;
+        if response != command.expected_response() {
+            error!("PUT KEY response does not match the new keys");
+            return Err(Error::Line(line!()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use aes::cipher::BlockDecrypt;
+
+    fn keys(version: u8, seed: u8) -> Scp03Keys {
+        Scp03Keys {
+            version,
+            enc: [seed; 16],
+            mac: [seed + 1; 16],
+            dek: [seed + 2; 16],
+        }
+    }
+
+    /// Expect PUT KEY replacing `old_keys` with `new_keys`, answered with the new version and the key check values
+    ///
+    /// The last key check value is corrupted if `corrupt` is set.
+    fn expect_put_key(
+        script: &mut ScriptedTransport,
+        old_keys: &Scp03Keys,
+        new_keys: &Scp03Keys,
+        corrupt: bool,
+    ) {
+        let command = PutScp03Keys::new(old_keys, new_keys);
+        let mut apdu = vec![0x80, 0xD8, old_keys.version, 0x81, PUT_KEY_DATA_LEN as u8];
+        apdu.extend_from_slice(&command.data);
+        let mut response = vec![new_keys.version];
+        for kcv in command.expected_response().check_values {
+            response.extend_from_slice(&kcv);
+        }
+        *response.last_mut().unwrap() ^= corrupt as u8;
+        response.extend_from_slice(&[0x90, 0x00]);
+        script.expect(&apdu, &response);
+    }
+
+    #[test]
+    fn put_key_data() {
+        let old_keys = keys(1, 0x10);
+        let new_keys = keys(2, 0x40);
+        let command = PutScp03Keys::new(&old_keys, &new_keys);
+        assert_eq!(command.data[0], 2);
+        let dek = Aes128::new((&old_keys.dek).into());
+        for (component, key) in command.data[1..]
+            .chunks_exact(KEY_COMPONENT_LEN)
+            .zip(new_keys.keys())
+        {
+            assert_eq!(component[..3], [0x88, 0x11, 0x10]);
+            let mut block = *aes::Block::from_slice(&component[3..19]);
+            dek.decrypt_block(&mut block);
+            assert_eq!(block.as_slice(), key);
+            assert_eq!(component[19], 3);
+            assert_eq!(component[20..], key_check_value(key));
+        }
+    }
+
+    #[test]
+    fn rotate() {
+        let mut script = ScriptedTransport::new();
+        expect_put_key(&mut script, &keys(1, 0x10), &keys(2, 0x40), false);
+        expect_put_key(&mut script, &keys(2, 0x40), &keys(3, 0x70), true);
+        let mut se05x = script.enable();
+        se05x
+            .rotate_scp03_keys(&keys(1, 0x10), &keys(2, 0x40))
+            .unwrap();
+        assert!(se05x
+            .rotate_scp03_keys(&keys(2, 0x40), &keys(3, 0x70))
+            .is_err());
+        assert!(se05x.into_transport().into_inner().is_done());
+    }
+}
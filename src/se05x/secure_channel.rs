@@ -0,0 +1,391 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! SCP03 secure messaging of AES sessions
+//!
+//! [`Se05X::authenticate_aes128_session_with_level`] returns a [`SecureChannel`] holding the session keys derived during the authentication.
+//! [`Se05X::run_secure_command`] wraps the commands of the session as required by its [`SecurityLevel`], following GlobalPlatform Amendment D:
+//!
+//! - C-MAC: the command carries the first 8 bytes of a CMAC chained with the previous command,
+//! - C-DECRYPTION: the command data is padded and encrypted with AES-CBC,
+//! - R-MAC: the response carries a CMAC, checked before the response is parsed,
+//! - R-ENCRYPTION: the response data is decrypted.
+//!
+//! The commands of a session must all be run through its channel, in order: the MAC chaining value and the encryption counter are shared with the se05x.
+
+use core::fmt;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::{Aes128, Block};
+use cmac::{Cmac, Mac};
+
+use super::expected_len::{split_apdu, BufferSink, SplitApdu};
+use super::transport::{ApduWriter, Se05xTransport};
+use super::{
+    Error, ProcessSessionCmd, Se05X, Se05XCommand, Se05XResponse, SecurityLevel, SessionId,
+    MAX_APDU_OVERHEAD, MAX_APDU_PAYLOAD_LENGTH,
+};
+
+/// Length of the C-MAC and R-MAC appended to commands and responses
+const MAC_LEN: usize = 8;
+const BLOCK_LEN: usize = 16;
+/// Largest Lc of short APDUs
+const MAX_SHORT_LC: usize = 255;
+/// Largest Le of short APDUs
+const MAX_SHORT_LE: usize = 256;
+
+/// Session keys of an SCP03 session, derived from the authentication key and the challenges
+///
+/// The keys are overwritten with zeroes when dropped.
+pub(super) struct SessionKeys {
+    pub(super) enc: [u8; BLOCK_LEN],
+    pub(super) mac: [u8; BLOCK_LEN],
+    pub(super) rmac: [u8; BLOCK_LEN],
+}
+
+impl Drop for SessionKeys {
+    fn drop(&mut self) {
+        self.enc.fill(0);
+        self.mac.fill(0);
+        self.rmac.fill(0);
+        compiler_fence(Ordering::SeqCst);
+    }
+}
+
+/// Secure messaging state of an authenticated AES session
+///
+/// Returned by [`Se05X::authenticate_aes128_session_with_level`], and used to run the commands of the session with [`Se05X::run_secure_command`].
+pub struct SecureChannel {
+    session_id: SessionId,
+    security_level: SecurityLevel,
+    keys: SessionKeys,
+    /// Full CMAC of the last command
+    mac_chaining: [u8; BLOCK_LEN],
+    /// Encryption counter of the next command
+    counter: u128,
+}
+
+impl fmt::Debug for SecureChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecureChannel")
+            .field("session_id", &self.session_id)
+            .field("security_level", &self.security_level)
+            .field("counter", &self.counter)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SecureChannel {
+    /// Channel of a session authenticated by an EXTERNAL AUTHENTICATE command with the CMAC `mac_chaining`
+    pub(super) fn new(
+        session_id: SessionId,
+        security_level: SecurityLevel,
+        keys: SessionKeys,
+        mac_chaining: [u8; BLOCK_LEN],
+    ) -> Self {
+        Self {
+            session_id,
+            security_level,
+            keys,
+            mac_chaining,
+            // The first command after the authentication uses the counter 1
+            counter: 1,
+        }
+    }
+
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    pub fn security_level(&self) -> SecurityLevel {
+        self.security_level
+    }
+
+    /// Initial chaining value of the AES-CBC encryption of the command data, or of the response data if `response` is set
+    fn icv(&self, response: bool) -> Block {
+        let mut icv = Block::from(self.counter.to_be_bytes());
+        if response {
+            icv[0] = 0x80;
+        }
+        Aes128::new(&self.keys.enc.into()).encrypt_block(&mut icv);
+        icv
+    }
+
+    /// Wrap the serialized command `apdu` into `buf`, returning the length of the wrapped command
+    fn wrap(&mut self, apdu: &[u8], buf: &mut [u8]) -> Result<usize, Error> {
+        let SplitApdu { header, data, le } = split_apdu(apdu)?;
+        let le = match *le {
+            [] => None,
+            [0] => Some(MAX_SHORT_LE),
+            [le] => Some(le.into()),
+            [0, 0] | [0, 0, 0] => Some(0x10000),
+            [le1, le2] | [0, le1, le2] => Some(u16::from_be_bytes([le1, le2]).into()),
+            _ => return Err(Error::Line(line!())),
+        };
+        let encrypt = self.security_level.command_encryption() && !data.is_empty();
+        let data_len = if encrypt {
+            // Padding of at least one byte
+            (data.len() / BLOCK_LEN + 1) * BLOCK_LEN
+        } else {
+            data.len()
+        };
+        let lc = data_len + MAC_LEN;
+        let extended = lc > MAX_SHORT_LC || le.is_some_and(|le| le > MAX_SHORT_LE);
+        let lc_len = if extended { 3 } else { 1 };
+        let mac_offset = header.len() + lc_len + data_len;
+        let le_len = match (le, extended) {
+            (None, _) => 0,
+            (Some(_), true) => 2,
+            (Some(_), false) => 1,
+        };
+        let Some(buf) = buf.get_mut(..mac_offset + MAC_LEN + le_len) else {
+            error!("Wrapped command too long");
+            return Err(Error::CommandTooLong);
+        };
+
+        buf[..header.len()].copy_from_slice(header);
+        buf[0] = self.security_level.cla().into_inner();
+        if extended {
+            buf[4] = 0;
+            buf[5..7].copy_from_slice(&(lc as u16).to_be_bytes());
+        } else {
+            buf[4] = lc as u8;
+        }
+        let (command, trailer) = buf.split_at_mut(mac_offset);
+        let body = &mut command[header.len() + lc_len..];
+        body[..data.len()].copy_from_slice(data);
+        if encrypt {
+            body[data.len()] = 0x80;
+            body[data.len() + 1..].fill(0);
+            let mut chaining = self.icv(false);
+            let cipher = Aes128::new(&self.keys.enc.into());
+            for block in body.chunks_exact_mut(BLOCK_LEN) {
+                let block = Block::from_mut_slice(block);
+                xor(block, &chaining);
+                cipher.encrypt_block(block);
+                chaining = *block;
+            }
+        }
+
+        let mut mac = <Cmac<Aes128> as Mac>::new(&self.keys.mac.into());
+        mac.update(&self.mac_chaining);
+        mac.update(command);
+        self.mac_chaining = mac.finalize().into_bytes().into();
+        let (mac, le_field) = trailer.split_at_mut(MAC_LEN);
+        mac.copy_from_slice(&self.mac_chaining[..MAC_LEN]);
+        // 256 and 65536 are encoded as 0
+        match (le, extended) {
+            (None, _) => {}
+            (Some(le), true) => le_field.copy_from_slice(&(le as u16).to_be_bytes()),
+            (Some(le), false) => le_field[0] = le as u8,
+        }
+        Ok(buf.len())
+    }
+
+    /// Check and decrypt in place the data of a successful response, returning the length of the plain data
+    fn unwrap(&self, response: &mut [u8]) -> Result<usize, Error> {
+        let mut len = response.len();
+        if self.security_level.response_mac() {
+            let Some(data_len) = len.checked_sub(MAC_LEN) else {
+                error!("Response without R-MAC");
+                return Err(Error::Line(line!()));
+            };
+            let mut mac = <Cmac<Aes128> as Mac>::new(&self.keys.rmac.into());
+            mac.update(&self.mac_chaining);
+            mac.update(&response[..data_len]);
+            mac.update(&[0x90, 0x00]);
+            let expected = mac.finalize().into_bytes();
+            if response[data_len..] != expected[..MAC_LEN] {
+                error!("Invalid R-MAC");
+                return Err(Error::Line(line!()));
+            }
+            len = data_len;
+        }
+        if self.security_level.response_encryption() && len != 0 {
+            if !len.is_multiple_of(BLOCK_LEN) {
+                error!("Encrypted response of {len} bytes");
+                return Err(Error::Line(line!()));
+            }
+            let mut chaining = self.icv(true);
+            let cipher = Aes128::new(&self.keys.enc.into());
+            for block in response[..len].chunks_exact_mut(BLOCK_LEN) {
+                let block = Block::from_mut_slice(block);
+                let encrypted = *block;
+                cipher.decrypt_block(block);
+                xor(block, &chaining);
+                chaining = encrypted;
+            }
+            let Some(padding) = response[..len].iter().rposition(|&b| b != 0) else {
+                error!("Missing response padding");
+                return Err(Error::Line(line!()));
+            };
+            if response[padding] != 0x80 {
+                error!("Invalid response padding");
+                return Err(Error::Line(line!()));
+            }
+            len = padding;
+        }
+        Ok(len)
+    }
+}
+
+fn xor(block: &mut Block, other: &Block) {
+    for (b, o) in block.iter_mut().zip(other) {
+        *b ^= o;
+    }
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Run `command` in the session of `channel`, wrapped as required by its security level
+    ///
+    /// The response is parsed once its R-MAC is checked and its data decrypted.
+    /// The encryption counter of the channel advances even if the command fails, as it does on the se05x.
+    pub fn run_secure_command<'buf, C: for<'a> Se05XCommand<ApduWriter<'a>>>(
+        &mut self,
+        channel: &mut SecureChannel,
+        command: &C,
+        response_buf: &'buf mut [u8],
+    ) -> Result<<C as Se05XCommand<ApduWriter<'_>>>::Response<'buf>, Error> {
+        if channel.security_level == SecurityLevel::None {
+            return self.run_session_command(channel.session_id, command, response_buf);
+        }
+        command.validate()?;
+        let mut apdu = [0; MAX_APDU_PAYLOAD_LENGTH + MAX_APDU_OVERHEAD];
+        let mut sink = BufferSink {
+            buf: &mut apdu,
+            len: 0,
+        };
+        command.to_writer(&mut ApduWriter::new(&mut sink))?;
+        let len = sink.len;
+        let mut wrapped = [0; MAX_APDU_PAYLOAD_LENGTH + MAX_APDU_OVERHEAD];
+        let wrapped_len = channel.wrap(&apdu[..len], &mut wrapped)?;
+        let response = self
+            .run_command_buf_response(
+                &ProcessSessionCmd {
+                    session_id: channel.session_id,
+                    apdu: &wrapped[..wrapped_len],
+                },
+                response_buf,
+            )
+            .map(|response| response.len())
+            .and_then(|len| channel.unwrap(&mut response_buf[..len]));
+        channel.counter += 1;
+        Se05XResponse::from_response(&response_buf[..response?])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use crate::se05x::commands::{GetRandom, WriteBinary};
+    use crate::se05x::ObjectId;
+    use hex_literal::hex;
+
+    const SESSION_ID: SessionId = SessionId(hex!("0102030405060708"));
+
+    fn channel(security_level: SecurityLevel) -> SecureChannel {
+        SecureChannel::new(
+            SESSION_ID,
+            security_level,
+            SessionKeys {
+                enc: hex!("404142434445464748494A4B4C4D4E4F"),
+                mac: hex!("505152535455565758595A5B5C5D5E5F"),
+                rmac: hex!("606162636465666768696A6B6C6D6E6F"),
+            },
+            hex!("000102030405060708090A0B0C0D0E0F"),
+        )
+    }
+
+    const WRITE: WriteBinary = WriteBinary {
+        transient: false,
+        policy: None,
+        object_id: ObjectId(hex!("01020304")),
+        offset: None,
+        file_length: None,
+        data: Some(&hex!("AABBCC")),
+    };
+
+    #[test]
+    fn mac_enc_rmac_renc() {
+        let random = GetRandom { length: 16.into() };
+        let mut script = ScriptedTransport::new();
+        script
+            .expect(
+                &hex!(
+                    "80 05 00 00 00 00 29 10 08 0102030405060708 41 1D"
+                    "84 01 06 00 18 AA8508D3F45E61CB480A04A37A6B2582 F2805CDCD4BADC32"
+                    "00 00"
+                ),
+                &hex!("333F0564785B4626 90 00"),
+            )
+            .expect(
+                &hex!(
+                    "80 05 00 00 00 00 2D 10 08 0102030405060708 41 21"
+                    "84 04 00 49 00 00 18 4765EF191491C0CA783BEC570E25DEEA E5C7E6D937FF19BD 00 00"
+                    "00 00"
+                ),
+                &hex!(
+                    "50BBFDAFF1AF614DF89399A05A571E37 A59FA461D35C446FE3C3C30BBC43EA92"
+                    "483D155447763134 90 00"
+                ),
+            )
+            .expect(
+                &hex!(
+                    "80 05 00 00 00 00 2D 10 08 0102030405060708 41 21"
+                    "84 04 00 49 00 00 18 97B62AA14416EB473D5F7061021B1E65 DFCC416D508ADD82 00 00"
+                    "00 00"
+                ),
+                // Tampered R-MAC
+                &hex!(
+                    "EB9D177506C5CACFBF1EB9470575CFBA 9EA69D64A9FFE7B88DFC104B83EF848F"
+                    "6E3FB57F4886282C 90 00"
+                ),
+            );
+        let mut se05x = script.enable();
+        let mut channel = channel(SecurityLevel::MacEncRMacREnc);
+        let buf = &mut [0; 128];
+        se05x.run_secure_command(&mut channel, &WRITE, buf).unwrap();
+        let response = se05x
+            .run_secure_command(&mut channel, &random, buf)
+            .unwrap();
+        assert_eq!(response.data, hex!("A0A1A2A3A4A5A6A7A8A9AAABACADAEAF"));
+        assert!(se05x
+            .run_secure_command(&mut channel, &random, buf)
+            .is_err());
+        assert_eq!(channel.counter, 4);
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+
+    #[test]
+    fn mac_only() {
+        let mut script = ScriptedTransport::new();
+        script.expect(
+            &hex!(
+                "80 05 00 00 00 00 24 10 08 0102030405060708 41 18"
+                "84 01 06 00 13 41 04 01020304 44 03 AABBCC E32EDBDC39CF8A01"
+                "00 00"
+            ),
+            &hex!("90 00"),
+        );
+        // Without secure messaging, commands are only run in the session
+        script.expect(
+            &hex!(
+                "80 05 00 00 00 00 1C 10 08 0102030405060708 41 10"
+                "80 01 06 00 0B 41 04 01020304 44 03 AABBCC"
+                "00 00"
+            ),
+            &hex!("90 00"),
+        );
+        let mut se05x = script.enable();
+        let buf = &mut [0; 128];
+        se05x
+            .run_secure_command(&mut channel(SecurityLevel::Mac), &WRITE, buf)
+            .unwrap();
+        se05x
+            .run_secure_command(&mut channel(SecurityLevel::None), &WRITE, buf)
+            .unwrap();
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Class byte of commands
+//!
+//! Generated commands are serialized with the [`NO_SM_CLA`](iso7816::command::class::NO_SM_CLA) class byte.
+//! Under an SCP03 session with secure messaging, the class byte must announce it, and a secure messaging layer wraps the commands.
+//! [`WithClass`] serializes any command with another class byte, so that such a layer can handle the generated commands without duplicating them.
+//!
+//! ```
+//! # use iso7816::command::class::SM_CLA;
+//! # use se05x::se05x::{commands::GetRandom, secure_messaging::*};
+//! let command = GetRandom { length: 16.into() }.with_secure_messaging();
+//! assert_eq!(command.cla, SM_CLA);
+//! ```
+
+use iso7816::command::class::{Class, SM_CLA};
+use iso7816::command::{DataSource, DataStream, Writer};
+
+use super::{Error, Se05XCommand};
+
+/// Command serialized with the class byte `cla` instead of its own
+///
+/// The response is the response of `command`, and `command` is validated before the command is sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WithClass<C> {
+    pub cla: Class,
+    /// Wrapped command
+    pub command: C,
+}
+
+/// [`Writer`] replacing the first byte written to `inner` with a class byte
+pub struct ClassWriter<'w, W> {
+    inner: &'w mut W,
+    cla: Option<u8>,
+}
+
+impl<W: Writer> Writer for ClassWriter<'_, W> {
+    type Error = W::Error;
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, W::Error> {
+        match (self.cla, data) {
+            (Some(cla), [_, rest @ ..]) => {
+                self.inner.write_all(&[cla])?;
+                self.cla = None;
+                Ok(1 + self.inner.write(rest)?)
+            }
+            _ => self.inner.write(data),
+        }
+    }
+}
+
+impl<C: DataSource> DataSource for WithClass<C> {
+    fn len(&self) -> usize {
+        self.command.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.command.is_empty()
+    }
+}
+
+impl<W: Writer, C: for<'w> DataStream<ClassWriter<'w, W>>> DataStream<W> for WithClass<C> {
+    fn to_writer(&self, writer: &mut W) -> Result<(), <W as Writer>::Error> {
+        self.command.to_writer(&mut ClassWriter {
+            inner: writer,
+            cla: Some(self.cla.into_inner()),
+        })
+    }
+}
+
+impl<W: Writer, C: Se05XCommand<W> + for<'w> DataStream<ClassWriter<'w, W>>> Se05XCommand<W>
+    for WithClass<C>
+{
+    type Response<'a> = C::Response<'a>;
+
+    fn validate(&self) -> Result<(), Error> {
+        self.command.validate()
+    }
+}
+
+/// Combinators setting the class byte of any command
+pub trait WithSecureMessaging: Sized {
+    /// Serialize the command with the class byte `cla`
+    fn with_class(self, cla: Class) -> WithClass<Self> {
+        WithClass { cla, command: self }
+    }
+
+    /// Serialize the command with the [`SM_CLA`] class byte, for a secure messaging layer to wrap it
+    fn with_secure_messaging(self) -> WithClass<Self> {
+        self.with_class(SM_CLA)
+    }
+}
+
+impl<C: DataSource> WithSecureMessaging for C {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::commands::WriteBinary;
+    use crate::se05x::ObjectId;
+    use iso7816::command::class::NO_SM_CLA;
+
+    fn serialize<C: for<'w> DataStream<ClassWriter<'w, heapless::Vec<u8, 64>>>>(
+        command: &WithClass<C>,
+    ) -> heapless::Vec<u8, 64> {
+        let mut buf = heapless::Vec::new();
+        command.to_writer(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn class_byte() {
+        let write = WriteBinary {
+            transient: false,
+            policy: None,
+            object_id: ObjectId([1, 2, 3, 4]),
+            offset: None,
+            file_length: None,
+            data: Some(&[0xAB; 8]),
+        };
+        let mut plain: heapless::Vec<u8, 64> = heapless::Vec::new();
+        write.to_writer(&mut plain).unwrap();
+        assert_eq!(plain[0], NO_SM_CLA.into_inner());
+
+        let sm = write.clone().with_secure_messaging();
+        assert_eq!(sm.len(), plain.len());
+        let serialized = serialize(&sm);
+        assert_eq!(serialized[0], SM_CLA.into_inner());
+        assert_eq!(serialized[1..], plain[1..]);
+        let cla = Class::from_byte(0x8C).unwrap();
+        assert_eq!(serialize(&write.with_class(cla))[0], 0x8C);
+    }
+}
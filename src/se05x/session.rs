@@ -0,0 +1,200 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+use super::commands::{
+    CloseSession, CreateSession, ExchangeSessionData, ExchangeSessionDataResponse, ReadAttributes,
+    RefreshSession, VerifySessionUserId,
+};
+use super::policies::SessionPolicy;
+use super::transport::{ApduWriter, Se05xTransport};
+use super::{
+    Error, ObjectId, Se05X, Se05XCommand, SessionId, SetIndicator, OBJECT_ATTRIBUTES_MAX_LEN,
+};
+
+/// Authentication attempts of an authentication object, returned by [`Se05X::read_max_attempts`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthAttempts {
+    /// Failed attempts counted by the se05x
+    pub failed: u16,
+    /// Maximum number of failed attempts, `0` if unlimited
+    pub max: u16,
+}
+
+impl AuthAttempts {
+    /// Attempts left before the object is locked, `None` if unlimited
+    pub fn remaining(&self) -> Option<u16> {
+        (self.max != 0).then(|| self.max.saturating_sub(self.failed))
+    }
+}
+
+/// An open session with the se05x
+///
+/// Created with [`Se05X::open_session`]. Commands run with [`run`](Self::run) are wrapped in [`ProcessSessionCmd`](super::ProcessSessionCmd).
+///
+/// The session should be closed with [`close`](Self::close). If it is dropped instead, closing is attempted and errors are ignored.
+pub struct Session<'se, T: Se05xTransport> {
+    se: &'se mut Se05X<T>,
+    id: SessionId,
+    closed: bool,
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Open a session authenticated by `auth_object_id`
+    ///
+    /// The session still needs to be authenticated, for example with [`Session::verify_user_id`].
+    pub fn open_session(&mut self, auth_object_id: ObjectId) -> Result<Session<'_, T>, Error> {
+        let buf = &mut [0; 16];
+        let id = self
+            .run_command(
+                &CreateSession {
+                    object_id: auth_object_id,
+                },
+                buf,
+            )?
+            .session_id;
+        Ok(Session {
+            se: self,
+            id,
+            closed: false,
+        })
+    }
+
+    /// Read the attempt counter of the authentication object `object_id`, to monitor the risk of locking it
+    pub fn read_max_attempts(&mut self, object_id: ObjectId) -> Result<AuthAttempts, Error> {
+        let buf = &mut [0; OBJECT_ATTRIBUTES_MAX_LEN + 8];
+        let attributes = self
+            .run_command(
+                &ReadAttributes {
+                    object_id,
+                    offset: None,
+                    length: None,
+                    rsa_key_component: None,
+                },
+                buf,
+            )?
+            .attributes;
+        if attributes.authentication_indicator() != SetIndicator::Set {
+            error!("Not an authentication object: {object_id:?}");
+            return Err(Error::Line(line!()));
+        }
+        Ok(AuthAttempts {
+            failed: attributes.authentication_attempts_counter(),
+            max: attributes.max_authentication_attempts(),
+        })
+    }
+}
+
+impl<T: Se05xTransport> Session<'_, T> {
+    pub fn id(&self) -> SessionId {
+        self.id
+    }
+
+    /// Run a command within the session
+    pub fn run<'buf, C: for<'a> Se05XCommand<ApduWriter<'a>>>(
+        &mut self,
+        command: &C,
+        response_buf: &'buf mut [u8],
+    ) -> Result<<C as Se05XCommand<ApduWriter<'_>>>::Response<'buf>, Error> {
+        self.se.run_session_command(self.id, command, response_buf)
+    }
+
+    /// Authenticate the session with a [`UserId`](super::SecureObjectType::Userid) object
+    ///
+    /// A wrong user ID fails with [`Error::AuthFailed`].
+    pub fn verify_user_id(&mut self, user_id: &[u8]) -> Result<(), Error> {
+        self.run(&VerifySessionUserId { user_id }, &mut [0; 2])
+            .map_err(Error::auth_failure)?;
+        Ok(())
+    }
+
+    /// Refresh the session, optionally replacing its policy
+    pub fn refresh(&mut self, policy: Option<SessionPolicy>) -> Result<(), Error> {
+        self.run(&RefreshSession { policy }, &mut [0; 2])?;
+        Ok(())
+    }
+
+    /// Set the policy of the session
+    pub fn exchange_policy<'buf>(
+        &mut self,
+        session_policy: SessionPolicy,
+        c_mac: &[u8],
+        response_buf: &'buf mut [u8],
+    ) -> Result<ExchangeSessionDataResponse<'buf>, Error> {
+        self.run(
+            &ExchangeSessionData {
+                session_policy,
+                c_mac,
+            },
+            response_buf,
+        )
+    }
+
+    /// Close the session
+    pub fn close(mut self) -> Result<(), Error> {
+        self.closed = true;
+        self.run(&CloseSession {}, &mut [0; 2])?;
+        Ok(())
+    }
+}
+
+impl<T: Se05xTransport> Drop for Session<'_, T> {
+    fn drop(&mut self) {
+        if self.closed {
+            return;
+        }
+        if let Err(_err) = self.run(&CloseSession {}, &mut [0; 2]) {
+            warn!("Failed to close session on drop: {_err:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::ScriptedTransport;
+    use hex_literal::hex;
+
+    #[test]
+    fn auth_attempts() {
+        let mut script = ScriptedTransport::new();
+        // ReadAttributes of the user ID `00000001` with 1 of 3 attempts failed, then of the EC key `00000002`
+        script
+            .expect(
+                &hex!("80 02 00 3B 06 41 04 00000001"),
+                &hex!("42 0E 00000001 0C 02 0001 00000000 0003 90 00"),
+            )
+            .expect(
+                &hex!("80 02 00 3B 06 41 04 00000002"),
+                &hex!("42 0E 00000002 0B 01 0000 00000000 0000 90 00"),
+            )
+            // CreateSession, then VerifySessionUserId rejected and CloseSession within the session
+            .expect(
+                &hex!("80 04 00 1B 06 41 04 00000001 0C"),
+                &hex!("41 08 0102030405060708 90 00"),
+            )
+            .expect(
+                &hex!("80 05 00 00 00 00 18 10 08 0102030405060708 41 0C 80 04 00 2C 07 41 05 77726F6E67 00 00"),
+                &hex!("63 C2"),
+            )
+            .expect(
+                &hex!("80 05 00 00 00 00 10 10 08 0102030405060708 41 04 80 04 00 1C 00 00"),
+                &hex!("90 00"),
+            );
+        let mut se05x = script.enable();
+        let user_id = ObjectId([0, 0, 0, 1]);
+        let attempts = se05x.read_max_attempts(user_id).unwrap();
+        assert_eq!(attempts, AuthAttempts { failed: 1, max: 3 });
+        assert_eq!(attempts.remaining(), Some(2));
+        assert!(se05x.read_max_attempts(ObjectId([0, 0, 0, 2])).is_err());
+
+        let mut session = se05x.open_session(user_id).unwrap();
+        assert_eq!(
+            session.verify_user_id(b"wrong"),
+            Err(Error::AuthFailed {
+                remaining_attempts: 2
+            })
+        );
+        session.close().unwrap();
+        assert!(se05x.transport().inner_mut().is_done());
+    }
+}
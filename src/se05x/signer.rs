@@ -0,0 +1,174 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Adapters implementing the [`signature`] traits with keys stored in the se05x
+//!
+//! The traits take `&self`, so the adapters hold the [`Se05X`] in a [`RefCell`].
+//! Signing while the secure element is already borrowed fails with a [`signature::Error`].
+
+use core::cell::RefCell;
+
+use ::signature::{Error as SignatureError, Signer, Verifier};
+
+use super::transport::Se05xTransport;
+use super::{Error, ObjectId, Se05X};
+
+fn signature_error(_err: Error) -> SignatureError {
+    debug!("Signature operation failed: {_err:?}");
+    SignatureError::new()
+}
+
+/// NIST P-256 ECDSA with SHA-256, backed by [`EcdsaSign`](super::commands::EcdsaSign) and [`EcdsaVerify`](super::commands::EcdsaVerify)
+///
+/// The message is hashed on the host.
+#[cfg(feature = "p256")]
+pub struct Se05xP256Signer<'se, T> {
+    se: RefCell<&'se mut Se05X<T>>,
+    key_id: ObjectId,
+}
+
+#[cfg(feature = "p256")]
+impl<'se, T: Se05xTransport> Se05xP256Signer<'se, T> {
+    pub fn new(se: &'se mut Se05X<T>, key_id: ObjectId) -> Self {
+        Self {
+            se: RefCell::new(se),
+            key_id,
+        }
+    }
+
+    pub fn key_id(&self) -> ObjectId {
+        self.key_id
+    }
+}
+
+#[cfg(feature = "p256")]
+impl<T: Se05xTransport> Signer<p256::ecdsa::Signature> for Se05xP256Signer<'_, T> {
+    fn try_sign(&self, msg: &[u8]) -> Result<p256::ecdsa::Signature, SignatureError> {
+        let mut se = self
+            .se
+            .try_borrow_mut()
+            .map_err(|_| SignatureError::new())?;
+        let buf = &mut [0; 128];
+        let response = se
+            .sign_message(self.key_id, super::EcDsaSignatureAlgo::Sha256, msg, buf)
+            .map_err(signature_error)?;
+        p256::ecdsa::Signature::from_der(response.signature)
+    }
+}
+
+#[cfg(feature = "p256")]
+impl<T: Se05xTransport> Verifier<p256::ecdsa::Signature> for Se05xP256Signer<'_, T> {
+    fn verify(&self, msg: &[u8], signature: &p256::ecdsa::Signature) -> Result<(), SignatureError> {
+        let mut se = self
+            .se
+            .try_borrow_mut()
+            .map_err(|_| SignatureError::new())?;
+        let algo = super::EcDsaSignatureAlgo::Sha256;
+        let digest_buf = &mut [0; 64];
+        let der = signature.to_der();
+        let digest = algo.host_digest(msg, digest_buf).map_err(signature_error)?;
+        let buf = &mut [0; 16];
+        let response = se
+            .run_command(
+                &super::commands::EcdsaVerify {
+                    key_id: self.key_id,
+                    algo,
+                    data: digest,
+                    signature: der.as_bytes(),
+                },
+                buf,
+            )
+            .map_err(signature_error)?;
+        if response.result.is_success() {
+            Ok(())
+        } else {
+            Err(SignatureError::new())
+        }
+    }
+}
+
+/// Ed25519, backed by [`EddsaSign`](super::commands::EddsaSign) and [`EddsaVerify`](super::commands::EddsaVerify)
+#[cfg(feature = "ed25519")]
+pub struct Se05xEd25519Signer<'se, T> {
+    se: RefCell<&'se mut Se05X<T>>,
+    key_id: ObjectId,
+}
+
+/// The se05x encodes `R` and `S` as big-endian, RFC 8032 uses little-endian
+#[cfg(feature = "ed25519")]
+fn swap_ed25519_endianness(signature: &mut [u8; 64]) {
+    signature[..32].reverse();
+    signature[32..].reverse();
+}
+
+#[cfg(feature = "ed25519")]
+impl<'se, T: Se05xTransport> Se05xEd25519Signer<'se, T> {
+    pub fn new(se: &'se mut Se05X<T>, key_id: ObjectId) -> Self {
+        Self {
+            se: RefCell::new(se),
+            key_id,
+        }
+    }
+
+    pub fn key_id(&self) -> ObjectId {
+        self.key_id
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl<T: Se05xTransport> Signer<ed25519::Signature> for Se05xEd25519Signer<'_, T> {
+    fn try_sign(&self, msg: &[u8]) -> Result<ed25519::Signature, SignatureError> {
+        let mut se = self
+            .se
+            .try_borrow_mut()
+            .map_err(|_| SignatureError::new())?;
+        se.require_feature(super::AppletConfig::EDDSA)
+            .map_err(signature_error)?;
+        let buf = &mut [0; 128];
+        let response = se
+            .run_command(
+                &super::commands::EddsaSign {
+                    key_id: self.key_id,
+                    algo: super::EdDsaSignatureAlgo::Pure,
+                    data: msg,
+                },
+                buf,
+            )
+            .map_err(signature_error)?;
+        let mut signature: [u8; 64] = response
+            .signature
+            .try_into()
+            .map_err(|_| SignatureError::new())?;
+        swap_ed25519_endianness(&mut signature);
+        Ok(ed25519::Signature::from_bytes(&signature))
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl<T: Se05xTransport> Verifier<ed25519::Signature> for Se05xEd25519Signer<'_, T> {
+    fn verify(&self, msg: &[u8], signature: &ed25519::Signature) -> Result<(), SignatureError> {
+        let mut se = self
+            .se
+            .try_borrow_mut()
+            .map_err(|_| SignatureError::new())?;
+        let mut signature = signature.to_bytes();
+        swap_ed25519_endianness(&mut signature);
+        let buf = &mut [0; 16];
+        let response = se
+            .run_command(
+                &super::commands::EddsaVerify {
+                    key_id: self.key_id,
+                    algo: super::EdDsaSignatureAlgo::Pure,
+                    data: msg,
+                    signature: &signature,
+                },
+                buf,
+            )
+            .map_err(signature_error)?;
+        if response.result.is_success() {
+            Ok(())
+        } else {
+            Err(SignatureError::new())
+        }
+    }
+}
@@ -0,0 +1,266 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Converting ECDSA signatures between the DER and raw encodings
+//!
+//! [`EcdsaSign`](super::commands::EcdsaSign) returns DER-encoded signatures, and [`EcdsaVerify`](super::commands::EcdsaVerify) expects them.
+//! Many protocols (COSE, JWS, WebAuthn with some algorithms) use the raw encoding instead: `r || s`, each left-padded to the byte length of the curve order.
+//! For P-521 the scalars are 66 bytes long, as the order is 521 bits long.
+
+use super::commands::EcdsaSignResponse;
+use super::{EcCurve, Error};
+
+/// Maximum length of a raw signature, reached with P-521
+pub const MAX_RAW_SIGNATURE_LEN: usize = 2 * 66;
+
+/// Maximum length of a DER-encoded signature, reached with P-521
+pub const MAX_DER_SIGNATURE_LEN: usize = 3 + 2 * (2 + 67);
+
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_INTEGER: u8 = 0x02;
+
+impl EcCurve {
+    /// Length in bytes of each of the `r` and `s` scalars of an ECDSA signature on this curve
+    ///
+    /// None for curves that are not used for ECDSA.
+    pub fn ecdsa_scalar_len(&self) -> Option<usize> {
+        match self {
+            Self::Brainpool160 => Some(20),
+            Self::Secp160k1 => Some(21),
+            Self::NistP192 | Self::Brainpool192 | Self::Secp192k1 => Some(24),
+            Self::NistP224 | Self::Brainpool224 => Some(28),
+            Self::Secp224k1 => Some(29),
+            Self::NistP256 | Self::Brainpool256 | Self::Secp256k1 | Self::TpmEccBnP256 => Some(32),
+            Self::Brainpool320 => Some(40),
+            Self::NistP384 | Self::Brainpool384 => Some(48),
+            Self::Brainpool512 => Some(64),
+            Self::NistP521 => Some(66),
+            Self::IdEccEd25519 | Self::IdEccMontDh25519 => None,
+        }
+    }
+}
+
+fn scalar_len(curve: EcCurve) -> Result<usize, Error> {
+    curve.ecdsa_scalar_len().ok_or_else(|| {
+        error!("Curve not used for ECDSA: {curve:?}");
+        Error::Line(line!())
+    })
+}
+
+/// Split a DER TLV with a definite length of at most 255 bytes
+fn take_der(tag: u8, data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let [t, len, ref rem @ ..] = *data else {
+        return None;
+    };
+    if t != tag {
+        return None;
+    }
+    let (len, rem) = match len {
+        0x81 => {
+            let (&len, rem) = rem.split_first()?;
+            if len < 0x80 {
+                return None;
+            }
+            (len, rem)
+        }
+        len if len < 0x80 => (len, rem),
+        _ => return None,
+    };
+    let len = len.into();
+    (rem.len() >= len).then(|| rem.split_at(len))
+}
+
+/// Parse a non-negative DER INTEGER into `out`, left-padded with zeroes
+fn parse_scalar<'a>(data: &'a [u8], out: &mut [u8]) -> Option<&'a [u8]> {
+    let (value, rem) = take_der(TAG_INTEGER, data)?;
+    let (&first, _) = value.split_first()?;
+    if first & 0x80 != 0 {
+        return None;
+    }
+    let start = value.iter().position(|&b| b != 0).unwrap_or(value.len());
+    let value = &value[start..];
+    let padding = out.len().checked_sub(value.len())?;
+    out[..padding].fill(0);
+    out[padding..].copy_from_slice(value);
+    Some(rem)
+}
+
+/// Write `scalar` as a DER INTEGER, returning the number of bytes written
+fn write_scalar(scalar: &[u8], out: &mut [u8]) -> usize {
+    let start = scalar
+        .iter()
+        .position(|&b| b != 0)
+        .unwrap_or(scalar.len() - 1);
+    let scalar = &scalar[start..];
+    let padding = usize::from(scalar[0] & 0x80 != 0);
+    let len = padding + scalar.len();
+    out[0] = TAG_INTEGER;
+    out[1] = len as u8;
+    out[2] = 0;
+    out[2 + padding..][..scalar.len()].copy_from_slice(scalar);
+    2 + len
+}
+
+/// Convert a DER-encoded ECDSA signature on `curve` to `r || s`
+///
+/// `buf` must hold at least twice [`ecdsa_scalar_len`](EcCurve::ecdsa_scalar_len) bytes.
+pub fn der_to_raw<'buf>(
+    der: &[u8],
+    curve: EcCurve,
+    buf: &'buf mut [u8],
+) -> Result<&'buf [u8], Error> {
+    let len = scalar_len(curve)?;
+    let Some(raw) = buf.get_mut(..2 * len) else {
+        error!("Buffer too small for raw signature");
+        return Err(Error::Line(line!()));
+    };
+    let (r, s) = raw.split_at_mut(len);
+    let parsed = take_der(TAG_SEQUENCE, der)
+        .filter(|(_, rem)| rem.is_empty())
+        .and_then(|(sequence, _)| parse_scalar(sequence, r))
+        .and_then(|rem| parse_scalar(rem, s));
+    match parsed {
+        Some([]) => Ok(raw),
+        _ => {
+            error!("Invalid DER signature for {curve:?}");
+            Err(Error::Line(line!()))
+        }
+    }
+}
+
+/// Convert a raw `r || s` ECDSA signature on `curve` to DER
+///
+/// `buf` must hold at least [`MAX_DER_SIGNATURE_LEN`] bytes, or less for smaller curves.
+pub fn raw_to_der<'buf>(
+    raw: &[u8],
+    curve: EcCurve,
+    buf: &'buf mut [u8],
+) -> Result<&'buf [u8], Error> {
+    let len = scalar_len(curve)?;
+    if raw.len() != 2 * len {
+        error!(
+            "Raw signature has length {}, expected {}",
+            raw.len(),
+            2 * len
+        );
+        return Err(Error::Line(line!()));
+    }
+    let mut scalars = [0; MAX_DER_SIGNATURE_LEN];
+    let (r, s) = raw.split_at(len);
+    let r_len = write_scalar(r, &mut scalars);
+    let s_len = write_scalar(s, &mut scalars[r_len..]);
+    let content_len = r_len + s_len;
+    let header_len = if content_len < 0x80 { 2 } else { 3 };
+    let Some(der) = buf.get_mut(..header_len + content_len) else {
+        error!("Buffer too small for DER signature");
+        return Err(Error::Line(line!()));
+    };
+    der[0] = TAG_SEQUENCE;
+    if header_len == 2 {
+        der[1] = content_len as u8;
+    } else {
+        der[1] = 0x81;
+        der[2] = content_len as u8;
+    }
+    der[header_len..].copy_from_slice(&scalars[..content_len]);
+    Ok(der)
+}
+
+impl<'data> EcdsaSignResponse<'data> {
+    /// Convert the signature to `r || s`, see [`der_to_raw`]
+    pub fn to_raw_signature<'buf>(
+        &self,
+        curve: EcCurve,
+        buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        der_to_raw(self.signature, curve, buf)
+    }
+
+    /// Build a response from a raw `r || s` signature, encoding it to DER in `buf`, see [`raw_to_der`]
+    pub fn from_raw_signature(
+        raw: &[u8],
+        curve: EcCurve,
+        buf: &'data mut [u8],
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            signature: raw_to_der(raw, curve, buf)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn integer(value: &[u8]) -> Vec<u8> {
+        [&[TAG_INTEGER, value.len() as u8], value].concat()
+    }
+
+    fn round_trip(curve: EcCurve, raw: &[u8], der: &[u8]) {
+        let buf = &mut [0; MAX_RAW_SIGNATURE_LEN];
+        assert_eq!(der_to_raw(der, curve, buf).unwrap(), raw);
+        let buf = &mut [0; MAX_DER_SIGNATURE_LEN];
+        assert_eq!(raw_to_der(raw, curve, buf).unwrap(), der);
+        let response = EcdsaSignResponse::from_raw_signature(raw, curve, buf).unwrap();
+        assert_eq!(response.signature, der);
+        let buf = &mut [0; MAX_RAW_SIGNATURE_LEN];
+        assert_eq!(response.to_raw_signature(curve, buf).unwrap(), raw);
+    }
+
+    #[test]
+    fn p256() {
+        // r needs a padding byte to stay positive, s is shortened by its leading zero
+        let r = [[0x80].as_slice(), &[0x11; 31]].concat();
+        let s = [[0x00, 0x7F].as_slice(), &[0x22; 30]].concat();
+        let content = [integer(&[[0].as_slice(), &r].concat()), integer(&s[1..])].concat();
+        assert_eq!(content.len(), 0x44);
+        let der = [[TAG_SEQUENCE, 0x44].as_slice(), &content].concat();
+        round_trip(EcCurve::NistP256, &[r, s].concat(), &der);
+    }
+
+    #[test]
+    fn p521() {
+        let r = [[0x01].as_slice(), &[0xFF; 65]].concat();
+        let s = [[0x00, 0x01].as_slice(), &[0xAB; 64]].concat();
+        let content = [integer(&r), integer(&s[1..])].concat();
+        assert_eq!(content.len(), 0x87);
+        let der = [[TAG_SEQUENCE, 0x81, 0x87].as_slice(), &content].concat();
+        round_trip(EcCurve::NistP521, &[r, s].concat(), &der);
+    }
+
+    #[test]
+    fn invalid() {
+        let buf = &mut [0; MAX_DER_SIGNATURE_LEN];
+        let der = [
+            [TAG_SEQUENCE, 0x06].as_slice(),
+            &integer(&[1]),
+            &integer(&[2]),
+        ]
+        .concat();
+        assert_eq!(der_to_raw(&der, EcCurve::NistP256, buf).unwrap().len(), 64);
+        // Trailing data
+        let trailing = [der.as_slice(), &[0]].concat();
+        assert!(der_to_raw(&trailing, EcCurve::NistP256, buf).is_err());
+        // Not used for ECDSA
+        assert!(der_to_raw(&der, EcCurve::IdEccEd25519, buf).is_err());
+        // Negative scalar
+        let negative = [
+            [TAG_SEQUENCE, 0x06].as_slice(),
+            &integer(&[0x81]),
+            &integer(&[2]),
+        ]
+        .concat();
+        assert!(der_to_raw(&negative, EcCurve::NistP256, buf).is_err());
+        // Scalar too long for the curve
+        let long = [
+            [TAG_SEQUENCE, 0x23].as_slice(),
+            &integer(&[0x11; 29]),
+            &integer(&[2]),
+        ]
+        .concat();
+        assert!(der_to_raw(&long, EcCurve::NistP224, buf).is_err());
+        assert!(raw_to_der(&[1; 56], EcCurve::NistP256, buf).is_err());
+        // Buffer too small
+        assert!(der_to_raw(&der, EcCurve::NistP256, &mut [0; 63]).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! APDUs over TCP, to reach a se05x exposed by a network bridge
+//!
+//! Hardware-in-the-loop rigs can share a se05x over the network, and run the full command stack against it from CI.
+//! Each command APDU is sent as its length, encoded as a big-endian `u32`, followed by the APDU.
+//! The bridge answers each command the same way, with the response APDU including the status word.
+//!
+//! ```rust,no_run
+//! use se05x::se05x::apdu::{ApduLink, ApduSe05X};
+//! use se05x::se05x::tcp::TcpTransport;
+//!
+//! let transport = TcpTransport::connect("192.0.2.1:8050").unwrap();
+//! let mut se05x = ApduSe05X::from_transport(ApduLink::new(transport));
+//! se05x.enable().unwrap();
+//! ```
+
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+
+use super::apdu::ApduTransport;
+use super::Error;
+
+/// [`ApduTransport`] sending length-prefixed APDUs over a stream, usually a [`TcpStream`]
+pub struct TcpTransport<S = TcpStream> {
+    stream: S,
+}
+
+impl TcpTransport {
+    pub fn connect(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+        Ok(Self::new(stream))
+    }
+}
+
+impl<S: Read + Write> TcpTransport<S> {
+    pub fn new(stream: S) -> Self {
+        Self { stream }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    fn exchange<'buf>(
+        &mut self,
+        command: &[u8],
+        response_buf: &'buf mut [u8],
+    ) -> io::Result<Result<&'buf [u8], Error>> {
+        let len = u32::try_from(command.len()).map_err(|_| io::ErrorKind::InvalidInput)?;
+        self.stream.write_all(&len.to_be_bytes())?;
+        self.stream.write_all(command)?;
+        self.stream.flush()?;
+
+        let mut len = [0; 4];
+        self.stream.read_exact(&mut len)?;
+        let len = u32::from_be_bytes(len) as usize;
+        let Some(response) = response_buf.get_mut(..len) else {
+            error!("Response too long for buffer: {len}");
+            return Ok(Err(Error::Line(line!())));
+        };
+        self.stream.read_exact(response)?;
+        Ok(Ok(response))
+    }
+}
+
+impl<S: Read + Write> ApduTransport for TcpTransport<S> {
+    fn transmit<'buf>(
+        &mut self,
+        command: &[u8],
+        response_buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        self.exchange(command, response_buf).unwrap_or_else(|_err| {
+            error!("TCP exchange failed: {_err:?}");
+            Err(Error::Line(line!()))
+        })
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        std::thread::sleep(std::time::Duration::from_micros(us.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::se05x::apdu::{ApduLink, ApduSe05X};
+    use crate::se05x::commands::GetRandom;
+    use io::Cursor;
+
+    /// Stream replaying the responses of a bridge, and recording what is written to it
+    struct Bridge {
+        responses: Cursor<Vec<u8>>,
+        sent: Vec<u8>,
+    }
+
+    impl Read for Bridge {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.responses.read(buf)
+        }
+    }
+
+    impl Write for Bridge {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.sent.write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn length_prefixed() {
+        let select = [0x03, 0x01, 0x00, 0x3F, 0xFF, 0x01, 0x00, 0x90, 0x00];
+        let random = [0x41, 0x02, 0xCA, 0xFE, 0x90, 0x00];
+        let mut responses = Vec::new();
+        for response in [&select[..], &random] {
+            responses.extend_from_slice(&(response.len() as u32).to_be_bytes());
+            responses.extend_from_slice(response);
+        }
+        let bridge = Bridge {
+            responses: Cursor::new(responses),
+            sent: Vec::new(),
+        };
+
+        let mut se05x = ApduSe05X::from_transport(ApduLink::new(TcpTransport::new(bridge)));
+        se05x.enable().unwrap();
+        let buf = &mut [0; 16];
+        let response = se05x
+            .run_command(&GetRandom { length: 2.into() }, buf)
+            .unwrap();
+        assert_eq!(response.data, [0xCA, 0xFE]);
+
+        let sent = se05x.into_transport().into_inner().into_inner().sent;
+        let select_len = u32::from_be_bytes(sent[..4].try_into().unwrap()) as usize;
+        assert_eq!(&sent[4..][..4], [0x00, 0xA4, 0x04, 0x00]);
+        let random_command = &sent[4 + select_len..];
+        assert_eq!(
+            u32::from_be_bytes(random_command[..4].try_into().unwrap()) as usize,
+            random_command.len() - 4
+        );
+        assert_eq!(&random_command[4..][..4], [0x80, 0x04, 0x00, 0x49]);
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Golden APDU encodings of commands, to detect regressions of the command generator
+//!
+//! `generate_commands.py` generates a [`TestVector`] for each command of `commands.toml`: it serializes a sample of the command with [`encode`].
+//! The expected APDU is computed by the generator from the constants of the crate following the layouts of AN12413 and ISO 7816-4,
+//! including the `Le` field of the commands expecting response data.
+//! A field type without a sample value in `generate_commands.py` makes the generation fail.
+//!
+//! After regenerating the commands, run [`check`]:
+//!
+//! ```rust
+//! se05x::se05x::test_vectors::check().unwrap();
+//! ```
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use iso7816::command::{DataSource, DataStream, Writer};
+
+pub use super::commands::TEST_VECTORS;
+use super::Error;
+
+/// [`Writer`] collecting a command APDU in memory
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BufferWriter {
+    pub buf: Vec<u8>,
+}
+
+impl Writer for BufferWriter {
+    type Error = Error;
+    fn write(&mut self, data: &[u8]) -> Result<usize, Error> {
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+}
+
+/// Serialize `command`, checking that its length is the one announced by [`DataSource::len`]
+pub fn encode<C: DataSource + DataStream<BufferWriter>>(command: &C) -> Result<Vec<u8>, Error> {
+    let mut writer = BufferWriter::default();
+    command.to_writer(&mut writer)?;
+    if writer.buf.len() != command.len() {
+        error!(
+            "Command announced {} bytes, wrote {}",
+            command.len(),
+            writer.buf.len()
+        );
+        return Err(Error::Line(line!()));
+    }
+    Ok(writer.buf)
+}
+
+/// Command and its expected APDU
+pub struct TestVector {
+    pub name: &'static str,
+    pub encode: fn() -> Result<Vec<u8>, Error>,
+    pub expected: &'static [u8],
+}
+
+/// Check all [`TEST_VECTORS`], returning the name and encoding of the first mismatch
+pub fn check() -> Result<(), (&'static str, Vec<u8>)> {
+    for vector in TEST_VECTORS {
+        match (vector.encode)() {
+            Ok(apdu) if apdu == vector.expected => {}
+            Ok(apdu) => return Err((vector.name, apdu)),
+            Err(_) => return Err((vector.name, Vec::new())),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn golden_vectors() {
+        super::check().unwrap();
+    }
+}
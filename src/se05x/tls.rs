@@ -0,0 +1,187 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! TLS 1.2 key derivation in the se05x
+//!
+//! Embedded TLS stacks can keep the secrets of a PSK or ECDHE-PSK handshake in the se05x:
+//!
+//! 1. [`Se05X::tls_pre_master_secret`] computes the pre-master secret from a pre-shared key object, and stores it in an HMAC key object.
+//! 2. [`Se05X::tls_master_secret`] derives the master secret from it with the TLS PRF.
+//! 3. [`Se05X::tls_key_block`] expands the master secret into the key block of the connection.
+
+use super::commands::{TlsCalculatePreMasterSecret, TlsPerformPrf};
+use super::transport::Se05xTransport;
+use super::{AppletConfig, Digest, Error, ObjectId, Se05X, TlsPrfType};
+
+/// Length of the master secret
+pub const MASTER_SECRET_LEN: u16 = 48;
+
+/// Random of a client or server hello message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlsRandom(pub [u8; 32]);
+
+/// Randoms exchanged in the hello messages of a handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlsRandoms {
+    pub client: TlsRandom,
+    pub server: TlsRandom,
+}
+
+impl TlsRandoms {
+    /// Seed of the master secret derivation: the client random followed by the server random
+    pub fn master_secret_seed(&self) -> [u8; 64] {
+        concat(&self.client, &self.server)
+    }
+
+    /// Seed of the key expansion: the server random followed by the client random
+    pub fn key_expansion_seed(&self) -> [u8; 64] {
+        concat(&self.server, &self.client)
+    }
+}
+
+fn concat(first: &TlsRandom, second: &TlsRandom) -> [u8; 64] {
+    let mut seed = [0; 64];
+    seed[..32].copy_from_slice(&first.0);
+    seed[32..].copy_from_slice(&second.0);
+    seed
+}
+
+/// Ephemeral key of an ECDHE-PSK key exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlsEcdhe<'a> {
+    /// EC key pair of the se05x
+    pub key_pair_id: ObjectId,
+    /// Public key of the peer, in the format described in [`ec_key`](super::ec_key)
+    pub peer_public: &'a [u8],
+}
+
+impl<T: Se05xTransport> Se05X<T> {
+    /// Compute the pre-master secret of a PSK or ECDHE-PSK handshake, and store it in the HMAC key object `target`
+    pub fn tls_pre_master_secret(
+        &mut self,
+        psk_id: ObjectId,
+        ecdhe: Option<TlsEcdhe<'_>>,
+        target: ObjectId,
+    ) -> Result<(), Error> {
+        self.require_feature(AppletConfig::TLS)?;
+        self.run_command(
+            &TlsCalculatePreMasterSecret {
+                psk_id,
+                key_pair_id: ecdhe.map(|ecdhe| ecdhe.key_pair_id),
+                hmac_key_id: target,
+                input_data: ecdhe.map(|ecdhe| ecdhe.peer_public),
+            },
+            &mut [0; 2],
+        )?;
+        Ok(())
+    }
+
+    /// Derive the master secret from the pre-master secret stored in `pms_id`
+    ///
+    /// `response_buf` must hold [`MASTER_SECRET_LEN`] bytes and 4 bytes of overhead.
+    pub fn tls_master_secret<'buf>(
+        &mut self,
+        pms_id: ObjectId,
+        digest: Digest,
+        prf_type: TlsPrfType,
+        randoms: &TlsRandoms,
+        response_buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        self.tls_prf(
+            pms_id,
+            digest,
+            prf_type,
+            b"master secret",
+            &randoms.master_secret_seed(),
+            MASTER_SECRET_LEN,
+            response_buf,
+        )
+    }
+
+    /// Expand the master secret stored in `master_secret_id` into `len` bytes of key material
+    ///
+    /// `response_buf` must hold `len` bytes and 4 bytes of overhead.
+    pub fn tls_key_block<'buf>(
+        &mut self,
+        master_secret_id: ObjectId,
+        digest: Digest,
+        prf_type: TlsPrfType,
+        randoms: &TlsRandoms,
+        len: u16,
+        response_buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        self.tls_prf(
+            master_secret_id,
+            digest,
+            prf_type,
+            b"key expansion",
+            &randoms.key_expansion_seed(),
+            len,
+            response_buf,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn tls_prf<'buf>(
+        &mut self,
+        hmac_key_id: ObjectId,
+        digest: Digest,
+        prf_type: TlsPrfType,
+        label: &[u8],
+        random: &[u8],
+        len: u16,
+        response_buf: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Error> {
+        self.require_feature(AppletConfig::TLS)?;
+        Ok(self
+            .run_command(
+                &TlsPerformPrf {
+                    prf_type,
+                    hmac_key_id,
+                    digest,
+                    label,
+                    random,
+                    requested_len: len.into(),
+                },
+                response_buf,
+            )?
+            .data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds() {
+        let randoms = TlsRandoms {
+            client: TlsRandom([1; 32]),
+            server: TlsRandom([2; 32]),
+        };
+        let master = randoms.master_secret_seed();
+        assert_eq!((&master[..32], &master[32..]), (&[1; 32][..], &[2; 32][..]));
+        let expansion = randoms.key_expansion_seed();
+        assert_eq!(
+            (&expansion[..32], &expansion[32..]),
+            (&[2; 32][..], &[1; 32][..])
+        );
+
+        let prf = TlsPerformPrf {
+            prf_type: TlsPrfType::ClientRandom,
+            hmac_key_id: ObjectId([1, 2, 3, 4]),
+            digest: Digest::Sha256,
+            label: b"master secret",
+            random: &master,
+            requested_len: MASTER_SECRET_LEN.into(),
+        };
+        assert_eq!(prf.validate(), Ok(()));
+        assert!(TlsPerformPrf { label: b"", ..prf }.validate().is_err());
+        assert!(TlsPerformPrf {
+            random: &master[..48],
+            ..prf
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright (C) 2023 Nitrokey GmbH
+// SPDX-License-Identifier: LGPL-3.0-only
+
+//! Links between the host and the se05x
+//!
+//! [`Se05X`](super::Se05X) runs commands over any [`Se05xTransport`].
+//! The transport is implemented for the T=1 over I2C link [`T1oI2C`], and for transports exchanging whole APDUs through [`ApduLink`](super::apdu::ApduLink).
+//!
+//! Command APDUs are streamed to the transport through an [`ApduWriter`], so that transports are not required to buffer them.
+
+use core::marker::PhantomData;
+
+use iso7816::command::Writer;
+use iso7816::Status;
+
+use super::Error;
+#[cfg(feature = "transport")]
+use crate::embedded_hal::Delay;
+use crate::t1::FRAME_BUFFER_LEN;
+#[cfg(feature = "transport")]
+use crate::t1::{apdu_response, FrameSender, I2CForT1, T1oI2C};
+
+/// Link to the se05x
+pub trait Se05xTransport {
+    /// Receives the bytes of a command APDU
+    type Sink<'a>: ApduSink
+    where
+        Self: 'a;
+
+    /// Start sending a command APDU of `len` bytes
+    ///
+    /// All the bytes must be written to the returned sink before calling [`receive_apdu`](Self::receive_apdu).
+    fn send_apdu(&mut self, len: usize) -> Result<Self::Sink<'_>, Error>;
+
+    /// Send a command APDU of `len` bytes, written by `write` to the sink
+    ///
+    /// Transports that buffer frames, such as [`T1oI2C`], buffer them in `frame_buffer` instead of memory of their own.
+    /// The default implementation ignores `frame_buffer` and writes to the sink returned by [`send_apdu`](Self::send_apdu).
+    fn send_apdu_in(
+        &mut self,
+        len: usize,
+        frame_buffer: &mut [u8; FRAME_BUFFER_LEN],
+        write: &mut dyn FnMut(&mut dyn ApduSink) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let _ = frame_buffer;
+        write(&mut self.send_apdu(len)?)
+    }
+
+    /// Receive the response to the last command APDU, split between the data and the status word
+    fn receive_apdu<'buf>(
+        &mut self,
+        response_buf: &'buf mut [u8],
+    ) -> Result<(&'buf [u8], Status), Error>;
+
+    /// Bring the link back to a state where the applet can be selected
+    fn reset(&mut self) -> Result<(), Error>;
+
+    /// Wait for `us` microseconds
+    fn delay_us(&mut self, us: u32);
+}
+
+/// Destination of the bytes of a command APDU
+pub trait ApduSink {
+    /// Write the next bytes of the command APDU, returning how many were accepted
+    fn write_apdu(&mut self, data: &[u8]) -> Result<usize, Error>;
+}
+
+impl<S: ApduSink + ?Sized> ApduSink for &mut S {
+    fn write_apdu(&mut self, data: &[u8]) -> Result<usize, Error> {
+        (**self).write_apdu(data)
+    }
+}
+
+/// [`Writer`] serializing a command APDU to an [`ApduSink`]
+///
+/// Commands are serialized to a borrowed sink. [`RawCommand`] wraps a writer owning the sink of the transport.
+pub struct ApduWriter<'a, S: ApduSink = &'a mut dyn ApduSink> {
+    sink: S,
+    written: usize,
+    header: [u8; 4],
+    _sink: PhantomData<&'a mut dyn ApduSink>,
+}
+
+impl<'a> ApduWriter<'a> {
+    pub fn new(sink: &'a mut dyn ApduSink) -> Self {
+        Self::with_sink(sink)
+    }
+}
+
+impl<S: ApduSink> ApduWriter<'_, S> {
+    fn with_sink(sink: S) -> Self {
+        Self {
+            sink,
+            written: 0,
+            header: [0; 4],
+            _sink: PhantomData,
+        }
+    }
+
+    /// CLA, INS, P1 and P2 of the APDU being written
+    pub fn header(&self) -> [u8; 4] {
+        self.header
+    }
+}
+
+/// [`Writer`] streaming a command APDU to the transport, returned by [`Se05X::begin_raw_command`](super::Se05X::begin_raw_command)
+///
+/// Exactly the announced number of bytes must be written before calling [`finish`](Self::finish).
+pub struct RawCommand<'a, T: Se05xTransport + 'a> {
+    writer: ApduWriter<'a, T::Sink<'a>>,
+    len: usize,
+}
+
+impl<'a, T: Se05xTransport + 'a> RawCommand<'a, T> {
+    pub(super) fn new(sink: T::Sink<'a>, len: usize) -> Self {
+        Self {
+            writer: ApduWriter::with_sink(sink),
+            len,
+        }
+    }
+
+    /// Check that the whole command was written, so that its response can be received with [`Se05X::receive_raw_response`](super::Se05X::receive_raw_response)
+    pub fn finish(self) -> Result<RawCommandSent, Error> {
+        if self.writer.written != self.len {
+            error!(
+                "Raw command incomplete: {} of {} bytes",
+                self.writer.written, self.len
+            );
+            return Err(Error::Line(line!()));
+        }
+        Ok(RawCommandSent {
+            header: self.writer.header(),
+            len: self.len,
+        })
+    }
+}
+
+impl<'a, T: Se05xTransport + 'a> Writer for RawCommand<'a, T> {
+    type Error = Error;
+    fn write(&mut self, data: &[u8]) -> Result<usize, Error> {
+        if self.writer.written + data.len() > self.len {
+            error!("Writing more data than announced");
+            return Err(Error::Line(line!()));
+        }
+        self.writer.write(data)
+    }
+}
+
+/// Command APDU written with a [`RawCommand`], waiting for its response
+#[must_use = "the response must be received with Se05X::receive_raw_response"]
+#[derive(Debug)]
+pub struct RawCommandSent {
+    pub(super) header: [u8; 4],
+    pub(super) len: usize,
+}
+
+impl iso7816::command::writer::Error for Error {
+    fn failed_serialization(_cause: &'static str) -> Self {
+        error!("Failed serialization: {}", _cause);
+        Self::Line(line!())
+    }
+}
+
+impl<S: ApduSink> Writer for ApduWriter<'_, S> {
+    type Error = Error;
+    fn write(&mut self, data: &[u8]) -> Result<usize, Error> {
+        let accepted = self.sink.write_apdu(data)?;
+        if let Some(header) = self.header.get_mut(self.written..) {
+            let len = header.len().min(accepted);
+            header[..len].copy_from_slice(&data[..len]);
+        }
+        self.written += accepted;
+        Ok(accepted)
+    }
+}
+
+#[cfg(feature = "transport")]
+impl<Twi: I2CForT1, D: Delay, B: AsRef<[u8]> + AsMut<[u8]>> ApduSink
+    for FrameSender<'_, Twi, D, B>
+{
+    fn write_apdu(&mut self, data: &[u8]) -> Result<usize, Error> {
+        Ok(self.write_data(data)?)
+    }
+}
+
+#[cfg(feature = "transport")]
+impl<Twi: I2CForT1, D: Delay> Se05xTransport for T1oI2C<Twi, D> {
+    type Sink<'a>
+        = FrameSender<'a, Twi, D>
+    where
+        Self: 'a;
+
+    fn send_apdu(&mut self, len: usize) -> Result<FrameSender<'_, Twi, D>, Error> {
+        Ok(FrameSender::new(self, len))
+    }
+
+    fn send_apdu_in(
+        &mut self,
+        len: usize,
+        frame_buffer: &mut [u8; FRAME_BUFFER_LEN],
+        write: &mut dyn FnMut(&mut dyn ApduSink) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        write(&mut FrameSender::with_buffer(self, len, frame_buffer))
+    }
+
+    fn receive_apdu<'buf>(
+        &mut self,
+        response_buf: &'buf mut [u8],
+    ) -> Result<(&'buf [u8], Status), Error> {
+        self.wait_segt();
+        let data = self.receive_data(response_buf)?;
+        let Some((len, status)) = apdu_response(response_buf, data) else {
+            error!("Got too short apdu");
+            return Err(Error::Line(line!()));
+        };
+        Ok((&response_buf[..len], status))
+    }
+
+    fn reset(&mut self) -> Result<(), Error> {
+        self.resync()?;
+        self.interface_soft_reset(&mut [0; 64])?;
+        Ok(())
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        T1oI2C::delay_us(self, us)
+    }
+}
@@ -2,20 +2,27 @@
 // SPDX-License-Identifier: LGPL-3.0-only
 
 use hex_literal::hex;
+#[cfg(feature = "transport")]
 use iso7816::command::writer::IntoWriter;
+#[cfg(feature = "transport")]
 use iso7816::command::Writer;
+#[cfg(feature = "transport")]
+use iso7816::Status;
 
+#[cfg(feature = "transport")]
 pub type Crc = crc16::State<crc16::X_25>;
 
 use core::fmt::{self, Debug};
 use core::ops::Not;
 
+#[cfg(feature = "transport")]
 use crate::embedded_hal::{
     i2c::{Read, Write, WriteRead},
     Delay,
 };
 use crate::macros::enum_u8;
 
+#[cfg(feature = "transport")]
 mod i2cimpl;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -277,10 +284,12 @@ impl Pcb {
     }
 }
 
+#[cfg(feature = "transport")]
 pub trait I2CErrorNack: Debug {
     fn is_address_nack(&self) -> bool;
     fn is_data_nack(&self) -> bool;
 }
+#[cfg(feature = "transport")]
 pub trait I2CForT1:
     Read<u8, Error = <Self as I2CForT1>::Error>
     + Write<u8, Error = <Self as I2CForT1>::Error>
@@ -289,6 +298,7 @@ pub trait I2CForT1:
     type Error: I2CErrorNack;
 }
 
+#[cfg(feature = "transport")]
 impl<T> I2CForT1 for T
 where
     T: Read<u8>
@@ -328,6 +338,9 @@ impl fmt::Display for Error {
     }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
 impl iso7816::command::writer::Error for Error {
     fn failed_serialization(_cause: &'static str) -> Self {
         error!("Failed serializaiton: {}", _cause);
@@ -335,6 +348,45 @@ impl iso7816::command::writer::Error for Error {
     }
 }
 
+/// Timing and retry configuration of the T=1 over I2C layer
+///
+/// Timing values that are `None` use the defaults until the se05x reports its own in the ATR returned by [`interface_soft_reset`](T1oI2C::interface_soft_reset).
+/// Values that are set override both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct T1Config {
+    /// Retry count for attempts to write data to the se while it NACKs its address
+    pub retry_count: u32,
+    /// Maximum number of retries of a frame write after the se NACKs the data
+    ///
+    /// The se05x NACKs data while it is briefly unable to receive, the frame is written again after the guard time.
+    /// Defaults to 3, set to 0 to fail on the first data NACK.
+    pub max_data_nack_retries: u32,
+    /// Secure element guard time (microseconds)
+    pub segt_us: Option<u32>,
+    /// Block waiting time: maximum time the se05x can take to respond (microseconds)
+    pub bwt_us: Option<u32>,
+    /// Polling interval between attempts to read (microseconds)
+    pub mpot_us: Option<u32>,
+    /// Maximum total waiting time the se05x can request with waiting time extensions while processing a single command (microseconds)
+    ///
+    /// Long operations such as RSA key generation make the se05x request extensions. Once this limit is exceeded, the command fails with [`Error::Timeout`].
+    pub max_wtx_us: u32,
+}
+
+impl Default for T1Config {
+    fn default() -> Self {
+        Self {
+            retry_count: DEFAULT_RETRY_COUNT,
+            max_data_nack_retries: DEFAULT_MAX_DATA_NACK_RETRIES,
+            segt_us: None,
+            bwt_us: None,
+            mpot_us: None,
+            max_wtx_us: DEFAULT_MAX_WTX_US,
+        }
+    }
+}
+
+#[cfg(feature = "transport")]
 pub struct T1oI2C<Twi, D> {
     twi: Twi,
     se_address: u8,
@@ -348,6 +400,12 @@ pub struct T1oI2C<Twi, D> {
     mpot: u32,
     /// Retry count for attempts to write data to the se
     pub retry_count: u32,
+    /// Retry count for attempts to write data to the se after a data NACK
+    pub max_data_nack_retries: u32,
+    /// Timing overrides, taking precedence over the values from the ATR
+    timing_overrides: T1Config,
+    /// Maximum information field size of the frames sent to the se, read from the ATR or announced by the se
+    ifsc: usize,
     delay: D,
     segt: u32,
     /// Block waiting time
@@ -355,6 +413,33 @@ pub struct T1oI2C<Twi, D> {
     ///
     /// Microseconds
     bwt: u32,
+    /// Called while waiting for the se05x to respond
+    wait_hook: Option<&'static mut (dyn WaitHook + Send)>,
+}
+
+/// Reason for waiting, reported to the [`WaitHook`]
+#[cfg(feature = "transport")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitReason {
+    /// The se05x is still processing the command
+    Busy,
+    /// The se05x requested a waiting time extension, which was granted
+    TimeExtension,
+}
+
+/// Hook called while waiting for the se05x to respond, set with [`T1oI2C::set_wait_hook`]
+///
+/// Implemented by closures, so that a hook can own a watchdog or count the waits.
+#[cfg(feature = "transport")]
+pub trait WaitHook {
+    fn on_wait(&mut self, reason: WaitReason);
+}
+
+#[cfg(feature = "transport")]
+impl<F: FnMut(WaitReason)> WaitHook for F {
+    fn on_wait(&mut self, reason: WaitReason) {
+        self(reason)
+    }
 }
 
 // const TWI_RETRIES: usize = 128;
@@ -363,11 +448,14 @@ pub struct T1oI2C<Twi, D> {
 /// SEGT value in microseconds
 /// Minimun time between reading attempts
 const SEGT_US: u32 = 10;
+#[cfg(feature = "transport")]
 const BWT_US: u32 = 100_000;
 
 /// See table 4 of UM1225
+#[cfg(feature = "transport")]
 const NAD_HD_TO_SE: u8 = 0x5A;
 /// See table 4 of UM1225
+#[cfg(feature = "transport")]
 const NAD_SE_TO_HD: u8 = 0xA5;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -384,8 +472,59 @@ pub enum DataReceived {
     },
 }
 
+/// Progress of a response polled with [`T1oI2C::poll_receive_data`], started with [`T1oI2C::start_reception`]
+#[cfg(feature = "transport")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reception {
+    /// Length of the data received so far
+    written: usize,
+    polls: u32,
+    /// Number of polls before timing out, reset by waiting time extensions
+    max_polls: u32,
+    /// Total waiting time extensions requested by the se05x
+    wtx_us: u32,
+    wait_us: u32,
+    /// The last poll granted a waiting time extension
+    time_extension: bool,
+}
+
+#[cfg(feature = "transport")]
+impl Reception {
+    /// Advised delay before the next poll (microseconds)
+    ///
+    /// `0` if the next frame can be read immediately.
+    pub fn wait_us(&self) -> u32 {
+        self.wait_us
+    }
+
+    /// Reason for the delay advised by [`wait_us`](Self::wait_us)
+    pub fn wait_reason(&self) -> WaitReason {
+        if self.time_extension {
+            WaitReason::TimeExtension
+        } else {
+            WaitReason::Busy
+        }
+    }
+}
+
+/// Length of the response data and status word of the response APDU received as `data` in `buf`, `None` if it is not a valid response
+#[cfg(feature = "transport")]
+pub(crate) fn apdu_response(buf: &[u8], data: DataReceived) -> Option<(usize, Status)> {
+    match data {
+        DataReceived::IBlocks(len) if len >= 2 => {
+            Some((len - 2, Status::from([buf[len - 2], buf[len - 1]])))
+        }
+        _ => None,
+    }
+}
+
 const DEFAULT_RETRY_COUNT: u32 = 1024;
+/// Default for [`T1Config::max_data_nack_retries`]
+const DEFAULT_MAX_DATA_NACK_RETRIES: u32 = 3;
+/// Default for [`T1Config::max_wtx_us`]
+const DEFAULT_MAX_WTX_US: u32 = 60_000_000;
 
+#[cfg(feature = "transport")]
 #[cfg(feature = "embedded-hal-v0.2.7")]
 impl<M, N, E> T1oI2C<crate::embedded_hal::Hal027<M>, crate::embedded_hal::Hal027<N>>
 where
@@ -404,6 +543,7 @@ where
     }
 }
 
+#[cfg(feature = "transport")]
 #[cfg(feature = "embedded-hal-v1.0")]
 impl<M, N, E> T1oI2C<crate::embedded_hal::Hal10<M>, crate::embedded_hal::Hal10<N>>
 where
@@ -420,10 +560,14 @@ where
     }
 }
 
+#[cfg(feature = "transport")]
 impl<Twi: I2CForT1, D: Delay> T1oI2C<Twi, D> {
     pub fn new(twi: Twi, se_address: u8, delay: D) -> Self {
-        // Default MPOT value.
-        // TODO: get from ATR
+        Self::with_config(twi, se_address, delay, T1Config::default())
+    }
+
+    pub fn with_config(twi: Twi, se_address: u8, delay: D, config: T1Config) -> Self {
+        // Default MPOT value, replaced by the ATR value after an interface soft reset
         const DMPOT_MS: u32 = 1;
         Self {
             twi,
@@ -433,11 +577,30 @@ impl<Twi: I2CForT1, D: Delay> T1oI2C<Twi, D> {
             nad_se2hd: NAD_SE_TO_HD,
             iseq_snd: Seq::ZERO,
             iseq_rcv: Seq::ZERO,
-            mpot: DMPOT_MS * 1000,
-            segt: SEGT_US as _,
-            retry_count: DEFAULT_RETRY_COUNT,
-            bwt: BWT_US,
+            mpot: config.mpot_us.unwrap_or(DMPOT_MS * 1000),
+            segt: config.segt_us.unwrap_or(SEGT_US),
+            retry_count: config.retry_count,
+            max_data_nack_retries: config.max_data_nack_retries,
+            timing_overrides: config,
+            bwt: config.bwt_us.unwrap_or(BWT_US),
+            ifsc: MAX_FRAME_DATA_LEN,
             delay,
+            wait_hook: None,
+        }
+    }
+
+    /// Call `hook` while waiting for the se05x to respond
+    ///
+    /// The hook is called before each polling delay, with [`WaitReason::TimeExtension`] after each waiting time extension granted to the se05x.
+    /// Long operations such as RSA key generation block for several seconds: use the hook to kick a watchdog while they run.
+    /// The hook is borrowed for the lifetime of the driver: allocate it in a `static` or with `Box::leak`.
+    pub fn set_wait_hook(&mut self, hook: Option<&'static mut (dyn WaitHook + Send)>) {
+        self.wait_hook = hook;
+    }
+
+    fn call_wait_hook(&mut self, reason: WaitReason) {
+        if let Some(hook) = self.wait_hook.as_mut() {
+            hook.on_wait(reason);
         }
     }
 
@@ -479,147 +642,248 @@ impl<Twi: I2CForT1, D: Delay> T1oI2C<Twi, D> {
         }
     }
 
-    pub fn receive_data(&mut self, buffer: &mut [u8]) -> Result<DataReceived, Error> {
-        let mut written = 0;
-        let mut retry_count = self.bwt / self.mpot + 1;
-        let mut i = 0;
-        loop {
-            let mut header_buffer = [0; HEADER_LEN];
-            let mut crc_buf = [0; TRAILER_LEN];
-            i += 1;
-            if i == retry_count {
-                break;
-            }
+    /// Start polling a response with [`poll_receive_data`](Self::poll_receive_data)
+    pub fn start_reception(&self) -> Reception {
+        Reception {
+            written: 0,
+            polls: 0,
+            max_polls: self.bwt / self.mpot + 1,
+            wtx_us: 0,
+            wait_us: 0,
+            time_extension: false,
+        }
+    }
 
-            let read = self.read(&mut header_buffer);
-            match read {
-                Ok(()) => {}
-                Err(Error::AddressNack) => {
-                    self.wait_mpot();
-                    continue;
-                }
-                Err(err) => {
-                    return Err(err);
-                }
+    /// Read at most one frame of the response, without waiting
+    ///
+    /// Returns [`nb::Error::WouldBlock`] while the se05x is busy, or after processing a frame that does not complete the response.
+    /// Poll again after [`Reception::wait_us`]: in the meantime, cooperative schedulers can run other tasks.
+    /// The timeouts of the T=1 layer are counted in polls, so polling faster than advised shortens them.
+    pub fn poll_receive_data(
+        &mut self,
+        buffer: &mut [u8],
+        reception: &mut Reception,
+    ) -> nb::Result<DataReceived, Error> {
+        reception.polls += 1;
+        if reception.polls >= reception.max_polls {
+            error!("Waited for btw");
+            return Err(nb::Error::Other(Error::Timeout));
+        }
+        reception.wait_us = 0;
+        reception.time_extension = false;
+        let written = reception.written;
+        let mut header_buffer = [0; HEADER_LEN];
+        let mut crc_buf = [0; TRAILER_LEN];
+
+        match self.read(&mut header_buffer) {
+            Ok(()) => {}
+            Err(Error::AddressNack) => {
+                reception.wait_us = self.mpot;
+                return Err(nb::Error::WouldBlock);
             }
-
-            let [nad, pcb, len] = header_buffer;
-            debug!("Received header: {:02x?}", header_buffer);
-
-            if buffer.len() < written + len as usize {
-                error!("Buffer too small");
-                return Err(Error::ReceptionBuffer);
+            Err(err) => {
+                return Err(err.into());
             }
+        }
 
-            if len as usize > MAX_FRAME_DATA_LEN {
-                error!("Frame too large");
-                return Err(Error::ReceptionBuffer);
-            }
+        let [nad, pcb, len] = header_buffer;
+        debug!("Received header: {:02x?}", header_buffer);
 
-            let mut data_buf = [0; MAX_FRAME_DATA_LEN];
-            let current_buf = &mut buffer[written..][..len as usize];
-            let data_buf = &mut data_buf[..len as _];
+        if buffer.len() < written + len as usize {
+            error!("Buffer too small");
+            return Err(Error::ReceptionBuffer.into());
+        }
 
-            if nad != self.nad_se2hd {
-                error!("Received bad nad: {:02x}", nad);
-                return Err(Error::BadAddress);
-            }
+        if len as usize > MAX_FRAME_DATA_LEN {
+            error!("Frame too large");
+            return Err(Error::ReceptionBuffer.into());
+        }
 
-            if len != 0 {
-                self.read(data_buf)?;
-            }
-            self.read(&mut crc_buf)?;
+        let mut data_buf = [0; MAX_FRAME_DATA_LEN];
+        let current_buf = &mut buffer[written..][..len as usize];
+        let data_buf = &mut data_buf[..len as _];
 
-            let pcb = Pcb::parse(pcb).map_err(|_| Error::BadPcb)?;
+        if nad != self.nad_se2hd {
+            error!("Received bad nad: {:02x}", nad);
+            return Err(Error::BadAddress.into());
+        }
 
-            let mut crc = Crc::new();
-            crc.update(&header_buffer);
-            crc.update(data_buf);
-            let crc = crc.get().to_le_bytes();
-            if crc_buf != crc {
-                error!("Got bad crc: {:02x?} expected {:02x?}", &data_buf[..2], crc);
-                // TODO: write R-Block with error
-                return Err(Error::BadCrc);
-            }
+        if len != 0 {
+            self.read(data_buf)?;
+        }
+        self.read(&mut crc_buf)?;
+
+        let pcb = Pcb::parse(pcb).map_err(|_| Error::BadPcb)?;
+
+        let mut crc = Crc::new();
+        crc.update(&header_buffer);
+        crc.update(data_buf);
+        let crc = crc.get().to_le_bytes();
+        if crc_buf != crc {
+            error!("Got bad crc: {:02x?} expected {:02x?}", &data_buf[..2], crc);
+            // TODO: write R-Block with error
+            return Err(Error::BadCrc.into());
+        }
 
-            let (seq, more) = match pcb {
-                Pcb::S(SBlock::WtxRequest) => {
-                    if len != 1 {
-                        return Err(Error::Line(line!()));
-                    }
-                    let mult = data_buf[0];
-                    debug!("Got WtxRequest, {mult}");
-                    let frame = [
-                        self.nad_hd2se,
-                        Pcb::S(SBlock::WtxResponse).to_byte(),
-                        1,
-                        mult,
-                    ];
-                    let [crc1, crc2] = Crc::calculate(&frame).to_le_bytes();
-                    self.write(&[frame[0], frame[1], frame[2], frame[3], crc1, crc2])?;
-
-                    retry_count = (self.bwt * mult as u32) / self.mpot + 1;
-                    i = 0;
-                    self.delay.delay_us(100_000);
-                    continue;
-                }
-                Pcb::S(block) => {
-                    current_buf.copy_from_slice(data_buf);
-                    return Ok(DataReceived::SBlock {
-                        block,
-                        i_data: written,
-                        s_data: len as usize,
-                    });
+        let (seq, more) = match pcb {
+            Pcb::S(SBlock::WtxRequest) => {
+                if len != 1 {
+                    return Err(Error::Line(line!()).into());
                 }
-                Pcb::R(_, _) => {
-                    error!("Got unexpected R-Block in receive");
-                    return Err(Error::Line(line!()));
+                let mult = data_buf[0];
+                debug!("Got WtxRequest, {mult}");
+                let extension = self.bwt.saturating_mul(mult.into());
+                reception.wtx_us = reception.wtx_us.saturating_add(extension);
+                if mult == 0 || reception.wtx_us > self.timing_overrides.max_wtx_us {
+                    error!(
+                        "Waiting time extension refused: {mult}, total {}us",
+                        reception.wtx_us
+                    );
+                    return Err(Error::Timeout.into());
                 }
-                Pcb::I(seq, more) => (seq, more),
-            };
-            current_buf.copy_from_slice(data_buf);
-            written += len as usize;
-
-            if seq != self.iseq_rcv {
-                warn!("Got bad seq");
+                let frame = [
+                    self.nad_hd2se,
+                    Pcb::S(SBlock::WtxResponse).to_byte(),
+                    1,
+                    mult,
+                ];
+                let [crc1, crc2] = Crc::calculate(&frame).to_le_bytes();
+                self.write(&[frame[0], frame[1], frame[2], frame[3], crc1, crc2])?;
+
+                reception.max_polls = extension / self.mpot + 1;
+                reception.polls = 0;
+                reception.wait_us = 100_000;
+                reception.time_extension = true;
+                return Err(nb::Error::WouldBlock);
+            }
+            Pcb::S(SBlock::IfsRequest) if len == 1 => {
+                let ifsc = data_buf[0];
+                debug!("Got IfsRequest, {ifsc}");
+                self.set_ifsc(ifsc.into());
+                let frame = [
+                    self.nad_hd2se,
+                    Pcb::S(SBlock::IfsResponse).to_byte(),
+                    1,
+                    ifsc,
+                ];
+                let [crc1, crc2] = Crc::calculate(&frame).to_le_bytes();
+                self.write(&[frame[0], frame[1], frame[2], frame[3], crc1, crc2])?;
+                return Err(nb::Error::WouldBlock);
             }
-            self.iseq_rcv = !seq;
+            Pcb::S(block) => {
+                current_buf.copy_from_slice(data_buf);
+                return Ok(DataReceived::SBlock {
+                    block,
+                    i_data: written,
+                    s_data: len as usize,
+                });
+            }
+            Pcb::R(_, _) => {
+                error!("Got unexpected R-Block in receive");
+                return Err(Error::Line(line!()).into());
+            }
+            Pcb::I(seq, more) => (seq, more),
+        };
+        current_buf.copy_from_slice(data_buf);
+        reception.written += len as usize;
+
+        if seq != self.iseq_rcv {
+            warn!("Got bad seq");
+        }
+        self.iseq_rcv = !seq;
+
+        if !more {
+            return Ok(DataReceived::IBlocks(reception.written));
+        }
+        let frame = [
+            self.nad_hd2se,
+            Pcb::R(!seq, RBlockError::NoError).to_byte(),
+            0,
+        ];
+        let [crc1, crc2] = Crc::calculate(&frame).to_le_bytes();
+        self.write(&[frame[0], frame[1], frame[2], crc1, crc2])?;
+        Err(nb::Error::WouldBlock)
+    }
 
-            if !more {
-                return Ok(DataReceived::IBlocks(written));
+    /// Receive the response, polling until it is complete
+    pub fn receive_data(&mut self, buffer: &mut [u8]) -> Result<DataReceived, Error> {
+        let mut reception = self.start_reception();
+        loop {
+            match self.poll_receive_data(buffer, &mut reception) {
+                Ok(data) => return Ok(data),
+                Err(nb::Error::Other(err)) => return Err(err),
+                Err(nb::Error::WouldBlock) if reception.wait_us != 0 => {
+                    self.call_wait_hook(reception.wait_reason());
+                    self.delay.delay_us(reception.wait_us);
+                }
+                Err(nb::Error::WouldBlock) => {}
             }
-            let frame = [
-                self.nad_hd2se,
-                Pcb::R(!seq, RBlockError::NoError).to_byte(),
-                0,
-            ];
-            let [crc1, crc2] = Crc::calculate(&frame).to_le_bytes();
-            self.write(&[frame[0], frame[1], frame[2], crc1, crc2])?;
         }
-        error!("Waited for btw");
-        Err(Error::Timeout)
     }
 
-    pub fn resync(&mut self) -> Result<(), Error> {
-        trace!("Resync");
-        let header = [self.nad_hd2se, Pcb::S(SBlock::ResyncRequest).to_byte(), 0];
+    /// Read at most one frame of the response APDU, without waiting, see [`poll_receive_data`](Self::poll_receive_data)
+    ///
+    /// Once complete, returns the length of the response data, without the status word, and the status word.
+    /// Before the first poll, the se05x must be given the guard time with [`wait_segt`](Self::wait_segt), or by waiting [`segt_us`](Self::segt_us) in the scheduler.
+    pub fn poll_receive_apdu(
+        &mut self,
+        response_buf: &mut [u8],
+        reception: &mut Reception,
+    ) -> nb::Result<(usize, Status), Error> {
+        let data = self.poll_receive_data(response_buf, reception)?;
+        apdu_response(response_buf, data).ok_or_else(|| {
+            error!("Got too short apdu");
+            nb::Error::Other(Error::Line(line!()))
+        })
+    }
+
+    /// Send the S-block `request` without data and wait for `response`, also without data
+    fn exchange_s_block(&mut self, request: SBlock, response: SBlock) -> Result<(), Error> {
+        let header = [self.nad_hd2se, Pcb::S(request).to_byte(), 0];
         let [crc1, crc2] = Crc::calculate(&header).to_le_bytes();
         let frame = [header[0], header[1], header[2], crc1, crc2];
         debug!("Sending: {frame:02x?}");
         self.write(&frame)?;
         self.wait_segt();
         let data = self.receive_data(&mut [])?;
-        if !matches!(
-            data,
-            DataReceived::SBlock {
-                block: SBlock::ResyncResponse,
+        if data
+            != (DataReceived::SBlock {
+                block: response,
                 i_data: 0,
-                s_data: 0
-            }
-        ) {
+                s_data: 0,
+            })
+        {
             error!("Got unexpected error: {data:?}");
             return Err(Error::BadPcb);
         }
+        Ok(())
+    }
+
+    pub fn resync(&mut self) -> Result<(), Error> {
+        trace!("Resync");
+        self.exchange_s_block(SBlock::ResyncRequest, SBlock::ResyncResponse)?;
+        self.iseq_snd = Seq::ZERO;
+        self.iseq_rcv = Seq::ZERO;
+        Ok(())
+    }
+
+    /// Signal the end of the APDU session, letting the se enter its low power state
+    ///
+    /// The se wakes up on the next frame.
+    pub fn end_of_apdu_session(&mut self) -> Result<(), Error> {
+        trace!("End of APDU session");
+        self.exchange_s_block(
+            SBlock::EndOfApduSessionRequest,
+            SBlock::EndOfApduSessionResponse,
+        )
+    }
+
+    /// Perform a cold reset of the se
+    ///
+    /// The state of the applet is lost, it must be selected again.
+    pub fn chip_reset(&mut self) -> Result<(), Error> {
+        trace!("Chip reset");
+        self.exchange_s_block(SBlock::SeChipResetRequest, SBlock::SeChipResetResponse)?;
         self.iseq_snd = Seq::ZERO;
         self.iseq_rcv = Seq::ZERO;
         Ok(())
@@ -654,9 +918,11 @@ impl<Twi: I2CForT1, D: Delay> T1oI2C<Twi, D> {
         let atr = Atr::parse(&buffer[..received]);
         if let Ok(atr) = &atr {
             let mpot: u32 = atr.mpot.into();
-            self.mpot = 1000 * mpot;
-            self.segt = atr.segt.into();
-            self.bwt = (atr.bwt as u32) * 1000;
+            let overrides = self.timing_overrides;
+            self.mpot = overrides.mpot_us.unwrap_or(1000 * mpot);
+            self.segt = overrides.segt_us.unwrap_or(atr.segt.into());
+            self.bwt = overrides.bwt_us.unwrap_or((atr.bwt as u32) * 1000);
+            self.set_ifsc(atr.ifsc);
         };
         self.iseq_snd = Seq::ZERO;
         self.iseq_rcv = Seq::ZERO;
@@ -664,6 +930,61 @@ impl<Twi: I2CForT1, D: Delay> T1oI2C<Twi, D> {
         Ok(atr.unwrap_or_default())
     }
 
+    /// Maximum information field size of the frames sent to the se
+    ///
+    /// Longer APDUs are split in chained frames of this size.
+    pub fn ifsc(&self) -> usize {
+        self.ifsc
+    }
+
+    fn set_ifsc(&mut self, ifsc: u16) {
+        if (1..=MAX_FRAME_DATA_LEN).contains(&ifsc.into()) {
+            self.ifsc = ifsc.into();
+        } else {
+            warn!("Ignoring invalid IFSC: {ifsc}");
+        }
+    }
+
+    /// Announce to the se the maximum information field size of the frames it sends (IFSD)
+    ///
+    /// Longer responses are then split in chained frames of at most `ifsd` bytes.
+    pub fn negotiate_ifsd(&mut self, ifsd: u8) -> Result<(), Error> {
+        trace!("IFS request: {ifsd}");
+        if !(1..=MAX_FRAME_DATA_LEN).contains(&ifsd.into()) {
+            error!("Invalid IFSD: {ifsd}");
+            return Err(Error::Line(line!()));
+        }
+        let frame = [
+            self.nad_hd2se,
+            Pcb::S(SBlock::IfsRequest).to_byte(),
+            1,
+            ifsd,
+        ];
+        let [crc1, crc2] = Crc::calculate(&frame).to_le_bytes();
+        self.write(&[frame[0], frame[1], frame[2], frame[3], crc1, crc2])?;
+        self.wait_segt();
+        let mut response = [0; 1];
+        let data = self.receive_data(&mut response)?;
+        if !matches!(
+            data,
+            DataReceived::SBlock {
+                block: SBlock::IfsResponse,
+                i_data: 0,
+                s_data: 1
+            }
+        ) || response != [ifsd]
+        {
+            error!("Got unexpected IFS response: {data:?}");
+            return Err(Error::BadPcb);
+        }
+        Ok(())
+    }
+
+    /// Secure element guard time to wait before reading a response (microseconds)
+    pub fn segt_us(&self) -> u32 {
+        self.segt
+    }
+
     pub fn wait_segt(&mut self) {
         self.delay.delay_us(self.segt)
     }
@@ -671,6 +992,10 @@ impl<Twi: I2CForT1, D: Delay> T1oI2C<Twi, D> {
     pub fn wait_mpot(&mut self) {
         self.delay.delay_us(self.mpot)
     }
+
+    pub fn delay_us(&mut self, us: u32) {
+        self.delay.delay_us(us)
+    }
 }
 
 /// UM1225 2.1.1
@@ -678,23 +1003,30 @@ const MAX_FRAME_DATA_LEN: usize = 0xFE;
 const HEADER_LEN: usize = 3;
 const TRAILER_LEN: usize = 2;
 const MAX_FRAME_LEN: usize = MAX_FRAME_DATA_LEN + HEADER_LEN + TRAILER_LEN;
-
-pub struct FrameSender<'writer, Twi, D> {
+/// Length of the buffer of a [`FrameSender`], holding a whole frame
+pub const FRAME_BUFFER_LEN: usize = MAX_FRAME_LEN;
+
+/// Sends application data in I-blocks, buffering one frame at a time
+///
+/// The frame is buffered in `B`: an array owned by the sender, or a buffer borrowed with [`with_buffer`](FrameSender::with_buffer).
+#[cfg(feature = "transport")]
+pub struct FrameSender<'writer, Twi, D, B = [u8; MAX_FRAME_LEN]> {
     writer: &'writer mut T1oI2C<Twi, D>,
     /// Total amount of application data that will be written
     data: usize,
     /// Amount of application data already written, includes data currently in `current_frame_buffer`
     written: usize,
     sent: usize,
-    current_frame_buffer: [u8; MAX_FRAME_LEN],
+    current_frame_buffer: B,
+    /// First bytes of the application data, holding the APDU header
+    apdu_header: [u8; 4],
 }
 
+#[cfg(feature = "transport")]
 impl<'writer, Twi: I2CForT1, D: Delay> FrameSender<'writer, Twi, D> {
-    fn current_offset(&self) -> usize {
-        debug_assert!(self.written - self.sent <= MAX_FRAME_LEN);
-        self.written - self.sent
-    }
-
+    /// Create a sender for `data` bytes of application data, usually a command APDU, sent over `writer`
+    ///
+    /// This is what [`IntoWriter::into_writer`] returns for a [`T1oI2C`]. All the `data` bytes must be written before receiving the response.
     pub fn new(writer: &'writer mut T1oI2C<Twi, D>, data: usize) -> Self {
         Self {
             writer,
@@ -702,8 +1034,43 @@ impl<'writer, Twi: I2CForT1, D: Delay> FrameSender<'writer, Twi, D> {
             written: 0,
             sent: 0,
             current_frame_buffer: [0; MAX_FRAME_LEN],
+            apdu_header: [0; 4],
         }
     }
+}
+
+#[cfg(feature = "transport")]
+impl<'writer, Twi: I2CForT1, D: Delay>
+    FrameSender<'writer, Twi, D, &'writer mut [u8; MAX_FRAME_LEN]>
+{
+    /// Create a sender buffering the frames in `buffer` instead of an array of its own
+    pub fn with_buffer(
+        writer: &'writer mut T1oI2C<Twi, D>,
+        data: usize,
+        buffer: &'writer mut [u8; FRAME_BUFFER_LEN],
+    ) -> Self {
+        Self {
+            writer,
+            data,
+            written: 0,
+            sent: 0,
+            current_frame_buffer: buffer,
+            apdu_header: [0; 4],
+        }
+    }
+}
+
+#[cfg(feature = "transport")]
+impl<Twi: I2CForT1, D: Delay, B: AsRef<[u8]> + AsMut<[u8]>> FrameSender<'_, Twi, D, B> {
+    fn current_offset(&self) -> usize {
+        debug_assert!(self.written - self.sent <= MAX_FRAME_LEN);
+        self.written - self.sent
+    }
+
+    /// CLA, INS, P1 and P2 of the APDU being written
+    pub fn apdu_header(&self) -> [u8; 4] {
+        self.apdu_header
+    }
 
     pub fn write_data(&mut self, data: &[u8]) -> Result<usize, Error> {
         // Prevent false positive when delog is disabled
@@ -723,11 +1090,15 @@ impl<'writer, Twi: I2CForT1, D: Delay> FrameSender<'writer, Twi, D> {
         }
 
         let current_offset = self.current_offset();
-        let available_in_frame = MAX_FRAME_DATA_LEN - current_offset;
+        let available_in_frame = self.writer.ifsc - current_offset;
         let chunk_len = available_in_frame.min(data.len());
         let chunk = &data[..chunk_len];
+        if let Some(header) = self.apdu_header.get_mut(self.written..) {
+            let len = header.len().min(chunk_len);
+            header[..len].copy_from_slice(&chunk[..len]);
+        }
         self.written += chunk_len;
-        self.current_frame_buffer[HEADER_LEN + current_offset..][..chunk_len]
+        self.current_frame_buffer.as_mut()[HEADER_LEN + current_offset..][..chunk_len]
             .copy_from_slice(chunk);
 
         // frame is full, must flush
@@ -750,31 +1121,34 @@ impl<'writer, Twi: I2CForT1, D: Delay> FrameSender<'writer, Twi, D> {
         self.writer.iseq_snd = !self.writer.iseq_snd;
 
         let header = [self.writer.nad_hd2se, pcb, data_len as u8];
-        self.current_frame_buffer[0..HEADER_LEN].copy_from_slice(&header);
-        let trailer =
-            Crc::calculate(&self.current_frame_buffer[..HEADER_LEN + data_len]).to_le_bytes();
-        self.current_frame_buffer[HEADER_LEN + data_len..][..TRAILER_LEN].copy_from_slice(&trailer);
+        self.current_frame_buffer.as_mut()[0..HEADER_LEN].copy_from_slice(&header);
+        let trailer = Crc::calculate(&self.current_frame_buffer.as_ref()[..HEADER_LEN + data_len])
+            .to_le_bytes();
+        self.current_frame_buffer.as_mut()[HEADER_LEN + data_len..][..TRAILER_LEN]
+            .copy_from_slice(&trailer);
         trace!(
             "Sending:\n\tHeader: {:02x?}\n\tData: {:02x?}\n\tTrailer: {:02x?}",
-            &self.current_frame_buffer[..HEADER_LEN],
-            &self.current_frame_buffer[HEADER_LEN..][..data_len],
-            &self.current_frame_buffer[HEADER_LEN + data_len..][..TRAILER_LEN],
+            &self.current_frame_buffer.as_ref()[..HEADER_LEN],
+            &self.current_frame_buffer.as_ref()[HEADER_LEN..][..data_len],
+            &self.current_frame_buffer.as_ref()[HEADER_LEN + data_len..][..TRAILER_LEN],
         );
 
         let mut wrote_success = false;
+        let mut data_nacks = 0;
         for _ in 0..self.writer.retry_count {
             match self
                 .writer
-                .write(&self.current_frame_buffer[..data_len + HEADER_LEN + TRAILER_LEN])
+                .write(&self.current_frame_buffer.as_ref()[..data_len + HEADER_LEN + TRAILER_LEN])
             {
                 Ok(()) => {
                     wrote_success = true;
                     break;
                 }
-                // Err(Error::DataNack) => {
-                //     self.writer.wait_segt();
-                //     continue;
-                // }
+                Err(Error::DataNack) if data_nacks < self.writer.max_data_nack_retries => {
+                    data_nacks += 1;
+                    self.writer.wait_segt();
+                    continue;
+                }
                 Err(Error::AddressNack) => {
                     self.writer.wait_segt();
                     continue;
@@ -845,13 +1219,15 @@ impl<'writer, Twi: I2CForT1, D: Delay> FrameSender<'writer, Twi, D> {
     }
 }
 
-impl<Twi: I2CForT1, D: Delay> Writer for FrameSender<'_, Twi, D> {
+#[cfg(feature = "transport")]
+impl<Twi: I2CForT1, D: Delay, B: AsRef<[u8]> + AsMut<[u8]>> Writer for FrameSender<'_, Twi, D, B> {
     type Error = Error;
     fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error> {
         self.write_data(data)
     }
 }
 
+#[cfg(feature = "transport")]
 impl<'writer, Twi: I2CForT1, D: Delay> IntoWriter for &'writer mut T1oI2C<Twi, D> {
     type Writer = FrameSender<'writer, Twi, D>;
     fn into_writer(self, to_write: usize) -> Result<Self::Writer, <Self::Writer as Writer>::Error> {